@@ -1,8 +1,10 @@
 use crate::{
     middleware::AuthenticationMiddleware,
+    subscriptions::CommitFeed,
     uses::{
-        get_nonce, gql_playground, health_check, query_graph, register_indexer_assets,
-        revert_indexer, stop_indexer, verify_signature,
+        get_nonce, gql_playground, health_check, indexer_status, query_graph,
+        register_indexer_assets, revert_indexer, stop_indexer, subscribe_graph,
+        verify_signature,
     },
 };
 
@@ -19,7 +21,9 @@ use axum::{
 };
 use fuel_indexer_database::{IndexerConnectionPool, IndexerDatabaseError};
 use fuel_indexer_graphql::graphql::GraphqlError;
-use fuel_indexer_lib::{config::IndexerConfig, utils::ServiceRequest};
+use fuel_indexer_lib::{
+    config::IndexerConfig, manifest::ManifestError, utils::ServiceRequest,
+};
 use fuel_indexer_schema::db::{manager::SchemaManager, IndexerSchemaError};
 use hyper::Method;
 use serde_json::json;
@@ -82,6 +86,12 @@ pub enum ApiError {
     JsonWebToken(#[from] jsonwebtoken::errors::Error),
     #[error("HexError: {0:?}")]
     HexError(#[from] hex::FromHexError),
+    #[error("Manifest error: {0:?}")]
+    Manifest(#[from] ManifestError),
+    #[error("Invalid schema: {0}")]
+    InvalidSchema(String),
+    #[error("Subscription selects more than {0} entities")]
+    SubscriptionTooLarge(usize),
 }
 
 impl Default for ApiError {
@@ -90,13 +100,13 @@ impl Default for ApiError {
     }
 }
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
+impl ApiError {
+    /// The status and details reported to clients, which don't include
+    /// internal errors.
+    pub(crate) fn details(&self) -> (StatusCode, String) {
         let generic_details = "Internal server error.".to_string();
 
-        error!("ApiError: {self:?}");
-
-        let (status, details) = match self {
+        match self {
             Self::JsonWebToken(e) => (
                 StatusCode::BAD_REQUEST,
                 format!("Could not process JWT: {e}"),
@@ -119,12 +129,34 @@ impl IntoResponse for ApiError {
                 (StatusCode::BAD_REQUEST, format!("Crypto error: {e}."))
             }
             Self::Graphql(e) => (StatusCode::BAD_REQUEST, format!("GraphQL error: {e}.")),
+            Self::SchemaError(e @ IndexerSchemaError::DestructiveMigration(_)) => {
+                (StatusCode::BAD_REQUEST, format!("Schema error: {e}."))
+            }
+            Self::Manifest(e) => {
+                (StatusCode::BAD_REQUEST, format!("Manifest error: {e}."))
+            }
+            Self::SchemaError(IndexerSchemaError::InvalidSchema(e))
+            | Self::InvalidSchema(e) => {
+                (StatusCode::BAD_REQUEST, format!("Invalid schema: {e}."))
+            }
             Self::SchemaError(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Schema error: {e}."),
             ),
+            Self::SubscriptionTooLarge(_) => (
+                StatusCode::BAD_REQUEST,
+                format!("{self}; select fewer with a filter or `first`."),
+            ),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, generic_details),
-        };
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        error!("ApiError: {self:?}");
+
+        let (status, details) = self.details();
 
         error!("{status:?} - {details}");
 
@@ -157,9 +189,14 @@ impl GraphQlApi {
         let schema_manager = Arc::new(RwLock::new(sm));
         let max_body_size = config.graphql_api.max_body_size;
         let start_time = Arc::new(Instant::now());
+        let commits = CommitFeed::listen(&pool);
 
         let graph_routes = Router::new()
-            .route("/:namespace/:identifier", post(query_graph))
+            .route(
+                "/:namespace/:identifier",
+                post(query_graph).get(subscribe_graph),
+            )
+            .layer(Extension(commits))
             .layer(Extension(schema_manager.clone()))
             .layer(Extension(pool.clone()))
             .layer(RequestBodyLimitLayer::new(max_body_size));
@@ -187,7 +224,9 @@ impl GraphQlApi {
             .route("/health", get(health_check))
             .layer(Extension(config.clone()))
             .layer(Extension(pool.clone()))
-            .layer(Extension(start_time));
+            .layer(Extension(start_time))
+            .route("/status/:namespace/:identifier", get(indexer_status))
+            .layer(Extension(pool.clone()));
 
         #[cfg(feature = "metrics")]
         let root_routes = root_routes
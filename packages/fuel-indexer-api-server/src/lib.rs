@@ -5,6 +5,7 @@ pub mod cli;
 pub(crate) mod commands;
 pub(crate) mod middleware;
 pub(crate) mod models;
+mod subscriptions;
 mod uses;
 
 pub mod utils {
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct VerifySignatureRequest {
@@ -11,3 +12,35 @@ pub struct VerifySignatureRequest {
 pub(crate) struct QueryResponse {
     pub data: Value,
 }
+
+/// Query parameters accepted when registering an indexer's assets.
+#[derive(Deserialize, Default, Debug)]
+pub(crate) struct RegisterIndexerParams {
+    /// Migrate the indexer's tables even if the new schema loses indexed data.
+    #[serde(default)]
+    pub force_migration: bool,
+    /// Index into a shadow schema, replacing the current version of a
+    /// registered indexer once the new version catches up with the chain.
+    #[serde(default)]
+    pub blue_green: bool,
+}
+
+/// A GraphQL document to answer from the graph of an indexer.
+#[derive(Clone, Debug)]
+pub(crate) struct GraphqlRequest {
+    pub query: String,
+    pub variables: HashMap<String, Value>,
+    pub operation_name: Option<String>,
+}
+
+impl TryFrom<async_graphql::Request> for GraphqlRequest {
+    type Error = serde_json::Error;
+
+    fn try_from(req: async_graphql::Request) -> Result<Self, Self::Error> {
+        Ok(Self {
+            variables: serde_json::from_value(req.variables.into_value().into_json()?)?,
+            query: req.query,
+            operation_name: req.operation_name,
+        })
+    }
+}
@@ -0,0 +1,492 @@
+//! GraphQL subscriptions to the graph of an indexer, served over a WebSocket
+//! with either the `graphql-transport-ws` protocol or the older `graphql-ws`
+//! protocol of `subscriptions-transport-ws`.
+//!
+//! A subscription is answered with the records it selects, then answered again
+//! each time the indexer commits a batch of blocks, with only the records that
+//! are new or changed since.
+//!
+//! Answers are buffered for each WebSocket up to `SUBSCRIPTION_OUTGOING_SIZE`.
+//! A subscriber that reads too slowly to keep up has its next answer sent
+//! whole, rather than only the changes it missed, and a subscription selecting
+//! more than `SUBSCRIPTION_MAX_RECORDS` records fails.
+
+use crate::{
+    api::{ApiError, ApiResult},
+    models::GraphqlRequest,
+    uses::query_indexer,
+};
+use async_graphql::http::{ClientMessage, WebSocketProtocols};
+use async_std::sync::{Arc, RwLock};
+use axum::extract::ws::{CloseFrame, Message, WebSocket};
+use fuel_indexer_database::{queries, IndexerConnectionPool};
+use fuel_indexer_lib::defaults::{
+    COMMIT_NOTIFICATION_CHANNEL, SUBSCRIPTION_CHANNEL_SIZE, SUBSCRIPTION_MAX_RECORDS,
+    SUBSCRIPTION_OUTGOING_SIZE, SUBSCRIPTION_POLL_INTERVAL_MS,
+};
+use fuel_indexer_schema::db::manager::SchemaManager;
+use serde_json::{json, Value};
+use sqlx::postgres::{PgListener, PgPool};
+use std::collections::{hash_map::Entry, HashMap};
+use tokio::{
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::{self, error::TrySendError},
+    },
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
+use tracing::{debug, error};
+
+/// Close code for messages that can't be parsed.
+const INVALID_MESSAGE: u16 = 4400;
+
+/// Close code for subscribing before initializing the connection.
+const UNAUTHORIZED: u16 = 4401;
+
+/// Close code for reusing the ID of a subscription that's still running.
+const SUBSCRIBER_EXISTS: u16 = 4409;
+
+/// Commits announced by the indexers of the database, as the
+/// `namespace.identifier` of the indexer that committed.
+#[derive(Clone)]
+pub(crate) struct CommitFeed {
+    commits: Option<broadcast::Sender<String>>,
+}
+
+impl CommitFeed {
+    /// Listen for the commits of every indexer. Postgres notifies listeners of
+    /// each commit, while SQLite can't, so its subscriptions poll the
+    /// checkpoint of their indexer instead.
+    pub(crate) fn listen(pool: &IndexerConnectionPool) -> Self {
+        match pool {
+            IndexerConnectionPool::Postgres(pool) => {
+                let (tx, _) = broadcast::channel(SUBSCRIPTION_CHANNEL_SIZE);
+                tokio::spawn(forward_commits(pool.clone(), tx.clone()));
+                Self { commits: Some(tx) }
+            }
+            IndexerConnectionPool::Sqlite(_) => Self { commits: None },
+        }
+    }
+
+    /// Start watching for the commits of an indexer. Commits made once this
+    /// returns are never missed, so a subscription starts watching before it
+    /// first runs its query.
+    async fn watch(
+        &self,
+        pool: &IndexerConnectionPool,
+        namespace: &str,
+        identifier: &str,
+    ) -> ApiResult<CommitWatch> {
+        match &self.commits {
+            Some(commits) => Ok(CommitWatch::Notified {
+                commits: commits.subscribe(),
+                indexer: format!("{namespace}.{identifier}"),
+            }),
+            None => {
+                let mut conn = pool.acquire().await?;
+                let block_height =
+                    queries::checkpoint_for_indexer(&mut conn, namespace, identifier)
+                        .await?
+                        .map(|c| c.block_height);
+
+                Ok(CommitWatch::Polled {
+                    pool: pool.clone(),
+                    namespace: namespace.to_string(),
+                    identifier: identifier.to_string(),
+                    block_height,
+                })
+            }
+        }
+    }
+}
+
+/// Forward the commit notifications of the database to the subscriptions of
+/// this server.
+async fn forward_commits(pool: PgPool, commits: broadcast::Sender<String>) {
+    let interval = Duration::from_millis(SUBSCRIPTION_POLL_INTERVAL_MS);
+
+    let mut listener = loop {
+        match PgListener::connect_with(&pool).await {
+            Ok(mut listener) => {
+                match listener.listen(COMMIT_NOTIFICATION_CHANNEL).await {
+                    Ok(()) => break listener,
+                    Err(e) => error!("Failed to listen for commits: {e}."),
+                }
+            }
+            Err(e) => error!("Failed to connect commit listener: {e}."),
+        }
+        sleep(interval).await;
+    };
+
+    loop {
+        // The listener reconnects on the next call after losing its connection.
+        match listener.recv().await {
+            Ok(notification) => {
+                // Nobody is subscribed when sending fails.
+                let _ = commits.send(notification.payload().to_string());
+            }
+            Err(e) => {
+                error!("Commit listener error: {e}.");
+                sleep(interval).await;
+            }
+        }
+    }
+}
+
+/// Waits for the commits of a single indexer.
+enum CommitWatch {
+    Notified {
+        commits: broadcast::Receiver<String>,
+        indexer: String,
+    },
+    Polled {
+        pool: IndexerConnectionPool,
+        namespace: String,
+        identifier: String,
+        block_height: Option<u64>,
+    },
+}
+
+impl CommitWatch {
+    /// Wait until the indexer commits again. Commits made while the
+    /// subscription was answering the last one are all answered at once.
+    async fn committed(&mut self) -> ApiResult<()> {
+        match self {
+            CommitWatch::Notified { commits, indexer } => loop {
+                match commits.recv().await {
+                    Ok(committed) if committed == *indexer => {}
+                    Ok(_) => continue,
+                    // Some notifications were dropped, and any of them could
+                    // have been for this indexer.
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return Err(ApiError::default()),
+                }
+
+                while !commits.is_empty() {
+                    let _ = commits.try_recv();
+                }
+                return Ok(());
+            },
+            CommitWatch::Polled {
+                pool,
+                namespace,
+                identifier,
+                block_height,
+            } => loop {
+                sleep(Duration::from_millis(SUBSCRIPTION_POLL_INTERVAL_MS)).await;
+
+                let mut conn = pool.acquire().await?;
+                let latest =
+                    queries::checkpoint_for_indexer(&mut conn, namespace, identifier)
+                        .await?
+                        .map(|c| c.block_height);
+
+                if latest != *block_height {
+                    *block_height = latest;
+                    return Ok(());
+                }
+            },
+        }
+    }
+}
+
+/// The graph of an indexer that a WebSocket subscribes to.
+#[derive(Clone)]
+pub(crate) struct Graph {
+    pub namespace: String,
+    pub identifier: String,
+    pub pool: IndexerConnectionPool,
+    pub manager: Arc<RwLock<SchemaManager>>,
+    pub commits: CommitFeed,
+}
+
+/// The records last sent to a subscriber, so that only the records that are
+/// new or changed are sent after a commit. At most `SUBSCRIPTION_MAX_RECORDS`
+/// records are kept.
+#[derive(Debug, Default)]
+struct SentRecords {
+    data: Option<Value>,
+    records: HashMap<String, Value>,
+}
+
+impl SentRecords {
+    /// The part of a response that the subscriber hasn't been sent yet, if
+    /// any. Records are told apart by their ID, and a response that isn't a
+    /// list of records, like a connection, is sent whole whenever it changes.
+    fn unsent(&mut self, data: Value) -> ApiResult<Option<Value>> {
+        if matches!(&data, Value::Array(records) if records.len() > SUBSCRIPTION_MAX_RECORDS)
+        {
+            return Err(ApiError::SubscriptionTooLarge(SUBSCRIPTION_MAX_RECORDS));
+        }
+
+        let first = self.data.is_none();
+        if self.data.as_ref() == Some(&data) {
+            return Ok(None);
+        }
+        self.data = Some(data.clone());
+
+        let records = match data {
+            Value::Array(records) => records,
+            data => return Ok(Some(data)),
+        };
+
+        let mut latest = HashMap::with_capacity(records.len());
+        let mut unsent = Vec::new();
+        for record in records {
+            let key = match record.get("id") {
+                Some(id) => id.to_string(),
+                None => record.to_string(),
+            };
+            if self.records.get(&key) != Some(&record) {
+                unsent.push(record.clone());
+            }
+            latest.insert(key, record);
+        }
+        self.records = latest;
+
+        // The first response is always sent, even with no records, so the
+        // subscriber knows the subscription started.
+        match first || !unsent.is_empty() {
+            true => Ok(Some(Value::Array(unsent))),
+            false => Ok(None),
+        }
+    }
+}
+
+/// Run a subscription until the subscriber stops it or it fails.
+async fn run_subscription(
+    graph: Graph,
+    protocol: WebSocketProtocols,
+    id: String,
+    req: GraphqlRequest,
+    outgoing: mpsc::Sender<String>,
+) {
+    let Graph {
+        namespace,
+        identifier,
+        pool,
+        manager,
+        commits,
+    } = graph;
+
+    let result: ApiResult<()> = async {
+        let mut watch = commits.watch(&pool, &namespace, &identifier).await?;
+        let mut sent = SentRecords::default();
+
+        loop {
+            let response =
+                query_indexer(&pool, &manager, &namespace, &identifier, &req, true)
+                    .await?;
+
+            if let Some(data) = sent.unsent(response["data"].clone())? {
+                let message = json!({
+                    "type": next_message_type(protocol),
+                    "id": id,
+                    "payload": { "data": data },
+                });
+                match outgoing.try_send(message.to_string()) {
+                    Ok(()) => {}
+                    // The subscriber missed this answer, so it's sent every
+                    // record with the next one.
+                    Err(TrySendError::Full(_)) => sent = SentRecords::default(),
+                    Err(TrySendError::Closed(_)) => return Ok(()),
+                }
+            }
+
+            watch.committed().await?;
+        }
+    }
+    .await;
+
+    if let Err(e) = result {
+        let (_, details) = e.details();
+        let _ = outgoing.send(error_message(protocol, &id, &details)).await;
+    }
+}
+
+/// The message ending a subscription that failed.
+fn error_message(protocol: WebSocketProtocols, id: &str, details: &str) -> String {
+    let error = json!({ "message": details });
+    let payload = match protocol {
+        WebSocketProtocols::GraphQLWS => json!([error]),
+        WebSocketProtocols::SubscriptionsTransportWS => error,
+    };
+
+    json!({ "type": "error", "id": id, "payload": payload }).to_string()
+}
+
+fn next_message_type(protocol: WebSocketProtocols) -> &'static str {
+    match protocol {
+        WebSocketProtocols::GraphQLWS => "next",
+        WebSocketProtocols::SubscriptionsTransportWS => "data",
+    }
+}
+
+/// Serve the subscriptions of a WebSocket until it closes.
+pub(crate) async fn serve_subscriptions(mut socket: WebSocket, graph: Graph) {
+    let protocol = socket
+        .protocol()
+        .and_then(|p| p.to_str().ok())
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(WebSocketProtocols::GraphQLWS);
+
+    // Answers of subscriptions are buffered, while the replies to the client's
+    // own messages are sent directly.
+    let (outgoing, mut to_send) = mpsc::channel::<String>(SUBSCRIPTION_OUTGOING_SIZE);
+    let mut subscriptions: HashMap<String, JoinHandle<()>> = HashMap::new();
+    let mut initialized = false;
+
+    loop {
+        let message = tokio::select! {
+            Some(message) = to_send.recv() => {
+                if socket.send(Message::Text(message)).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+            message = socket.recv() => message,
+        };
+
+        let message = match message {
+            Some(Ok(Message::Text(text))) => ClientMessage::from_bytes(text),
+            Some(Ok(Message::Binary(bytes))) => ClientMessage::from_bytes(bytes),
+            Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+            Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+        };
+
+        let (reply, close) = match message {
+            Ok(ClientMessage::ConnectionInit { .. }) => {
+                initialized = true;
+                (Some(json!({ "type": "connection_ack" }).to_string()), None)
+            }
+            Ok(ClientMessage::Start { .. }) if !initialized => {
+                (None, Some((UNAUTHORIZED, "Unauthorized".to_string())))
+            }
+            Ok(ClientMessage::Start { id, payload }) => {
+                subscriptions.retain(|_, handle| !handle.is_finished());
+                match subscriptions.entry(id) {
+                    Entry::Occupied(entry) => (
+                        None,
+                        Some((
+                            SUBSCRIBER_EXISTS,
+                            format!("Subscriber for {} already exists", entry.key()),
+                        )),
+                    ),
+                    Entry::Vacant(entry) => {
+                        let id = entry.key().clone();
+                        match GraphqlRequest::try_from(payload) {
+                            Ok(req) => {
+                                debug!("Starting subscription {id}.");
+                                entry.insert(tokio::spawn(run_subscription(
+                                    graph.clone(),
+                                    protocol,
+                                    id,
+                                    req,
+                                    outgoing.clone(),
+                                )));
+                                (None, None)
+                            }
+                            Err(e) => {
+                                let details = format!("Invalid variables: {e}");
+                                (Some(error_message(protocol, &id, &details)), None)
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(ClientMessage::Stop { id }) => {
+                if let Some(handle) = subscriptions.remove(&id) {
+                    handle.abort();
+                }
+                (None, None)
+            }
+            Ok(ClientMessage::ConnectionTerminate) => break,
+            Ok(ClientMessage::Ping { .. }) => {
+                (Some(json!({ "type": "pong" }).to_string()), None)
+            }
+            Ok(ClientMessage::Pong { .. }) => (None, None),
+            Err(e) => (
+                None,
+                Some((INVALID_MESSAGE, format!("Invalid message: {e}"))),
+            ),
+        };
+
+        if let Some(reply) = reply {
+            if socket.send(Message::Text(reply)).await.is_err() {
+                break;
+            }
+        }
+
+        if let Some((code, reason)) = close {
+            let frame = CloseFrame {
+                code,
+                reason: reason.into(),
+            };
+            let _ = socket.send(Message::Close(Some(frame))).await;
+            break;
+        }
+    }
+
+    for (_, handle) in subscriptions {
+        handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_new_and_changed_records_are_unsent() {
+        let mut sent = SentRecords::default();
+
+        assert_eq!(sent.unsent(json!([])).unwrap(), Some(json!([])));
+        assert_eq!(sent.unsent(json!([])).unwrap(), None);
+
+        let first = json!([{ "id": 1, "value": 1 }, { "id": 2, "value": 2 }]);
+        assert_eq!(sent.unsent(first.clone()).unwrap(), Some(first.clone()));
+        assert_eq!(sent.unsent(first).unwrap(), None);
+
+        let changed = json!([
+            { "id": 1, "value": 1 },
+            { "id": 2, "value": 20 },
+            { "id": 3, "value": 3 },
+        ]);
+        assert_eq!(
+            sent.unsent(changed).unwrap(),
+            Some(json!([{ "id": 2, "value": 20 }, { "id": 3, "value": 3 }]))
+        );
+
+        // Deleted records aren't sent.
+        assert_eq!(sent.unsent(json!([{ "id": 1, "value": 1 }])).unwrap(), None);
+    }
+
+    #[test]
+    fn test_too_many_records_are_not_tracked() {
+        let mut sent = SentRecords::default();
+
+        let records = (0..=SUBSCRIPTION_MAX_RECORDS)
+            .map(|id| json!({ "id": id }))
+            .collect();
+        match sent.unsent(Value::Array(records)) {
+            Err(ApiError::SubscriptionTooLarge(max)) => {
+                assert_eq!(max, SUBSCRIPTION_MAX_RECORDS)
+            }
+            o => panic!("Should have gotten SubscriptionTooLarge, got {o:?}"),
+        }
+        assert!(sent.records.is_empty());
+    }
+
+    #[test]
+    fn test_objects_are_unsent_whole_when_changed() {
+        let mut sent = SentRecords::default();
+
+        let page = json!({ "thingConnection": { "totalCount": 1 } });
+        assert_eq!(sent.unsent(page.clone()).unwrap(), Some(page.clone()));
+        assert_eq!(sent.unsent(page).unwrap(), None);
+
+        let page = json!({ "thingConnection": { "totalCount": 2 } });
+        assert_eq!(sent.unsent(page.clone()).unwrap(), Some(page));
+    }
+}
@@ -1,13 +1,18 @@
 use crate::{
     api::{ApiError, ApiResult, HttpError},
-    models::{QueryResponse, VerifySignatureRequest},
+    models::{
+        GraphqlRequest, QueryResponse, RegisterIndexerParams, VerifySignatureRequest,
+    },
+    subscriptions::{serve_subscriptions, CommitFeed, Graph},
+};
+use async_graphql::http::{
+    playground_source, GraphQLPlaygroundConfig, ALL_WEBSOCKET_PROTOCOLS,
 };
-use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql_axum::GraphQLRequest;
 use async_std::sync::{Arc, RwLock};
 use axum::{
     body::Body,
-    extract::{multipart::Multipart, Extension, Json, Path},
+    extract::{multipart::Multipart, ws::WebSocketUpgrade, Extension, Json, Path, Query},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
@@ -15,26 +20,34 @@ use fuel_crypto::{Message, Signature};
 use fuel_indexer_database::{
     queries,
     types::{IndexAsset, IndexAssetType},
-    IndexerConnectionPool,
+    DbType, IndexerConnection, IndexerConnectionPool,
 };
-use fuel_indexer_graphql::graphql::GraphqlQueryBuilder;
+use fuel_indexer_graphql::graphql::{GraphqlError, GraphqlQueryBuilder};
 use fuel_indexer_lib::{
     config::{
         auth::{AuthenticationStrategy, Claims},
         IndexerConfig,
     },
-    defaults,
+    defaults::{self, SHADOW_IDENTIFIER_SUFFIX},
+    manifest::Manifest,
     utils::{
         AssetReloadRequest, FuelNodeHealthResponse, IndexRevertRequest, IndexStopRequest,
-        ServiceRequest, ServiceStatus,
+        ServiceRequest, ServiceStatus, ShadowDeployRequest,
+    },
+};
+use fuel_indexer_schema::{
+    db::{
+        manager::SchemaManager,
+        tables::{Schema, SchemaBuilder},
     },
+    utils::inject_native_entities_into_schema,
 };
-use fuel_indexer_schema::db::{manager::SchemaManager, tables::Schema};
 use hyper::Client;
 use hyper_rustls::HttpsConnectorBuilder;
 use jsonwebtoken::{encode, EncodingKey, Header};
 use serde_json::{json, Value};
 use std::{
+    collections::HashMap,
     convert::From,
     str::FromStr,
     time::{Instant, SystemTime, UNIX_EPOCH},
@@ -54,14 +67,49 @@ pub(crate) async fn query_graph(
     Extension(manager): Extension<Arc<RwLock<SchemaManager>>>,
     req: GraphQLRequest,
 ) -> ApiResult<axum::Json<Value>> {
-    match manager
+    let req = GraphqlRequest::try_from(req.into_inner())?;
+
+    query_indexer(&pool, &manager, &namespace, &identifier, &req, false)
+        .await
+        .map(axum::Json)
+}
+
+/// Answer a GraphQL request from the graph of an indexer. Subscriptions are
+/// only answered when `subscription` is set, as they're run again each time
+/// the indexer commits.
+pub(crate) async fn query_indexer(
+    pool: &IndexerConnectionPool,
+    manager: &RwLock<SchemaManager>,
+    namespace: &str,
+    identifier: &str,
+    req: &GraphqlRequest,
+    subscription: bool,
+) -> ApiResult<Value> {
+    let mut conn = pool.acquire().await?;
+
+    // Blue/green deployments swap an indexer's tables while holding this lock
+    // exclusively, so a query never sees half of a swap.
+    queries::start_transaction(&mut conn).await?;
+    queries::lock_indexer(&mut conn, namespace, identifier, false).await?;
+
+    let result = match manager
         .read()
         .await
-        .load_schema(&namespace, &identifier)
+        .load_schema(namespace, identifier)
         .await
     {
-        Ok(schema) => match run_query(req.into_inner().query, schema, &pool).await {
-            Ok(query_res) => Ok(axum::Json(query_res)),
+        Ok(schema) => match run_query(
+            req.query.clone(),
+            req.variables.clone(),
+            req.operation_name.clone(),
+            subscription,
+            schema,
+            pool.database_type(),
+            &mut conn,
+        )
+        .await
+        {
+            Ok(query_res) => Ok(query_res),
             Err(e) => {
                 error!("query_graph error: {e}");
                 Err(e)
@@ -70,7 +118,31 @@ pub(crate) async fn query_graph(
         Err(_e) => Err(ApiError::Http(HttpError::NotFound(format!(
             "The graph '{namespace}.{identifier}' was not found."
         )))),
-    }
+    };
+
+    queries::commit_transaction(&mut conn).await?;
+
+    result
+}
+
+/// Subscribe to the graph of an indexer over a WebSocket.
+pub(crate) async fn subscribe_graph(
+    Path((namespace, identifier)): Path<(String, String)>,
+    Extension(pool): Extension<IndexerConnectionPool>,
+    Extension(manager): Extension<Arc<RwLock<SchemaManager>>>,
+    Extension(commits): Extension<CommitFeed>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let graph = Graph {
+        namespace,
+        identifier,
+        pool,
+        manager,
+        commits,
+    };
+
+    ws.protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| serve_subscriptions(socket, graph))
 }
 
 pub(crate) async fn get_fuel_status(config: &IndexerConfig) -> ServiceStatus {
@@ -119,6 +191,57 @@ pub(crate) async fn health_check(
     })))
 }
 
+pub(crate) async fn indexer_status(
+    Path((namespace, identifier)): Path<(String, String)>,
+    Extension(pool): Extension<IndexerConnectionPool>,
+) -> ApiResult<axum::Json<Value>> {
+    let mut conn = pool.acquire().await?;
+
+    let index = queries::index_is_registered(&mut conn, &namespace, &identifier)
+        .await?
+        .ok_or_else(|| {
+            ApiError::Http(HttpError::NotFound(format!(
+                "Indexer({namespace}.{identifier}) is not registered."
+            )))
+        })?;
+
+    let checkpoint =
+        queries::checkpoint_for_indexer(&mut conn, &namespace, &identifier).await?;
+    let dead_letters = queries::dead_letters(&mut conn, &namespace, &identifier).await?;
+
+    let blue_green =
+        match queries::shadow_deployment_for(&mut conn, &namespace, &identifier).await? {
+            Some(shadow) => {
+                let shadow_identifier = format!("{identifier}{SHADOW_IDENTIFIER_SUFFIX}");
+                let checkpoint = queries::checkpoint_for_indexer(
+                    &mut conn,
+                    &namespace,
+                    &shadow_identifier,
+                )
+                .await?;
+
+                json!({
+                    "caught_up": shadow.caught_up,
+                    "block_height": checkpoint.map(|c| c.block_height),
+                })
+            }
+            None => Value::Null,
+        };
+
+    Ok(Json(json!({
+        "namespace": namespace,
+        "identifier": identifier,
+        "completed": index.completed,
+        "status": index.status,
+        "status_message": index.status_message,
+        "dead_letters": dead_letters,
+        "block_height": checkpoint.as_ref().map(|c| c.block_height),
+        "block_id": checkpoint.as_ref().and_then(|c| c.block_id.clone()),
+        "cursor": checkpoint.and_then(|c| c.cursor),
+        "blue_green": blue_green,
+    })))
+}
+
 pub(crate) async fn stop_indexer(
     Path((namespace, identifier)): Path<(String, String)>,
     Extension(tx): Extension<Sender<ServiceRequest>>,
@@ -188,6 +311,7 @@ pub(crate) async fn revert_indexer(
 
 pub(crate) async fn register_indexer_assets(
     Path((namespace, identifier)): Path<(String, String)>,
+    Query(params): Query<RegisterIndexerParams>,
     Extension(tx): Extension<Sender<ServiceRequest>>,
     Extension(schema_manager): Extension<Arc<RwLock<SchemaManager>>>,
     Extension(claims): Extension<Claims>,
@@ -198,66 +322,57 @@ pub(crate) async fn register_indexer_assets(
         return Err(ApiError::Http(HttpError::Unauthorized));
     }
 
-    let mut assets: Vec<IndexAsset> = Vec::new();
-
     if let Some(mut multipart) = multipart {
         let mut conn = pool.acquire().await?;
 
-        let _ = queries::start_transaction(&mut conn).await?;
-
-        while let Some(field) = multipart.next_field().await.unwrap() {
-            let name = field.name().unwrap_or("").to_string();
-            let data = field.bytes().await.unwrap_or_default();
-            let asset_type =
-                IndexAssetType::from_str(&name).expect("Invalid asset type.");
-
-            let asset: IndexAsset = match asset_type {
-                IndexAssetType::Wasm | IndexAssetType::Manifest => {
-                    queries::register_index_asset(
-                        &mut conn,
-                        &namespace,
-                        &identifier,
-                        data.to_vec(),
-                        asset_type,
-                        Some(&claims.sub),
-                    )
-                    .await?
-                }
-                IndexAssetType::Schema => {
-                    match queries::register_index_asset(
-                        &mut conn,
-                        &namespace,
-                        &identifier,
-                        data.to_vec(),
-                        IndexAssetType::Schema,
-                        Some(&claims.sub),
-                    )
-                    .await
-                    {
-                        Ok(result) => {
-                            schema_manager
-                                .write()
-                                .await
-                                .new_schema(
-                                    &namespace,
-                                    &identifier,
-                                    &String::from_utf8_lossy(&data),
-                                    &mut conn,
-                                )
-                                .await?;
-
-                            result
-                        }
-                        Err(e) => {
-                            return Err(e.into());
-                        }
-                    }
-                }
-            };
+        if params.blue_green {
+            if let Some(index) =
+                queries::index_is_registered(&mut conn, &namespace, &identifier).await?
+            {
+                register_shadow_deployment(
+                    &namespace,
+                    &identifier,
+                    &index.id,
+                    multipart,
+                    &mut conn,
+                    pool.database_type(),
+                )
+                .await?;
+
+                tx.send(ServiceRequest::ShadowDeploy(ShadowDeployRequest {
+                    namespace,
+                    identifier,
+                }))
+                .await?;
 
-            assets.push(asset);
+                return Ok(Json(json!({
+                    "success": "true",
+                    "blue_green": true,
+                })));
+            }
         }
 
+        let _ = queries::start_transaction(&mut conn).await?;
+
+        // Nothing registered so far may be committed if any asset is rejected.
+        let assets = match register_assets(
+            &namespace,
+            &identifier,
+            &params,
+            &claims,
+            &schema_manager,
+            &mut multipart,
+            &mut conn,
+        )
+        .await
+        {
+            Ok(assets) => assets,
+            Err(e) => {
+                queries::revert_transaction(&mut conn).await?;
+                return Err(e);
+            }
+        };
+
         let _ = queries::commit_transaction(&mut conn).await?;
 
         tx.send(ServiceRequest::AssetReload(AssetReloadRequest {
@@ -275,6 +390,132 @@ pub(crate) async fn register_indexer_assets(
     Err(ApiError::default())
 }
 
+/// Register the assets uploaded in `multipart`, migrating the indexer's schema
+/// to any new schema among them.
+async fn register_assets(
+    namespace: &str,
+    identifier: &str,
+    params: &RegisterIndexerParams,
+    claims: &Claims,
+    schema_manager: &RwLock<SchemaManager>,
+    multipart: &mut Multipart,
+    conn: &mut IndexerConnection,
+) -> ApiResult<Vec<IndexAsset>> {
+    let mut assets: Vec<IndexAsset> = Vec::new();
+
+    while let Some(field) = multipart.next_field().await.unwrap() {
+        let name = field.name().unwrap_or("").to_string();
+        let data = field.bytes().await.unwrap_or_default();
+        let asset_type = IndexAssetType::from_str(&name).expect("Invalid asset type.");
+
+        let asset: IndexAsset = match asset_type {
+            IndexAssetType::Wasm | IndexAssetType::Manifest => {
+                queries::register_index_asset(
+                    conn,
+                    namespace,
+                    identifier,
+                    data.to_vec(),
+                    asset_type,
+                    Some(&claims.sub),
+                )
+                .await?
+            }
+            IndexAssetType::Schema => {
+                match queries::register_index_asset(
+                    conn,
+                    namespace,
+                    identifier,
+                    data.to_vec(),
+                    IndexAssetType::Schema,
+                    Some(&claims.sub),
+                )
+                .await
+                {
+                    Ok(result) => {
+                        schema_manager
+                            .write()
+                            .await
+                            .migrate_schema(
+                                namespace,
+                                identifier,
+                                &String::from_utf8_lossy(&data),
+                                params.force_migration,
+                                conn,
+                            )
+                            .await?;
+
+                        result
+                    }
+                    Err(e) => {
+                        return Err(e.into());
+                    }
+                }
+            }
+        };
+
+        assets.push(asset);
+    }
+
+    Ok(assets)
+}
+
+/// Deploy new assets for a registered indexer alongside its current version.
+///
+/// The new version indexes into a shadow schema until it catches up with the
+/// chain, and only then replaces the current version.
+async fn register_shadow_deployment(
+    namespace: &str,
+    identifier: &str,
+    index_id: &i64,
+    mut multipart: Multipart,
+    conn: &mut IndexerConnection,
+    db_type: DbType,
+) -> ApiResult<()> {
+    let (mut wasm, mut manifest, mut schema) = (None, None, None);
+
+    while let Some(field) = multipart.next_field().await.unwrap() {
+        let name = field.name().unwrap_or("").to_string();
+        let data = field.bytes().await.unwrap_or_default().to_vec();
+
+        match IndexAssetType::from_str(&name).expect("Invalid asset type.") {
+            IndexAssetType::Wasm => wasm = Some(data),
+            IndexAssetType::Manifest => manifest = Some(data),
+            IndexAssetType::Schema => schema = Some(data),
+        }
+    }
+
+    match (wasm, manifest, schema) {
+        (Some(wasm), Some(manifest), Some(schema)) => {
+            // The service only picks these up later, so anything it couldn't
+            // start is rejected here.
+            Manifest::try_from(&manifest)?;
+            validate_schema(namespace, identifier, &schema, db_type)?;
+
+            queries::put_shadow_deployment(conn, index_id, wasm, manifest, schema)
+                .await?;
+            Ok(())
+        }
+        _ => Err(ApiError::Http(HttpError::BadRequest)),
+    }
+}
+
+/// Check that `schema` can be built into tables, which would otherwise only
+/// fail once the indexer is started.
+fn validate_schema(
+    namespace: &str,
+    identifier: &str,
+    schema: &[u8],
+    db_type: DbType,
+) -> ApiResult<()> {
+    let schema = std::str::from_utf8(schema)
+        .map_err(|e| ApiError::InvalidSchema(e.to_string()))?;
+    let schema = inject_native_entities_into_schema(schema);
+
+    SchemaBuilder::new(namespace, identifier, "", db_type).build(&schema)?;
+
+    Ok(())
+}
+
 pub(crate) async fn get_nonce(
     Extension(pool): Extension<IndexerConnectionPool>,
 ) -> ApiResult<axum::Json<Value>> {
@@ -354,20 +595,48 @@ pub(crate) async fn verify_signature(
 
 pub async fn run_query(
     query: String,
+    variables: HashMap<String, Value>,
+    operation_name: Option<String>,
+    subscription: bool,
     schema: Schema,
-    pool: &IndexerConnectionPool,
+    db_type: DbType,
+    conn: &mut IndexerConnection,
 ) -> ApiResult<Value> {
-    let builder = GraphqlQueryBuilder::new(&schema, &query)?;
-    let query = builder.build()?;
+    let builder = GraphqlQueryBuilder::new(&schema, &query)?
+        .variables(variables)
+        .operation_name(operation_name);
+    if builder.is_subscription() && !subscription {
+        return Err(GraphqlError::SubscriptionWithoutWebSocket.into());
+    }
+    if builder.is_introspection() {
+        return Ok(serde_json::json!(QueryResponse {
+            data: builder.introspect()?
+        }));
+    }
 
-    let queries = query.as_sql(&schema, pool.database_type())?.join(";\n");
+    let query = builder.build()?;
+    let is_connection = query.is_connection();
 
-    let mut conn = pool.acquire().await?;
+    let queries = query.as_sql(&schema, db_type)?.join(";\n");
 
-    match queries::run_query(&mut conn, queries).await {
+    match queries::run_query(conn, queries).await {
         Ok(ans) => {
             let ans_json: Value = serde_json::from_value(ans)?;
 
+            // Each connection is returned as an object keyed by its field, and
+            // these are merged into the response data.
+            if is_connection {
+                let mut data = serde_json::Map::new();
+                for row in ans_json.as_array().into_iter().flatten() {
+                    if let Value::Object(fields) = row {
+                        data.extend(fields.clone());
+                    }
+                }
+                return Ok(serde_json::json!(QueryResponse {
+                    data: Value::Object(data)
+                }));
+            }
+
             // If the response is paginated, remove the array wrapping.
             if ans_json[0].get("page_info").is_some() {
                 Ok(serde_json::json!(QueryResponse {
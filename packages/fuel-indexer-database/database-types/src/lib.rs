@@ -46,6 +46,18 @@ pub struct NewGraphRoot {
     pub schema: String,
 }
 
+/// A change to the tables of an indexer's schema, as applied when the
+/// indexer's GraphQL schema changed.
+#[derive(Debug)]
+pub struct NewSchemaMigration {
+    pub schema_name: String,
+    pub schema_identifier: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub statements: Vec<String>,
+    pub forced: bool,
+}
+
 #[derive(Debug)]
 pub struct TypeId {
     pub id: i64,
@@ -54,6 +66,8 @@ pub struct TypeId {
     pub schema_identifier: String,
     pub graphql_name: String,
     pub table_name: String,
+    /// Whether the type's table keeps the history of its entities.
+    pub versioned: bool,
 }
 
 #[derive(Debug)]
@@ -89,13 +103,13 @@ pub struct Columns {
 }
 
 impl NewColumn {
-    pub fn sql_fragment(&self) -> String {
+    pub fn sql_fragment(&self, db_type: &DbType) -> String {
         let null_frag = if self.nullable { "" } else { "not null" };
         let unique_frag = if self.unique { "unique" } else { "" };
         format!(
             "{} {} {} {}",
             self.column_name,
-            self.sql_type(),
+            self.sql_type(db_type),
             null_frag,
             unique_frag
         )
@@ -103,7 +117,25 @@ impl NewColumn {
         .to_string()
     }
 
-    /// Derive the respective PostgreSQL field type for a given `NewColumn`
+    /// The column's definition in the history table of its entity, where each
+    /// entity has a row per version, and only the ID is constrained.
+    pub fn history_sql_fragment(&self, db_type: &DbType) -> String {
+        let id_type = match db_type {
+            DbType::Postgres => "numeric(20, 0)",
+            DbType::Sqlite => "numeric",
+        };
+
+        if self.column_name == IdCol::to_lowercase_string() {
+            return format!("{} {id_type} not null", self.column_name);
+        }
+
+        match ColumnType::from(self.column_type.as_str()) {
+            ColumnType::ID => format!("{} {id_type}", self.column_name),
+            _ => format!("{} {}", self.column_name, self.sql_type(db_type)),
+        }
+    }
+
+    /// Derive the respective database field type for a given `NewColumn`
     ///
     /// Here we're essentially matching `ColumnType`s to PostgreSQL field
     /// types. Note that we're using `numeric` field types for integer-like
@@ -111,33 +143,68 @@ impl NewColumn {
     /// crates don't play well with unsigned integers (e.g., `sqlx`), so we
     /// just define these types as `numeric`, then convert them into their base
     /// types (e.g., u64) using `BigDecimal`.
-    fn sql_type(&self) -> &str {
-        match ColumnType::from(self.column_type.as_str()) {
-            ColumnType::ID => "numeric(20, 0) primary key",
-            ColumnType::Address => "varchar(64)",
-            ColumnType::Bytes4 => "varchar(8)",
-            ColumnType::Bytes8 => "varchar(16)",
-            ColumnType::Bytes32 => "varchar(64)",
-            ColumnType::AssetId => "varchar(64)",
-            ColumnType::ContractId => "varchar(64)",
-            ColumnType::Salt => "varchar(64)",
-            ColumnType::Int4 => "integer",
-            ColumnType::Int8 => "bigint",
-            ColumnType::Int16 => "numeric(39, 0)",
-            ColumnType::UInt4 => "integer",
-            ColumnType::UInt8 => "numeric(20, 0)",
-            ColumnType::UInt16 => "numeric(39, 0)",
-            ColumnType::Timestamp => "timestamp",
-            ColumnType::Object => "bytea",
-            ColumnType::Blob => "varchar(10485760)",
-            ColumnType::ForeignKey => {
-                panic!("ForeignKey ColumnType is a reference type only.")
-            }
-            ColumnType::Json => "Json",
-            ColumnType::MessageId => "varchar(64)",
-            ColumnType::Charfield => "varchar(255)",
-            ColumnType::Identity => "varchar(66)",
-            ColumnType::Boolean => "boolean",
+    ///
+    /// SQLite only has a handful of storage classes, so its columns are
+    /// declared with the affinity their values are stored with. Its integers
+    /// are signed, so unsigned 64-bit values, like IDs, are stored with
+    /// `sqlite_uint`. Wider integers are stored as floating point numbers in
+    /// `numeric` columns, and lose precision.
+    fn sql_type(&self, db_type: &DbType) -> &str {
+        let typ = ColumnType::from(self.column_type.as_str());
+
+        match db_type {
+            DbType::Postgres => match typ {
+                ColumnType::ID => "numeric(20, 0) primary key",
+                ColumnType::Address => "varchar(64)",
+                ColumnType::Bytes4 => "varchar(8)",
+                ColumnType::Bytes8 => "varchar(16)",
+                ColumnType::Bytes32 => "varchar(64)",
+                ColumnType::AssetId => "varchar(64)",
+                ColumnType::ContractId => "varchar(64)",
+                ColumnType::Salt => "varchar(64)",
+                ColumnType::Int4 => "integer",
+                ColumnType::Int8 => "bigint",
+                ColumnType::Int16 => "numeric(39, 0)",
+                ColumnType::UInt4 => "integer",
+                ColumnType::UInt8 => "numeric(20, 0)",
+                ColumnType::UInt16 => "numeric(39, 0)",
+                ColumnType::Timestamp => "timestamp",
+                ColumnType::Object => "bytea",
+                ColumnType::Blob => "varchar(10485760)",
+                ColumnType::ForeignKey => {
+                    panic!("ForeignKey ColumnType is a reference type only.")
+                }
+                ColumnType::Json => "Json",
+                ColumnType::MessageId => "varchar(64)",
+                ColumnType::Charfield => "varchar(255)",
+                ColumnType::Identity => "varchar(66)",
+                ColumnType::Boolean => "boolean",
+            },
+            DbType::Sqlite => match typ {
+                ColumnType::ID => "numeric primary key",
+                ColumnType::Address
+                | ColumnType::Bytes4
+                | ColumnType::Bytes8
+                | ColumnType::Bytes32
+                | ColumnType::AssetId
+                | ColumnType::ContractId
+                | ColumnType::Salt
+                | ColumnType::Blob
+                | ColumnType::Json
+                | ColumnType::MessageId
+                | ColumnType::Charfield
+                | ColumnType::Identity => "text",
+                ColumnType::Int4
+                | ColumnType::Int8
+                | ColumnType::UInt4
+                | ColumnType::Timestamp => "integer",
+                ColumnType::Int16 | ColumnType::UInt8 | ColumnType::UInt16 => "numeric",
+                ColumnType::Object => "blob",
+                ColumnType::ForeignKey => {
+                    panic!("ForeignKey ColumnType is a reference type only.")
+                }
+                ColumnType::Boolean => "boolean",
+            },
         }
     }
 }
@@ -149,6 +216,25 @@ pub struct ColumnInfo {
     pub column_position: i32,
     pub column_name: String,
     pub column_type: String,
+    /// Whether the column's table keeps the history of its entities.
+    pub versioned: bool,
+}
+
+/// The table that keeps the history of the entities in `table`.
+pub fn history_table_name(table: &str) -> String {
+    format!("{table}_history")
+}
+
+/// SQLite integers are signed, so unsigned 64-bit values, like IDs, are
+/// stored offset by 2^63: they're exact, and ordered as they would be
+/// unsigned. Queries read them back with `from_sqlite_uint`.
+pub fn sqlite_uint(n: u64) -> i64 {
+    (n ^ (1 << 63)) as i64
+}
+
+/// The unsigned value stored by `sqlite_uint`.
+pub fn from_sqlite_uint(n: i64) -> u64 {
+    n as u64 ^ (1 << 63)
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -245,9 +331,10 @@ impl From<i32> for ColumnType {
     }
 }
 
-impl From<&str> for ColumnType {
-    fn from(name: &str) -> ColumnType {
-        match name {
+impl ColumnType {
+    /// The column type of a GraphQL scalar, if it can be stored.
+    pub fn from_name(name: &str) -> Option<ColumnType> {
+        let typ = match name {
             "ID" => ColumnType::ID,
             "Address" => ColumnType::Address,
             "AssetId" => ColumnType::AssetId,
@@ -271,7 +358,50 @@ impl From<&str> for ColumnType {
             "Object" => ColumnType::Object,
             "UInt16" => ColumnType::UInt16,
             "Int16" => ColumnType::Int16,
-            _ => panic!("Invalid column type: '{name}'"),
+            _ => return None,
+        };
+
+        Some(typ)
+    }
+}
+
+impl From<&str> for ColumnType {
+    fn from(name: &str) -> ColumnType {
+        ColumnType::from_name(name)
+            .unwrap_or_else(|| panic!("Invalid column type: '{name}'"))
+    }
+}
+
+/// A column value bound to a query parameter, typed to match the SQL type of
+/// its column, so that values never have to be spliced into query text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindValue {
+    Integer(Option<i32>),
+    BigInt(Option<i64>),
+    /// Unsigned 64-bit integers, like IDs.
+    UInt8(Option<u64>),
+    /// Integers too wide for `bigint`, as decimal text.
+    Numeric(Option<String>),
+    /// Seconds since the Unix epoch.
+    Timestamp(Option<i64>),
+    Text(Option<String>),
+    Json(Option<String>),
+    Boolean(Option<bool>),
+    Bytes(Option<Vec<u8>>),
+}
+
+impl BindValue {
+    pub fn is_null(&self) -> bool {
+        match self {
+            BindValue::Integer(v) => v.is_none(),
+            BindValue::BigInt(v) => v.is_none(),
+            BindValue::UInt8(v) => v.is_none(),
+            BindValue::Numeric(v) => v.is_none(),
+            BindValue::Timestamp(v) => v.is_none(),
+            BindValue::Text(v) => v.is_none(),
+            BindValue::Json(v) => v.is_none(),
+            BindValue::Boolean(v) => v.is_none(),
+            BindValue::Bytes(v) => v.is_none(),
         }
     }
 }
@@ -309,6 +439,9 @@ pub struct RegisteredIndex {
     pub namespace: String,
     pub identifier: String,
     pub pubkey: Option<String>,
+    pub completed: bool,
+    pub status: IndexerStatus,
+    pub status_message: Option<String>,
 }
 
 impl RegisteredIndex {
@@ -317,16 +450,45 @@ impl RegisteredIndex {
     }
 }
 
+/// Whether an indexer's handler is currently succeeding.
+#[derive(
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    EnumString,
+    AsRefStr,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerStatus {
+    #[default]
+    #[strum(serialize = "running")]
+    Running,
+    #[strum(serialize = "retrying")]
+    Retrying,
+    #[strum(serialize = "halted")]
+    Halted,
+}
+
 #[derive(Eq, PartialEq, Debug, Clone, Default)]
 pub enum DbType {
     #[default]
     Postgres,
+    Sqlite,
 }
 
 impl DbType {
+    /// The name that `table_name` is referred to by in queries. SQLite has no
+    /// schemas, so the tables of every indexer live side by side, prefixed
+    /// with their namespace.
     pub fn table_name(&self, namespace: &str, table_name: &str) -> String {
         match self {
             DbType::Postgres => format!("{namespace}.{table_name}"),
+            DbType::Sqlite => format!("{namespace}__{table_name}"),
         }
     }
 }
@@ -370,6 +532,15 @@ impl CreateStatement for ColumnIndex {
                     self.column_name
                 );
             }
+            DbType::Sqlite => {
+                let _ = write!(
+                    frag,
+                    "INDEX {} ON {} ({});",
+                    self.db_type.table_name(&self.namespace, &self.name()),
+                    self.db_type.table_name(&self.namespace, &self.table_name),
+                    self.column_name
+                );
+            }
         }
 
         frag
@@ -438,6 +609,19 @@ impl ForeignKey {
             self.reference_column_name
         )
     }
+
+    /// The constraint as declared alongside its column, which is how SQLite
+    /// tables get their foreign keys, since they can't be added afterwards.
+    pub fn column_constraint(&self) -> String {
+        format!(
+            "REFERENCES {}({}) ON DELETE {} ON UPDATE {} DEFERRABLE INITIALLY DEFERRED",
+            self.db_type
+                .table_name(&self.namespace, &self.reference_table_name),
+            self.reference_column_name,
+            self.on_delete.as_ref(),
+            self.on_update.as_ref()
+        )
+    }
 }
 
 impl CreateStatement for ForeignKey {
@@ -457,6 +641,8 @@ impl CreateStatement for ForeignKey {
                     self.on_update.as_ref()
                 )
             }
+            // Declared with the column instead, see `column_constraint`.
+            DbType::Sqlite => String::new(),
         }
     }
 }
@@ -488,3 +674,46 @@ impl Nonce {
         now >= self.expiry
     }
 }
+
+/// The last block an indexer committed, written in the same transaction as
+/// the handler's writes so that the indexer can resume exactly where it left off.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexerCheckpoint {
+    pub namespace: String,
+    pub identifier: String,
+    pub block_height: u64,
+    pub block_id: Option<String>,
+    pub cursor: Option<String>,
+}
+
+/// A block that an indexer's handler could not index, and that was skipped.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub block_height: u64,
+    pub block_id: String,
+    pub error: String,
+}
+
+/// A new version of an indexer, indexing into shadow tables until it has
+/// caught up with the chain and replaces the running version.
+#[derive(Debug)]
+pub struct ShadowDeployment {
+    pub index_id: i64,
+    pub namespace: String,
+    pub identifier: String,
+    pub wasm: Vec<u8>,
+    pub manifest: Vec<u8>,
+    pub schema: Vec<u8>,
+    pub caught_up: bool,
+}
+
+/// The state of an entity row before a block batch wrote to it, kept so
+/// that the batch can be undone if the chain reorganizes.
+#[derive(Debug)]
+pub struct RollbackJournalEntry {
+    pub id: i64,
+    pub block_height: i64,
+    pub type_id: i64,
+    pub object_id: u64,
+    pub object: Option<Vec<u8>>,
+}
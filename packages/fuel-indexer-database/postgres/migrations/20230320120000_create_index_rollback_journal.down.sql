@@ -0,0 +1 @@
+drop table if exists index_rollback_journal;
@@ -0,0 +1,12 @@
+create table if not exists index_rollback_journal (
+   id bigserial primary key,
+   namespace varchar(32) not null,
+   identifier varchar(32) not null,
+   block_height bigint not null,
+   type_id bigint not null,
+   object_id bigint not null,
+   object bytea
+);
+
+create index if not exists index_rollback_journal_height_idx
+    on index_rollback_journal (namespace, identifier, block_height);
@@ -0,0 +1 @@
+alter table index_registry drop column completed;
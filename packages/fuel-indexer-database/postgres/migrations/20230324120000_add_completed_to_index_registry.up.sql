@@ -0,0 +1 @@
+alter table index_registry add column completed boolean not null default false;
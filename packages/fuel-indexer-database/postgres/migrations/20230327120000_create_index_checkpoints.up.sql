@@ -0,0 +1,9 @@
+create table if not exists index_checkpoints (
+   namespace varchar(32) not null,
+   identifier varchar(32) not null,
+   block_height bigint not null,
+   block_id varchar(66),
+   cursor varchar(255),
+   updated_at timestamp not null default now(),
+   primary key (namespace, identifier)
+);
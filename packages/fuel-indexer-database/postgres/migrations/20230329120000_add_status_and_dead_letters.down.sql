@@ -0,0 +1,4 @@
+drop table if exists index_dead_letters;
+
+alter table index_registry drop column status_message;
+alter table index_registry drop column status;
@@ -0,0 +1,15 @@
+alter table index_registry add column status varchar(16) not null default 'running';
+alter table index_registry add column status_message text;
+
+create table if not exists index_dead_letters (
+   id bigserial primary key,
+   namespace varchar(32) not null,
+   identifier varchar(32) not null,
+   block_height bigint not null,
+   block_id varchar(66) not null,
+   error text not null,
+   created_at timestamp not null default now()
+);
+
+create index if not exists index_dead_letters_indexer_idx
+    on index_dead_letters (namespace, identifier);
@@ -0,0 +1 @@
+drop table if exists graph_registry_schema_migrations;
@@ -0,0 +1,10 @@
+create table if not exists graph_registry_schema_migrations (
+   id bigserial primary key,
+   schema_name varchar(32) not null,
+   schema_identifier varchar(255) not null,
+   from_version varchar(512) not null,
+   to_version varchar(512) not null,
+   statements text not null,
+   forced boolean not null,
+   created_at timestamp not null default now()
+);
@@ -0,0 +1,5 @@
+drop table if exists index_shadow_deployments;
+
+alter table index_checkpoints alter column identifier type varchar(32);
+alter table index_rollback_journal alter column identifier type varchar(32);
+alter table index_dead_letters alter column identifier type varchar(32);
@@ -0,0 +1,18 @@
+create table if not exists index_shadow_deployments (
+   id bigserial primary key,
+   index_id bigint not null unique,
+   wasm bytea not null,
+   manifest bytea not null,
+   schema bytea not null,
+   caught_up boolean not null default false,
+   created_at timestamp not null default now(),
+    constraint fk_index_registry_id
+        foreign key(index_id)
+            references index_registry(id)
+            on delete cascade
+	        deferrable initially deferred
+);
+
+alter table index_checkpoints alter column identifier type varchar(255);
+alter table index_rollback_journal alter column identifier type varchar(255);
+alter table index_dead_letters alter column identifier type varchar(255);
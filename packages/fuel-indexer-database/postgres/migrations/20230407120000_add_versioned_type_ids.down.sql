@@ -0,0 +1 @@
+alter table graph_registry_type_ids drop column versioned;
@@ -0,0 +1 @@
+alter table graph_registry_type_ids add column versioned boolean not null default false;
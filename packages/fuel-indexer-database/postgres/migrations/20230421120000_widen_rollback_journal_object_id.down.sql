@@ -0,0 +1 @@
+alter table index_rollback_journal alter column object_id type bigint;
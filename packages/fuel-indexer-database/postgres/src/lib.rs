@@ -1,8 +1,16 @@
 #![deny(unused_crate_dependencies)]
 
 use fuel_indexer_database_types::*;
-use fuel_indexer_lib::utils::sha256_digest;
-use sqlx::{pool::PoolConnection, postgres::PgRow, types::JsonValue, Postgres, Row};
+use fuel_indexer_lib::{
+    defaults::{COMMIT_NOTIFICATION_CHANNEL, SHADOW_IDENTIFIER_SUFFIX},
+    utils::sha256_digest,
+};
+use sqlx::{
+    pool::PoolConnection,
+    postgres::{PgArguments, PgRow},
+    types::JsonValue,
+    Postgres, Row,
+};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::info;
 
@@ -11,39 +19,214 @@ use fuel_indexer_metrics::METRICS;
 
 const NONCE_EXPIRY: u64 = 3600; // 1 hour
 
-pub async fn put_object(
+/// Max number of parameters Postgres accepts in a single statement.
+const MAX_BIND_PARAMS: usize = u16::MAX as usize;
+
+/// Placeholder for parameter `index`, cast where Postgres can't convert the
+/// bound value to its column's type on assignment.
+fn placeholder(value: &BindValue, index: usize) -> String {
+    match value {
+        BindValue::UInt8(_) | BindValue::Numeric(_) => format!("${index}::numeric"),
+        BindValue::Timestamp(_) => format!("to_timestamp(${index})"),
+        BindValue::Json(_) => format!("${index}::json"),
+        _ => format!("${index}"),
+    }
+}
+
+fn bind_value(
+    query: sqlx::query::Query<'_, Postgres, PgArguments>,
+    value: BindValue,
+) -> sqlx::query::Query<'_, Postgres, PgArguments> {
+    match value {
+        BindValue::Integer(v) => query.bind(v),
+        BindValue::BigInt(v) | BindValue::Timestamp(v) => query.bind(v),
+        BindValue::UInt8(v) => query.bind(v.map(|n| n.to_string())),
+        BindValue::Numeric(v) | BindValue::Text(v) | BindValue::Json(v) => query.bind(v),
+        BindValue::Boolean(v) => query.bind(v),
+        BindValue::Bytes(v) => query.bind(v),
+    }
+}
+
+/// Insert or update entity rows, each holding a value for every one of
+/// `columns`, in as few statements as possible.
+pub async fn put_objects(
     conn: &mut PoolConnection<Postgres>,
-    query: String,
-    bytes: Vec<u8>,
+    table: &str,
+    columns: &[String],
+    rows: Vec<Vec<BindValue>>,
 ) -> sqlx::Result<usize> {
     #[cfg(feature = "metrics")]
-    METRICS.db.postgres.put_object_calls.inc();
+    METRICS.db.postgres.put_objects_calls.inc();
 
-    let mut builder = sqlx::QueryBuilder::new(query);
+    let updates: Vec<_> = columns
+        .iter()
+        .filter(|c| *c != &IdCol::to_lowercase_string())
+        .map(|c| format!("{c} = excluded.{c}"))
+        .collect();
 
-    let query = builder.build();
-    let query = query.bind(bytes);
-    let result = query.execute(conn).await?;
+    let rows_per_statement = (MAX_BIND_PARAMS / columns.len().max(1)).max(1);
+    let mut rows = rows.into_iter().peekable();
+    let mut affected = 0;
 
-    Ok(result.rows_affected() as usize)
+    while rows.peek().is_some() {
+        let batch: Vec<Vec<BindValue>> = rows.by_ref().take(rows_per_statement).collect();
+
+        let mut index = 0;
+        let values: Vec<_> = batch
+            .iter()
+            .map(|row| {
+                let placeholders: Vec<_> = row
+                    .iter()
+                    .map(|value| {
+                        index += 1;
+                        placeholder(value, index)
+                    })
+                    .collect();
+                format!("({})", placeholders.join(", "))
+            })
+            .collect();
+
+        let query = format!(
+            "INSERT INTO {table}
+                ({})
+             VALUES
+                {}
+             ON CONFLICT(id)
+             DO UPDATE SET {}",
+            columns.join(", "),
+            values.join(", "),
+            updates.join(", "),
+        );
+
+        let mut query = sqlx::query(&query);
+        for value in batch.into_iter().flatten() {
+            query = bind_value(query, value);
+        }
+        affected += query.execute(&mut *conn).await?.rows_affected() as usize;
+    }
+
+    Ok(affected)
 }
 
 pub async fn get_object(
     conn: &mut PoolConnection<Postgres>,
-    query: String,
+    table: &str,
+    object_id: u64,
 ) -> sqlx::Result<Vec<u8>> {
     #[cfg(feature = "metrics")]
     METRICS.db.postgres.get_object_calls.inc();
 
-    let mut builder = sqlx::QueryBuilder::new(query);
-
-    let query = builder.build();
+    let query = format!("SELECT object FROM {table} WHERE id = $1::numeric");
 
-    let row = query.fetch_one(conn).await?;
+    let row = sqlx::query(&query)
+        .bind(object_id.to_string())
+        .fetch_one(conn)
+        .await?;
 
     Ok(row.get(0))
 }
 
+/// Entity rows whose `columns` all have the given values, ordered by ID.
+pub async fn find_objects(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+    columns: Vec<(String, BindValue)>,
+    limit: Option<u64>,
+) -> sqlx::Result<Vec<Vec<u8>>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.find_objects_calls.inc();
+
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    for (column, value) in columns {
+        if value.is_null() {
+            conditions.push(format!("{column} IS NULL"));
+        } else {
+            conditions.push(format!(
+                "{column} = {}",
+                placeholder(&value, values.len() + 1)
+            ));
+            values.push(value);
+        }
+    }
+
+    let mut query = format!("SELECT object FROM {table}");
+    if !conditions.is_empty() {
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    query.push_str(" ORDER BY id");
+    if let Some(limit) = limit {
+        query.push_str(&format!(" LIMIT {limit}"));
+    }
+
+    let mut query = sqlx::query(&query);
+    for value in values {
+        query = bind_value(query, value);
+    }
+
+    Ok(query
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| row.get(0))
+        .collect())
+}
+
+/// Up to `limit` entity rows with IDs above `after`, as their IDs and
+/// serialized objects, ordered by ID.
+pub async fn list_objects(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+    after: Option<u64>,
+    limit: i64,
+) -> sqlx::Result<Vec<(u64, Vec<u8>)>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.list_objects_calls.inc();
+
+    let query = format!(
+        "SELECT id::text, object FROM {table}
+        WHERE $1::numeric IS NULL OR id > $1::numeric
+        ORDER BY id LIMIT $2"
+    );
+
+    sqlx::query(&query)
+        .bind(after.map(|id| id.to_string()))
+        .bind(limit)
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let id: String = row.get(0);
+            let id = id
+                .parse::<u64>()
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            Ok((id, row.get(1)))
+        })
+        .collect()
+}
+
+/// Replace the serialized object of an entity row, leaving its columns as
+/// they are.
+pub async fn set_object_bytes(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+    object_id: u64,
+    bytes: Vec<u8>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.set_object_bytes_calls.inc();
+
+    let query = format!("UPDATE {table} SET object = $1 WHERE id = $2::numeric");
+
+    let result = sqlx::query(&query)
+        .bind(bytes)
+        .bind(object_id.to_string())
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
 pub async fn run_migration(conn: &mut PoolConnection<Postgres>) -> sqlx::Result<()> {
     #[cfg(feature = "metrics")]
     METRICS.db.postgres.run_migration_calls.inc();
@@ -201,6 +384,78 @@ pub async fn graph_root_latest(
     })
 }
 
+/// Remove the type and column metadata of every version of an indexer's
+/// schema, along with the graph root of `version`, so that `version` can be
+/// registered in their place.
+pub async fn remove_schema_metadata(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    version: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.remove_schema_metadata_calls.inc();
+
+    let mut removed = 0;
+
+    for query in [
+        "DELETE FROM graph_registry_columns WHERE type_id IN (
+            SELECT id FROM graph_registry_type_ids
+            WHERE schema_name = $1 AND schema_identifier = $2)",
+        "DELETE FROM graph_registry_type_ids
+        WHERE schema_name = $1 AND schema_identifier = $2",
+    ] {
+        removed += sqlx::query(query)
+            .bind(namespace)
+            .bind(identifier)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+
+    for query in [
+        "DELETE FROM graph_registry_root_columns WHERE root_id IN (
+            SELECT id FROM graph_registry_graph_root
+            WHERE schema_name = $1 AND schema_identifier = $2 AND version = $3)",
+        "DELETE FROM graph_registry_graph_root
+        WHERE schema_name = $1 AND schema_identifier = $2 AND version = $3",
+    ] {
+        removed += sqlx::query(query)
+            .bind(namespace)
+            .bind(identifier)
+            .bind(version)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+
+    Ok(removed as usize)
+}
+
+pub async fn new_schema_migration(
+    conn: &mut PoolConnection<Postgres>,
+    migration: NewSchemaMigration,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.new_schema_migration_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO graph_registry_schema_migrations
+            (schema_name, schema_identifier, from_version, to_version, statements, forced)
+        VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(migration.schema_name)
+    .bind(migration.schema_identifier)
+    .bind(migration.from_version)
+    .bind(migration.to_version)
+    .bind(migration.statements.join(";\n"))
+    .bind(migration.forced)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
 pub async fn type_id_list_by_name(
     conn: &mut PoolConnection<Postgres>,
     namespace: &str,
@@ -228,6 +483,7 @@ pub async fn type_id_list_by_name(
         let schema_name: String = row.get(2);
         let graphql_name: String = row.get(3);
         let table_name: String = row.get(4);
+        let versioned: bool = row.get("versioned");
 
         TypeId {
             id,
@@ -236,6 +492,7 @@ pub async fn type_id_list_by_name(
             table_name,
             graphql_name,
             schema_identifier: identifier.to_string(),
+            versioned,
         }
     })
     .collect::<Vec<TypeId>>())
@@ -272,7 +529,7 @@ pub async fn type_id_insert(
     #[cfg(feature = "metrics")]
     METRICS.db.postgres.type_id_insert_calls.inc();
 
-    let mut builder = sqlx::QueryBuilder::new("INSERT INTO graph_registry_type_ids (id, schema_version, schema_name, schema_identifier, graphql_name, table_name)");
+    let mut builder = sqlx::QueryBuilder::new("INSERT INTO graph_registry_type_ids (id, schema_version, schema_name, schema_identifier, graphql_name, table_name, versioned)");
 
     builder.push_values(type_ids.into_iter(), |mut b, tid| {
         b.push_bind(tid.id)
@@ -280,7 +537,8 @@ pub async fn type_id_insert(
             .push_bind(tid.schema_name)
             .push_bind(tid.schema_identifier)
             .push_bind(tid.graphql_name)
-            .push_bind(tid.table_name);
+            .push_bind(tid.table_name)
+            .push_bind(tid.versioned);
     });
 
     let query = builder.build();
@@ -393,7 +651,8 @@ pub async fn columns_get_schema(
             t.table_name as table_name,
             c.column_position as column_position,
             c.column_name as column_name,
-            c.column_type as column_type
+            c.column_type as column_type,
+            t.versioned as versioned
             FROM graph_registry_type_ids as t
             INNER JOIN graph_registry_columns as c ON t.id = c.type_id
             WHERE t.schema_name = $1 
@@ -413,6 +672,7 @@ pub async fn columns_get_schema(
         let column_position: i32 = row.get(2);
         let column_name: String = row.get(3);
         let column_type: String = row.get(4);
+        let versioned: bool = row.get(5);
 
         ColumnInfo {
             type_id,
@@ -420,6 +680,7 @@ pub async fn columns_get_schema(
             column_position,
             column_name,
             column_type,
+            versioned,
         }
     })
     .collect::<Vec<ColumnInfo>>())
@@ -448,6 +709,9 @@ pub async fn index_is_registered(
             namespace: row.get(1),
             identifier: row.get(2),
             pubkey: row.get(3),
+            completed: row.get(4),
+            status: row.get::<String, _>(5).parse().unwrap_or_default(),
+            status_message: row.get(6),
         })),
         None => Ok(None),
     }
@@ -481,12 +745,18 @@ pub async fn register_index(
     let namespace: String = row.get(1);
     let identifier: String = row.get(2);
     let pubkey = row.get(3);
+    let completed: bool = row.get(4);
+    let status: String = row.get(5);
+    let status_message = row.get(6);
 
     Ok(RegisteredIndex {
         id,
         namespace,
         identifier,
         pubkey,
+        completed,
+        status: status.parse().unwrap_or_default(),
+        status_message,
     })
 }
 
@@ -505,17 +775,73 @@ pub async fn registered_indices(
             let namespace: String = row.get(1);
             let identifier: String = row.get(2);
             let pubkey = row.get(3);
+            let completed: bool = row.get(4);
+            let status: String = row.get(5);
+            let status_message = row.get(6);
 
             RegisteredIndex {
                 id,
                 namespace,
                 identifier,
                 pubkey,
+                completed,
+                status: status.parse().unwrap_or_default(),
+                status_message,
             }
         })
         .collect::<Vec<RegisteredIndex>>())
 }
 
+pub async fn set_index_completed(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    completed: bool,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.set_index_completed_calls.inc();
+
+    let result = sqlx::query(
+        "UPDATE index_registry
+        SET completed = $1
+        WHERE namespace = $2
+        AND identifier = $3",
+    )
+    .bind(completed)
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn set_index_status(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    status: IndexerStatus,
+    message: Option<&str>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.set_index_status_calls.inc();
+
+    let result = sqlx::query(
+        "UPDATE index_registry
+        SET status = $1, status_message = $2
+        WHERE namespace = $3
+        AND identifier = $4",
+    )
+    .bind(status.as_ref())
+    .bind(message)
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
 pub async fn index_asset_version(
     conn: &mut PoolConnection<Postgres>,
     index_id: &i64,
@@ -657,29 +983,152 @@ pub async fn latest_assets_for_index(
     })
 }
 
-pub async fn last_block_height_for_indexer(
+pub async fn put_checkpoint(
     conn: &mut PoolConnection<Postgres>,
     namespace: &str,
     identifier: &str,
-) -> sqlx::Result<u64> {
+    block_height: u64,
+    block_id: Option<&str>,
+    cursor: Option<&str>,
+) -> sqlx::Result<usize> {
     #[cfg(feature = "metrics")]
-    METRICS
-        .db
-        .postgres
-        .last_block_height_for_indexer_calls
-        .inc();
+    METRICS.db.postgres.put_checkpoint_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO index_checkpoints (namespace, identifier, block_height, block_id, cursor)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (namespace, identifier)
+        DO UPDATE SET
+            block_height = excluded.block_height,
+            block_id = excluded.block_id,
+            cursor = excluded.cursor,
+            updated_at = now()",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(block_height as i64)
+    .bind(block_id)
+    .bind(cursor)
+    .execute(conn)
+    .await?;
 
-    let query = format!(
-        "SELECT MAX(id) FROM {namespace}_{identifier}.indexmetadataentity LIMIT 1"
-    );
+    Ok(result.rows_affected() as usize)
+}
 
-    let row = sqlx::query(&query).fetch_one(conn).await?;
-    let id: i64 = match row.try_get(0) {
-        Ok(id) => id,
-        Err(_e) => return Ok(1),
-    };
+pub async fn checkpoint_for_indexer(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Option<IndexerCheckpoint>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.checkpoint_for_indexer_calls.inc();
 
-    Ok(id as u64)
+    Ok(sqlx::query(
+        "SELECT namespace, identifier, block_height, block_id, cursor
+        FROM index_checkpoints
+        WHERE namespace = $1
+        AND identifier = $2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_optional(conn)
+    .await?
+    .map(|row| {
+        let namespace: String = row.get(0);
+        let identifier: String = row.get(1);
+        let block_height: i64 = row.get(2);
+        let block_id: Option<String> = row.get(3);
+        let cursor: Option<String> = row.get(4);
+
+        IndexerCheckpoint {
+            namespace,
+            identifier,
+            block_height: block_height as u64,
+            block_id,
+            cursor,
+        }
+    }))
+}
+
+pub async fn put_dead_letter(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    block_height: u64,
+    block_id: &str,
+    error: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.put_dead_letter_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO index_dead_letters (namespace, identifier, block_height, block_id, error)
+        VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(block_height as i64)
+    .bind(block_id)
+    .bind(error)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn dead_letters(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Vec<DeadLetter>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.dead_letters_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT block_height, block_id, error
+        FROM index_dead_letters
+        WHERE namespace = $1
+        AND identifier = $2
+        ORDER BY block_height ASC",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let block_height: i64 = row.get(0);
+        let block_id: String = row.get(1);
+        let error: String = row.get(2);
+
+        DeadLetter {
+            block_height: block_height as u64,
+            block_id,
+            error,
+        }
+    })
+    .collect::<Vec<DeadLetter>>())
+}
+
+pub async fn remove_checkpoint(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.remove_checkpoint_calls.inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_checkpoints
+        WHERE namespace = $1
+        AND identifier = $2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
 }
 
 // TODO: https://github.com/FuelLabs/fuel-indexer/issues/251
@@ -838,6 +1287,218 @@ pub async fn remove_indexer(
     )
     .await?;
 
+    remove_checkpoint(conn, namespace, identifier).await?;
+
+    sqlx::query(
+        "DELETE FROM index_dead_letters
+        WHERE namespace = $1
+        AND identifier = $2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(&mut *conn)
+    .await?;
+
+    remove_indexer_state(
+        conn,
+        namespace,
+        &format!("{identifier}{SHADOW_IDENTIFIER_SUFFIX}"),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Store the assets of a blue/green deployment of an indexer, replacing any
+/// that hasn't caught up yet.
+pub async fn put_shadow_deployment(
+    conn: &mut PoolConnection<Postgres>,
+    index_id: &i64,
+    wasm: Vec<u8>,
+    manifest: Vec<u8>,
+    schema: Vec<u8>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.put_shadow_deployment_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO index_shadow_deployments (index_id, wasm, manifest, schema)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (index_id) DO UPDATE SET
+        wasm = excluded.wasm,
+        manifest = excluded.manifest,
+        schema = excluded.schema,
+        caught_up = false,
+        created_at = now()",
+    )
+    .bind(index_id)
+    .bind(wasm)
+    .bind(manifest)
+    .bind(schema)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn shadow_deployment_for(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Option<ShadowDeployment>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.shadow_deployment_for_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT s.index_id, r.namespace, r.identifier, s.wasm, s.manifest, s.schema, s.caught_up
+        FROM index_shadow_deployments AS s
+        INNER JOIN index_registry AS r ON r.id = s.index_id
+        WHERE r.namespace = $1
+        AND r.identifier = $2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_optional(conn)
+    .await?
+    .map(|row| ShadowDeployment {
+        index_id: row.get(0),
+        namespace: row.get(1),
+        identifier: row.get(2),
+        wasm: row.get(3),
+        manifest: row.get(4),
+        schema: row.get(5),
+        caught_up: row.get(6),
+    }))
+}
+
+/// Record that a blue/green deployment has caught up with the chain, and is
+/// ready to replace the running version of its indexer.
+pub async fn set_shadow_caught_up(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.set_shadow_caught_up_calls.inc();
+
+    let result = sqlx::query(
+        "UPDATE index_shadow_deployments SET caught_up = true
+        WHERE index_id IN (
+            SELECT id FROM index_registry WHERE namespace = $1 AND identifier = $2)",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn remove_shadow_deployment(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.remove_shadow_deployment_calls.inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_shadow_deployments
+        WHERE index_id IN (
+            SELECT id FROM index_registry WHERE namespace = $1 AND identifier = $2)",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Remove the checkpoint, rollback journal and dead letters recorded under an
+/// indexer's identifier.
+pub async fn remove_indexer_state(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.remove_indexer_state_calls.inc();
+
+    let mut removed = 0;
+
+    for table in [
+        "index_checkpoints",
+        "index_rollback_journal",
+        "index_dead_letters",
+    ] {
+        removed += sqlx::query(&format!(
+            "DELETE FROM {table} WHERE namespace = $1 AND identifier = $2"
+        ))
+        .bind(namespace)
+        .bind(identifier)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(removed as usize)
+}
+
+/// Move the checkpoint, rollback journal and dead letters recorded under one
+/// identifier to another.
+pub async fn rename_indexer_state(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    from_identifier: &str,
+    to_identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.rename_indexer_state_calls.inc();
+
+    let mut renamed = 0;
+
+    for table in [
+        "index_checkpoints",
+        "index_rollback_journal",
+        "index_dead_letters",
+    ] {
+        renamed += sqlx::query(&format!(
+            "UPDATE {table} SET identifier = $3 WHERE namespace = $1 AND identifier = $2"
+        ))
+        .bind(namespace)
+        .bind(from_identifier)
+        .bind(to_identifier)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(renamed as usize)
+}
+
+/// Take a lock on an indexer's tables until the open transaction ends.
+/// Shared locks are held while reading, and an exclusive one while the
+/// tables are swapped for those of a blue/green deployment.
+pub async fn lock_indexer(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    exclusive: bool,
+) -> sqlx::Result<()> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.lock_indexer_calls.inc();
+
+    let query = match exclusive {
+        true => "SELECT pg_advisory_xact_lock(hashtext($1))",
+        false => "SELECT pg_advisory_xact_lock_shared(hashtext($1))",
+    };
+
+    sqlx::query(query)
+        .bind(format!("{namespace}_{identifier}"))
+        .execute(conn)
+        .await?;
+
     Ok(())
 }
 
@@ -910,3 +1571,356 @@ pub async fn get_nonce(
 
     Ok(Nonce { uid, expiry })
 }
+
+pub async fn put_rollback_journal_entries(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    table: &str,
+    block_height: u64,
+    type_id: i64,
+    object_ids: &[u64],
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.put_rollback_journal_entries_calls.inc();
+
+    let query = format!(
+        "INSERT INTO index_rollback_journal
+            (namespace, identifier, block_height, type_id, object_id, object)
+        SELECT $1, $2, $3, $4, ids.id, t.object
+        FROM unnest($5::numeric[]) AS ids(id)
+        LEFT JOIN {table} t ON t.id = ids.id"
+    );
+
+    let object_ids: Vec<String> = object_ids.iter().map(|id| id.to_string()).collect();
+
+    let result = sqlx::query(&query)
+        .bind(namespace)
+        .bind(identifier)
+        .bind(block_height as i64)
+        .bind(type_id)
+        .bind(object_ids)
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn rollback_journal_entries(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    from_height: u64,
+) -> sqlx::Result<Vec<RollbackJournalEntry>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.rollback_journal_entries_calls.inc();
+
+    sqlx::query(
+        "SELECT id, block_height, type_id, object_id::text, object
+        FROM index_rollback_journal
+        WHERE namespace = $1
+        AND identifier = $2
+        AND block_height >= $3
+        ORDER BY id DESC",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(from_height as i64)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let id: i64 = row.get(0);
+        let block_height: i64 = row.get(1);
+        let type_id: i64 = row.get(2);
+        let object_id: String = row.get(3);
+        let object_id = object_id
+            .parse::<u64>()
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let object: Option<Vec<u8>> = row.get(4);
+
+        Ok(RollbackJournalEntry {
+            id,
+            block_height,
+            type_id,
+            object_id,
+            object,
+        })
+    })
+    .collect()
+}
+
+pub async fn remove_rollback_journal_entries(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    from_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS
+        .db
+        .postgres
+        .remove_rollback_journal_entries_calls
+        .inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_rollback_journal
+        WHERE namespace = $1
+        AND identifier = $2
+        AND block_height >= $3",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(from_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn prune_rollback_journal(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    below_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.prune_rollback_journal_calls.inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_rollback_journal
+        WHERE namespace = $1
+        AND identifier = $2
+        AND block_height < $3",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(below_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn delete_object(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+    object_id: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.delete_object_calls.inc();
+
+    let query = format!("DELETE FROM {table} WHERE id = $1::numeric");
+
+    let result = sqlx::query(&query)
+        .bind(object_id.to_string())
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Foreign keys that reference `table`, as declared in the database.
+pub async fn foreign_keys_referencing(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+) -> sqlx::Result<Vec<ForeignKey>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.foreign_keys_referencing_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT cn.nspname, cl.relname, ca.attname, pl.relname, pa.attname,
+            c.confdeltype::text
+        FROM pg_constraint c
+        JOIN pg_class cl ON cl.oid = c.conrelid
+        JOIN pg_namespace cn ON cn.oid = cl.relnamespace
+        JOIN pg_attribute ca ON ca.attrelid = c.conrelid AND ca.attnum = c.conkey[1]
+        JOIN pg_class pl ON pl.oid = c.confrelid
+        JOIN pg_attribute pa ON pa.attrelid = c.confrelid AND pa.attnum = c.confkey[1]
+        WHERE c.contype = 'f' AND c.confrelid = $1::regclass",
+    )
+    .bind(table)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let on_delete = match row.get::<String, usize>(5).as_str() {
+            "c" => OnDelete::Cascade,
+            "n" => OnDelete::SetNull,
+            _ => OnDelete::NoAction,
+        };
+
+        ForeignKey {
+            db_type: DbType::Postgres,
+            namespace: row.get(0),
+            table_name: row.get(1),
+            column_name: row.get(2),
+            reference_table_name: row.get(3),
+            reference_column_name: row.get(4),
+            on_delete,
+            ..Default::default()
+        }
+    })
+    .collect())
+}
+
+/// Journal the rows of `fk`'s table that reference any of `object_ids` in the
+/// table it points at, returning the IDs of the journaled rows.
+pub async fn put_rollback_journal_entries_referencing(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+    fk: &ForeignKey,
+    block_height: u64,
+    type_id: i64,
+    object_ids: &[u64],
+) -> sqlx::Result<Vec<u64>> {
+    #[cfg(feature = "metrics")]
+    METRICS
+        .db
+        .postgres
+        .put_rollback_journal_entries_referencing_calls
+        .inc();
+
+    let query = format!(
+        "INSERT INTO index_rollback_journal
+            (namespace, identifier, block_height, type_id, object_id, object)
+        SELECT $1, $2, $3, $4, c.id, c.object
+        FROM {schema}.{table} c
+        JOIN {schema}.{reference_table} p ON c.{column} = p.{reference_column}
+        WHERE p.id = ANY($5::numeric[])
+        RETURNING object_id::text",
+        schema = fk.namespace,
+        table = fk.table_name,
+        column = fk.column_name,
+        reference_table = fk.reference_table_name,
+        reference_column = fk.reference_column_name,
+    );
+
+    let object_ids: Vec<String> = object_ids.iter().map(|id| id.to_string()).collect();
+
+    sqlx::query(&query)
+        .bind(namespace)
+        .bind(identifier)
+        .bind(block_height as i64)
+        .bind(type_id)
+        .bind(object_ids)
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| {
+            row.get::<String, usize>(0)
+                .parse::<u64>()
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .collect()
+}
+
+/// Close the open versions of `object_ids` in the history of `table` at
+/// `block_height`, replacing any version recorded at that same height.
+pub async fn close_object_versions(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+    object_ids: &[u64],
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.close_object_versions_calls.inc();
+
+    let history = history_table_name(table);
+    let object_ids: Vec<String> = object_ids.iter().map(|id| id.to_string()).collect();
+
+    sqlx::query(&format!(
+        "DELETE FROM {history} WHERE id = ANY($1::numeric[]) AND valid_from = $2"
+    ))
+    .bind(&object_ids)
+    .bind(block_height as i64)
+    .execute(&mut *conn)
+    .await?;
+
+    let result = sqlx::query(&format!(
+        "UPDATE {history} SET valid_to = $2
+        WHERE id = ANY($1::numeric[]) AND valid_to IS NULL"
+    ))
+    .bind(&object_ids)
+    .bind(block_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Record the current state of `object_ids` in `table` as their version
+/// valid from `block_height`, closing the versions it supersedes.
+pub async fn put_object_versions(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+    columns: &[String],
+    object_ids: &[u64],
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.put_object_versions_calls.inc();
+
+    close_object_versions(conn, table, object_ids, block_height).await?;
+
+    let columns = columns.join(", ");
+    let object_ids: Vec<String> = object_ids.iter().map(|id| id.to_string()).collect();
+
+    let result = sqlx::query(&format!(
+        "INSERT INTO {history} ({columns}, valid_from)
+        SELECT {columns}, $2 FROM {table} WHERE id = ANY($1::numeric[])",
+        history = history_table_name(table),
+    ))
+    .bind(object_ids)
+    .bind(block_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Undo the history of `table` recorded at or after `block_height`.
+pub async fn rollback_object_versions(
+    conn: &mut PoolConnection<Postgres>,
+    table: &str,
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.rollback_object_versions_calls.inc();
+
+    let history = history_table_name(table);
+
+    let result = sqlx::query(&format!("DELETE FROM {history} WHERE valid_from >= $1"))
+        .bind(block_height as i64)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(&format!(
+        "UPDATE {history} SET valid_to = NULL WHERE valid_to >= $1"
+    ))
+    .bind(block_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Tell the listeners of `COMMIT_NOTIFICATION_CHANNEL` that the indexer
+/// committed a transaction.
+pub async fn notify_commit(
+    conn: &mut PoolConnection<Postgres>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.postgres.notify_commit_calls.inc();
+
+    let result = sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(COMMIT_NOTIFICATION_CHANNEL)
+        .bind(format!("{namespace}.{identifier}"))
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() as usize)
+}
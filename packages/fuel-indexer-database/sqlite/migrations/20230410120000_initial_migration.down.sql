@@ -0,0 +1,14 @@
+drop table if exists index_shadow_deployments;
+drop table if exists index_dead_letters;
+drop table if exists index_checkpoints;
+drop table if exists index_rollback_journal;
+drop table if exists nonce;
+drop table if exists index_asset_registry_manifest;
+drop table if exists index_asset_registry_schema;
+drop table if exists index_asset_registry_wasm;
+drop table if exists index_registry;
+drop table if exists graph_registry_schema_migrations;
+drop table if exists graph_registry_root_columns;
+drop table if exists graph_registry_graph_root;
+drop table if exists graph_registry_columns;
+drop table if exists graph_registry_type_ids;
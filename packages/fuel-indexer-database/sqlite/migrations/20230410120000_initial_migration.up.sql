@@ -0,0 +1,163 @@
+-- The registry tables of every Postgres migration up to
+-- 20230407120000_add_versioned_type_ids, with columns in the same order.
+
+create table if not exists graph_registry_type_ids (
+    id integer primary key,
+    schema_version varchar(512) not null,
+    schema_name varchar(32) not null,
+    graphql_name varchar(32) not null,
+    table_name varchar(32) not null,
+    schema_identifier varchar(255) default 'unnamed',
+    versioned boolean not null default false
+);
+
+create table if not exists graph_registry_columns (
+    id integer primary key autoincrement,
+    type_id integer not null,
+    column_position integer not null,
+    column_name varchar(32) not null,
+    column_type varchar(32) not null,
+    nullable boolean not null,
+    graphql_type varchar not null,
+    constraint fk_table_name
+        foreign key(type_id)
+            references graph_registry_type_ids(id)
+);
+
+create table if not exists graph_registry_graph_root (
+    id integer primary key autoincrement,
+    version varchar not null,
+    schema_name varchar not null,
+    query varchar not null,
+    schema varchar not null,
+    schema_identifier varchar(255) default 'unnamed',
+    unique(version, schema_name, schema_identifier)
+);
+
+create table if not exists graph_registry_root_columns (
+    id integer primary key autoincrement,
+    root_id integer not null,
+    column_name varchar(32) not null,
+    graphql_type varchar(32) not null,
+    constraint fk_root_id
+        foreign key(root_id)
+            references graph_registry_graph_root(id)
+);
+
+create table if not exists graph_registry_schema_migrations (
+    id integer primary key autoincrement,
+    schema_name varchar(32) not null,
+    schema_identifier varchar(255) not null,
+    from_version varchar(512) not null,
+    to_version varchar(512) not null,
+    statements text not null,
+    forced boolean not null,
+    created_at timestamp not null default current_timestamp
+);
+
+create table if not exists index_registry (
+    id integer primary key autoincrement,
+    namespace varchar(32) not null,
+    identifier varchar(32) not null,
+    pubkey varchar(255),
+    completed boolean not null default false,
+    status varchar(16) not null default 'running',
+    status_message text,
+    unique(namespace, identifier)
+);
+
+create table if not exists index_asset_registry_wasm (
+    id integer primary key autoincrement,
+    index_id integer,
+    version integer not null,
+    digest varchar(64) not null,
+    bytes blob not null,
+    constraint fk_index_registry_id
+        foreign key(index_id)
+            references index_registry(id)
+            on delete cascade
+            deferrable initially deferred
+);
+
+create table if not exists index_asset_registry_schema (
+    id integer primary key autoincrement,
+    index_id integer,
+    version integer not null,
+    digest varchar(64) not null,
+    bytes blob not null,
+    constraint fk_index_registry_id
+        foreign key(index_id)
+            references index_registry(id)
+            on delete cascade
+            deferrable initially deferred
+);
+
+create table if not exists index_asset_registry_manifest (
+    id integer primary key autoincrement,
+    index_id integer,
+    version integer not null,
+    digest varchar(64) not null,
+    bytes blob not null,
+    constraint fk_index_registry_id
+        foreign key(index_id)
+            references index_registry(id)
+            on delete cascade
+            deferrable initially deferred
+);
+
+create table if not exists nonce (
+    id integer primary key autoincrement,
+    uid varchar(64) unique not null,
+    expiry integer not null
+);
+
+create table if not exists index_rollback_journal (
+    id integer primary key autoincrement,
+    namespace varchar(32) not null,
+    identifier varchar(255) not null,
+    block_height integer not null,
+    type_id integer not null,
+    object_id integer not null,
+    object blob
+);
+
+create index if not exists index_rollback_journal_height_idx
+    on index_rollback_journal (namespace, identifier, block_height);
+
+create table if not exists index_checkpoints (
+    namespace varchar(32) not null,
+    identifier varchar(255) not null,
+    block_height integer not null,
+    block_id varchar(66),
+    cursor varchar(255),
+    updated_at timestamp not null default current_timestamp,
+    primary key (namespace, identifier)
+);
+
+create table if not exists index_dead_letters (
+    id integer primary key autoincrement,
+    namespace varchar(32) not null,
+    identifier varchar(255) not null,
+    block_height integer not null,
+    block_id varchar(66) not null,
+    error text not null,
+    created_at timestamp not null default current_timestamp
+);
+
+create index if not exists index_dead_letters_indexer_idx
+    on index_dead_letters (namespace, identifier);
+
+create table if not exists index_shadow_deployments (
+    id integer primary key autoincrement,
+    index_id integer not null unique,
+    wasm blob not null,
+    manifest blob not null,
+    schema blob not null,
+    caught_up boolean not null default false,
+    created_at timestamp not null default current_timestamp,
+    constraint fk_index_registry_id
+        foreign key(index_id)
+            references index_registry(id)
+            on delete cascade
+            deferrable initially deferred
+);
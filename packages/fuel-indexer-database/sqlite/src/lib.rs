@@ -0,0 +1,1892 @@
+#![deny(unused_crate_dependencies)]
+
+use fuel_indexer_database_types::*;
+use fuel_indexer_lib::{defaults::SHADOW_IDENTIFIER_SUFFIX, utils::sha256_digest};
+use serde_json::Value as JsonValue;
+use sqlx::{
+    pool::PoolConnection,
+    sqlite::{SqliteArguments, SqliteRow},
+    Row, Sqlite,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+#[cfg(feature = "metrics")]
+use fuel_indexer_metrics::METRICS;
+
+const NONCE_EXPIRY: u64 = 3600; // 1 hour
+
+/// Max number of parameters SQLite accepts in a single statement.
+const MAX_BIND_PARAMS: usize = 32766;
+
+fn bind_value<'q>(
+    query: sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: BindValue,
+) -> sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        BindValue::Integer(v) => query.bind(v),
+        BindValue::BigInt(v) | BindValue::Timestamp(v) => query.bind(v),
+        BindValue::UInt8(v) => query.bind(v.map(sqlite_uint)),
+        BindValue::Numeric(v) => query.bind(v),
+        BindValue::Text(v) | BindValue::Json(v) => query.bind(v),
+        BindValue::Boolean(v) => query.bind(v),
+        BindValue::Bytes(v) => query.bind(v),
+    }
+}
+
+/// IDs as a JSON array, bound as a single parameter and expanded with
+/// `json_each`, since SQLite has no array types.
+fn id_array(object_ids: &[u64]) -> String {
+    let ids: Vec<_> = object_ids
+        .iter()
+        .map(|id| sqlite_uint(*id).to_string())
+        .collect();
+    format!("[{}]", ids.join(","))
+}
+
+/// Split a table name into the namespace and table it was built from with
+/// `DbType::table_name`.
+fn split_table_name(table: &str) -> (String, String) {
+    match table.rsplit_once("__") {
+        Some((namespace, table)) => (namespace.to_string(), table.to_string()),
+        None => (String::new(), table.to_string()),
+    }
+}
+
+/// Insert or update entity rows, each holding a value for every one of
+/// `columns`, in as few statements as possible.
+pub async fn put_objects(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    columns: &[String],
+    rows: Vec<Vec<BindValue>>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.put_objects_calls.inc();
+
+    let updates: Vec<_> = columns
+        .iter()
+        .filter(|c| *c != &IdCol::to_lowercase_string())
+        .map(|c| format!("{c} = excluded.{c}"))
+        .collect();
+
+    let rows_per_statement = (MAX_BIND_PARAMS / columns.len().max(1)).max(1);
+    let mut rows = rows.into_iter().peekable();
+    let mut affected = 0;
+
+    while rows.peek().is_some() {
+        let batch: Vec<Vec<BindValue>> = rows.by_ref().take(rows_per_statement).collect();
+
+        let mut index = 0;
+        let values: Vec<_> = batch
+            .iter()
+            .map(|row| {
+                let placeholders: Vec<_> = row
+                    .iter()
+                    .map(|_| {
+                        index += 1;
+                        format!("?{index}")
+                    })
+                    .collect();
+                format!("({})", placeholders.join(", "))
+            })
+            .collect();
+
+        let query = format!(
+            "INSERT INTO {table}
+                ({})
+             VALUES
+                {}
+             ON CONFLICT(id)
+             DO UPDATE SET {}",
+            columns.join(", "),
+            values.join(", "),
+            updates.join(", "),
+        );
+
+        let mut query = sqlx::query(&query);
+        for value in batch.into_iter().flatten() {
+            query = bind_value(query, value);
+        }
+        affected += query.execute(&mut *conn).await?.rows_affected() as usize;
+    }
+
+    Ok(affected)
+}
+
+pub async fn get_object(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    object_id: u64,
+) -> sqlx::Result<Vec<u8>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.get_object_calls.inc();
+
+    let query = format!("SELECT object FROM {table} WHERE id = ?1");
+
+    let row = sqlx::query(&query)
+        .bind(sqlite_uint(object_id))
+        .fetch_one(conn)
+        .await?;
+
+    Ok(row.get(0))
+}
+
+/// Entity rows whose `columns` all have the given values, ordered by ID.
+pub async fn find_objects(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    columns: Vec<(String, BindValue)>,
+    limit: Option<u64>,
+) -> sqlx::Result<Vec<Vec<u8>>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.find_objects_calls.inc();
+
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    for (column, value) in columns {
+        if value.is_null() {
+            conditions.push(format!("{column} IS NULL"));
+        } else {
+            conditions.push(format!("{column} = ?{}", values.len() + 1));
+            values.push(value);
+        }
+    }
+
+    let mut query = format!("SELECT object FROM {table}");
+    if !conditions.is_empty() {
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    query.push_str(" ORDER BY id");
+    if let Some(limit) = limit {
+        query.push_str(&format!(" LIMIT {limit}"));
+    }
+
+    let mut query = sqlx::query(&query);
+    for value in values {
+        query = bind_value(query, value);
+    }
+
+    Ok(query
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| row.get(0))
+        .collect())
+}
+
+/// Up to `limit` entity rows with IDs above `after`, as their IDs and
+/// serialized objects, ordered by ID.
+pub async fn list_objects(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    after: Option<u64>,
+    limit: i64,
+) -> sqlx::Result<Vec<(u64, Vec<u8>)>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.list_objects_calls.inc();
+
+    let query = format!(
+        "SELECT id, object FROM {table}
+        WHERE ?1 IS NULL OR id > ?1
+        ORDER BY id LIMIT ?2"
+    );
+
+    Ok(sqlx::query(&query)
+        .bind(after.map(sqlite_uint))
+        .bind(limit)
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| (from_sqlite_uint(row.get(0)), row.get(1)))
+        .collect())
+}
+
+/// Replace the serialized object of an entity row, leaving its columns as
+/// they are.
+pub async fn set_object_bytes(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    object_id: u64,
+    bytes: Vec<u8>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.set_object_bytes_calls.inc();
+
+    let query = format!("UPDATE {table} SET object = ?1 WHERE id = ?2");
+
+    let result = sqlx::query(&query)
+        .bind(bytes)
+        .bind(sqlite_uint(object_id))
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn run_migration(conn: &mut PoolConnection<Sqlite>) -> sqlx::Result<()> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.run_migration_calls.inc();
+
+    sqlx::migrate!().run(conn).await?;
+
+    Ok(())
+}
+
+/// Run a query whose rows are each a single JSON document, which SQLite
+/// returns as text.
+pub async fn run_query(
+    conn: &mut PoolConnection<Sqlite>,
+    query: String,
+) -> sqlx::Result<JsonValue> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.run_query_calls.inc();
+
+    let mut builder = sqlx::QueryBuilder::new(query);
+
+    let query = builder.build();
+
+    let rows = query
+        .fetch_all(conn)
+        .await?
+        .iter()
+        .map(|r| {
+            serde_json::from_str::<JsonValue>(&r.get::<'_, String, usize>(0))
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        })
+        .collect::<sqlx::Result<Vec<JsonValue>>>()?;
+
+    Ok(JsonValue::Array(rows))
+}
+
+pub async fn execute_query(
+    conn: &mut PoolConnection<Sqlite>,
+    query: String,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.execute_query_calls.inc();
+
+    let mut builder = sqlx::QueryBuilder::new(query);
+
+    let query = builder.build();
+
+    let result = query.execute(conn).await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn root_columns_list_by_id(
+    conn: &mut PoolConnection<Sqlite>,
+    root_id: i64,
+) -> sqlx::Result<Vec<RootColumns>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.root_columns_list_by_id_calls.inc();
+
+    Ok(
+        sqlx::query("SELECT * FROM graph_registry_root_columns WHERE root_id = ?1")
+            .bind(root_id)
+            .fetch_all(conn)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let id: i64 = row.get(0);
+                let root_id: i64 = row.get(1);
+                let column_name: String = row.get(2);
+                let graphql_type: String = row.get(3);
+                RootColumns {
+                    id,
+                    root_id,
+                    column_name,
+                    graphql_type,
+                }
+            })
+            .collect::<Vec<RootColumns>>(),
+    )
+}
+
+pub async fn new_root_columns(
+    conn: &mut PoolConnection<Sqlite>,
+    cols: Vec<NewRootColumns>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.new_root_columns_calls.inc();
+
+    let mut builder = sqlx::QueryBuilder::new(
+        "INSERT INTO graph_registry_root_columns (root_id, column_name, graphql_type)",
+    );
+
+    builder.push_values(cols.into_iter(), |mut b, new_col| {
+        b.push_bind(new_col.root_id)
+            .push_bind(new_col.column_name)
+            .push_bind(new_col.graphql_type);
+    });
+
+    let query = builder.build();
+
+    let result = query.execute(conn).await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn new_graph_root(
+    conn: &mut PoolConnection<Sqlite>,
+    root: NewGraphRoot,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.new_graph_root_calls.inc();
+
+    let mut builder = sqlx::QueryBuilder::new(
+        "INSERT INTO graph_registry_graph_root (version, schema_name, schema_identifier, query, schema)",
+    );
+
+    builder.push_values(std::iter::once(root), |mut b, root| {
+        b.push_bind(root.version)
+            .push_bind(root.schema_name)
+            .push_bind(root.schema_identifier)
+            .push_bind(root.query)
+            .push_bind(root.schema);
+    });
+
+    let query = builder.build();
+
+    let result = query.execute(conn).await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn graph_root_latest(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<GraphRoot> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.graph_root_latest_calls.inc();
+
+    let row = sqlx::query(
+        "SELECT * FROM graph_registry_graph_root
+        WHERE schema_name = ?1 AND schema_identifier = ?2
+        ORDER BY id DESC LIMIT 1",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_one(conn)
+    .await?;
+
+    let id: i64 = row.get(0);
+    let version: String = row.get(1);
+    let schema_name: String = row.get(2);
+    let query: String = row.get(3);
+    let schema: String = row.get(4);
+
+    Ok(GraphRoot {
+        id,
+        version,
+        schema_name,
+        query,
+        schema,
+        schema_identifier: identifier.to_string(),
+    })
+}
+
+/// Remove the type and column metadata of every version of an indexer's
+/// schema, along with the graph root of `version`, so that `version` can be
+/// registered in their place.
+pub async fn remove_schema_metadata(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    version: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.remove_schema_metadata_calls.inc();
+
+    let mut removed = 0;
+
+    for query in [
+        "DELETE FROM graph_registry_columns WHERE type_id IN (
+            SELECT id FROM graph_registry_type_ids
+            WHERE schema_name = ?1 AND schema_identifier = ?2)",
+        "DELETE FROM graph_registry_type_ids
+        WHERE schema_name = ?1 AND schema_identifier = ?2",
+    ] {
+        removed += sqlx::query(query)
+            .bind(namespace)
+            .bind(identifier)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+
+    for query in [
+        "DELETE FROM graph_registry_root_columns WHERE root_id IN (
+            SELECT id FROM graph_registry_graph_root
+            WHERE schema_name = ?1 AND schema_identifier = ?2 AND version = ?3)",
+        "DELETE FROM graph_registry_graph_root
+        WHERE schema_name = ?1 AND schema_identifier = ?2 AND version = ?3",
+    ] {
+        removed += sqlx::query(query)
+            .bind(namespace)
+            .bind(identifier)
+            .bind(version)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+
+    Ok(removed as usize)
+}
+
+pub async fn new_schema_migration(
+    conn: &mut PoolConnection<Sqlite>,
+    migration: NewSchemaMigration,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.new_schema_migration_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO graph_registry_schema_migrations
+            (schema_name, schema_identifier, from_version, to_version, statements, forced)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )
+    .bind(migration.schema_name)
+    .bind(migration.schema_identifier)
+    .bind(migration.from_version)
+    .bind(migration.to_version)
+    .bind(migration.statements.join(";\n"))
+    .bind(migration.forced)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn type_id_list_by_name(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    version: &str,
+    identifier: &str,
+) -> sqlx::Result<Vec<TypeId>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.type_id_list_by_name_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT * FROM graph_registry_type_ids
+        WHERE schema_name = ?1
+        AND schema_version = ?2
+        AND schema_identifier = ?3",
+    )
+    .bind(namespace)
+    .bind(version)
+    .bind(identifier)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let id: i64 = row.get(0);
+        let schema_version: String = row.get(1);
+        let schema_name: String = row.get(2);
+        let graphql_name: String = row.get(3);
+        let table_name: String = row.get(4);
+        let versioned: bool = row.get("versioned");
+
+        TypeId {
+            id,
+            schema_version,
+            schema_name,
+            table_name,
+            graphql_name,
+            schema_identifier: identifier.to_string(),
+            versioned,
+        }
+    })
+    .collect::<Vec<TypeId>>())
+}
+
+pub async fn type_id_latest(
+    conn: &mut PoolConnection<Sqlite>,
+    schema_name: &str,
+    identifier: &str,
+) -> sqlx::Result<String> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.type_id_latest_calls.inc();
+
+    let latest = sqlx::query(
+        "SELECT schema_version FROM graph_registry_type_ids
+        WHERE schema_name = ?1
+        AND schema_identifier = ?2
+        ORDER BY id",
+    )
+    .bind(schema_name)
+    .bind(identifier)
+    .fetch_one(conn)
+    .await?;
+
+    let schema_version: String = latest.get(0);
+
+    Ok(schema_version)
+}
+
+pub async fn type_id_insert(
+    conn: &mut PoolConnection<Sqlite>,
+    type_ids: Vec<TypeId>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.type_id_insert_calls.inc();
+
+    let mut builder = sqlx::QueryBuilder::new("INSERT INTO graph_registry_type_ids (id, schema_version, schema_name, schema_identifier, graphql_name, table_name, versioned)");
+
+    builder.push_values(type_ids.into_iter(), |mut b, tid| {
+        b.push_bind(tid.id)
+            .push_bind(tid.schema_version)
+            .push_bind(tid.schema_name)
+            .push_bind(tid.schema_identifier)
+            .push_bind(tid.graphql_name)
+            .push_bind(tid.table_name)
+            .push_bind(tid.versioned);
+    });
+
+    let query = builder.build();
+
+    let result = query.execute(conn).await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn schema_exists(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    version: &str,
+) -> sqlx::Result<bool> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.schema_exists_calls.inc();
+
+    let count = sqlx::query(
+        "SELECT COUNT(*) AS count FROM graph_registry_type_ids
+        WHERE schema_name = ?1
+        AND schema_identifier = ?2
+        AND schema_version = ?3",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(version)
+    .fetch_one(conn)
+    .await?;
+
+    let count: i64 = count.get(0);
+
+    Ok(count > 0)
+}
+
+pub async fn new_column_insert(
+    conn: &mut PoolConnection<Sqlite>,
+    cols: Vec<NewColumn>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.new_column_insert_calls.inc();
+
+    let mut builder = sqlx::QueryBuilder::new("INSERT INTO graph_registry_columns (type_id, column_position, column_name, column_type, nullable, graphql_type)");
+
+    builder.push_values(cols.into_iter(), |mut b, new_col| {
+        b.push_bind(new_col.type_id)
+            .push_bind(new_col.column_position)
+            .push_bind(new_col.column_name)
+            .push_bind(new_col.column_type)
+            .push_bind(new_col.nullable)
+            .push_bind(new_col.graphql_type);
+    });
+
+    let query = builder.build();
+
+    let result = query.execute(conn).await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn list_column_by_id(
+    conn: &mut PoolConnection<Sqlite>,
+    col_id: i64,
+) -> sqlx::Result<Vec<Columns>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.list_column_by_id_calls.inc();
+
+    Ok(
+        sqlx::query("SELECT * FROM graph_registry_columns WHERE type_id = ?1")
+            .bind(col_id)
+            .fetch_all(conn)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let id: i64 = row.get(0);
+                let type_id: i64 = row.get(1);
+                let column_position: i32 = row.get(2);
+                let column_name: String = row.get(3);
+                let column_type: String = row.get(4);
+                let nullable: bool = row.get(5);
+                let graphql_type: String = row.get(6);
+
+                Columns {
+                    id,
+                    type_id,
+                    column_position,
+                    column_name,
+                    column_type,
+                    nullable,
+                    graphql_type,
+                }
+            })
+            .collect::<Vec<Columns>>(),
+    )
+}
+
+pub async fn columns_get_schema(
+    conn: &mut PoolConnection<Sqlite>,
+    name: &str,
+    identifier: &str,
+    version: &str,
+) -> sqlx::Result<Vec<ColumnInfo>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.columns_get_schema_calls.inc();
+
+    Ok(sqlx::query(
+        "
+            SELECT
+            c.type_id as type_id,
+            t.table_name as table_name,
+            c.column_position as column_position,
+            c.column_name as column_name,
+            c.column_type as column_type,
+            t.versioned as versioned
+            FROM graph_registry_type_ids as t
+            INNER JOIN graph_registry_columns as c ON t.id = c.type_id
+            WHERE t.schema_name = ?1
+            AND t.schema_identifier = ?2
+            AND t.schema_version = ?3
+            ORDER BY c.type_id, c.column_position",
+    )
+    .bind(name)
+    .bind(identifier)
+    .bind(version)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row: SqliteRow| {
+        let type_id: i64 = row.get(0);
+        let table_name: String = row.get(1);
+        let column_position: i32 = row.get(2);
+        let column_name: String = row.get(3);
+        let column_type: String = row.get(4);
+        let versioned: bool = row.get(5);
+
+        ColumnInfo {
+            type_id,
+            table_name,
+            column_position,
+            column_name,
+            column_type,
+            versioned,
+        }
+    })
+    .collect::<Vec<ColumnInfo>>())
+}
+
+pub async fn index_is_registered(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Option<RegisteredIndex>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.index_is_registered_calls.inc();
+
+    match sqlx::query(
+        "SELECT * FROM index_registry
+        WHERE namespace = ?1
+        AND identifier = ?2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_optional(conn)
+    .await?
+    {
+        Some(row) => Ok(Some(RegisteredIndex {
+            id: row.get(0),
+            namespace: row.get(1),
+            identifier: row.get(2),
+            pubkey: row.get(3),
+            completed: row.get(4),
+            status: row.get::<String, _>(5).parse().unwrap_or_default(),
+            status_message: row.get(6),
+        })),
+        None => Ok(None),
+    }
+}
+
+pub async fn register_index(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    pubkey: Option<&str>,
+) -> sqlx::Result<RegisteredIndex> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.register_index_calls.inc();
+
+    if let Some(index) = index_is_registered(conn, namespace, identifier).await? {
+        return Ok(index);
+    }
+
+    let row = sqlx::query(
+        "INSERT INTO index_registry (namespace, identifier, pubkey)
+         VALUES (?1, ?2, ?3)
+         RETURNING *",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(pubkey)
+    .fetch_one(conn)
+    .await?;
+
+    let id: i64 = row.get(0);
+    let namespace: String = row.get(1);
+    let identifier: String = row.get(2);
+    let pubkey = row.get(3);
+    let completed: bool = row.get(4);
+    let status: String = row.get(5);
+    let status_message = row.get(6);
+
+    Ok(RegisteredIndex {
+        id,
+        namespace,
+        identifier,
+        pubkey,
+        completed,
+        status: status.parse().unwrap_or_default(),
+        status_message,
+    })
+}
+
+pub async fn registered_indices(
+    conn: &mut PoolConnection<Sqlite>,
+) -> sqlx::Result<Vec<RegisteredIndex>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.registered_indices_calls.inc();
+
+    Ok(sqlx::query("SELECT * FROM index_registry")
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let id: i64 = row.get(0);
+            let namespace: String = row.get(1);
+            let identifier: String = row.get(2);
+            let pubkey = row.get(3);
+            let completed: bool = row.get(4);
+            let status: String = row.get(5);
+            let status_message = row.get(6);
+
+            RegisteredIndex {
+                id,
+                namespace,
+                identifier,
+                pubkey,
+                completed,
+                status: status.parse().unwrap_or_default(),
+                status_message,
+            }
+        })
+        .collect::<Vec<RegisteredIndex>>())
+}
+
+pub async fn set_index_completed(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    completed: bool,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.set_index_completed_calls.inc();
+
+    let result = sqlx::query(
+        "UPDATE index_registry
+        SET completed = ?1
+        WHERE namespace = ?2
+        AND identifier = ?3",
+    )
+    .bind(completed)
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn set_index_status(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    status: IndexerStatus,
+    message: Option<&str>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.set_index_status_calls.inc();
+
+    let result = sqlx::query(
+        "UPDATE index_registry
+        SET status = ?1, status_message = ?2
+        WHERE namespace = ?3
+        AND identifier = ?4",
+    )
+    .bind(status.as_ref())
+    .bind(message)
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn index_asset_version(
+    conn: &mut PoolConnection<Sqlite>,
+    index_id: &i64,
+    asset_type: &IndexAssetType,
+) -> sqlx::Result<i64> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.index_asset_version_calls.inc();
+
+    match sqlx::query(&format!(
+        "SELECT COUNT(*)
+        FROM index_asset_registry_{}
+        WHERE index_id = {}",
+        asset_type.as_ref(),
+        index_id,
+    ))
+    .fetch_one(conn)
+    .await
+    {
+        Ok(row) => Ok(row.try_get::<'_, i64, usize>(0).unwrap_or(0)),
+        Err(_e) => Ok(0),
+    }
+}
+
+pub async fn register_index_asset(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    bytes: Vec<u8>,
+    asset_type: IndexAssetType,
+    pubkey: Option<&str>,
+) -> sqlx::Result<IndexAsset> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.register_index_asset_calls.inc();
+
+    let index = match index_is_registered(conn, namespace, identifier).await? {
+        Some(index) => index,
+        None => register_index(conn, namespace, identifier, pubkey).await?,
+    };
+
+    let digest = sha256_digest(&bytes);
+
+    if let Some(asset) =
+        asset_already_exists(conn, &asset_type, &bytes, &index.id).await?
+    {
+        info!(
+            "Asset({asset_type:?}) for Indexer({}) already registered.",
+            index.uid()
+        );
+        return Ok(asset);
+    }
+
+    let current_version = index_asset_version(conn, &index.id, &asset_type)
+        .await
+        .expect("Failed to get asset version.");
+
+    let query = format!(
+        "INSERT INTO index_asset_registry_{} (index_id, bytes, version, digest) VALUES ({}, ?1, {}, '{digest}') RETURNING *",
+        asset_type.as_ref(),
+        index.id,
+        current_version + 1,
+    );
+
+    let row = sqlx::QueryBuilder::new(query)
+        .build()
+        .bind(bytes)
+        .fetch_one(conn)
+        .await?;
+
+    info!(
+        "Registered Asset({:?}) to Indexer({}).",
+        asset_type,
+        index.uid()
+    );
+
+    let id = row.get(0);
+    let index_id = row.get(1);
+    let version = row.get(2);
+    let digest = row.get(3);
+    let bytes = row.get(4);
+
+    Ok(IndexAsset {
+        id,
+        index_id,
+        version,
+        digest,
+        bytes,
+    })
+}
+
+pub async fn latest_asset_for_index(
+    conn: &mut PoolConnection<Sqlite>,
+    index_id: &i64,
+    asset_type: IndexAssetType,
+) -> sqlx::Result<IndexAsset> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.latest_asset_for_index_calls.inc();
+
+    let query = format!(
+        "SELECT * FROM index_asset_registry_{} WHERE index_id = {} ORDER BY id DESC LIMIT 1",
+        asset_type.as_ref(),
+        index_id
+    );
+
+    let row = sqlx::query(&query).fetch_one(conn).await?;
+
+    let id = row.get(0);
+    let index_id = row.get(1);
+    let version = row.get(2);
+    let digest = row.get(3);
+    let bytes = row.get(4);
+
+    Ok(IndexAsset {
+        id,
+        index_id,
+        version,
+        digest,
+        bytes,
+    })
+}
+
+pub async fn latest_assets_for_index(
+    conn: &mut PoolConnection<Sqlite>,
+    index_id: &i64,
+) -> sqlx::Result<IndexAssetBundle> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.latest_assets_for_index_calls.inc();
+
+    let wasm = latest_asset_for_index(conn, index_id, IndexAssetType::Wasm).await?;
+
+    let schema = latest_asset_for_index(conn, index_id, IndexAssetType::Schema).await?;
+
+    let manifest =
+        latest_asset_for_index(conn, index_id, IndexAssetType::Manifest).await?;
+
+    Ok(IndexAssetBundle {
+        wasm,
+        schema,
+        manifest,
+    })
+}
+
+pub async fn put_checkpoint(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    block_height: u64,
+    block_id: Option<&str>,
+    cursor: Option<&str>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.put_checkpoint_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO index_checkpoints (namespace, identifier, block_height, block_id, cursor)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT (namespace, identifier)
+        DO UPDATE SET
+            block_height = excluded.block_height,
+            block_id = excluded.block_id,
+            cursor = excluded.cursor,
+            updated_at = current_timestamp",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(block_height as i64)
+    .bind(block_id)
+    .bind(cursor)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn checkpoint_for_indexer(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Option<IndexerCheckpoint>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.checkpoint_for_indexer_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT namespace, identifier, block_height, block_id, cursor
+        FROM index_checkpoints
+        WHERE namespace = ?1
+        AND identifier = ?2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_optional(conn)
+    .await?
+    .map(|row| {
+        let namespace: String = row.get(0);
+        let identifier: String = row.get(1);
+        let block_height: i64 = row.get(2);
+        let block_id: Option<String> = row.get(3);
+        let cursor: Option<String> = row.get(4);
+
+        IndexerCheckpoint {
+            namespace,
+            identifier,
+            block_height: block_height as u64,
+            block_id,
+            cursor,
+        }
+    }))
+}
+
+pub async fn put_dead_letter(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    block_height: u64,
+    block_id: &str,
+    error: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.put_dead_letter_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO index_dead_letters (namespace, identifier, block_height, block_id, error)
+        VALUES (?1, ?2, ?3, ?4, ?5)",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(block_height as i64)
+    .bind(block_id)
+    .bind(error)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn dead_letters(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Vec<DeadLetter>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.dead_letters_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT block_height, block_id, error
+        FROM index_dead_letters
+        WHERE namespace = ?1
+        AND identifier = ?2
+        ORDER BY block_height ASC",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let block_height: i64 = row.get(0);
+        let block_id: String = row.get(1);
+        let error: String = row.get(2);
+
+        DeadLetter {
+            block_height: block_height as u64,
+            block_id,
+            error,
+        }
+    })
+    .collect::<Vec<DeadLetter>>())
+}
+
+pub async fn remove_checkpoint(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.remove_checkpoint_calls.inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_checkpoints
+        WHERE namespace = ?1
+        AND identifier = ?2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn asset_already_exists(
+    conn: &mut PoolConnection<Sqlite>,
+    asset_type: &IndexAssetType,
+    bytes: &Vec<u8>,
+    index_id: &i64,
+) -> sqlx::Result<Option<IndexAsset>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.asset_already_exists_calls.inc();
+
+    let digest = sha256_digest(bytes);
+
+    let query = format!(
+        "SELECT * FROM index_asset_registry_{} WHERE index_id = {} AND digest = '{}'",
+        asset_type.as_ref(),
+        index_id,
+        digest
+    );
+
+    match sqlx::QueryBuilder::new(query).build().fetch_one(conn).await {
+        Ok(row) => {
+            let id = row.get(0);
+            let index_id = row.get(1);
+            let version = row.get(2);
+            let digest = row.get(3);
+            let bytes = row.get(4);
+
+            Ok(Some(IndexAsset {
+                id,
+                index_id,
+                version,
+                digest,
+                bytes,
+            }))
+        }
+        Err(_e) => Ok(None),
+    }
+}
+
+pub async fn index_id_for(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<i64> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.index_id_for_calls.inc();
+
+    let row = sqlx::query(
+        "SELECT id FROM index_registry
+        WHERE namespace = ?1
+        AND identifier = ?2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_one(conn)
+    .await?;
+
+    let id: i64 = row.get(0);
+
+    Ok(id)
+}
+
+pub async fn penultimate_asset_for_index(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    asset_type: IndexAssetType,
+) -> sqlx::Result<IndexAsset> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.penultimate_asset_for_index_calls.inc();
+
+    let index_id = index_id_for(conn, namespace, identifier).await?;
+    let query = format!(
+        "SELECT * FROM index_asset_registry_{}
+        WHERE index_id = {} ORDER BY id DESC LIMIT 1 OFFSET 1",
+        asset_type.as_ref(),
+        index_id
+    );
+    let row = sqlx::query(&query).fetch_one(conn).await?;
+
+    let id = row.get(0);
+    let index_id = row.get(1);
+    let version = row.get(2);
+    let digest = row.get(3);
+    let bytes = row.get(4);
+
+    Ok(IndexAsset {
+        id,
+        index_id,
+        version,
+        digest,
+        bytes,
+    })
+}
+
+pub async fn start_transaction(conn: &mut PoolConnection<Sqlite>) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.start_transaction_calls.inc();
+
+    execute_query(conn, "BEGIN".into()).await
+}
+
+pub async fn commit_transaction(
+    conn: &mut PoolConnection<Sqlite>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.commit_transaction_calls.inc();
+
+    execute_query(conn, "COMMIT".into()).await
+}
+
+pub async fn revert_transaction(
+    conn: &mut PoolConnection<Sqlite>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.revert_transaction_calls.inc();
+
+    execute_query(conn, "ROLLBACK".into()).await
+}
+
+pub async fn remove_indexer(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<()> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.remove_indexer.inc();
+
+    let index_id = index_id_for(conn, namespace, identifier).await?;
+
+    execute_query(
+        conn,
+        format!("DELETE FROM index_asset_registry_wasm WHERE index_id = {index_id}",),
+    )
+    .await?;
+
+    execute_query(
+        conn,
+        format!("DELETE FROM index_asset_registry_manifest WHERE index_id = {index_id}",),
+    )
+    .await?;
+
+    execute_query(
+        conn,
+        format!("DELETE FROM index_asset_registry_schema WHERE index_id = {index_id}",),
+    )
+    .await?;
+
+    execute_query(
+        conn,
+        format!("DELETE FROM index_registry WHERE id = {index_id}",),
+    )
+    .await?;
+
+    remove_checkpoint(conn, namespace, identifier).await?;
+
+    sqlx::query(
+        "DELETE FROM index_dead_letters
+        WHERE namespace = ?1
+        AND identifier = ?2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(&mut *conn)
+    .await?;
+
+    remove_indexer_state(
+        conn,
+        namespace,
+        &format!("{identifier}{SHADOW_IDENTIFIER_SUFFIX}"),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Store the assets of a blue/green deployment of an indexer, replacing any
+/// that hasn't caught up yet.
+pub async fn put_shadow_deployment(
+    conn: &mut PoolConnection<Sqlite>,
+    index_id: &i64,
+    wasm: Vec<u8>,
+    manifest: Vec<u8>,
+    schema: Vec<u8>,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.put_shadow_deployment_calls.inc();
+
+    let result = sqlx::query(
+        "INSERT INTO index_shadow_deployments (index_id, wasm, manifest, schema)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT (index_id) DO UPDATE SET
+        wasm = excluded.wasm,
+        manifest = excluded.manifest,
+        schema = excluded.schema,
+        caught_up = false,
+        created_at = current_timestamp",
+    )
+    .bind(index_id)
+    .bind(wasm)
+    .bind(manifest)
+    .bind(schema)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn shadow_deployment_for(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Option<ShadowDeployment>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.shadow_deployment_for_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT s.index_id, r.namespace, r.identifier, s.wasm, s.manifest, s.schema, s.caught_up
+        FROM index_shadow_deployments AS s
+        INNER JOIN index_registry AS r ON r.id = s.index_id
+        WHERE r.namespace = ?1
+        AND r.identifier = ?2",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .fetch_optional(conn)
+    .await?
+    .map(|row| ShadowDeployment {
+        index_id: row.get(0),
+        namespace: row.get(1),
+        identifier: row.get(2),
+        wasm: row.get(3),
+        manifest: row.get(4),
+        schema: row.get(5),
+        caught_up: row.get(6),
+    }))
+}
+
+/// Record that a blue/green deployment has caught up with the chain, and is
+/// ready to replace the running version of its indexer.
+pub async fn set_shadow_caught_up(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.set_shadow_caught_up_calls.inc();
+
+    let result = sqlx::query(
+        "UPDATE index_shadow_deployments SET caught_up = true
+        WHERE index_id IN (
+            SELECT id FROM index_registry WHERE namespace = ?1 AND identifier = ?2)",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn remove_shadow_deployment(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.remove_shadow_deployment_calls.inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_shadow_deployments
+        WHERE index_id IN (
+            SELECT id FROM index_registry WHERE namespace = ?1 AND identifier = ?2)",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Remove the checkpoint, rollback journal and dead letters recorded under an
+/// indexer's identifier.
+pub async fn remove_indexer_state(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.remove_indexer_state_calls.inc();
+
+    let mut removed = 0;
+
+    for table in [
+        "index_checkpoints",
+        "index_rollback_journal",
+        "index_dead_letters",
+    ] {
+        removed += sqlx::query(&format!(
+            "DELETE FROM {table} WHERE namespace = ?1 AND identifier = ?2"
+        ))
+        .bind(namespace)
+        .bind(identifier)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(removed as usize)
+}
+
+/// Move the checkpoint, rollback journal and dead letters recorded under one
+/// identifier to another.
+pub async fn rename_indexer_state(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    from_identifier: &str,
+    to_identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.rename_indexer_state_calls.inc();
+
+    let mut renamed = 0;
+
+    for table in [
+        "index_checkpoints",
+        "index_rollback_journal",
+        "index_dead_letters",
+    ] {
+        renamed += sqlx::query(&format!(
+            "UPDATE {table} SET identifier = ?3 WHERE namespace = ?1 AND identifier = ?2"
+        ))
+        .bind(namespace)
+        .bind(from_identifier)
+        .bind(to_identifier)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(renamed as usize)
+}
+
+/// SQLite has no advisory locks. Writers already hold a lock on the whole
+/// database until their transaction ends, and blue/green deployments, which
+/// the exclusive lock guards, aren't supported on SQLite.
+pub async fn lock_indexer(
+    _conn: &mut PoolConnection<Sqlite>,
+    _namespace: &str,
+    _identifier: &str,
+    _exclusive: bool,
+) -> sqlx::Result<()> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.lock_indexer_calls.inc();
+
+    Ok(())
+}
+
+pub async fn remove_asset_by_version(
+    conn: &mut PoolConnection<Sqlite>,
+    index_id: &i64,
+    version: &i32,
+    asset_type: IndexAssetType,
+) -> sqlx::Result<()> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.remove_asset_by_version_calls.inc();
+
+    execute_query(
+        conn,
+        format!(
+            "DELETE FROM index_asset_registry_{0} WHERE index_id = {1} AND version = '{2}'",
+            asset_type.as_ref(),
+            index_id,
+            version
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn create_nonce(conn: &mut PoolConnection<Sqlite>) -> sqlx::Result<Nonce> {
+    let uid = uuid::Uuid::new_v4().as_simple().to_string();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let expiry = now + NONCE_EXPIRY;
+
+    let row = sqlx::QueryBuilder::new(&format!(
+        "INSERT INTO nonce (uid, expiry) VALUES ('{uid}', {expiry}) RETURNING *"
+    ))
+    .build()
+    .fetch_one(conn)
+    .await?;
+
+    let uid: String = row.get(1);
+    let expiry: i64 = row.get(2);
+
+    Ok(Nonce { uid, expiry })
+}
+
+pub async fn delete_nonce(
+    conn: &mut PoolConnection<Sqlite>,
+    nonce: &Nonce,
+) -> sqlx::Result<()> {
+    let _ = sqlx::query(&format!("DELETE FROM nonce WHERE uid = '{}'", nonce.uid))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_nonce(
+    conn: &mut PoolConnection<Sqlite>,
+    uid: &str,
+) -> sqlx::Result<Nonce> {
+    let row = sqlx::query(&format!("SELECT * FROM nonce WHERE uid = '{uid}'"))
+        .fetch_one(conn)
+        .await?;
+
+    let uid: String = row.get(1);
+    let expiry: i64 = row.get(2);
+
+    Ok(Nonce { uid, expiry })
+}
+
+pub async fn put_rollback_journal_entries(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    table: &str,
+    block_height: u64,
+    type_id: i64,
+    object_ids: &[u64],
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.put_rollback_journal_entries_calls.inc();
+
+    let query = format!(
+        "INSERT INTO index_rollback_journal
+            (namespace, identifier, block_height, type_id, object_id, object)
+        SELECT ?1, ?2, ?3, ?4, ids.value, t.object
+        FROM json_each(?5) AS ids
+        LEFT JOIN {table} t ON t.id = ids.value"
+    );
+
+    let result = sqlx::query(&query)
+        .bind(namespace)
+        .bind(identifier)
+        .bind(block_height as i64)
+        .bind(type_id)
+        .bind(id_array(object_ids))
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn rollback_journal_entries(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    from_height: u64,
+) -> sqlx::Result<Vec<RollbackJournalEntry>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.rollback_journal_entries_calls.inc();
+
+    Ok(sqlx::query(
+        "SELECT id, block_height, type_id, object_id, object
+        FROM index_rollback_journal
+        WHERE namespace = ?1
+        AND identifier = ?2
+        AND block_height >= ?3
+        ORDER BY id DESC",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(from_height as i64)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let id: i64 = row.get(0);
+        let block_height: i64 = row.get(1);
+        let type_id: i64 = row.get(2);
+        let object_id = from_sqlite_uint(row.get(3));
+        let object: Option<Vec<u8>> = row.get(4);
+
+        RollbackJournalEntry {
+            id,
+            block_height,
+            type_id,
+            object_id,
+            object,
+        }
+    })
+    .collect::<Vec<RollbackJournalEntry>>())
+}
+
+pub async fn remove_rollback_journal_entries(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    from_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS
+        .db
+        .sqlite
+        .remove_rollback_journal_entries_calls
+        .inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_rollback_journal
+        WHERE namespace = ?1
+        AND identifier = ?2
+        AND block_height >= ?3",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(from_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn prune_rollback_journal(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    below_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.prune_rollback_journal_calls.inc();
+
+    let result = sqlx::query(
+        "DELETE FROM index_rollback_journal
+        WHERE namespace = ?1
+        AND identifier = ?2
+        AND block_height < ?3",
+    )
+    .bind(namespace)
+    .bind(identifier)
+    .bind(below_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+pub async fn delete_object(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    object_id: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.delete_object_calls.inc();
+
+    let query = format!("DELETE FROM {table} WHERE id = ?1");
+
+    let result = sqlx::query(&query)
+        .bind(sqlite_uint(object_id))
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Foreign keys that reference `table`, as declared in the database.
+pub async fn foreign_keys_referencing(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+) -> sqlx::Result<Vec<ForeignKey>> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.foreign_keys_referencing_calls.inc();
+
+    let (_, reference_table_name) = split_table_name(table);
+
+    Ok(sqlx::query(
+        "SELECT m.name, fk.\"from\", fk.\"to\", fk.on_delete
+        FROM sqlite_master AS m, pragma_foreign_key_list(m.name) AS fk
+        WHERE m.type = 'table' AND fk.\"table\" = ?1",
+    )
+    .bind(table)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let (namespace, table_name) = split_table_name(&row.get::<String, usize>(0));
+
+        ForeignKey {
+            db_type: DbType::Sqlite,
+            namespace,
+            table_name,
+            column_name: row.get(1),
+            reference_table_name: reference_table_name.clone(),
+            reference_column_name: row.get(2),
+            on_delete: row.get::<String, usize>(3).parse().unwrap_or_default(),
+            ..Default::default()
+        }
+    })
+    .collect())
+}
+
+/// Journal the rows of `fk`'s table that reference any of `object_ids` in the
+/// table it points at, returning the IDs of the journaled rows.
+pub async fn put_rollback_journal_entries_referencing(
+    conn: &mut PoolConnection<Sqlite>,
+    namespace: &str,
+    identifier: &str,
+    fk: &ForeignKey,
+    block_height: u64,
+    type_id: i64,
+    object_ids: &[u64],
+) -> sqlx::Result<Vec<u64>> {
+    #[cfg(feature = "metrics")]
+    METRICS
+        .db
+        .sqlite
+        .put_rollback_journal_entries_referencing_calls
+        .inc();
+
+    let query = format!(
+        "INSERT INTO index_rollback_journal
+            (namespace, identifier, block_height, type_id, object_id, object)
+        SELECT ?1, ?2, ?3, ?4, c.id, c.object
+        FROM {table} c
+        JOIN {reference_table} p ON c.{column} = p.{reference_column}
+        WHERE p.id IN (SELECT value FROM json_each(?5))
+        RETURNING object_id",
+        table = DbType::Sqlite.table_name(&fk.namespace, &fk.table_name),
+        column = fk.column_name,
+        reference_table =
+            DbType::Sqlite.table_name(&fk.namespace, &fk.reference_table_name),
+        reference_column = fk.reference_column_name,
+    );
+
+    Ok(sqlx::query(&query)
+        .bind(namespace)
+        .bind(identifier)
+        .bind(block_height as i64)
+        .bind(type_id)
+        .bind(id_array(object_ids))
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| from_sqlite_uint(row.get(0)))
+        .collect())
+}
+
+/// Close the open versions of `object_ids` in the history of `table` at
+/// `block_height`, replacing any version recorded at that same height.
+pub async fn close_object_versions(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    object_ids: &[u64],
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.close_object_versions_calls.inc();
+
+    let history = history_table_name(table);
+    let object_ids = id_array(object_ids);
+
+    sqlx::query(&format!(
+        "DELETE FROM {history}
+        WHERE id IN (SELECT value FROM json_each(?1)) AND valid_from = ?2"
+    ))
+    .bind(&object_ids)
+    .bind(block_height as i64)
+    .execute(&mut *conn)
+    .await?;
+
+    let result = sqlx::query(&format!(
+        "UPDATE {history} SET valid_to = ?2
+        WHERE id IN (SELECT value FROM json_each(?1)) AND valid_to IS NULL"
+    ))
+    .bind(&object_ids)
+    .bind(block_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Record the current state of `object_ids` in `table` as their version
+/// valid from `block_height`, closing the versions it supersedes.
+pub async fn put_object_versions(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    columns: &[String],
+    object_ids: &[u64],
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.put_object_versions_calls.inc();
+
+    close_object_versions(conn, table, object_ids, block_height).await?;
+
+    let columns = columns.join(", ");
+
+    let result = sqlx::query(&format!(
+        "INSERT INTO {history} ({columns}, valid_from)
+        SELECT {columns}, ?2 FROM {table} WHERE id IN (SELECT value FROM json_each(?1))",
+        history = history_table_name(table),
+    ))
+    .bind(id_array(object_ids))
+    .bind(block_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Undo the history of `table` recorded at or after `block_height`.
+pub async fn rollback_object_versions(
+    conn: &mut PoolConnection<Sqlite>,
+    table: &str,
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.rollback_object_versions_calls.inc();
+
+    let history = history_table_name(table);
+
+    let result = sqlx::query(&format!("DELETE FROM {history} WHERE valid_from >= ?1"))
+        .bind(block_height as i64)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(&format!(
+        "UPDATE {history} SET valid_to = NULL WHERE valid_to >= ?1"
+    ))
+    .bind(block_height as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// SQLite has no way to notify other connections, so subscribers watch the
+/// checkpoint of the indexer instead.
+pub async fn notify_commit(
+    _conn: &mut PoolConnection<Sqlite>,
+    _namespace: &str,
+    _identifier: &str,
+) -> sqlx::Result<usize> {
+    #[cfg(feature = "metrics")]
+    METRICS.db.sqlite.notify_commit_calls.inc();
+
+    Ok(0)
+}
@@ -12,7 +12,10 @@ use fuel_indexer_lib::{
 };
 use fuel_indexer_postgres as postgres;
 use sqlx::{
-    pool::PoolConnection, postgres::PgConnectOptions, ConnectOptions, Error as SqlxError,
+    pool::PoolConnection,
+    postgres::PgConnectOptions,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode},
+    ConnectOptions, Error as SqlxError,
 };
 use std::{cmp::Ordering, collections::HashMap, str::FromStr};
 use thiserror::Error;
@@ -34,17 +37,20 @@ pub enum IndexerDatabaseError {
 #[derive(Debug)]
 pub enum IndexerConnection {
     Postgres(Box<PoolConnection<sqlx::Postgres>>),
+    Sqlite(Box<PoolConnection<sqlx::Sqlite>>),
 }
 
 #[derive(Clone, Debug)]
 pub enum IndexerConnectionPool {
     Postgres(sqlx::Pool<sqlx::Postgres>),
+    Sqlite(sqlx::Pool<sqlx::Sqlite>),
 }
 
 impl IndexerConnectionPool {
     pub fn database_type(&self) -> DbType {
         match self {
             IndexerConnectionPool::Postgres(_) => DbType::Postgres,
+            IndexerConnectionPool::Sqlite(_) => DbType::Sqlite,
         }
     }
 
@@ -90,6 +96,26 @@ impl IndexerConnectionPool {
 
                 Ok(IndexerConnectionPool::Postgres(pool))
             }
+            "sqlite" => {
+                // WAL lets the web API read while an indexer is writing.
+                let mut opts = SqliteConnectOptions::from_str(url.as_str())?
+                    .create_if_missing(true)
+                    .journal_mode(SqliteJournalMode::Wal);
+                match verbose.as_ref() {
+                    "true" => {}
+                    "false" => {
+                        opts.disable_statement_logging();
+                    }
+                    _ => unimplemented!(),
+                };
+
+                let pool = attempt_database_connection(|| {
+                    sqlx::sqlite::SqlitePoolOptions::new().connect_with(opts.clone())
+                })
+                .await;
+
+                Ok(IndexerConnectionPool::Sqlite(pool))
+            }
             err => Err(IndexerDatabaseError::BackendNotSupported(err.into())),
         }
     }
@@ -107,6 +133,13 @@ impl IndexerConnectionPool {
                     _ => Ok(ServiceStatus::NotOk),
                 }
             }
+            IndexerConnectionPool::Sqlite(p) => {
+                // SQLite reports no affected rows for a SELECT.
+                let mut conn = p.acquire().await?;
+                sqlx::query("SELECT 1").fetch_one(&mut conn).await?;
+
+                Ok(ServiceStatus::OK)
+            }
         }
     }
 
@@ -115,6 +148,9 @@ impl IndexerConnectionPool {
             IndexerConnectionPool::Postgres(p) => {
                 Ok(IndexerConnection::Postgres(Box::new(p.acquire().await?)))
             }
+            IndexerConnectionPool::Sqlite(p) => {
+                Ok(IndexerConnection::Sqlite(Box::new(p.acquire().await?)))
+            }
         }
     }
 }
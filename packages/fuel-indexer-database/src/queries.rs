@@ -1,5 +1,6 @@
 use crate::{types::*, IndexerConnection};
 use fuel_indexer_postgres as postgres;
+use fuel_indexer_sqlite as sqlite;
 use sqlx::types::JsonValue;
 
 pub async fn graph_root_latest(
@@ -11,6 +12,9 @@ pub async fn graph_root_latest(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::graph_root_latest(c, namespace, identifier).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::graph_root_latest(c, namespace, identifier).await
+        }
     }
 }
 
@@ -20,6 +24,37 @@ pub async fn new_graph_root(
 ) -> sqlx::Result<usize> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::new_graph_root(c, root).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::new_graph_root(c, root).await,
+    }
+}
+
+pub async fn remove_schema_metadata(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    version: &str,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::remove_schema_metadata(c, namespace, identifier, version).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::remove_schema_metadata(c, namespace, identifier, version).await
+        }
+    }
+}
+
+pub async fn new_schema_migration(
+    conn: &mut IndexerConnection,
+    migration: NewSchemaMigration,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::new_schema_migration(c, migration).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::new_schema_migration(c, migration).await
+        }
     }
 }
 
@@ -33,6 +68,9 @@ pub async fn type_id_list_by_name(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::type_id_list_by_name(c, name, version, identifier).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::type_id_list_by_name(c, name, version, identifier).await
+        }
     }
 }
 
@@ -45,6 +83,9 @@ pub async fn type_id_latest(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::type_id_latest(c, schema_name, identifier).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::type_id_latest(c, schema_name, identifier).await
+        }
     }
 }
 
@@ -56,6 +97,7 @@ pub async fn type_id_insert(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::type_id_insert(c, type_ids).await
         }
+        IndexerConnection::Sqlite(ref mut c) => sqlite::type_id_insert(c, type_ids).await,
     }
 }
 
@@ -69,6 +111,9 @@ pub async fn schema_exists(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::schema_exists(c, namespace, identifier, version).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::schema_exists(c, namespace, identifier, version).await
+        }
     }
 }
 
@@ -80,6 +125,7 @@ pub async fn new_column_insert(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::new_column_insert(c, cols).await
         }
+        IndexerConnection::Sqlite(ref mut c) => sqlite::new_column_insert(c, cols).await,
     }
 }
 
@@ -91,6 +137,9 @@ pub async fn list_column_by_id(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::list_column_by_id(c, col_id).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::list_column_by_id(c, col_id).await
+        }
     }
 }
 
@@ -104,27 +153,88 @@ pub async fn columns_get_schema(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::columns_get_schema(c, name, identifier, version).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::columns_get_schema(c, name, identifier, version).await
+        }
     }
 }
 
-pub async fn put_object(
+pub async fn put_objects(
     conn: &mut IndexerConnection,
-    query: String,
-    bytes: Vec<u8>,
+    table: &str,
+    columns: &[String],
+    rows: Vec<Vec<BindValue>>,
 ) -> sqlx::Result<usize> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => {
-            postgres::put_object(c, query, bytes).await
+            postgres::put_objects(c, table, columns, rows).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::put_objects(c, table, columns, rows).await
+        }
+    }
+}
+
+pub async fn find_objects(
+    conn: &mut IndexerConnection,
+    table: &str,
+    columns: Vec<(String, BindValue)>,
+    limit: Option<u64>,
+) -> sqlx::Result<Vec<Vec<u8>>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::find_objects(c, table, columns, limit).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::find_objects(c, table, columns, limit).await
         }
     }
 }
 
 pub async fn get_object(
     conn: &mut IndexerConnection,
-    query: String,
+    table: &str,
+    object_id: u64,
 ) -> sqlx::Result<Vec<u8>> {
     match conn {
-        IndexerConnection::Postgres(ref mut c) => postgres::get_object(c, query).await,
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::get_object(c, table, object_id).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::get_object(c, table, object_id).await
+        }
+    }
+}
+
+pub async fn list_objects(
+    conn: &mut IndexerConnection,
+    table: &str,
+    after: Option<u64>,
+    limit: i64,
+) -> sqlx::Result<Vec<(u64, Vec<u8>)>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::list_objects(c, table, after, limit).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::list_objects(c, table, after, limit).await
+        }
+    }
+}
+
+pub async fn set_object_bytes(
+    conn: &mut IndexerConnection,
+    table: &str,
+    object_id: u64,
+    bytes: Vec<u8>,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::set_object_bytes(c, table, object_id, bytes).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::set_object_bytes(c, table, object_id, bytes).await
+        }
     }
 }
 
@@ -134,6 +244,7 @@ pub async fn run_query(
 ) -> sqlx::Result<JsonValue> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::run_query(c, query).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::run_query(c, query).await,
     }
 }
 
@@ -143,6 +254,7 @@ pub async fn execute_query(
 ) -> sqlx::Result<usize> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::execute_query(c, query).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::execute_query(c, query).await,
     }
 }
 
@@ -154,6 +266,9 @@ pub async fn root_columns_list_by_id(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::root_columns_list_by_id(c, root_id).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::root_columns_list_by_id(c, root_id).await
+        }
     }
 }
 
@@ -165,6 +280,7 @@ pub async fn new_root_columns(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::new_root_columns(c, cols).await
         }
+        IndexerConnection::Sqlite(ref mut c) => sqlite::new_root_columns(c, cols).await,
     }
 }
 
@@ -177,6 +293,9 @@ pub async fn index_is_registered(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::index_is_registered(c, namespace, identifier).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::index_is_registered(c, namespace, identifier).await
+        }
     }
 }
 
@@ -190,6 +309,9 @@ pub async fn register_index(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::register_index(c, namespace, identifier, pubkey).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::register_index(c, namespace, identifier, pubkey).await
+        }
     }
 }
 
@@ -198,6 +320,40 @@ pub async fn registered_indices(
 ) -> sqlx::Result<Vec<RegisteredIndex>> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::registered_indices(c).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::registered_indices(c).await,
+    }
+}
+
+pub async fn set_index_completed(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    completed: bool,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::set_index_completed(c, namespace, identifier, completed).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::set_index_completed(c, namespace, identifier, completed).await
+        }
+    }
+}
+
+pub async fn set_index_status(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    status: IndexerStatus,
+    message: Option<&str>,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::set_index_status(c, namespace, identifier, status, message).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::set_index_status(c, namespace, identifier, status, message).await
+        }
     }
 }
 
@@ -210,6 +366,9 @@ pub async fn index_asset_version(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::index_asset_version(c, index_id, asset_type).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::index_asset_version(c, index_id, asset_type).await
+        }
     }
 }
 
@@ -228,6 +387,12 @@ pub async fn register_index_asset(
             )
             .await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::register_index_asset(
+                c, namespace, identifier, bytes, asset_type, pubkey,
+            )
+            .await
+        }
     }
 }
 
@@ -240,6 +405,9 @@ pub async fn latest_asset_for_index(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::latest_asset_for_index(c, index_id, asset_type).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::latest_asset_for_index(c, index_id, asset_type).await
+        }
     }
 }
 
@@ -251,17 +419,121 @@ pub async fn latest_assets_for_index(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::latest_assets_for_index(c, index_id).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::latest_assets_for_index(c, index_id).await
+        }
+    }
+}
+
+pub async fn put_checkpoint(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    block_height: u64,
+    block_id: Option<&str>,
+    cursor: Option<&str>,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::put_checkpoint(
+                c,
+                namespace,
+                identifier,
+                block_height,
+                block_id,
+                cursor,
+            )
+            .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::put_checkpoint(
+                c,
+                namespace,
+                identifier,
+                block_height,
+                block_id,
+                cursor,
+            )
+            .await
+        }
+    }
+}
+
+pub async fn checkpoint_for_indexer(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Option<IndexerCheckpoint>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::checkpoint_for_indexer(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::checkpoint_for_indexer(c, namespace, identifier).await
+        }
     }
 }
 
-pub async fn last_block_height_for_indexer(
+pub async fn put_dead_letter(
     conn: &mut IndexerConnection,
     namespace: &str,
     identifier: &str,
-) -> sqlx::Result<u64> {
+    block_height: u64,
+    block_id: &str,
+    error: &str,
+) -> sqlx::Result<usize> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => {
-            postgres::last_block_height_for_indexer(c, namespace, identifier).await
+            postgres::put_dead_letter(
+                c,
+                namespace,
+                identifier,
+                block_height,
+                block_id,
+                error,
+            )
+            .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::put_dead_letter(
+                c,
+                namespace,
+                identifier,
+                block_height,
+                block_id,
+                error,
+            )
+            .await
+        }
+    }
+}
+
+pub async fn dead_letters(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Vec<DeadLetter>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::dead_letters(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::dead_letters(c, namespace, identifier).await
+        }
+    }
+}
+
+pub async fn remove_checkpoint(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::remove_checkpoint(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::remove_checkpoint(c, namespace, identifier).await
         }
     }
 }
@@ -276,6 +548,9 @@ pub async fn asset_already_exists(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::asset_already_exists(c, asset_type, bytes, index_id).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::asset_already_exists(c, asset_type, bytes, index_id).await
+        }
     }
 }
 
@@ -288,6 +563,9 @@ pub async fn index_id_for(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::index_id_for(c, namespace, identifier).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::index_id_for(c, namespace, identifier).await
+        }
     }
 }
 
@@ -302,30 +580,38 @@ pub async fn penultimate_asset_for_index(
             postgres::penultimate_asset_for_index(c, namespace, identifier, asset_type)
                 .await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::penultimate_asset_for_index(c, namespace, identifier, asset_type)
+                .await
+        }
     }
 }
 
 pub async fn start_transaction(conn: &mut IndexerConnection) -> sqlx::Result<usize> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::start_transaction(c).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::start_transaction(c).await,
     }
 }
 
 pub async fn commit_transaction(conn: &mut IndexerConnection) -> sqlx::Result<usize> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::commit_transaction(c).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::commit_transaction(c).await,
     }
 }
 
 pub async fn revert_transaction(conn: &mut IndexerConnection) -> sqlx::Result<usize> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::revert_transaction(c).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::revert_transaction(c).await,
     }
 }
 
 pub async fn run_migration(conn: &mut IndexerConnection) -> sqlx::Result<()> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::run_migration(c).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::run_migration(c).await,
     }
 }
 
@@ -338,6 +624,120 @@ pub async fn remove_indexer(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::remove_indexer(c, namespace, identifier).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::remove_indexer(c, namespace, identifier).await
+        }
+    }
+}
+
+pub async fn put_shadow_deployment(
+    conn: &mut IndexerConnection,
+    index_id: &i64,
+    wasm: Vec<u8>,
+    manifest: Vec<u8>,
+    schema: Vec<u8>,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::put_shadow_deployment(c, index_id, wasm, manifest, schema).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::put_shadow_deployment(c, index_id, wasm, manifest, schema).await
+        }
+    }
+}
+
+pub async fn shadow_deployment_for(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<Option<ShadowDeployment>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::shadow_deployment_for(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::shadow_deployment_for(c, namespace, identifier).await
+        }
+    }
+}
+
+pub async fn set_shadow_caught_up(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::set_shadow_caught_up(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::set_shadow_caught_up(c, namespace, identifier).await
+        }
+    }
+}
+
+pub async fn remove_shadow_deployment(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::remove_shadow_deployment(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::remove_shadow_deployment(c, namespace, identifier).await
+        }
+    }
+}
+
+pub async fn remove_indexer_state(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::remove_indexer_state(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::remove_indexer_state(c, namespace, identifier).await
+        }
+    }
+}
+
+pub async fn rename_indexer_state(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    from_identifier: &str,
+    to_identifier: &str,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::rename_indexer_state(c, namespace, from_identifier, to_identifier)
+                .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::rename_indexer_state(c, namespace, from_identifier, to_identifier)
+                .await
+        }
+    }
+}
+
+pub async fn lock_indexer(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    exclusive: bool,
+) -> sqlx::Result<()> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::lock_indexer(c, namespace, identifier, exclusive).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::lock_indexer(c, namespace, identifier, exclusive).await
+        }
     }
 }
 
@@ -351,18 +751,23 @@ pub async fn remove_asset_by_version(
         IndexerConnection::Postgres(ref mut c) => {
             postgres::remove_asset_by_version(c, index_id, version, asset_type).await
         }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::remove_asset_by_version(c, index_id, version, asset_type).await
+        }
     }
 }
 
 pub async fn create_nonce(conn: &mut IndexerConnection) -> sqlx::Result<Nonce> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::create_nonce(c).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::create_nonce(c).await,
     }
 }
 
 pub async fn get_nonce(conn: &mut IndexerConnection, uid: &str) -> sqlx::Result<Nonce> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::get_nonce(c, uid).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::get_nonce(c, uid).await,
     }
 }
 
@@ -372,5 +777,229 @@ pub async fn delete_nonce(
 ) -> sqlx::Result<()> {
     match conn {
         IndexerConnection::Postgres(ref mut c) => postgres::delete_nonce(c, nonce).await,
+        IndexerConnection::Sqlite(ref mut c) => sqlite::delete_nonce(c, nonce).await,
+    }
+}
+
+pub async fn put_rollback_journal_entries(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    table: &str,
+    block_height: u64,
+    type_id: i64,
+    object_ids: &[u64],
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::put_rollback_journal_entries(
+                c,
+                namespace,
+                identifier,
+                table,
+                block_height,
+                type_id,
+                object_ids,
+            )
+            .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::put_rollback_journal_entries(
+                c,
+                namespace,
+                identifier,
+                table,
+                block_height,
+                type_id,
+                object_ids,
+            )
+            .await
+        }
+    }
+}
+
+pub async fn rollback_journal_entries(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    from_height: u64,
+) -> sqlx::Result<Vec<RollbackJournalEntry>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::rollback_journal_entries(c, namespace, identifier, from_height)
+                .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::rollback_journal_entries(c, namespace, identifier, from_height).await
+        }
+    }
+}
+
+pub async fn remove_rollback_journal_entries(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    from_height: u64,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::remove_rollback_journal_entries(
+                c,
+                namespace,
+                identifier,
+                from_height,
+            )
+            .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::remove_rollback_journal_entries(c, namespace, identifier, from_height)
+                .await
+        }
+    }
+}
+
+pub async fn prune_rollback_journal(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    below_height: u64,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::prune_rollback_journal(c, namespace, identifier, below_height).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::prune_rollback_journal(c, namespace, identifier, below_height).await
+        }
+    }
+}
+
+pub async fn delete_object(
+    conn: &mut IndexerConnection,
+    table: &str,
+    object_id: u64,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::delete_object(c, table, object_id).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::delete_object(c, table, object_id).await
+        }
+    }
+}
+
+pub async fn foreign_keys_referencing(
+    conn: &mut IndexerConnection,
+    table: &str,
+) -> sqlx::Result<Vec<ForeignKey>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::foreign_keys_referencing(c, table).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::foreign_keys_referencing(c, table).await
+        }
+    }
+}
+
+pub async fn put_rollback_journal_entries_referencing(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    fk: &ForeignKey,
+    block_height: u64,
+    type_id: i64,
+    object_ids: &[u64],
+) -> sqlx::Result<Vec<u64>> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::put_rollback_journal_entries_referencing(
+                c,
+                namespace,
+                identifier,
+                fk,
+                block_height,
+                type_id,
+                object_ids,
+            )
+            .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::put_rollback_journal_entries_referencing(
+                c,
+                namespace,
+                identifier,
+                fk,
+                block_height,
+                type_id,
+                object_ids,
+            )
+            .await
+        }
+    }
+}
+
+pub async fn put_object_versions(
+    conn: &mut IndexerConnection,
+    table: &str,
+    columns: &[String],
+    object_ids: &[u64],
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::put_object_versions(c, table, columns, object_ids, block_height)
+                .await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::put_object_versions(c, table, columns, object_ids, block_height).await
+        }
+    }
+}
+
+pub async fn close_object_versions(
+    conn: &mut IndexerConnection,
+    table: &str,
+    object_ids: &[u64],
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::close_object_versions(c, table, object_ids, block_height).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::close_object_versions(c, table, object_ids, block_height).await
+        }
+    }
+}
+
+pub async fn rollback_object_versions(
+    conn: &mut IndexerConnection,
+    table: &str,
+    block_height: u64,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::rollback_object_versions(c, table, block_height).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::rollback_object_versions(c, table, block_height).await
+        }
+    }
+}
+
+pub async fn notify_commit(
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+) -> sqlx::Result<usize> {
+    match conn {
+        IndexerConnection::Postgres(ref mut c) => {
+            postgres::notify_commit(c, namespace, identifier).await
+        }
+        IndexerConnection::Sqlite(ref mut c) => {
+            sqlite::notify_commit(c, namespace, identifier).await
+        }
     }
 }
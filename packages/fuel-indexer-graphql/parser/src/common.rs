@@ -49,9 +49,10 @@ pub struct Directive<'a, T: Text<'a>> {
 /// But since there is no definition on limit of number in spec
 /// (only in implemetation), we do a trick similar to the one
 /// in `serde_json`: encapsulate value in new-type, allowing type
-/// to be extended later.
+/// to be extended later. It is signed, so that it holds negative
+/// integers as well as any `u64`.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Number(pub(crate) u64);
+pub struct Number(pub(crate) i128);
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BigNumber(pub(crate) u128);
@@ -113,6 +114,24 @@ impl BigNumber {
     }
 }
 
+impl From<u64> for Number {
+    fn from(n: u64) -> Self {
+        Number(n.into())
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Self {
+        Number(n.into())
+    }
+}
+
+impl From<u128> for BigNumber {
+    fn from(n: u128) -> Self {
+        BigNumber(n)
+    }
+}
+
 impl Number {
     /// Returns a number as i64 if it fits the type
     pub fn as_i64(&self) -> Option<i64> {
@@ -123,8 +142,13 @@ impl Number {
         }
     }
 
-    pub fn as_u64(&self) -> u64 {
-        self.0
+    /// Returns a number as u64 if it fits the type
+    pub fn as_u64(&self) -> Option<u64> {
+        if let Ok(n) = TryInto::<u64>::try_into(self.0) {
+            Some(n)
+        } else {
+            None
+        }
     }
 }
 
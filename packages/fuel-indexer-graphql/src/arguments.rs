@@ -1,9 +1,14 @@
 use super::graphql::GraphqlError;
-use fuel_indexer_database::DbType;
-use fuel_indexer_schema::db::tables::Schema;
+use super::introspection::{build_types, TypeDef, TypeKind, TypeRef};
+use fuel_indexer_database::{types::sqlite_uint, DbType};
+use fuel_indexer_schema::{db::tables::Schema, utils::normalize_field_type_name};
 
-use fuel_indexer_graphql_parser::query::Value;
-use std::{collections::BTreeMap, fmt};
+use fuel_indexer_graphql_parser::query::{Number, Value, VariableDefinition};
+use serde_json::Value as JsonValue;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
 
 /// Represents the full set of parameters that can be applied to a query.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -12,6 +17,16 @@ pub struct QueryParams {
     pub sorts: Vec<Sort>,
     pub offset: Option<u64>,
     pub limit: Option<u64>,
+    /// Height of the block whose state is queried, rather than the latest.
+    pub block: Option<u64>,
+    /// Columns by which the records of an aggregate query are grouped.
+    pub group_by: Vec<String>,
+    /// Only return the records following this position in a connection.
+    pub after: Option<Cursor>,
+    /// Only return the records preceding this position in a connection.
+    pub before: Option<Cursor>,
+    /// Return at most this many records, from the end of a connection.
+    pub last: Option<u64>,
 }
 
 impl QueryParams {
@@ -36,6 +51,15 @@ impl QueryParams {
                 }),
                 ParamType::Offset(n) => self.offset = Some(n),
                 ParamType::Limit(n) => self.limit = Some(n),
+                ParamType::Block(n) => self.block = Some(n),
+                ParamType::After(cursor) => self.after = Some(cursor),
+                ParamType::Before(cursor) => self.before = Some(cursor),
+                ParamType::Last(n) => self.last = Some(n),
+                ParamType::GroupBy(fields) => self.group_by.extend(
+                    fields
+                        .into_iter()
+                        .map(|field| format!("{}.{}", fully_qualified_table_name, field)),
+                ),
             }
         }
     }
@@ -63,7 +87,7 @@ impl QueryParams {
         let mut query_clause = "".to_string();
 
         match db_type {
-            DbType::Postgres => {
+            DbType::Postgres | DbType::Sqlite => {
                 if !self.sorts.is_empty() {
                     let sort_expressions = self
                         .sorts
@@ -80,6 +104,30 @@ impl QueryParams {
 
         query_clause
     }
+
+    /// Return a string grouping the result set by the query's grouping columns,
+    /// if any; groups are ordered by these same columns.
+    pub(crate) fn get_grouping_modifier(&self, db_type: &DbType) -> String {
+        let mut query_clause = "".to_string();
+
+        match db_type {
+            DbType::Postgres | DbType::Sqlite => {
+                if !self.group_by.is_empty() {
+                    let columns = self.group_by.join(", ");
+                    query_clause = [
+                        query_clause,
+                        "GROUP BY".to_string(),
+                        columns.clone(),
+                        "ORDER BY".to_string(),
+                        columns,
+                    ]
+                    .join(" ");
+                }
+            }
+        }
+
+        query_clause
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -102,6 +150,91 @@ pub enum ParamType {
     Sort(String, SortOrder),
     Offset(u64),
     Limit(u64),
+    Block(u64),
+    GroupBy(Vec<String>),
+    After(Cursor),
+    Before(Cursor),
+    Last(u64),
+}
+
+/// Represents a position in a connection: the value of the field the
+/// connection is ordered by, if any, and the ID of the record at that position.
+///
+/// Cursors are handed to clients as the hex encoding of a JSON array of these
+/// values as strings, which the database builds along with each record. The
+/// value of the field is `null` in the array, and `Some(None)` here, for
+/// records where it isn't set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub key: Option<Option<ParsedValue>>,
+    pub id: u64,
+}
+
+impl Cursor {
+    /// Decode a cursor returned by a previous query of a connection ordered
+    /// by a field of `key_type`, if any. The values in the cursor have to be
+    /// values of their fields' types.
+    pub fn decode(cursor: &str, key_type: Option<&str>) -> Result<Cursor, GraphqlError> {
+        let invalid = || GraphqlError::InvalidCursor(cursor.to_string());
+
+        if cursor.len() % 2 != 0 {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| {
+                cursor
+                    .get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+
+        let values: Vec<Option<String>> =
+            serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        let (key, id) = match values.as_slice() {
+            [Some(id)] => (None, id),
+            [key, Some(id)] => (Some(key), id),
+            _ => return Err(invalid()),
+        };
+        let key = match (key, key_type) {
+            (Some(Some(key)), Some(key_type)) => {
+                Some(Some(cursor_key(key, key_type).ok_or_else(invalid)?))
+            }
+            // The key of a cursor from a connection in another order is never
+            // read, as the cursor is rejected along with the query.
+            (Some(Some(key)), None) => Some(Some(ParsedValue::String(key.clone()))),
+            (Some(None), _) => Some(None),
+            (None, _) => None,
+        };
+
+        Ok(Cursor {
+            key,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Parse the value of the field a connection is ordered by, as it's written in
+/// a cursor, as a value of the field's type.
+fn cursor_key(value: &str, key_type: &str) -> Option<ParsedValue> {
+    match key_type {
+        "ID" | "UInt8" => value.parse().ok().map(ParsedValue::UInt8),
+        "UInt4" => value.parse().ok().map(ParsedValue::Number),
+        "UInt16" => value.parse().ok().map(ParsedValue::BigNumber),
+        "Int4" | "Int8" => value.parse().ok().map(ParsedValue::SignedNumber),
+        "Int16" => match value.parse::<i128>().ok()? {
+            n if n >= 0 => Some(ParsedValue::BigNumber(n as u128)),
+            n => i64::try_from(n).ok().map(ParsedValue::SignedNumber),
+        },
+        // SQLite stores booleans as integers.
+        "Boolean" => match value {
+            "true" | "1" => Some(ParsedValue::Boolean(true)),
+            "false" | "0" => Some(ParsedValue::Boolean(false)),
+            _ => None,
+        },
+        _ => Some(ParsedValue::String(value.to_string())),
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -130,10 +263,24 @@ impl fmt::Display for SortOrder {
 pub enum ParsedValue {
     BigNumber(u128),
     Number(u64),
+    /// The value of a field holding unsigned 64-bit integers, like an ID.
+    UInt8(u64),
+    SignedNumber(i64),
     String(String),
     Boolean(bool),
 }
 
+impl ParsedValue {
+    /// The value as a SQL literal. SQLite stores unsigned 64-bit integers
+    /// with `sqlite_uint`, so they're compared as such.
+    pub(crate) fn to_sql(&self, db_type: &DbType) -> String {
+        match (db_type, self) {
+            (DbType::Sqlite, Self::UInt8(n)) => sqlite_uint(*n).to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
 /// Display trait implementation, mainly to be able to use `.to_string()`.
 ///
 /// Databases may support several value types in a filtering clause, e.g.
@@ -148,11 +295,14 @@ impl fmt::Display for ParsedValue {
             Self::Boolean(b) => {
                 write!(f, "{b}")
             }
-            Self::Number(n) => {
+            Self::Number(n) | Self::UInt8(n) => {
+                write!(f, "{n}")
+            }
+            Self::SignedNumber(n) => {
                 write!(f, "{n}")
             }
             Self::String(s) => {
-                write!(f, "\'{s}\'")
+                write!(f, "'{}'", s.replace('\'', "''"))
             }
         }
     }
@@ -206,32 +356,54 @@ impl FilterType {
     /// Returns a string to be used as part of a SQL database query.
     pub fn to_sql(&self, fully_qualified_table: String, db_type: &DbType) -> String {
         match db_type {
-            DbType::Postgres => match self {
+            DbType::Postgres | DbType::Sqlite => match self {
                 Self::Comparison(c) => match c {
                     Comparison::Between(field, min, max) => {
-                        format!("{fully_qualified_table}.{field} BETWEEN {min} AND {max}",)
+                        format!(
+                            "{fully_qualified_table}.{field} BETWEEN {} AND {}",
+                            min.to_sql(db_type),
+                            max.to_sql(db_type)
+                        )
                     }
                     Comparison::Equals(field, val) => {
-                        format!("{fully_qualified_table}.{field} = {val}",)
+                        format!(
+                            "{fully_qualified_table}.{field} = {}",
+                            val.to_sql(db_type)
+                        )
                     }
                     Comparison::NotEquals(field, val) => {
-                        format!("{fully_qualified_table}.{field} <> {val}",)
+                        format!(
+                            "{fully_qualified_table}.{field} <> {}",
+                            val.to_sql(db_type)
+                        )
                     }
                     Comparison::Greater(field, val) => {
-                        format!("{fully_qualified_table}.{field} > {val}",)
+                        format!(
+                            "{fully_qualified_table}.{field} > {}",
+                            val.to_sql(db_type)
+                        )
                     }
                     Comparison::GreaterEqual(field, val) => {
-                        format!("{fully_qualified_table}.{field} >= {val}",)
+                        format!(
+                            "{fully_qualified_table}.{field} >= {}",
+                            val.to_sql(db_type)
+                        )
                     }
                     Comparison::Less(field, val) => {
-                        format!("{fully_qualified_table}.{field} < {val}",)
+                        format!(
+                            "{fully_qualified_table}.{field} < {}",
+                            val.to_sql(db_type)
+                        )
                     }
                     Comparison::LessEqual(field, val) => {
-                        format!("{fully_qualified_table}.{field} <= {val}",)
+                        format!(
+                            "{fully_qualified_table}.{field} <= {}",
+                            val.to_sql(db_type)
+                        )
                     }
                 },
                 Self::IdSelection(id) => {
-                    format!("{fully_qualified_table}.id = {id}")
+                    format!("{fully_qualified_table}.id = {}", id.to_sql(db_type))
                 }
                 Self::LogicOp(lo) => match lo {
                     LogicOp::And(r1, r2) => format!(
@@ -255,7 +427,7 @@ impl FilterType {
                             "{fully_qualified_table}.{field} IN ({})",
                             member_set
                                 .iter()
-                                .map(|v| v.to_string())
+                                .map(|v| v.to_sql(db_type))
                                 .collect::<Vec<String>>()
                                 .join(", ")
                         )
@@ -265,7 +437,7 @@ impl FilterType {
                             "{fully_qualified_table}.{field} NOT IN ({})",
                             member_set
                                 .iter()
-                                .map(|v| v.to_string())
+                                .map(|v| v.to_sql(db_type))
                                 .collect::<Vec<String>>()
                                 .join(", ")
                         )
@@ -368,6 +540,194 @@ impl FilterType {
     }
 }
 
+/// The type-checked values of an operation's variables.
+#[derive(Debug, Clone, Default)]
+pub struct QueryVariables {
+    values: HashMap<String, Value<'static, String>>,
+}
+
+impl QueryVariables {
+    pub fn new<'a>(
+        schema: &Schema,
+        definitions: &[VariableDefinition<'a, &'a str>],
+        provided: &HashMap<String, JsonValue>,
+    ) -> Result<QueryVariables, GraphqlError> {
+        let types = build_types(schema);
+        let mut values = HashMap::new();
+
+        for def in definitions {
+            let ty = TypeRef::from(&def.var_type);
+            if !types.contains_key(ty.name()) {
+                return Err(GraphqlError::UnrecognizedType(ty.name().to_string()));
+            }
+
+            let value = match (provided.get(def.name), &def.default_value) {
+                (Some(json), _) => coerce_variable(def.name, json, &ty, &types)?,
+                (None, Some(default)) => default.into_static(),
+                (None, None) => coerce_variable(def.name, &JsonValue::Null, &ty, &types)?,
+            };
+            values.insert(def.name.to_string(), value);
+        }
+
+        Ok(QueryVariables { values })
+    }
+
+    /// Replace the variables referenced in `value` with their values.
+    pub fn substitute<'a: 'b, 'b>(
+        &'b self,
+        value: Value<'a, &'a str>,
+    ) -> Result<Value<'b, &'b str>, GraphqlError> {
+        let value = match value {
+            Value::Variable(name) => self
+                .values
+                .get(name)
+                .map(borrow_value)
+                .ok_or_else(|| GraphqlError::UndefinedVariable(name.to_string()))?,
+            Value::List(items) => Value::List(
+                items
+                    .into_iter()
+                    .map(|item| self.substitute(item))
+                    .collect::<Result<Vec<_>, GraphqlError>>()?,
+            ),
+            Value::Object(obj) => Value::Object(
+                obj.into_iter()
+                    .map(|(key, value)| Ok((key, self.substitute(value)?)))
+                    .collect::<Result<BTreeMap<_, _>, GraphqlError>>()?,
+            ),
+            Value::BigInt(n) => Value::BigInt(n),
+            Value::Int(n) => Value::Int(n),
+            Value::Float(f) => Value::Float(f),
+            Value::String(s) => Value::String(s),
+            Value::Boolean(b) => Value::Boolean(b),
+            Value::Null => Value::Null,
+            Value::Enum(e) => Value::Enum(e),
+        };
+
+        Ok(value)
+    }
+}
+
+fn borrow_value<'a>(value: &'a Value<'static, String>) -> Value<'a, &'a str> {
+    match value {
+        Value::Variable(name) => Value::Variable(name.as_str()),
+        Value::BigInt(n) => Value::BigInt(n.clone()),
+        Value::Int(n) => Value::Int(n.clone()),
+        Value::Float(f) => Value::Float(*f),
+        Value::String(s) => Value::String(s.clone()),
+        Value::Boolean(b) => Value::Boolean(*b),
+        Value::Null => Value::Null,
+        Value::Enum(e) => Value::Enum(e.as_str()),
+        Value::List(items) => Value::List(items.iter().map(borrow_value).collect()),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(key, value)| (key.as_str(), borrow_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Check a variable's JSON value against its declared type, converting it
+/// into the value it would have been if written in the query itself.
+fn coerce_variable(
+    name: &str,
+    json: &JsonValue,
+    ty: &TypeRef,
+    types: &BTreeMap<String, TypeDef>,
+) -> Result<Value<'static, String>, GraphqlError> {
+    let invalid = || {
+        GraphqlError::InvalidVariableValue(
+            name.to_string(),
+            ty.to_string(),
+            json.to_string(),
+        )
+    };
+
+    match (ty, json) {
+        (TypeRef::NonNull(_), JsonValue::Null) => Err(invalid()),
+        (TypeRef::NonNull(inner), _) => coerce_variable(name, json, inner, types),
+        (_, JsonValue::Null) => Ok(Value::Null),
+        (TypeRef::List(inner), JsonValue::Array(items)) => items
+            .iter()
+            .map(|item| coerce_variable(name, item, inner, types))
+            .collect::<Result<Vec<_>, GraphqlError>>()
+            .map(Value::List),
+        // A single value can be given where a list is expected.
+        (TypeRef::List(inner), _) => Ok(Value::List(vec![coerce_variable(
+            name, json, inner, types,
+        )?])),
+        (TypeRef::Named(type_name), _) => {
+            let def = types
+                .get(type_name)
+                .ok_or_else(|| GraphqlError::UnrecognizedType(type_name.to_string()))?;
+
+            match (def.kind, json) {
+                (TypeKind::Scalar, _) => {
+                    coerce_scalar(type_name, json).ok_or_else(invalid)
+                }
+                (TypeKind::Enum, JsonValue::String(s)) if def.enum_values.contains(s) => {
+                    Ok(Value::Enum(s.clone()))
+                }
+                (TypeKind::InputObject, JsonValue::Object(obj)) => {
+                    let missing_field = def.input_fields.iter().any(|field| {
+                        matches!(field.ty, TypeRef::NonNull(_))
+                            && !obj.contains_key(&field.name)
+                    });
+                    if missing_field {
+                        return Err(invalid());
+                    }
+
+                    let mut fields = BTreeMap::new();
+                    for (key, value) in obj {
+                        let field = def
+                            .input_fields
+                            .iter()
+                            .find(|field| field.name == *key)
+                            .ok_or_else(|| {
+                                GraphqlError::UnrecognizedArgument(
+                                    type_name.to_string(),
+                                    key.to_string(),
+                                )
+                            })?;
+                        fields.insert(
+                            key.to_string(),
+                            coerce_variable(name, value, &field.ty, types)?,
+                        );
+                    }
+                    Ok(Value::Object(fields))
+                }
+                _ => Err(invalid()),
+            }
+        }
+    }
+}
+
+/// Scalars defined by an indexer's schema, like `Address` or `UInt8`, can be
+/// written as strings, numbers or booleans, as they can in a query.
+fn coerce_scalar(type_name: &str, json: &JsonValue) -> Option<Value<'static, String>> {
+    let number = || {
+        json.as_i64()
+            .map(Number::from)
+            .or_else(|| json.as_u64().map(Number::from))
+            .map(Value::Int)
+    };
+
+    match type_name {
+        "Int" => number(),
+        "String" => json.as_str().map(|s| Value::String(s.to_string())),
+        "Boolean" => json.as_bool().map(Value::Boolean),
+        "ID" => match json {
+            JsonValue::String(s) => Some(Value::String(s.clone())),
+            _ => number(),
+        },
+        _ => match json {
+            JsonValue::String(s) => Some(Value::String(s.clone())),
+            JsonValue::Bool(b) => Some(Value::Boolean(*b)),
+            JsonValue::Number(_) => number(),
+            _ => None,
+        },
+    }
+}
+
 /// Parse an argument key-value pair into a `Filter`.
 ///
 /// `parse_arguments` is the entry point for parsing all API query arguments.
@@ -377,7 +737,10 @@ pub fn parse_argument_into_param<'a>(
     arg: &str,
     value: Value<'a, &'a str>,
     schema: &Schema,
+    variables: &QueryVariables,
 ) -> Result<ParamType, GraphqlError> {
+    let value = variables.substitute(value)?;
+
     match arg {
         "filter" => {
             // We instantiate an Option<Filter> in order to keep track of the last
@@ -393,9 +756,9 @@ pub fn parse_argument_into_param<'a>(
                 Err(GraphqlError::UnsupportedValueType(value.to_string()))
             }
         }
-        "id" => Ok(ParamType::Filter(FilterType::IdSelection(parse_value(
-            &value,
-        )?))),
+        "id" => Ok(ParamType::Filter(FilterType::IdSelection(
+            parse_field_value(&value, true)?,
+        ))),
         "order" => {
             if let Value::Object(obj) = value {
                 if let Some((sort_order, predicate)) = obj.into_iter().next() {
@@ -437,19 +800,14 @@ pub fn parse_argument_into_param<'a>(
                 Err(GraphqlError::UnsupportedValueType(value.to_string()))
             }
         }
-        "offset" => {
-            if let Value::Int(offset) = value {
-                Ok(ParamType::Offset(offset.as_u64()))
-            } else {
-                Err(GraphqlError::UnsupportedValueType(value.to_string()))
-            }
-        }
-        "first" => {
-            if let Value::Int(limit) = value {
-                Ok(ParamType::Limit(limit.as_u64()))
-            } else {
-                Err(GraphqlError::UnsupportedValueType(value.to_string()))
+        "offset" => Ok(ParamType::Offset(unsigned_int(&value)?)),
+        "first" => Ok(ParamType::Limit(unsigned_int(&value)?)),
+        "block" => {
+            if !schema.is_versioned(entity_type) {
+                return Err(GraphqlError::UnversionedEntity(entity_type.to_string()));
             }
+
+            Ok(ParamType::Block(unsigned_int(&value)?))
         }
         _ => Err(GraphqlError::UnrecognizedArgument(
             entity_type.to_string(),
@@ -458,6 +816,90 @@ pub fn parse_argument_into_param<'a>(
     }
 }
 
+/// The value of an `Int` argument that can't be negative, like `offset`.
+fn unsigned_int<'a>(value: &Value<'a, &'a str>) -> Result<u64, GraphqlError> {
+    match value {
+        Value::Int(n) => n.as_u64(),
+        _ => None,
+    }
+    .ok_or_else(|| GraphqlError::UnsupportedValueType(value.to_string()))
+}
+
+/// Parse the pagination arguments of a connection that aren't accepted by
+/// other queries: `last`, `after` and `before`. Cursors hold the value of the
+/// field of `entity_type` the connection is ordered by, if any.
+pub fn parse_cursor_argument<'a>(
+    entity_type: &str,
+    arg: &str,
+    value: Value<'a, &'a str>,
+    sort: Option<&str>,
+    schema: &Schema,
+    variables: &QueryVariables,
+) -> Result<ParamType, GraphqlError> {
+    let value = variables.substitute(value)?;
+    let key_type = sort.map(|field| match schema.has_u64_values(entity_type, field) {
+        true => "UInt8".to_string(),
+        false => schema
+            .field_type(entity_type, field)
+            .map(|ty| normalize_field_type_name(ty))
+            .unwrap_or_default(),
+    });
+
+    match (arg, value) {
+        ("last", value @ Value::Int(_)) => Ok(ParamType::Last(unsigned_int(&value)?)),
+        ("after", Value::String(cursor)) => Ok(ParamType::After(Cursor::decode(
+            &cursor,
+            key_type.as_deref(),
+        )?)),
+        ("before", Value::String(cursor)) => Ok(ParamType::Before(Cursor::decode(
+            &cursor,
+            key_type.as_deref(),
+        )?)),
+        (_, value) => Err(GraphqlError::UnsupportedValueType(value.to_string())),
+    }
+}
+
+/// Parse the `groupBy` argument of an aggregate query, which lists the fields
+/// of `entity_type` whose values the matched records are grouped by.
+pub fn parse_group_by<'a>(
+    entity_type: &String,
+    value: Value<'a, &'a str>,
+    schema: &Schema,
+    variables: &QueryVariables,
+) -> Result<ParamType, GraphqlError> {
+    let items = match variables.substitute(value)? {
+        Value::List(items) => items,
+        // A single value can be given where a list is expected.
+        value => vec![value],
+    };
+
+    let mut fields = Vec::with_capacity(items.len());
+    for item in items {
+        let field = match item {
+            Value::Enum(field) => field,
+            other => return Err(GraphqlError::UnsupportedValueType(other.to_string())),
+        };
+
+        match schema.field_type(entity_type, field) {
+            Some(ty) if ty.starts_with('[') || ty == "__" => {
+                return Err(GraphqlError::InvalidAggregate(
+                    "groupBy".to_string(),
+                    field.to_string(),
+                ))
+            }
+            Some(_) => fields.push(field.to_string()),
+            None => {
+                return Err(GraphqlError::UnrecognizedField(
+                    entity_type.to_string(),
+                    field.to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(ParamType::GroupBy(fields))
+}
+
 /// Parse an object from a parsed GraphQL document into a `Filter`.
 ///
 /// This serves as a helper function for starting the parsing operation for values under the "filter" key.
@@ -546,6 +988,10 @@ fn parse_arg_pred_pair<'a>(
         }
         other => {
             if schema.field_type(entity_type, other).is_some() {
+                let u64_values = schema.has_u64_values(entity_type, other);
+                let parse =
+                    |value: &Value<'a, &'a str>| parse_field_value(value, u64_values);
+
                 if let Value::Object(inner_obj) = predicate {
                     for (key, predicate) in inner_obj.iter() {
                         match *key {
@@ -555,8 +1001,7 @@ fn parse_arg_pred_pair<'a>(
                                         complex_comparison_obj.get("min"),
                                         complex_comparison_obj.get("max"),
                                     ) {
-                                        let (min, max) =
-                                            (parse_value(min)?, parse_value(max)?);
+                                        let (min, max) = (parse(min)?, parse(max)?);
                                         return Ok(FilterType::Comparison(
                                             Comparison::Between(
                                                 other.to_string(),
@@ -570,41 +1015,43 @@ fn parse_arg_pred_pair<'a>(
                             "equals" => {
                                 return Ok(FilterType::Comparison(Comparison::Equals(
                                     other.to_string(),
-                                    parse_value(predicate)?,
+                                    parse(predicate)?,
                                 )))
                             }
                             "gt" => {
                                 return Ok(FilterType::Comparison(Comparison::Greater(
                                     other.to_string(),
-                                    parse_value(predicate)?,
+                                    parse(predicate)?,
                                 )))
                             }
                             "gte" => {
                                 return Ok(FilterType::Comparison(
                                     Comparison::GreaterEqual(
                                         other.to_string(),
-                                        parse_value(predicate)?,
+                                        parse(predicate)?,
                                     ),
                                 ));
                             }
                             "lt" => {
                                 return Ok(FilterType::Comparison(Comparison::Less(
                                     other.to_string(),
-                                    parse_value(predicate)?,
+                                    parse(predicate)?,
                                 )))
                             }
                             "lte" => {
                                 return Ok(FilterType::Comparison(Comparison::LessEqual(
                                     other.to_string(),
-                                    parse_value(predicate)?,
+                                    parse(predicate)?,
                                 )))
                             }
                             "in" => {
                                 if let Value::List(elements) = predicate {
-                                    let parsed_elements = elements
-                                            .iter()
-                                            .map(parse_value)
-                                            .collect::<Result<Vec<ParsedValue>,GraphqlError>>();
+                                    let parsed_elements =
+                                        elements.iter().map(parse).collect::<Result<
+                                            Vec<ParsedValue>,
+                                            GraphqlError,
+                                        >>(
+                                        );
                                     if let Ok(elements) = parsed_elements {
                                         return Ok(FilterType::Membership(
                                             Membership::In(other.to_string(), elements),
@@ -725,8 +1172,30 @@ fn parse_value<'a>(value: &Value<'a, &'a str>) -> Result<ParsedValue, GraphqlErr
     match value {
         Value::BigInt(bn) => Ok(ParsedValue::BigNumber(bn.as_u128())),
         Value::Boolean(b) => Ok(ParsedValue::Boolean(*b)),
-        Value::Int(n) => Ok(ParsedValue::Number(n.as_u64())),
+        Value::Int(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => Ok(ParsedValue::Number(n)),
+            (None, Some(n)) => Ok(ParsedValue::SignedNumber(n)),
+            (None, None) => Err(GraphqlError::UnsupportedValueType(value.to_string())),
+        },
         Value::String(s) => Ok(ParsedValue::String(s.clone())),
         _ => Err(GraphqlError::UnsupportedValueType(value.to_string())),
     }
 }
+
+/// Parse the value a field is compared with. Fields holding unsigned 64-bit
+/// integers, like IDs, only accept such values, which may be written as
+/// strings.
+fn parse_field_value<'a>(
+    value: &Value<'a, &'a str>,
+    u64_values: bool,
+) -> Result<ParsedValue, GraphqlError> {
+    match parse_value(value)? {
+        ParsedValue::Number(n) if u64_values => Ok(ParsedValue::UInt8(n)),
+        ParsedValue::String(s) if u64_values => s
+            .parse()
+            .map(ParsedValue::UInt8)
+            .map_err(|_| GraphqlError::UnableToParseValue(s)),
+        _ if u64_values => Err(GraphqlError::UnsupportedValueType(value.to_string())),
+        parsed => Ok(parsed),
+    }
+}
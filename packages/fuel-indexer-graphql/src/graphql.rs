@@ -1,12 +1,22 @@
-use super::arguments::{parse_argument_into_param, ParamType, QueryParams};
-use super::queries::{JoinCondition, QueryElement, QueryJoinNode, UserQuery};
-use fuel_indexer_schema::{db::tables::Schema, sql_types::DbType};
+use super::arguments::{
+    parse_argument_into_param, parse_cursor_argument, parse_group_by, ParamType,
+    QueryParams, QueryVariables,
+};
+use super::introspection::{is_introspection, Introspection};
+use super::queries::{
+    AggregateElement, AggregateFunction, Connection, ConnectionField, EdgeField,
+    JoinCondition, PageInfoField, QueryElement, QueryJoinNode, UserQuery,
+};
+use fuel_indexer_schema::{
+    db::tables::Schema, sql_types::DbType, utils::normalize_field_type_name,
+};
 
 use fuel_indexer_graphql_parser::query as gql;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
-type GraphqlResult<T> = Result<T, GraphqlError>;
+pub(crate) type GraphqlResult<T> = Result<T, GraphqlError>;
 
 #[derive(Debug, Error)]
 pub enum GraphqlError {
@@ -40,12 +50,49 @@ pub enum GraphqlError {
     MissingPartnerForBinaryLogicalOperator,
     #[error("Paginated query must have an order applied to at least one field")]
     UnorderedPaginatedQuery,
+    #[error("{0:?} doesn't keep its history, so it can't be queried at a block.")]
+    UnversionedEntity(String),
+    #[error("Block can only be set on top-level selections: {0:?}")]
+    NestedBlockArgument(String),
+    #[error("Variable ${0} is not defined by the operation.")]
+    UndefinedVariable(String),
+    #[error("Variable ${0} expects a value of type {1}, got: {2}")]
+    InvalidVariableValue(String, String, String),
+    #[error("Operation {0:?} is not defined in the document.")]
+    UnknownOperation(String),
+    #[error("The document defines several operations, so one has to be named.")]
+    OperationNameRequired,
+    #[error("Inline fragment on {0:?} can't be used within {1:?}.")]
+    InvalidInlineFragment(String, String),
+    #[error("Unsupported directive: @{0}")]
+    UnsupportedDirective(String),
+    #[error("{1:?} can't be used with {0}.")]
+    InvalidAggregate(String, String),
+    #[error("{0:?} must be in groupBy to be selected.")]
+    UngroupedField(String),
+    #[error("Aggregate {0:?} doesn't select any value.")]
+    EmptyAggregate(String),
+    #[error("Invalid cursor: {0:?}")]
+    InvalidCursor(String),
+    #[error("Connections can only be ordered by a single field.")]
+    UnsupportedCursorOrder,
+    #[error("A connection can't be paginated with both first and last.")]
+    ConflictingPagination,
+    #[error("Subscriptions can only be answered over a WebSocket.")]
+    SubscriptionWithoutWebSocket,
 }
 
 #[derive(Clone, Debug)]
 pub enum Selection {
     Field(String, Vec<ParamType>, Selections, Option<String>),
     Fragment(String),
+    Aggregate(
+        String,
+        Vec<ParamType>,
+        Vec<AggregateElement>,
+        Option<String>,
+    ),
+    Connection(String, Vec<ParamType>, Connection, Selections),
 }
 
 #[derive(Clone, Debug)]
@@ -60,6 +107,7 @@ impl Selections {
         schema: &Schema,
         field_type: &str,
         set: &gql::SelectionSet<'a, &'a str>,
+        variables: &QueryVariables,
     ) -> GraphqlResult<Selections> {
         let mut selections = Vec::with_capacity(set.items.len());
         let mut has_fragments = false;
@@ -67,15 +115,139 @@ impl Selections {
         for item in &set.items {
             match item {
                 gql::Selection::Field(field) => {
-                    // TODO: directives and sub-selections for nested types...
                     let gql::Field {
                         name,
                         selection_set,
                         arguments,
                         alias,
+                        directives,
                         ..
                     } = field;
 
+                    if !is_included(directives, variables)? {
+                        continue;
+                    }
+
+                    if field_type == schema.query {
+                        if let Some((entity, entity_type)) =
+                            derived_field(schema, name, "Aggregate")
+                        {
+                            let mut params = Vec::with_capacity(arguments.len());
+                            for (arg, value) in arguments {
+                                params.push(match *arg {
+                                    "groupBy" => parse_group_by(
+                                        entity_type,
+                                        value.clone(),
+                                        schema,
+                                        variables,
+                                    )?,
+                                    "filter" | "block" => parse_argument_into_param(
+                                        entity_type,
+                                        arg,
+                                        value.clone(),
+                                        schema,
+                                        variables,
+                                    )?,
+                                    _ => {
+                                        return Err(GraphqlError::UnrecognizedArgument(
+                                            name.to_string(),
+                                            arg.to_string(),
+                                        ))
+                                    }
+                                });
+                            }
+
+                            let group_by = params
+                                .iter()
+                                .flat_map(|p| match p {
+                                    ParamType::GroupBy(fields) => fields.clone(),
+                                    _ => Vec::new(),
+                                })
+                                .collect::<Vec<String>>();
+
+                            let aggregates = parse_aggregates(
+                                schema,
+                                entity_type,
+                                selection_set,
+                                &group_by,
+                                variables,
+                            )?;
+                            if aggregates.is_empty() {
+                                return Err(GraphqlError::EmptyAggregate(
+                                    name.to_string(),
+                                ));
+                            }
+
+                            selections.push(Selection::Aggregate(
+                                entity.to_string(),
+                                params,
+                                aggregates,
+                                alias.map(str::to_string),
+                            ));
+                            continue;
+                        }
+
+                        if let Some((entity, entity_type)) =
+                            derived_field(schema, name, "Connection")
+                        {
+                            let mut params = Vec::with_capacity(arguments.len());
+                            for (arg, value) in arguments {
+                                match *arg {
+                                    "last" | "after" | "before" => {}
+                                    "first" | "filter" | "order" | "block" => params
+                                        .push(parse_argument_into_param(
+                                            entity_type,
+                                            arg,
+                                            value.clone(),
+                                            schema,
+                                            variables,
+                                        )?),
+                                    _ => {
+                                        return Err(GraphqlError::UnrecognizedArgument(
+                                            name.to_string(),
+                                            arg.to_string(),
+                                        ))
+                                    }
+                                }
+                            }
+                            // Cursors hold a value of the field the connection
+                            // is ordered by, so they're parsed once it's known.
+                            let sort = params.iter().find_map(|param| match param {
+                                ParamType::Sort(field, _) => Some(field.clone()),
+                                _ => None,
+                            });
+                            for (arg, value) in arguments {
+                                if matches!(*arg, "last" | "after" | "before") {
+                                    params.push(parse_cursor_argument(
+                                        entity_type,
+                                        arg,
+                                        value.clone(),
+                                        sort.as_deref(),
+                                        schema,
+                                        variables,
+                                    )?);
+                                }
+                            }
+
+                            let (connection, nodes) = parse_connection(
+                                schema,
+                                entity_type,
+                                alias.unwrap_or(name).to_string(),
+                                selection_set,
+                                variables,
+                            )?;
+                            has_fragments |= nodes.has_fragments;
+
+                            selections.push(Selection::Connection(
+                                entity.to_string(),
+                                params,
+                                connection,
+                                nodes,
+                            ));
+                            continue;
+                        }
+                    }
+
                     let subfield_type =
                         schema.field_type(field_type, name).ok_or_else(|| {
                             GraphqlError::UnrecognizedField(
@@ -92,12 +264,19 @@ impl Selections {
                                 arg,
                                 value.clone(),
                                 schema,
+                                variables,
                             )
                         })
                         .collect::<Result<Vec<ParamType>, GraphqlError>>()?;
 
+                    if field_type != schema.query
+                        && params.iter().any(|p| matches!(p, ParamType::Block(_)))
+                    {
+                        return Err(GraphqlError::NestedBlockArgument(name.to_string()));
+                    }
+
                     let sub_selections =
-                        Selections::new(schema, subfield_type, selection_set)?;
+                        Selections::new(schema, subfield_type, selection_set, variables)?;
                     selections.push(Selection::Field(
                         name.to_string(),
                         params,
@@ -106,12 +285,47 @@ impl Selections {
                     ));
                 }
                 gql::Selection::FragmentSpread(frag) => {
-                    let gql::FragmentSpread { fragment_name, .. } = frag;
+                    let gql::FragmentSpread {
+                        fragment_name,
+                        directives,
+                        ..
+                    } = frag;
+
+                    if !is_included(directives, variables)? {
+                        continue;
+                    }
+
                     has_fragments = true;
                     selections.push(Selection::Fragment(fragment_name.to_string()));
                 }
-                // Inline fragments not handled yet....
-                _ => return Err(GraphqlError::SelectionNotSupported),
+                gql::Selection::InlineFragment(frag) => {
+                    let gql::InlineFragment {
+                        type_condition,
+                        directives,
+                        selection_set,
+                        ..
+                    } = frag;
+
+                    if !is_included(directives, variables)? {
+                        continue;
+                    }
+
+                    // Entities don't implement interfaces or belong to unions, so
+                    // a type condition can only name the type being selected on.
+                    if let Some(gql::TypeCondition::On(cond)) = type_condition {
+                        if *cond != normalize_field_type_name(field_type) {
+                            return Err(GraphqlError::InvalidInlineFragment(
+                                cond.to_string(),
+                                field_type.to_string(),
+                            ));
+                        }
+                    }
+
+                    let inner =
+                        Selections::new(schema, field_type, selection_set, variables)?;
+                    has_fragments |= inner.has_fragments;
+                    selections.extend(inner.selections);
+                }
             }
         }
 
@@ -163,6 +377,20 @@ impl Selections {
                         alias.clone(),
                     ));
                 }
+                Selection::Aggregate(..) => selections.push(selection.clone()),
+                Selection::Connection(name, params, connection, nodes) => {
+                    let field_type = schema
+                        .field_type(cond, name)
+                        .expect("Unable to retrieve field type");
+                    let _ = nodes.resolve_fragments(schema, field_type, fragments)?;
+
+                    selections.push(Selection::Connection(
+                        name.to_string(),
+                        params.to_vec(),
+                        connection.clone(),
+                        nodes.clone(),
+                    ));
+                }
             }
         }
 
@@ -174,6 +402,27 @@ impl Selections {
     pub fn get_selections(&self) -> Vec<Selection> {
         self.selections.clone()
     }
+
+    /// Add the selections of `other` that aren't already selected under the
+    /// same key.
+    fn merge(&mut self, other: Selections) {
+        for selection in other.selections {
+            let selected = match &selection {
+                Selection::Field(name, _, _, alias) => {
+                    let key = alias.as_ref().unwrap_or(name);
+                    self.selections.iter().any(|s| match s {
+                        Selection::Field(n, _, _, a) => a.as_ref().unwrap_or(n) == key,
+                        _ => false,
+                    })
+                }
+                _ => false,
+            };
+            if !selected {
+                self.selections.push(selection);
+            }
+        }
+        self.has_fragments |= other.has_fragments;
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -187,8 +436,9 @@ impl Fragment {
         schema: &Schema,
         cond: String,
         selection_set: &gql::SelectionSet<'a, &'a str>,
+        variables: &QueryVariables,
     ) -> GraphqlResult<Fragment> {
-        let selections = Selections::new(schema, &cond, selection_set)?;
+        let selections = Selections::new(schema, &cond, selection_set, variables)?;
 
         Ok(Fragment { cond, selections })
     }
@@ -235,7 +485,7 @@ impl Operation {
         }
     }
 
-    pub fn parse(&self, schema: &Schema) -> Vec<UserQuery> {
+    pub fn parse(&self, schema: &Schema, db_type: &DbType) -> Vec<UserQuery> {
         let Operation {
             namespace,
             identifier,
@@ -246,11 +496,66 @@ impl Operation {
         let mut queries = Vec::new();
 
         for selection in selections.get_selections() {
+            // Connections return the same records as the field they're derived
+            // from, through a different layout.
+            let (selection, connection) = match selection {
+                Selection::Connection(entity_name, filters, connection, nodes) => (
+                    Selection::Field(entity_name, filters, nodes, None),
+                    Some(connection),
+                ),
+                selection => (selection, None),
+            };
+
+            if let Selection::Aggregate(entity_name, filters, aggregates, alias) =
+                selection
+            {
+                let at_block = filters.iter().any(|p| matches!(p, ParamType::Block(_)));
+                let table =
+                    table_name(db_type, namespace, identifier, &entity_name, at_block);
+
+                let mut query_params = QueryParams::default();
+                query_params.add_params(filters, table.clone());
+
+                let u64_columns = aggregates
+                    .iter()
+                    .flat_map(|aggregate| match aggregate {
+                        AggregateElement::Count { .. } => Vec::new(),
+                        AggregateElement::Function { fields, .. } => {
+                            fields.iter().map(|(_, field)| field).collect()
+                        }
+                        AggregateElement::GroupKey { value, .. } => vec![value],
+                    })
+                    .filter(|field| schema.has_u64_values(&entity_name, field))
+                    .map(|field| format!("{table}.{field}"))
+                    .collect();
+
+                queries.push(UserQuery {
+                    elements: Vec::new(),
+                    joins: HashMap::new(),
+                    namespace_identifier: format!("{namespace}_{identifier}"),
+                    entity_name,
+                    query_params,
+                    alias,
+                    versioned: match at_block {
+                        true => schema.versioned.clone(),
+                        false => HashSet::new(),
+                    },
+                    aggregates: aggregates
+                        .into_iter()
+                        .map(|aggregate| aggregate.qualify(&table))
+                        .collect(),
+                    connection: None,
+                    u64_columns,
+                });
+                continue;
+            }
+
             let mut elements: Vec<QueryElement> = Vec::new();
             let mut entities: Vec<String> = Vec::new();
 
             let mut joins: HashMap<String, QueryJoinNode> = HashMap::new();
             let mut query_params: QueryParams = QueryParams::default();
+            let mut u64_columns: HashSet<String> = HashSet::new();
 
             let mut nested_entity_stack: Vec<String> = Vec::new();
 
@@ -260,6 +565,11 @@ impl Operation {
             if let Selection::Field(entity_name, filters, selections, alias) = selection {
                 let mut queue: Vec<Selection> = Vec::new();
 
+                let at_block = filters.iter().any(|p| matches!(p, ParamType::Block(_)));
+                let table = |entity: &str| {
+                    table_name(db_type, namespace, identifier, entity, at_block)
+                };
+
                 // Selections and entities will be popped from their respective vectors
                 // easy access to an element. In order to be compliant with the GraphQL
                 // spec (which says that a query should be resovled top-down), the order
@@ -278,11 +588,19 @@ impl Operation {
                         .collect::<Vec<Selection>>(),
                 );
 
+                // Connections return the value of the field they're ordered
+                // by in their cursors.
+                for filter in filters.iter() {
+                    if let ParamType::Sort(field, _) = filter {
+                        if schema.has_u64_values(&entity_name, field) {
+                            u64_columns
+                                .insert(format!("{}.{field}", table(&entity_name)));
+                        }
+                    }
+                }
+
                 if !filters.is_empty() {
-                    query_params.add_params(
-                        filters,
-                        format!("{namespace}_{identifier}.{entity_name}"),
-                    );
+                    query_params.add_params(filters, table(&entity_name));
                 }
 
                 let mut last_seen_entities_len = entities.len();
@@ -309,17 +627,16 @@ impl Operation {
                         current
                     {
                         if subselections.selections.is_empty() {
+                            let value = format!("{}.{field_name}", table(&entity_name));
+                            if schema.has_u64_values(&entity_name, &field_name) {
+                                u64_columns.insert(value.clone());
+                            }
                             elements.push(QueryElement::Field {
                                 key: alias.unwrap_or(field_name.clone()),
-                                value: format!(
-                                    "{namespace}_{identifier}.{entity_name}.{field_name}"
-                                ),
+                                value,
                             });
                             if !filters.is_empty() {
-                                query_params.add_params(
-                                    filters,
-                                    format!("{namespace}_{identifier}.{entity_name}"),
-                                );
+                                query_params.add_params(filters, table(&entity_name));
                             }
                         } else {
                             let mut new_entity = field_name.clone();
@@ -333,13 +650,9 @@ impl Operation {
                                     field_to_foreign_key.get(&field_name.to_lowercase())
                                 {
                                     let join_condition = JoinCondition {
-                                        referencing_key_table: format!(
-                                            "{namespace}_{identifier}.{entity_name}"
-                                        ),
+                                        referencing_key_table: table(&entity_name),
                                         referencing_key_col: field_name.clone(),
-                                        primary_key_table: format!(
-                                            "{namespace}_{identifier}.{foreign_key_table}"
-                                        ),
+                                        primary_key_table: table(foreign_key_table),
                                         primary_key_col: foreign_key_col.clone(),
                                     };
 
@@ -404,8 +717,8 @@ impl Operation {
                                     };
                                     if !filters.is_empty() {
                                         query_params.add_params(
-                                    filters,
-                                    format!("{namespace}_{identifier}.{foreign_key_table}"),
+                                            filters,
+                                            table(foreign_key_table),
                                         );
                                     }
                                 }
@@ -439,6 +752,11 @@ impl Operation {
                     ]);
                 }
 
+                let versioned = match at_block {
+                    true => schema.versioned.clone(),
+                    false => HashSet::new(),
+                };
+
                 let query = UserQuery {
                     elements,
                     joins,
@@ -446,6 +764,10 @@ impl Operation {
                     entity_name,
                     query_params,
                     alias,
+                    versioned,
+                    aggregates: Vec::new(),
+                    connection,
+                    u64_columns,
                 };
 
                 queries.push(query)
@@ -462,22 +784,35 @@ pub struct GraphqlQuery {
 }
 
 impl GraphqlQuery {
-    pub fn parse(&self, schema: &Schema) -> Vec<UserQuery> {
+    pub fn parse(&self, schema: &Schema, db_type: &DbType) -> Vec<UserQuery> {
         let queries: Vec<UserQuery> = self
             .operations
             .iter()
-            .flat_map(|o| o.parse(schema))
+            .flat_map(|o| o.parse(schema, db_type))
             .collect::<Vec<UserQuery>>();
 
         queries
     }
 
+    /// Whether every query of the document returns a connection, which is
+    /// returned as a single object rather than as a list of records.
+    pub fn is_connection(&self) -> bool {
+        !self.operations.is_empty()
+            && self.operations.iter().all(|operation| {
+                operation
+                    .selections
+                    .selections
+                    .iter()
+                    .all(|selection| matches!(selection, Selection::Connection(..)))
+            })
+    }
+
     pub fn as_sql(
         &self,
         schema: &Schema,
         db_type: DbType,
     ) -> Result<Vec<String>, GraphqlError> {
-        let queries = self.parse(schema);
+        let queries = self.parse(schema, &db_type);
 
         queries
             .into_iter()
@@ -489,6 +824,8 @@ impl GraphqlQuery {
 pub struct GraphqlQueryBuilder<'a> {
     schema: &'a Schema,
     document: gql::Document<'a, &'a str>,
+    variables: HashMap<String, Value>,
+    operation_name: Option<String>,
 }
 
 impl<'a> GraphqlQueryBuilder<'a> {
@@ -497,24 +834,124 @@ impl<'a> GraphqlQueryBuilder<'a> {
         query: &'a str,
     ) -> GraphqlResult<GraphqlQueryBuilder<'a>> {
         let document = gql::parse_query::<&str>(query)?;
-        Ok(GraphqlQueryBuilder { schema, document })
+        Ok(GraphqlQueryBuilder {
+            schema,
+            document,
+            variables: HashMap::new(),
+            operation_name: None,
+        })
+    }
+
+    /// Set the values of the variables declared by the document's operations.
+    pub fn variables(mut self, variables: HashMap<String, Value>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Run the operation with this name, which documents defining several
+    /// operations need.
+    pub fn operation_name(mut self, operation_name: Option<String>) -> Self {
+        self.operation_name = operation_name;
+        self
+    }
+
+    /// Whether the document only asks about the schema itself, like the
+    /// introspection query sent by the playground.
+    pub fn is_introspection(&self) -> bool {
+        match self.operations() {
+            Ok(operations) => {
+                !operations.is_empty()
+                    && operations.iter().all(|operation| {
+                        operation_parts(operation)
+                            .map_or(false, |(set, _)| is_introspection(set))
+                    })
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Whether the document subscribes to updates, which can't be answered
+    /// by a single response.
+    pub fn is_subscription(&self) -> bool {
+        match self.operations() {
+            Ok(operations) => operations.iter().any(|operation| {
+                matches!(operation, gql::OperationDefinition::Subscription(_))
+            }),
+            Err(_) => false,
+        }
+    }
+
+    /// Answer an introspection query from the schema, without touching the database.
+    pub fn introspect(&self) -> GraphqlResult<Value> {
+        let mut data = Map::new();
+
+        for operation in self.operations()? {
+            let (set, definitions) = operation_parts(operation)?;
+            let variables =
+                QueryVariables::new(self.schema, definitions, &self.variables)?;
+
+            data.extend(
+                Introspection::new(self.schema, &self.document, variables)
+                    .execute(set)?,
+            );
+        }
+
+        Ok(Value::Object(data))
     }
 
     pub fn build(self) -> GraphqlResult<GraphqlQuery> {
-        let fragments = self.process_fragments()?;
-        let operations = self.process_operations(fragments)?;
+        let mut operations = Vec::new();
+
+        // Fragments can refer to variables, whose values depend on the
+        // operation the fragments are spread in.
+        for operation in self.operations()? {
+            let (_, definitions) = operation_parts(operation)?;
+            let variables =
+                QueryVariables::new(self.schema, definitions, &self.variables)?;
+            let fragments = self.process_fragments(&variables)?;
+
+            operations.push(self.process_operation(operation, &fragments, &variables)?);
+        }
 
         Ok(GraphqlQuery { operations })
     }
 
+    /// The operation to run: the one named by `operation_name`, if set, or
+    /// otherwise the only operation of the document.
+    fn operations(&self) -> GraphqlResult<Vec<&gql::OperationDefinition<'a, &'a str>>> {
+        let operations = self
+            .document
+            .definitions
+            .iter()
+            .filter_map(|def| match def {
+                gql::Definition::Operation(operation) => Some(operation),
+                _ => None,
+            })
+            .filter(|operation| match &self.operation_name {
+                Some(name) => operation_name(operation) == Some(name.as_str()),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+
+        match &self.operation_name {
+            Some(name) if operations.is_empty() => {
+                Err(GraphqlError::UnknownOperation(name.clone()))
+            }
+            None if operations.len() > 1 => Err(GraphqlError::OperationNameRequired),
+            _ => Ok(operations),
+        }
+    }
+
     fn process_operation(
         &self,
         operation: &gql::OperationDefinition<'a, &'a str>,
         fragments: &HashMap<String, Fragment>,
+        variables: &QueryVariables,
     ) -> GraphqlResult<Operation> {
         match operation {
             gql::OperationDefinition::SelectionSet(set) => {
-                let selections = Selections::new(self.schema, &self.schema.query, set)?;
+                let selections =
+                    Selections::new(self.schema, &self.schema.query, set, variables)?;
 
                 Ok(Operation::new(
                     self.schema.namespace.clone(),
@@ -523,17 +960,27 @@ impl<'a> GraphqlQueryBuilder<'a> {
                     selections,
                 ))
             }
-            gql::OperationDefinition::Query(q) => {
-                // TODO: directives and variable definitions....
-                let gql::Query {
-                    name,
-                    selection_set,
-                    ..
-                } = q;
+            // A subscription selects the same entities as a query, and is
+            // answered again each time the indexer commits.
+            gql::OperationDefinition::Query(gql::Query {
+                name,
+                selection_set,
+                ..
+            })
+            | gql::OperationDefinition::Subscription(gql::Subscription {
+                name,
+                selection_set,
+                ..
+            }) => {
+                // TODO: directives....
                 let name = name.map_or_else(|| "Unnamed".into(), |o| o.into());
 
-                let mut selections =
-                    Selections::new(self.schema, &self.schema.query, selection_set)?;
+                let mut selections = Selections::new(
+                    self.schema,
+                    &self.schema.query,
+                    selection_set,
+                    variables,
+                )?;
                 selections.resolve_fragments(
                     self.schema,
                     &self.schema.query,
@@ -550,30 +997,13 @@ impl<'a> GraphqlQueryBuilder<'a> {
             gql::OperationDefinition::Mutation(_) => {
                 Err(GraphqlError::OperationNotSupported("Mutation".into()))
             }
-            gql::OperationDefinition::Subscription(_) => {
-                Err(GraphqlError::OperationNotSupported("Subscription".into()))
-            }
         }
     }
 
-    fn process_operations(
+    fn process_fragments(
         &self,
-        fragments: HashMap<String, Fragment>,
-    ) -> GraphqlResult<Vec<Operation>> {
-        let mut operations = vec![];
-
-        for def in &self.document.definitions {
-            if let gql::Definition::Operation(operation) = def {
-                let op = self.process_operation(operation, &fragments)?;
-
-                operations.push(op);
-            }
-        }
-
-        Ok(operations)
-    }
-
-    fn process_fragments(&self) -> GraphqlResult<HashMap<String, Fragment>> {
+        variables: &QueryVariables,
+    ) -> GraphqlResult<HashMap<String, Fragment>> {
         let mut fragments = HashMap::new();
         let mut to_resolve = Vec::new();
 
@@ -592,7 +1022,12 @@ impl<'a> GraphqlQueryBuilder<'a> {
                     return Err(GraphqlError::UnrecognizedType(cond.to_string()));
                 }
 
-                let frag = Fragment::new(self.schema, cond.to_string(), selection_set)?;
+                let frag = Fragment::new(
+                    self.schema,
+                    cond.to_string(),
+                    selection_set,
+                    variables,
+                )?;
 
                 if frag.has_fragments() {
                     to_resolve.push((name.to_string(), frag));
@@ -629,6 +1064,351 @@ impl<'a> GraphqlQueryBuilder<'a> {
     }
 }
 
+/// Whether a selection is kept by its `@include(if:)` and `@skip(if:)` directives.
+pub(crate) fn is_included<'a>(
+    directives: &[gql::Directive<'a, &'a str>],
+    variables: &QueryVariables,
+) -> GraphqlResult<bool> {
+    let mut included = true;
+
+    for directive in directives {
+        let include = match directive.name {
+            "include" => true,
+            "skip" => false,
+            other => return Err(GraphqlError::UnsupportedDirective(other.to_string())),
+        };
+
+        let condition = match directive.arguments.iter().find(|(arg, _)| *arg == "if") {
+            Some((_, value)) => match variables.substitute(value.clone())? {
+                gql::Value::Boolean(condition) => condition,
+                value => {
+                    return Err(GraphqlError::UnsupportedValueType(value.to_string()))
+                }
+            },
+            None => {
+                return Err(GraphqlError::UnrecognizedArgument(
+                    format!("@{}", directive.name),
+                    "if".into(),
+                ))
+            }
+        };
+
+        included &= condition == include;
+    }
+
+    Ok(included)
+}
+
+/// The name by which the table of `entity` is referred to in a query.
+///
+/// Queries at a block read each table through a view of its state at that
+/// block, named after the table, so tables are referred to without their
+/// namespace.
+fn table_name(
+    db_type: &DbType,
+    namespace: &str,
+    identifier: &str,
+    entity: &str,
+    at_block: bool,
+) -> String {
+    match at_block {
+        true => entity.to_string(),
+        false => db_type.table_name(&format!("{namespace}_{identifier}"), entity),
+    }
+}
+
+/// The query root field that the query root field `name` is derived from by
+/// adding `suffix`, and the type of its entities: every query root field `foo`
+/// has an aggregate counterpart `fooAggregate`, and a connection counterpart
+/// `fooConnection`.
+fn derived_field<'s, 'n>(
+    schema: &'s Schema,
+    name: &'n str,
+    suffix: &str,
+) -> Option<(&'n str, &'s String)> {
+    if schema.field_type(&schema.query, name).is_some() {
+        return None;
+    }
+
+    let field = name.strip_suffix(suffix)?;
+    schema
+        .field_type(&schema.query, field)
+        .map(|entity_type| (field, entity_type))
+}
+
+/// Parse the selections of a connection to entities of `entity_type` into
+/// its layout, and the selections of its nodes; nodes selected both through
+/// `edges` and `nodes` share their selections.
+fn parse_connection<'a>(
+    schema: &Schema,
+    entity_type: &str,
+    key: String,
+    set: &gql::SelectionSet<'a, &'a str>,
+    variables: &QueryVariables,
+) -> GraphqlResult<(Connection, Selections)> {
+    let entity = normalize_field_type_name(entity_type);
+    let mut fields = Vec::with_capacity(set.items.len());
+    let mut nodes = Selections {
+        _field_type: entity_type.to_string(),
+        has_fragments: false,
+        selections: Vec::new(),
+    };
+
+    for field in included_fields(set, variables)? {
+        let key = field.alias.unwrap_or(field.name).to_string();
+
+        let field = match field.name {
+            "totalCount" => ConnectionField::TotalCount,
+            "pageInfo" => {
+                let mut page_info = Vec::new();
+                for info in included_fields(&field.selection_set, variables)? {
+                    let value = match info.name {
+                        "hasNextPage" => PageInfoField::HasNextPage,
+                        "hasPreviousPage" => PageInfoField::HasPreviousPage,
+                        "startCursor" => PageInfoField::StartCursor,
+                        "endCursor" => PageInfoField::EndCursor,
+                        other => {
+                            return Err(GraphqlError::UnrecognizedField(
+                                "PageInfo".to_string(),
+                                other.to_string(),
+                            ))
+                        }
+                    };
+                    page_info.push((info.alias.unwrap_or(info.name).to_string(), value));
+                }
+                ConnectionField::PageInfo(page_info)
+            }
+            "edges" => {
+                let mut edge = Vec::new();
+                for edge_field in included_fields(&field.selection_set, variables)? {
+                    let value = match edge_field.name {
+                        "cursor" => EdgeField::Cursor,
+                        "node" => {
+                            nodes.merge(Selections::new(
+                                schema,
+                                entity_type,
+                                &edge_field.selection_set,
+                                variables,
+                            )?);
+                            EdgeField::Node
+                        }
+                        other => {
+                            return Err(GraphqlError::UnrecognizedField(
+                                format!("{entity}Edge"),
+                                other.to_string(),
+                            ))
+                        }
+                    };
+                    let key = edge_field.alias.unwrap_or(edge_field.name).to_string();
+                    edge.push((key, value));
+                }
+                ConnectionField::Edges(edge)
+            }
+            "nodes" => {
+                nodes.merge(Selections::new(
+                    schema,
+                    entity_type,
+                    &field.selection_set,
+                    variables,
+                )?);
+                ConnectionField::Nodes
+            }
+            other => {
+                return Err(GraphqlError::UnrecognizedField(
+                    format!("{entity}Connection"),
+                    other.to_string(),
+                ))
+            }
+        };
+        fields.push((key, field));
+    }
+
+    Ok((Connection { key, fields }, nodes))
+}
+
+/// The fields of a selection set that are kept by their directives. Fragments
+/// can only be used on entities, and not on the types wrapping them.
+fn included_fields<'b, 'a>(
+    set: &'b gql::SelectionSet<'a, &'a str>,
+    variables: &QueryVariables,
+) -> GraphqlResult<Vec<&'b gql::Field<'a, &'a str>>> {
+    let mut fields = Vec::with_capacity(set.items.len());
+
+    for item in &set.items {
+        match item {
+            gql::Selection::Field(field) => {
+                if is_included(&field.directives, variables)? {
+                    fields.push(field);
+                }
+            }
+            _ => return Err(GraphqlError::SelectionNotSupported),
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Parse the selections of an aggregate query on entities of `entity_type`
+/// into the values it computes.
+fn parse_aggregates<'a>(
+    schema: &Schema,
+    entity_type: &str,
+    set: &gql::SelectionSet<'a, &'a str>,
+    group_by: &[String],
+    variables: &QueryVariables,
+) -> GraphqlResult<Vec<AggregateElement>> {
+    let aggregate_type = format!("{}Aggregate", normalize_field_type_name(entity_type));
+    let mut elements = Vec::with_capacity(set.items.len());
+
+    for item in &set.items {
+        match item {
+            gql::Selection::Field(field) => {
+                if !is_included(&field.directives, variables)? {
+                    continue;
+                }
+
+                let key = field.alias.unwrap_or(field.name).to_string();
+
+                if field.name == "count" {
+                    elements.push(AggregateElement::Count { key });
+                } else if let Some(function) =
+                    AggregateFunction::from_field_name(field.name)
+                {
+                    let fields = parse_aggregated_fields(
+                        schema,
+                        entity_type,
+                        function,
+                        &field.selection_set,
+                        variables,
+                    )?;
+                    elements.push(AggregateElement::Function {
+                        key,
+                        function,
+                        fields,
+                    });
+                } else if schema.field_type(entity_type, field.name).is_some() {
+                    if !group_by.iter().any(|f| f == field.name) {
+                        return Err(GraphqlError::UngroupedField(field.name.to_string()));
+                    }
+                    if !field.selection_set.items.is_empty() {
+                        return Err(GraphqlError::SelectionNotSupported);
+                    }
+                    elements.push(AggregateElement::GroupKey {
+                        key,
+                        value: field.name.to_string(),
+                    });
+                } else {
+                    return Err(GraphqlError::UnrecognizedField(
+                        aggregate_type,
+                        field.name.to_string(),
+                    ));
+                }
+            }
+            gql::Selection::InlineFragment(frag) => {
+                if !is_included(&frag.directives, variables)? {
+                    continue;
+                }
+
+                if let Some(gql::TypeCondition::On(cond)) = frag.type_condition {
+                    if cond != aggregate_type {
+                        return Err(GraphqlError::InvalidInlineFragment(
+                            cond.to_string(),
+                            aggregate_type,
+                        ));
+                    }
+                }
+
+                elements.extend(parse_aggregates(
+                    schema,
+                    entity_type,
+                    &frag.selection_set,
+                    group_by,
+                    variables,
+                )?);
+            }
+            gql::Selection::FragmentSpread(_) => {
+                return Err(GraphqlError::SelectionNotSupported)
+            }
+        }
+    }
+
+    Ok(elements)
+}
+
+/// Parse the fields an aggregate function is applied to, as pairs of the
+/// key of their result and the field.
+fn parse_aggregated_fields<'a>(
+    schema: &Schema,
+    entity_type: &str,
+    function: AggregateFunction,
+    set: &gql::SelectionSet<'a, &'a str>,
+    variables: &QueryVariables,
+) -> GraphqlResult<Vec<(String, String)>> {
+    let mut fields = Vec::with_capacity(set.items.len());
+
+    for item in &set.items {
+        let field = match item {
+            gql::Selection::Field(field) => field,
+            _ => return Err(GraphqlError::SelectionNotSupported),
+        };
+
+        if !is_included(&field.directives, variables)? {
+            continue;
+        }
+
+        let ty = schema.field_type(entity_type, field.name).ok_or_else(|| {
+            GraphqlError::UnrecognizedField(
+                entity_type.to_string(),
+                field.name.to_string(),
+            )
+        })?;
+        if !function.accepts(&normalize_field_type_name(ty)) {
+            return Err(GraphqlError::InvalidAggregate(
+                function.to_string(),
+                field.name.to_string(),
+            ));
+        }
+
+        let key = field.alias.unwrap_or(field.name).to_string();
+        fields.push((key, field.name.to_string()));
+    }
+
+    Ok(fields)
+}
+
+fn operation_name<'a>(
+    operation: &gql::OperationDefinition<'a, &'a str>,
+) -> Option<&'a str> {
+    match operation {
+        gql::OperationDefinition::SelectionSet(_) => None,
+        gql::OperationDefinition::Query(q) => q.name,
+        gql::OperationDefinition::Mutation(m) => m.name,
+        gql::OperationDefinition::Subscription(s) => s.name,
+    }
+}
+
+/// The selections of a query operation, and the variables it declares.
+#[allow(clippy::type_complexity)]
+fn operation_parts<'b, 'a>(
+    operation: &'b gql::OperationDefinition<'a, &'a str>,
+) -> GraphqlResult<(
+    &'b gql::SelectionSet<'a, &'a str>,
+    &'b [gql::VariableDefinition<'a, &'a str>],
+)> {
+    match operation {
+        gql::OperationDefinition::SelectionSet(set) => Ok((set, &[])),
+        gql::OperationDefinition::Query(q) => {
+            Ok((&q.selection_set, &q.variable_definitions))
+        }
+        gql::OperationDefinition::Subscription(s) => {
+            Ok((&s.selection_set, &s.variable_definitions))
+        }
+        gql::OperationDefinition::Mutation(_) => {
+            Err(GraphqlError::OperationNotSupported("Mutation".into()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -764,6 +1544,7 @@ mod tests {
             ]),
             fields,
             foreign_keys,
+            versioned: HashSet::new(),
         };
 
         let expected = vec![UserQuery {
@@ -829,7 +1610,15 @@ mod tests {
             entity_name: "tx".to_string(),
             query_params: QueryParams::default(),
             alias: None,
+            versioned: HashSet::new(),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::from([
+                "fuel_indexer_test_test_index.block.height".to_string(),
+                "fuel_indexer_test_test_index.block.id".to_string(),
+                "fuel_indexer_test_test_index.tx.id".to_string(),
+            ]),
         }];
-        assert_eq!(expected, operation.parse(&schema));
+        assert_eq!(expected, operation.parse(&schema, &DbType::Postgres));
     }
 }
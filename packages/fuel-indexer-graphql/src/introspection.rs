@@ -0,0 +1,978 @@
+use super::arguments::QueryVariables;
+use super::graphql::{is_included, GraphqlError, GraphqlResult};
+use super::queries::AggregateFunction;
+use fuel_indexer_graphql_parser::query as gql;
+use fuel_indexer_schema::db::tables::Schema;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// Top-level fields that are answered from the schema rather than the database.
+const INTROSPECTION_FIELDS: [&str; 3] = ["__schema", "__type", "__typename"];
+
+/// Scalars used by query arguments, whether or not an entity uses them.
+const BUILTIN_SCALARS: [&str; 4] = ["Boolean", "ID", "Int", "String"];
+
+/// Root type of subscriptions, which select the same entities as queries.
+const SUBSCRIPTION_ROOT: &str = "SubscriptionRoot";
+
+/// Where `@include` and `@skip` can be used.
+const DIRECTIVE_LOCATIONS: [&str; 3] = ["FIELD", "FRAGMENT_SPREAD", "INLINE_FRAGMENT"];
+
+/// Whether an operation only selects introspection fields.
+pub fn is_introspection<'a>(set: &gql::SelectionSet<'a, &'a str>) -> bool {
+    set.items.iter().all(|item| match item {
+        gql::Selection::Field(field) => INTROSPECTION_FIELDS.contains(&field.name),
+        _ => false,
+    })
+}
+
+/// A reference to a type, as used by fields and arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+impl TypeRef {
+    /// Parse a type as it's written in a schema, e.g. `[Address!]!`.
+    fn parse(s: &str) -> TypeRef {
+        let s = s.trim();
+        if let Some(inner) = s.strip_suffix('!') {
+            return TypeRef::NonNull(Box::new(TypeRef::parse(inner)));
+        }
+        match s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            Some(inner) => TypeRef::List(Box::new(TypeRef::parse(inner))),
+            None => TypeRef::Named(s.to_string()),
+        }
+    }
+
+    fn named(name: &str) -> TypeRef {
+        TypeRef::Named(name.to_string())
+    }
+
+    fn non_null(name: &str) -> TypeRef {
+        TypeRef::NonNull(Box::new(TypeRef::named(name)))
+    }
+
+    fn list_of(name: &str) -> TypeRef {
+        TypeRef::List(Box::new(TypeRef::non_null(name)))
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            TypeRef::Named(name) => name,
+            TypeRef::List(inner) | TypeRef::NonNull(inner) => inner.name(),
+        }
+    }
+
+    fn is_list(&self) -> bool {
+        match self {
+            TypeRef::Named(_) => false,
+            TypeRef::List(_) => true,
+            TypeRef::NonNull(inner) => inner.is_list(),
+        }
+    }
+}
+
+impl<'a> From<&gql::Type<'a, &'a str>> for TypeRef {
+    fn from(ty: &gql::Type<'a, &'a str>) -> TypeRef {
+        match ty {
+            gql::Type::NamedType(name) => TypeRef::named(name),
+            gql::Type::ListType(inner) => TypeRef::List(Box::new(inner.as_ref().into())),
+            gql::Type::NonNullType(inner) => {
+                TypeRef::NonNull(Box::new(inner.as_ref().into()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeRef::Named(name) => write!(f, "{name}"),
+            TypeRef::List(inner) => write!(f, "[{inner}]"),
+            TypeRef::NonNull(inner) => write!(f, "{inner}!"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TypeKind {
+    Scalar,
+    Object,
+    InputObject,
+    Enum,
+}
+
+impl TypeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TypeKind::Scalar => "SCALAR",
+            TypeKind::Object => "OBJECT",
+            TypeKind::InputObject => "INPUT_OBJECT",
+            TypeKind::Enum => "ENUM",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct InputValue {
+    pub(crate) name: String,
+    description: Option<&'static str>,
+    pub(crate) ty: TypeRef,
+}
+
+impl InputValue {
+    fn new(name: &str, ty: TypeRef) -> InputValue {
+        InputValue {
+            name: name.to_string(),
+            description: None,
+            ty,
+        }
+    }
+
+    fn describe(mut self, description: &'static str) -> InputValue {
+        self.description = Some(description);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct FieldDef {
+    name: String,
+    args: Vec<InputValue>,
+    ty: TypeRef,
+}
+
+impl FieldDef {
+    fn new(name: &str, ty: TypeRef) -> FieldDef {
+        FieldDef {
+            name: name.to_string(),
+            args: Vec::new(),
+            ty,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct TypeDef {
+    pub(crate) kind: TypeKind,
+    name: String,
+    fields: Vec<FieldDef>,
+    pub(crate) input_fields: Vec<InputValue>,
+    pub(crate) enum_values: Vec<String>,
+}
+
+impl TypeDef {
+    fn new(kind: TypeKind, name: &str) -> TypeDef {
+        TypeDef {
+            kind,
+            name: name.to_string(),
+            fields: Vec::new(),
+            input_fields: Vec::new(),
+            enum_values: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct DirectiveDef {
+    name: &'static str,
+    description: &'static str,
+    args: Vec<InputValue>,
+}
+
+/// The directives queries can use.
+fn build_directives() -> Vec<DirectiveDef> {
+    vec![
+        DirectiveDef {
+            name: "include",
+            description: "Only include this selection if the argument is true.",
+            args: vec![InputValue::new("if", TypeRef::non_null("Boolean"))],
+        },
+        DirectiveDef {
+            name: "skip",
+            description: "Skip this selection if the argument is true.",
+            args: vec![InputValue::new("if", TypeRef::non_null("Boolean"))],
+        },
+    ]
+}
+
+/// Build the types an indexer's GraphQL API exposes: its entities, the
+/// scalars they use, and the inputs accepted by the query root's arguments.
+pub(crate) fn build_types(schema: &Schema) -> BTreeMap<String, TypeDef> {
+    let mut types = BTreeMap::new();
+    let mut scalars: BTreeSet<String> =
+        BUILTIN_SCALARS.iter().map(|s| s.to_string()).collect();
+    let mut comparisons = BTreeSet::new();
+
+    let entities: BTreeMap<&String, &HashMap<String, String>> = schema
+        .fields
+        .iter()
+        .filter(|(name, _)| **name != schema.query)
+        .collect();
+
+    let mut query = TypeDef::new(TypeKind::Object, &schema.query);
+    let mut subscription = TypeDef::new(TypeKind::Object, SUBSCRIPTION_ROOT);
+    if let Some(fields) = schema.fields.get(&schema.query) {
+        let fields: BTreeMap<&String, &String> = fields.iter().collect();
+        for (name, ty) in fields.iter() {
+            let ty = TypeRef::parse(ty);
+            query.fields.push(FieldDef {
+                name: name.to_string(),
+                args: root_arguments(schema, ty.name()),
+                ty: ty.clone(),
+            });
+            subscription.fields.push(FieldDef {
+                name: name.to_string(),
+                args: root_arguments(schema, ty.name()),
+                ty: ty.clone(),
+            });
+
+            let aggregate = format!("{name}Aggregate");
+            if !fields.contains_key(&aggregate) {
+                query.fields.push(FieldDef {
+                    name: aggregate,
+                    args: aggregate_arguments(schema, ty.name()),
+                    ty: TypeRef::list_of(&format!("{}Aggregate", ty.name())),
+                });
+            }
+
+            let connection = format!("{name}Connection");
+            if !fields.contains_key(&connection) {
+                query.fields.push(FieldDef {
+                    name: connection,
+                    args: connection_arguments(schema, ty.name()),
+                    ty: TypeRef::non_null(&format!("{}Connection", ty.name())),
+                });
+            }
+        }
+        query.fields.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    types.insert(query.name.clone(), query);
+    types.insert(subscription.name.clone(), subscription);
+
+    let mut page_info = TypeDef::new(TypeKind::Object, "PageInfo");
+    page_info.fields = vec![
+        FieldDef::new("hasNextPage", TypeRef::non_null("Boolean")),
+        FieldDef::new("hasPreviousPage", TypeRef::non_null("Boolean")),
+        FieldDef::new("startCursor", TypeRef::named("String")),
+        FieldDef::new("endCursor", TypeRef::named("String")),
+    ];
+    types.insert(page_info.name.clone(), page_info);
+
+    for (entity, fields) in entities.iter() {
+        // Every table has an `object` column holding the serialized entity,
+        // which isn't part of the GraphQL schema.
+        let fields: BTreeMap<&String, TypeRef> = fields
+            .iter()
+            .filter(|(_, ty)| *ty != "__")
+            .map(|(name, ty)| (name, TypeRef::parse(ty)))
+            .collect();
+
+        let mut object = TypeDef::new(TypeKind::Object, entity);
+        let mut field_enum = TypeDef::new(TypeKind::Enum, &format!("{entity}Field"));
+        let mut filter = TypeDef::new(TypeKind::InputObject, &format!("{entity}Filter"));
+
+        let mut aggregate = TypeDef::new(TypeKind::Object, &format!("{entity}Aggregate"));
+        aggregate
+            .fields
+            .push(FieldDef::new("count", TypeRef::non_null("Int")));
+        let mut aggregated = [
+            ("sum", "Sum", AggregateFunction::Sum),
+            ("min", "Min", AggregateFunction::Min),
+            ("max", "Max", AggregateFunction::Max),
+            ("avg", "Avg", AggregateFunction::Avg),
+        ]
+        .map(|(name, title, function)| {
+            let def = TypeDef::new(TypeKind::Object, &format!("{entity}{title}Fields"));
+            (name, function, def)
+        });
+
+        for (name, ty) in fields {
+            let is_entity = entities.contains_key(&ty.name().to_string());
+            if !is_entity {
+                scalars.insert(ty.name().to_string());
+            }
+
+            // Foreign keys are filtered on the ID of the entity they reference.
+            if !ty.is_list() {
+                let scalar = if is_entity { "ID" } else { ty.name() };
+                comparisons.insert(scalar.to_string());
+                filter.input_fields.push(InputValue::new(
+                    name,
+                    TypeRef::named(&format!("{scalar}Comparison")),
+                ));
+            }
+
+            // Records can be grouped by any of their non-list fields, whose
+            // values are then selected alongside the aggregates.
+            if !ty.is_list() {
+                let scalar = if is_entity { "ID" } else { ty.name() };
+                aggregate
+                    .fields
+                    .push(FieldDef::new(name, TypeRef::named(scalar)));
+
+                for (_, function, def) in aggregated.iter_mut() {
+                    if !is_entity && function.accepts(scalar) {
+                        let scalar = match function {
+                            AggregateFunction::Avg => "Float",
+                            _ => scalar,
+                        };
+                        def.fields.push(FieldDef::new(name, TypeRef::named(scalar)));
+                    }
+                }
+            }
+
+            field_enum.enum_values.push(name.to_string());
+            object.fields.push(FieldDef::new(name, ty));
+        }
+
+        filter.input_fields.extend([
+            InputValue::new("has", TypeRef::list_of(&field_enum.name))
+                .describe("Only match entities where these fields aren't null."),
+            InputValue::new("and", TypeRef::named(&filter.name)),
+            InputValue::new("or", TypeRef::named(&filter.name)),
+            InputValue::new("not", TypeRef::named(&filter.name)),
+        ]);
+
+        let mut order = TypeDef::new(TypeKind::InputObject, &format!("{entity}Order"));
+        order.input_fields = vec![
+            InputValue::new("asc", TypeRef::named(&field_enum.name)),
+            InputValue::new("desc", TypeRef::named(&field_enum.name)),
+        ];
+
+        // Object types must have fields, so functions that can't be applied
+        // to any field of the entity are left out.
+        for (name, _, def) in aggregated {
+            if def.fields.is_empty() {
+                continue;
+            }
+            if name == "avg" {
+                scalars.insert("Float".to_string());
+            }
+            aggregate
+                .fields
+                .push(FieldDef::new(name, TypeRef::named(&def.name)));
+            types.insert(def.name.clone(), def);
+        }
+
+        let mut edge = TypeDef::new(TypeKind::Object, &format!("{entity}Edge"));
+        edge.fields = vec![
+            FieldDef::new("cursor", TypeRef::non_null("String")),
+            FieldDef::new("node", TypeRef::non_null(entity)),
+        ];
+
+        let mut connection =
+            TypeDef::new(TypeKind::Object, &format!("{entity}Connection"));
+        connection.fields = vec![
+            FieldDef::new("totalCount", TypeRef::non_null("Int")),
+            FieldDef::new("pageInfo", TypeRef::non_null("PageInfo")),
+            FieldDef::new(
+                "edges",
+                TypeRef::NonNull(Box::new(TypeRef::list_of(&edge.name))),
+            ),
+            FieldDef::new(
+                "nodes",
+                TypeRef::NonNull(Box::new(TypeRef::list_of(entity))),
+            ),
+        ];
+
+        for def in [
+            object, field_enum, filter, order, aggregate, edge, connection,
+        ] {
+            types.insert(def.name.clone(), def);
+        }
+    }
+
+    for scalar in comparisons {
+        let range_name = format!("{scalar}Range");
+        let mut range = TypeDef::new(TypeKind::InputObject, &range_name);
+        range.input_fields = vec![
+            InputValue::new("min", TypeRef::non_null(&scalar)),
+            InputValue::new("max", TypeRef::non_null(&scalar)),
+        ];
+
+        let mut comparison =
+            TypeDef::new(TypeKind::InputObject, &format!("{scalar}Comparison"));
+        comparison.input_fields = vec![
+            InputValue::new("equals", TypeRef::named(&scalar)),
+            InputValue::new("gt", TypeRef::named(&scalar)),
+            InputValue::new("gte", TypeRef::named(&scalar)),
+            InputValue::new("lt", TypeRef::named(&scalar)),
+            InputValue::new("lte", TypeRef::named(&scalar)),
+            InputValue::new("in", TypeRef::list_of(&scalar)),
+            InputValue::new("between", TypeRef::named(&range_name)),
+        ];
+
+        for def in [range, comparison] {
+            types.insert(def.name.clone(), def);
+        }
+    }
+
+    for scalar in scalars {
+        types
+            .entry(scalar.clone())
+            .or_insert_with(|| TypeDef::new(TypeKind::Scalar, &scalar));
+    }
+
+    types
+}
+
+/// The arguments accepted by a query root field returning `entity`.
+fn root_arguments(schema: &Schema, entity: &str) -> Vec<InputValue> {
+    let mut args = vec![
+        InputValue::new("id", TypeRef::named("ID")),
+        InputValue::new("filter", TypeRef::named(&format!("{entity}Filter"))),
+        InputValue::new("order", TypeRef::named(&format!("{entity}Order"))),
+        InputValue::new("first", TypeRef::named("Int"))
+            .describe("Paginate the results, returning at most this many entities."),
+        InputValue::new("offset", TypeRef::named("Int")),
+    ];
+
+    if schema.is_versioned(entity) {
+        args.push(
+            InputValue::new("block", TypeRef::named("Int"))
+                .describe("Read the entities as they were at this block height."),
+        );
+    }
+
+    args
+}
+
+/// The arguments accepted by the connection query root field of `entity`.
+fn connection_arguments(schema: &Schema, entity: &str) -> Vec<InputValue> {
+    let mut args = vec![
+        InputValue::new("filter", TypeRef::named(&format!("{entity}Filter"))),
+        InputValue::new("order", TypeRef::named(&format!("{entity}Order"))),
+        InputValue::new("first", TypeRef::named("Int"))
+            .describe("Return at most this many entities from the start of the list."),
+        InputValue::new("after", TypeRef::named("String"))
+            .describe("Only return the entities after this cursor."),
+        InputValue::new("last", TypeRef::named("Int"))
+            .describe("Return at most this many entities from the end of the list."),
+        InputValue::new("before", TypeRef::named("String"))
+            .describe("Only return the entities before this cursor."),
+    ];
+
+    if schema.is_versioned(entity) {
+        args.push(
+            InputValue::new("block", TypeRef::named("Int"))
+                .describe("Read the entities as they were at this block height."),
+        );
+    }
+
+    args
+}
+
+/// The arguments accepted by the aggregate query root field of `entity`.
+fn aggregate_arguments(schema: &Schema, entity: &str) -> Vec<InputValue> {
+    let mut args = vec![
+        InputValue::new("filter", TypeRef::named(&format!("{entity}Filter"))),
+        InputValue::new("groupBy", TypeRef::list_of(&format!("{entity}Field"))).describe(
+            "Aggregate each group of entities with the same values of these fields.",
+        ),
+    ];
+
+    if schema.is_versioned(entity) {
+        args.push(
+            InputValue::new("block", TypeRef::named("Int"))
+                .describe("Aggregate the entities as they were at this block height."),
+        );
+    }
+
+    args
+}
+
+/// Something an introspection selection set is resolved against.
+#[derive(Clone, Copy, Debug)]
+enum Node<'t> {
+    Root,
+    Schema,
+    NamedType(&'t TypeDef),
+    /// A list or non-null wrapper around another type.
+    WrappedType(&'t TypeRef),
+    Field(&'t FieldDef),
+    InputValue(&'t InputValue),
+    EnumValue(&'t str),
+    Directive(&'t DirectiveDef),
+}
+
+/// Answers introspection queries from an indexer's `Schema`.
+pub struct Introspection<'d, 'a> {
+    schema: &'d Schema,
+    types: BTreeMap<String, TypeDef>,
+    directives: Vec<DirectiveDef>,
+    fragments: HashMap<&'d str, &'d gql::FragmentDefinition<'a, &'a str>>,
+    variables: QueryVariables,
+}
+
+impl<'d, 'a> Introspection<'d, 'a> {
+    pub fn new(
+        schema: &'d Schema,
+        document: &'d gql::Document<'a, &'a str>,
+        variables: QueryVariables,
+    ) -> Self {
+        let fragments = document
+            .definitions
+            .iter()
+            .filter_map(|def| match def {
+                gql::Definition::Fragment(frag) => Some((frag.name, frag)),
+                _ => None,
+            })
+            .collect();
+
+        Introspection {
+            schema,
+            types: build_types(schema),
+            directives: build_directives(),
+            fragments,
+            variables,
+        }
+    }
+
+    /// Resolve the selections of an operation into a response object.
+    pub fn execute(
+        &self,
+        set: &gql::SelectionSet<'a, &'a str>,
+    ) -> GraphqlResult<Map<String, Value>> {
+        let mut data = Map::new();
+        self.collect(Node::Root, set, &mut data)?;
+        Ok(data)
+    }
+
+    fn typename(&self, node: Node) -> &str {
+        match node {
+            Node::Root => &self.schema.query,
+            Node::Schema => "__Schema",
+            Node::NamedType(_) | Node::WrappedType(_) => "__Type",
+            Node::Field(_) => "__Field",
+            Node::InputValue(_) => "__InputValue",
+            Node::EnumValue(_) => "__EnumValue",
+            Node::Directive(_) => "__Directive",
+        }
+    }
+
+    fn type_node<'t>(&'t self, ty: &'t TypeRef) -> GraphqlResult<Node<'t>> {
+        match ty {
+            TypeRef::Named(name) => self
+                .types
+                .get(name)
+                .map(Node::NamedType)
+                .ok_or_else(|| GraphqlError::UnrecognizedType(name.to_string())),
+            _ => Ok(Node::WrappedType(ty)),
+        }
+    }
+
+    fn resolve(
+        &self,
+        node: Node,
+        set: &gql::SelectionSet<'a, &'a str>,
+    ) -> GraphqlResult<Value> {
+        let mut object = Map::new();
+        self.collect(node, set, &mut object)?;
+        Ok(Value::Object(object))
+    }
+
+    fn resolve_list<'t>(
+        &self,
+        nodes: impl IntoIterator<Item = Node<'t>>,
+        set: &gql::SelectionSet<'a, &'a str>,
+    ) -> GraphqlResult<Value> {
+        nodes
+            .into_iter()
+            .map(|node| self.resolve(node, set))
+            .collect::<GraphqlResult<Vec<Value>>>()
+            .map(Value::Array)
+    }
+
+    /// Add the fields selected by `set` on `node` to `object`. The meta types
+    /// aren't polymorphic, so fragments are merged regardless of their type
+    /// condition.
+    fn collect(
+        &self,
+        node: Node,
+        set: &gql::SelectionSet<'a, &'a str>,
+        object: &mut Map<String, Value>,
+    ) -> GraphqlResult<()> {
+        for item in &set.items {
+            let directives = match item {
+                gql::Selection::Field(field) => &field.directives,
+                gql::Selection::FragmentSpread(spread) => &spread.directives,
+                gql::Selection::InlineFragment(frag) => &frag.directives,
+            };
+            if !is_included(directives, &self.variables)? {
+                continue;
+            }
+
+            match item {
+                gql::Selection::Field(field) => {
+                    let key = field.alias.unwrap_or(field.name);
+                    object.insert(key.to_string(), self.resolve_field(node, field)?);
+                }
+                gql::Selection::FragmentSpread(spread) => {
+                    let frag = self
+                        .fragments
+                        .get(spread.fragment_name)
+                        .ok_or(GraphqlError::FragmentResolverFailed)?;
+                    self.collect(node, &frag.selection_set, object)?;
+                }
+                gql::Selection::InlineFragment(frag) => {
+                    self.collect(node, &frag.selection_set, object)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn resolve_field(
+        &self,
+        node: Node,
+        field: &gql::Field<'a, &'a str>,
+    ) -> GraphqlResult<Value> {
+        let set = &field.selection_set;
+
+        if field.name == "__typename" {
+            return Ok(json!(self.typename(node)));
+        }
+
+        let value = match (node, field.name) {
+            (Node::Root, "__schema") => self.resolve(Node::Schema, set)?,
+            (Node::Root, "__type") => {
+                let name = match field.arguments.iter().find(|(arg, _)| *arg == "name") {
+                    Some((_, value)) => match self.variables.substitute(value.clone())? {
+                        gql::Value::String(name) => name,
+                        value => {
+                            return Err(GraphqlError::UnsupportedValueType(
+                                value.to_string(),
+                            ))
+                        }
+                    },
+                    None => {
+                        return Err(GraphqlError::UnrecognizedArgument(
+                            "__type".into(),
+                            "name".into(),
+                        ))
+                    }
+                };
+                match self.types.get(&name) {
+                    Some(def) => self.resolve(Node::NamedType(def), set)?,
+                    None => Value::Null,
+                }
+            }
+
+            (Node::Schema, "types") => {
+                self.resolve_list(self.types.values().map(Node::NamedType), set)?
+            }
+            (Node::Schema, "queryType") => {
+                let query = TypeRef::named(&self.schema.query);
+                self.resolve(self.type_node(&query)?, set)?
+            }
+            (Node::Schema, "subscriptionType") => {
+                let subscription = TypeRef::named(SUBSCRIPTION_ROOT);
+                self.resolve(self.type_node(&subscription)?, set)?
+            }
+            (Node::Schema, "directives") => {
+                self.resolve_list(self.directives.iter().map(Node::Directive), set)?
+            }
+            (Node::Schema, "description" | "mutationType") => Value::Null,
+
+            (Node::NamedType(def), "kind") => json!(def.kind.as_str()),
+            (Node::NamedType(def), "name") => json!(def.name),
+            (Node::NamedType(def), "fields") => match def.kind {
+                TypeKind::Object => {
+                    self.resolve_list(def.fields.iter().map(Node::Field), set)?
+                }
+                _ => Value::Null,
+            },
+            (Node::NamedType(def), "inputFields") => match def.kind {
+                TypeKind::InputObject => {
+                    self.resolve_list(def.input_fields.iter().map(Node::InputValue), set)?
+                }
+                _ => Value::Null,
+            },
+            (Node::NamedType(def), "enumValues") => match def.kind {
+                TypeKind::Enum => self.resolve_list(
+                    def.enum_values.iter().map(|v| Node::EnumValue(v)),
+                    set,
+                )?,
+                _ => Value::Null,
+            },
+            (Node::NamedType(def), "interfaces") => match def.kind {
+                TypeKind::Object => json!([]),
+                _ => Value::Null,
+            },
+            (
+                Node::NamedType(_),
+                "description" | "ofType" | "possibleTypes" | "specifiedByURL"
+                | "specifiedByUrl",
+            ) => Value::Null,
+
+            (Node::WrappedType(ty), "kind") => match ty {
+                TypeRef::List(_) => json!("LIST"),
+                _ => json!("NON_NULL"),
+            },
+            (Node::WrappedType(ty), "ofType") => match ty {
+                TypeRef::List(inner) | TypeRef::NonNull(inner) => {
+                    self.resolve(self.type_node(inner)?, set)?
+                }
+                TypeRef::Named(_) => Value::Null,
+            },
+            (
+                Node::WrappedType(_),
+                "name" | "description" | "fields" | "inputFields" | "enumValues"
+                | "interfaces" | "possibleTypes" | "specifiedByURL" | "specifiedByUrl",
+            ) => Value::Null,
+
+            (Node::Field(def), "name") => json!(def.name),
+            (Node::Field(def), "args") => {
+                self.resolve_list(def.args.iter().map(Node::InputValue), set)?
+            }
+            (Node::Field(def), "type") => self.resolve(self.type_node(&def.ty)?, set)?,
+
+            (Node::InputValue(value), "name") => json!(value.name),
+            (Node::InputValue(value), "description") => json!(value.description),
+            (Node::InputValue(value), "type") => {
+                self.resolve(self.type_node(&value.ty)?, set)?
+            }
+            (Node::InputValue(_), "defaultValue") => Value::Null,
+
+            (Node::EnumValue(value), "name") => json!(value),
+
+            (Node::Directive(def), "name") => json!(def.name),
+            (Node::Directive(def), "description") => json!(def.description),
+            (Node::Directive(_), "locations") => json!(DIRECTIVE_LOCATIONS),
+            (Node::Directive(def), "args") => {
+                self.resolve_list(def.args.iter().map(Node::InputValue), set)?
+            }
+            (Node::Directive(_), "isRepeatable") => json!(false),
+
+            (Node::Field(_) | Node::EnumValue(_), "isDeprecated") => json!(false),
+            (
+                Node::Field(_) | Node::EnumValue(_),
+                "description" | "deprecationReason",
+            ) => Value::Null,
+
+            (node, name) => {
+                return Err(GraphqlError::UnrecognizedField(
+                    self.typename(node).to_string(),
+                    name.to_string(),
+                ))
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::GraphqlQueryBuilder;
+    use std::collections::HashSet;
+
+    fn schema() -> Schema {
+        let fields = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<String, String>>()
+        };
+
+        Schema {
+            version: "".into(),
+            namespace: "test_namespace".to_string(),
+            identifier: "index1".to_string(),
+            query: "QueryRoot".into(),
+            types: HashSet::from(["QueryRoot".to_string(), "Thing".to_string()]),
+            fields: HashMap::from([
+                ("QueryRoot".to_string(), fields(&[("thing", "Thing")])),
+                (
+                    "Thing".to_string(),
+                    fields(&[
+                        ("id", "ID!"),
+                        ("account", "Address"),
+                        ("tags", "[Bytes32!]"),
+                        ("object", "__"),
+                    ]),
+                ),
+            ]),
+            foreign_keys: HashMap::new(),
+            versioned: HashSet::from(["thing".to_string()]),
+        }
+    }
+
+    fn introspect(query: &str) -> Value {
+        let schema = schema();
+        let builder = GraphqlQueryBuilder::new(&schema, query).unwrap();
+        assert!(builder.is_introspection());
+        builder.introspect().unwrap()
+    }
+
+    #[test]
+    fn test_introspection_describes_entities_and_root_arguments() {
+        let data = introspect(
+            r#"
+            query {
+                __typename
+                __schema { queryType { name } subscriptionType { name } }
+                entity: __type(name: "Thing") {
+                    kind
+                    fields { name type { kind name ofType { kind name } } }
+                }
+                root: __type(name: "QueryRoot") {
+                    fields { name args { name type { name } } }
+                }
+                missing: __type(name: "Nope") { name }
+            }
+            "#,
+        );
+
+        assert_eq!(data["__typename"], json!("QueryRoot"));
+        assert_eq!(
+            data["__schema"],
+            json!({
+                "queryType": { "name": "QueryRoot" },
+                "subscriptionType": { "name": "SubscriptionRoot" },
+            })
+        );
+        assert_eq!(data["missing"], Value::Null);
+        assert_eq!(
+            data["entity"],
+            json!({
+                "kind": "OBJECT",
+                "fields": [
+                    { "name": "account", "type": { "kind": "SCALAR", "name": "Address", "ofType": null } },
+                    { "name": "id", "type": { "kind": "NON_NULL", "name": null, "ofType": { "kind": "SCALAR", "name": "ID" } } },
+                    { "name": "tags", "type": { "kind": "LIST", "name": null, "ofType": { "kind": "NON_NULL", "name": null } } },
+                ]
+            })
+        );
+        assert_eq!(
+            data["root"]["fields"][0]["args"],
+            json!([
+                { "name": "id", "type": { "name": "ID" } },
+                { "name": "filter", "type": { "name": "ThingFilter" } },
+                { "name": "order", "type": { "name": "ThingOrder" } },
+                { "name": "first", "type": { "name": "Int" } },
+                { "name": "offset", "type": { "name": "Int" } },
+                { "name": "block", "type": { "name": "Int" } },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_introspection_resolves_fragments_over_all_types() {
+        let data = introspect(
+            r#"
+            query IntrospectionQuery {
+                __schema {
+                    types { ...FullType }
+                    directives { name }
+                }
+            }
+
+            fragment FullType on __Type {
+                kind
+                name
+                inputFields { ...InputValue }
+                enumValues(includeDeprecated: true) { name isDeprecated }
+            }
+
+            fragment InputValue on __InputValue {
+                name
+                type { ...TypeRef }
+            }
+
+            fragment TypeRef on __Type {
+                kind
+                name
+                ofType { kind name }
+            }
+            "#,
+        );
+
+        let types = data["__schema"]["types"].as_array().unwrap();
+        let names = types
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            names,
+            vec![
+                "Address",
+                "AddressComparison",
+                "AddressRange",
+                "Boolean",
+                "Bytes32",
+                "ID",
+                "IDComparison",
+                "IDRange",
+                "Int",
+                "PageInfo",
+                "QueryRoot",
+                "String",
+                "SubscriptionRoot",
+                "Thing",
+                "ThingAggregate",
+                "ThingConnection",
+                "ThingEdge",
+                "ThingField",
+                "ThingFilter",
+                "ThingMaxFields",
+                "ThingMinFields",
+                "ThingOrder",
+            ]
+        );
+
+        let filter = types.iter().find(|t| t["name"] == "ThingFilter").unwrap();
+        let filter_fields = filter["inputFields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["name"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            filter_fields,
+            vec!["account", "id", "has", "and", "or", "not"]
+        );
+
+        let field_enum = types.iter().find(|t| t["name"] == "ThingField").unwrap();
+        assert_eq!(
+            field_enum["enumValues"],
+            json!([
+                { "name": "account", "isDeprecated": false },
+                { "name": "id", "isDeprecated": false },
+                { "name": "tags", "isDeprecated": false },
+            ])
+        );
+        assert_eq!(
+            data["__schema"]["directives"],
+            json!([{ "name": "include" }, { "name": "skip" }])
+        );
+    }
+
+    #[test]
+    fn test_introspection_substitutes_variables() {
+        let schema = schema();
+        let query = r#"query Type($name: String!) { __type(name: $name) { kind } }"#;
+        let data = GraphqlQueryBuilder::new(&schema, query)
+            .unwrap()
+            .variables(HashMap::from([("name".to_string(), json!("ThingOrder"))]))
+            .introspect()
+            .unwrap();
+
+        assert_eq!(data, json!({ "__type": { "kind": "INPUT_OBJECT" } }));
+    }
+
+    #[test]
+    fn test_is_introspection_rejects_entity_queries() {
+        let schema = schema();
+        let builder =
+            GraphqlQueryBuilder::new(&schema, "{ __typename thing { id } }").unwrap();
+        assert!(!builder.is_introspection());
+    }
+}
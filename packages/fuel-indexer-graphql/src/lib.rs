@@ -1,3 +1,4 @@
 pub mod arguments;
 pub mod graphql;
+pub mod introspection;
 pub mod queries;
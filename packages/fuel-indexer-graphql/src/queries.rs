@@ -1,7 +1,13 @@
-use super::{arguments::QueryParams, graphql::GraphqlError};
-use fuel_indexer_database::DbType;
+use super::{
+    arguments::{Cursor, ParsedValue, QueryParams, SortOrder},
+    graphql::GraphqlError,
+};
+use fuel_indexer_database::{types::history_table_name, DbType};
 
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
 /// Represents a part of a user query. Each part can be a key-value pair
 /// describing an entity field and its corresponding database table, or a
@@ -14,6 +20,134 @@ pub enum QueryElement {
     ObjectClosingBoundary,
 }
 
+/// Scalars that can be summed and averaged.
+const NUMERIC_SCALARS: [&str; 6] = ["Int4", "Int8", "Int16", "UInt4", "UInt8", "UInt16"];
+
+/// Scalars, besides the numeric ones, whose values are ordered, and so have
+/// a minimum and a maximum.
+const ORDERED_SCALARS: [&str; 3] = ["ID", "Timestamp", "Charfield"];
+
+/// Represents a function computing a single value from the values of a field
+/// across many records.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AggregateFunction {
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl AggregateFunction {
+    /// Returns the function named by a field of an aggregate selection, if any.
+    pub fn from_field_name(name: &str) -> Option<AggregateFunction> {
+        match name {
+            "sum" => Some(Self::Sum),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "avg" => Some(Self::Avg),
+            _ => None,
+        }
+    }
+
+    /// Whether the function can be applied to values of `scalar`.
+    pub fn accepts(&self, scalar: &str) -> bool {
+        match self {
+            Self::Sum | Self::Avg => NUMERIC_SCALARS.contains(&scalar),
+            Self::Min | Self::Max => {
+                NUMERIC_SCALARS.contains(&scalar) || ORDERED_SCALARS.contains(&scalar)
+            }
+        }
+    }
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sum => write!(f, "SUM"),
+            Self::Min => write!(f, "MIN"),
+            Self::Max => write!(f, "MAX"),
+            Self::Avg => write!(f, "AVG"),
+        }
+    }
+}
+
+/// Represents a part of an aggregate query. Each part is either the number of
+/// matched records, an aggregate function applied to some fields, or a field
+/// by which records are grouped; field values refer to database columns.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AggregateElement {
+    Count {
+        key: String,
+    },
+    Function {
+        key: String,
+        function: AggregateFunction,
+        fields: Vec<(String, String)>,
+    },
+    GroupKey {
+        key: String,
+        value: String,
+    },
+}
+
+impl AggregateElement {
+    /// Returns the element with its field values prefixed by `table`.
+    pub fn qualify(self, table: &str) -> AggregateElement {
+        match self {
+            Self::Count { key } => Self::Count { key },
+            Self::Function {
+                key,
+                function,
+                fields,
+            } => Self::Function {
+                key,
+                function,
+                fields: fields
+                    .into_iter()
+                    .map(|(key, value)| (key, format!("{table}.{value}")))
+                    .collect(),
+            },
+            Self::GroupKey { key, value } => Self::GroupKey {
+                key,
+                value: format!("{table}.{value}"),
+            },
+        }
+    }
+}
+
+/// Represents the layout of a connection, through which records are paginated
+/// with cursors. Each part holds the JSON key it's returned under.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Connection {
+    pub key: String,
+    pub fields: Vec<(String, ConnectionField)>,
+}
+
+/// Represents a field of a connection.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ConnectionField {
+    TotalCount,
+    PageInfo(Vec<(String, PageInfoField)>),
+    Edges(Vec<(String, EdgeField)>),
+    Nodes,
+}
+
+/// Represents a field of the page information of a connection.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PageInfoField {
+    HasNextPage,
+    HasPreviousPage,
+    StartCursor,
+    EndCursor,
+}
+
+/// Represents a field of an edge of a connection, i.e. a record and its cursor.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EdgeField {
+    Cursor,
+    Node,
+}
+
 /// Represents the tables and columns used in a particular database join.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct JoinCondition {
@@ -59,15 +193,64 @@ pub struct UserQuery {
     pub query_params: QueryParams,
     // An optional user-suppled alias for an entity field.
     pub alias: Option<String>,
+    /// Tables that keep the history of their entities, whose state at the
+    /// queried block is read from their history.
+    pub versioned: HashSet<String>,
+    /// Values computed over the matched records, which are returned instead
+    /// of the records themselves when there are any.
+    pub aggregates: Vec<AggregateElement>,
+    /// The layout of the connection the records are returned through, if the
+    /// query paginates them with cursors.
+    pub connection: Option<Connection>,
+    /// Columns holding unsigned 64-bit integers, which SQLite stores offset
+    /// by 2^63, and so are read back as JSON numbers.
+    pub u64_columns: HashSet<String>,
 }
 
 impl UserQuery {
+    /// Returns the table that `table` is read from. Queries at a block read
+    /// versioned tables as they were at that block, and every other table as
+    /// it currently is.
+    fn source_table(&self, table: &str, db_type: &DbType) -> String {
+        let sql_table = db_type.table_name(&self.namespace_identifier, table);
+        match self.query_params.block {
+            Some(block) if self.versioned.contains(table) => format!(
+                "(SELECT * FROM {} WHERE valid_from <= {block} AND (valid_to IS NULL OR valid_to > {block})) AS {table}",
+                history_table_name(&sql_table)
+            ),
+            Some(_) => format!("{sql_table} AS {table}"),
+            None => table.to_string(),
+        }
+    }
+
+    /// Returns the table that the query's top-level entity is read from.
+    fn entity_table(&self, db_type: &DbType) -> String {
+        match self.query_params.block {
+            Some(_) => self.source_table(&self.entity_name, db_type),
+            None => db_type.table_name(&self.namespace_identifier, &self.entity_name),
+        }
+    }
+
+    /// Returns the value of `column` as it's returned.
+    fn column_value(&self, column: &str, db_type: &DbType) -> String {
+        match db_type {
+            DbType::Sqlite if self.u64_columns.contains(column) => {
+                sqlite_u64_json(column)
+            }
+            _ => column.to_string(),
+        }
+    }
+
     /// Returns the query as a database-specific SQL query.
     pub fn to_sql(&mut self, db_type: &DbType) -> Result<String, GraphqlError> {
+        if !self.aggregates.is_empty() {
+            return Ok(self.create_aggregate_query(db_type));
+        }
+
         // Different database solutions have unique ways of
         // constructing JSON-formatted queries and results.
         match db_type {
-            DbType::Postgres => {
+            DbType::Postgres | DbType::Sqlite => {
                 let selections = self.parse_query_elements_into_selections(db_type);
 
                 let selections_str = selections.join("");
@@ -91,7 +274,8 @@ impl UserQuery {
                     } else {
                         joins.push(format!(
                             "INNER JOIN {} ON {}",
-                            sj.primary_key_table, sj
+                            self.source_table(&sj.primary_key_table, db_type),
+                            sj
                         ));
                         last_seen_primary_key_table = sj.primary_key_table;
                     }
@@ -103,6 +287,15 @@ impl UserQuery {
                     "".to_string()
                 };
 
+                if let Some(connection) = self.connection.clone() {
+                    return self.create_connection_query(
+                        db_type,
+                        &connection,
+                        selections_str,
+                        joins_str,
+                    );
+                }
+
                 // If there's a limit applied to the query, then we need to create a query
                 // with pagination info. Otherwise, we can return the entire result set.
                 let query: String = if let Some(limit) = self.query_params.limit {
@@ -119,10 +312,10 @@ impl UserQuery {
                     }
                 } else {
                     format!(
-                        "SELECT json_build_object({}) FROM {}.{} {} {} {}",
+                        "SELECT {}({}) FROM {} {} {} {}",
+                        json_object_fn(db_type),
                         selections_str,
-                        self.namespace_identifier,
-                        self.entity_name,
+                        self.entity_table(db_type),
                         joins_str,
                         self.query_params.get_filtering_expression(db_type),
                         self.query_params.get_ordering_modififer(db_type)
@@ -134,6 +327,313 @@ impl UserQuery {
         }
     }
 
+    /// Returns the name by which the query's top-level entity table is referred to.
+    fn entity_ref(&self, db_type: &DbType) -> String {
+        match self.query_params.block {
+            Some(_) => self.entity_name.clone(),
+            None => db_type.table_name(&self.namespace_identifier, &self.entity_name),
+        }
+    }
+
+    /// Returns a SQL query that contains a page of the requested results, as
+    /// a connection whose records are paginated with cursors.
+    ///
+    /// Records are ordered by the requested field, if any, and then by their ID,
+    /// so that each record has a unique position in the connection which its
+    /// cursor encodes. Pages are then selected with conditions on these positions,
+    /// which unlike offsets remain stable as new records are added.
+    fn create_connection_query(
+        &self,
+        db_type: &DbType,
+        connection: &Connection,
+        selections_str: String,
+        joins_str: String,
+    ) -> Result<String, GraphqlError> {
+        let params = &self.query_params;
+
+        if params.sorts.len() > 1 {
+            return Err(GraphqlError::UnsupportedCursorOrder);
+        }
+        if params.limit.is_some() && params.last.is_some() {
+            return Err(GraphqlError::ConflictingPagination);
+        }
+
+        let id = format!("{}.id", self.entity_ref(db_type));
+        let sort = params
+            .sorts
+            .first()
+            .map(|sort| (sort.fully_qualified_table_name.clone(), sort.order.clone()));
+        let u64_sort = sort
+            .as_ref()
+            .map_or(false, |(column, _)| self.u64_columns.contains(column));
+        let direction = sort
+            .as_ref()
+            .map_or(SortOrder::Asc, |(_, order)| order.clone());
+
+        // Records are read backwards from the end of the connection when
+        // only the last ones are requested.
+        let backwards = params.last.is_some();
+        let read_direction = match (backwards, &direction) {
+            (false, order) => order.clone(),
+            (true, SortOrder::Asc) => SortOrder::Desc,
+            (true, SortOrder::Desc) => SortOrder::Asc,
+        };
+        // Records without a value for the field come last in the connection,
+        // whichever way it's ordered.
+        let nulls = match backwards {
+            false => "NULLS LAST",
+            true => "NULLS FIRST",
+        };
+        let ordering = match &sort {
+            Some((column, _)) => {
+                format!("{column} {read_direction} {nulls}, {id} {read_direction}")
+            }
+            None => format!("{id} {read_direction}"),
+        };
+
+        let cursor = match (db_type, &sort) {
+            (DbType::Postgres, Some((column, _))) => format!(
+                "encode(convert_to(json_build_array({column}::text, {id}::text)::text, 'UTF8'), 'hex')"
+            ),
+            (DbType::Postgres, None) => format!(
+                "encode(convert_to(json_build_array({id}::text)::text, 'UTF8'), 'hex')"
+            ),
+            (DbType::Sqlite, Some((column, _))) => {
+                let key = match u64_sort {
+                    true => sqlite_u64_text(column),
+                    false => format!("CAST({column} AS TEXT)"),
+                };
+                format!("lower(hex(json_array({key}, {})))", sqlite_u64_text(&id))
+            }
+            (DbType::Sqlite, None) => {
+                format!("lower(hex(json_array({})))", sqlite_u64_text(&id))
+            }
+        };
+
+        let filters = params
+            .filters
+            .iter()
+            .map(|f| f.to_sql(db_type))
+            .collect::<Vec<String>>();
+        let after = params
+            .after
+            .as_ref()
+            .map(|cursor| seek_condition(cursor, &sort, &id, &direction, true, db_type))
+            .transpose()?;
+        let before = params
+            .before
+            .as_ref()
+            .map(|cursor| seek_condition(cursor, &sort, &id, &direction, false, db_type))
+            .transpose()?;
+
+        let source = format!("{} {}", self.entity_table(db_type), joins_str);
+        let window = filters
+            .iter()
+            .chain(after.iter())
+            .chain(before.iter())
+            .cloned()
+            .collect::<Vec<String>>();
+
+        // One more record than requested is read, to tell whether there's
+        // another page in the direction the connection is read.
+        let page_size = params.limit.or(params.last);
+        let (limit, in_page) = match page_size {
+            Some(n) => (format!("LIMIT {}", n + 1), format!("WHERE rn <= {n}")),
+            None => (String::new(), String::new()),
+        };
+        let (first_row, last_row) = match backwards {
+            false => ("ASC", "DESC"),
+            true => ("DESC", "ASC"),
+        };
+
+        // Whether there are records past the page in the direction it was
+        // read, or past the cursor it was read from in the other direction.
+        let has_more =
+            page_size.map(|n| format!("(SELECT COUNT(*) FROM page_cte) > {n}"));
+        let has_past = |cursor: &Option<String>| {
+            cursor.as_ref().map(|condition| {
+                let conditions = filters
+                    .iter()
+                    .cloned()
+                    .chain([format!("NOT {condition}")])
+                    .collect::<Vec<String>>();
+                format!(
+                    "EXISTS (SELECT 1 FROM {source} {})",
+                    where_clause(&conditions)
+                )
+            })
+        };
+        let any = |conditions: Vec<Option<String>>| {
+            let conditions = conditions.into_iter().flatten().collect::<Vec<String>>();
+            match conditions.is_empty() {
+                true => "1 = 0".to_string(),
+                false => conditions.join(" OR "),
+            }
+        };
+        let (has_next_page, has_previous_page) = match backwards {
+            false => (
+                any(vec![has_more, has_past(&before)]),
+                any(vec![has_past(&after)]),
+            ),
+            true => (
+                any(vec![has_past(&before)]),
+                any(vec![has_more, has_past(&after)]),
+            ),
+        };
+
+        let json_object = json_object_fn(db_type);
+        let boolean = |condition: String| match db_type {
+            DbType::Postgres => format!("({condition})"),
+            DbType::Sqlite => {
+                format!("json(CASE WHEN {condition} THEN 'true' ELSE 'false' END)")
+            }
+        };
+        let page_cursor = |order: &str| {
+            format!("(SELECT cursor FROM page_cte {in_page} ORDER BY rn {order} LIMIT 1)")
+        };
+        // SQLite can't order the values of an aggregate, so the page is
+        // ordered beforehand; it can't aggregate whole rows either, so the
+        // already built objects are collected instead.
+        let page_array = |item: String| {
+            match db_type {
+            DbType::Postgres => format!(
+                "(SELECT COALESCE(json_agg({item} ORDER BY rn {first_row}), '[]'::json) FROM page_cte {in_page})"
+            ),
+            DbType::Sqlite => format!(
+                "(SELECT json_group_array({item}) FROM (SELECT * FROM page_cte {in_page} ORDER BY rn {first_row}))"
+            ),
+        }
+        };
+        let node = match db_type {
+            DbType::Postgres => "node",
+            DbType::Sqlite => "json(node)",
+        };
+
+        let fields = connection
+            .fields
+            .iter()
+            .map(|(key, field)| {
+                let value = match field {
+                    ConnectionField::TotalCount => format!(
+                        "(SELECT COUNT(*) FROM {source} {})",
+                        where_clause(&filters)
+                    ),
+                    ConnectionField::PageInfo(fields) => {
+                        let fields = fields
+                            .iter()
+                            .map(|(key, field)| {
+                                let value = match field {
+                                    PageInfoField::HasNextPage => {
+                                        boolean(has_next_page.clone())
+                                    }
+                                    PageInfoField::HasPreviousPage => {
+                                        boolean(has_previous_page.clone())
+                                    }
+                                    PageInfoField::StartCursor => page_cursor(first_row),
+                                    PageInfoField::EndCursor => page_cursor(last_row),
+                                };
+                                format!("'{key}', {value}")
+                            })
+                            .collect::<Vec<String>>()
+                            .join(", ");
+                        format!("{json_object}({fields})")
+                    }
+                    ConnectionField::Edges(fields) => {
+                        let fields = fields
+                            .iter()
+                            .map(|(key, field)| match field {
+                                EdgeField::Cursor => format!("'{key}', cursor"),
+                                EdgeField::Node => format!("'{key}', {node}"),
+                            })
+                            .collect::<Vec<String>>()
+                            .join(", ");
+                        page_array(format!("{json_object}({fields})"))
+                    }
+                    ConnectionField::Nodes => page_array(node.to_string()),
+                };
+                format!("'{key}', {value}")
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let page_cte = format!(
+            r#"WITH page_cte AS (
+                SELECT {json_object}({selections_str}) AS node,
+                    {cursor} AS cursor,
+                    ROW_NUMBER() OVER (ORDER BY {ordering}) AS rn
+                FROM {source}
+                {}
+                ORDER BY {ordering}
+                {limit})"#,
+            where_clause(&window),
+        );
+
+        let selection_query = format!(
+            "SELECT {json_object}('{}', {json_object}({fields}));",
+            connection.key
+        );
+
+        Ok([page_cte, selection_query].join("\n"))
+    }
+
+    /// Returns a SQL query computing the requested aggregates, with one row for
+    /// each group of records if the query groups them, or a single row otherwise.
+    fn create_aggregate_query(&self, db_type: &DbType) -> String {
+        let json_object = json_object_fn(db_type);
+
+        let selections = self
+            .aggregates
+            .iter()
+            .map(|element| match element {
+                AggregateElement::Count { key } => format!("'{key}', COUNT(*)"),
+                AggregateElement::Function {
+                    key,
+                    function,
+                    fields,
+                } => {
+                    let fields = fields
+                        .iter()
+                        .map(|(key, value)| {
+                            let value = match function {
+                                _ if *db_type == DbType::Postgres
+                                    || !self.u64_columns.contains(value) =>
+                                {
+                                    format!("{function}({value})")
+                                }
+                                // Sums and averages are taken of the values
+                                // themselves, which are only exact below 2^63.
+                                AggregateFunction::Sum | AggregateFunction::Avg => {
+                                    format!(
+                                        "{function}({value} + 9223372036854775807 + 1)"
+                                    )
+                                }
+                                AggregateFunction::Min | AggregateFunction::Max => {
+                                    sqlite_u64_json(&format!("{function}({value})"))
+                                }
+                            };
+                            format!("'{key}', {value}")
+                        })
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    format!("'{key}', {json_object}({fields})")
+                }
+                AggregateElement::GroupKey { key, value } => {
+                    format!("'{key}', {}", self.column_value(value, db_type))
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!(
+            "SELECT {}({}) FROM {} {} {}",
+            json_object,
+            selections,
+            self.entity_table(db_type),
+            self.query_params.get_filtering_expression(db_type),
+            self.query_params.get_grouping_modifier(db_type)
+        )
+    }
+
     /// Returns a SQL query that contains the requested results and a PageInfo object.
     fn create_query_with_pageinfo(
         &self,
@@ -156,14 +656,13 @@ impl UserQuery {
                 let selection_cte = format!(
                     r#"WITH selection_cte AS (
                         SELECT json_build_object({}) AS {}
-                        FROM {}.{}
+                        FROM {}
                         {}
                         {}
                         {}),"#,
                     selections_str,
                     self.entity_name,
-                    self.namespace_identifier,
-                    self.entity_name,
+                    self.entity_table(db_type),
                     joins_str,
                     self.query_params.get_filtering_expression(db_type),
                     self.query_params.get_ordering_modififer(db_type),
@@ -195,6 +694,53 @@ impl UserQuery {
                     );"#
                 );
 
+                [selection_cte, total_count_cte, selection_query].join("\n")
+            }
+            db_type @ DbType::Sqlite => {
+                let selection_cte = format!(
+                    r#"WITH selection_cte AS (
+                        SELECT json_object({}) AS {}
+                        FROM {}
+                        {}
+                        {}
+                        {}),"#,
+                    selections_str,
+                    self.entity_name,
+                    self.entity_table(db_type),
+                    joins_str,
+                    self.query_params.get_filtering_expression(db_type),
+                    self.query_params.get_ordering_modififer(db_type),
+                );
+
+                let total_count_cte =
+                    "total_count_cte AS (SELECT COUNT(*) as count FROM selection_cte)"
+                        .to_string();
+
+                let offset = self.query_params.offset.unwrap_or(0);
+                let alias = self.alias.clone().unwrap_or(self.entity_name.clone());
+                let entity = &self.entity_name;
+
+                // SQLite has no boolean type, and can't aggregate whole rows, so
+                // the already built objects of each row are collected instead.
+                let selection_query = format!(
+                    r#"SELECT json_object(
+                        'page_info', json_object(
+                            'has_next_page', json(CASE WHEN ({limit} + {offset}) < (SELECT count from total_count_cte) THEN 'true' ELSE 'false' END),
+                            'limit', {limit},
+                            'offset', {offset},
+                            'pages', ((SELECT count from total_count_cte) + {limit} - 1) / {limit},
+                            'total_count', (SELECT count from total_count_cte)
+                        ),
+                        '{alias}', (
+                            SELECT json_group_array(json({entity}))
+                            FROM (
+                                SELECT {entity} FROM selection_cte
+                                LIMIT {limit} OFFSET {offset}
+                            )
+                        )
+                    );"#
+                );
+
                 [selection_cte, total_count_cte, selection_query].join("\n")
             }
         }
@@ -210,14 +756,17 @@ impl UserQuery {
         let mut selections = Vec::new();
 
         match db_type {
-            DbType::Postgres => {
+            DbType::Postgres | DbType::Sqlite => {
                 while let Some(e) = peekable_elements.next() {
                     match e {
                         // Set the key for this JSON element to the name of the entity field
                         // and the value to the corresponding database table so that it can
                         // be successfully retrieved.
                         QueryElement::Field { key, value } => {
-                            selections.push(format!("'{key}', {value}"));
+                            selections.push(format!(
+                                "'{key}', {}",
+                                self.column_value(value, db_type)
+                            ));
 
                             // If the next element is not a closing boundary, then a comma should
                             // be added so that the resultant SQL query can be properly constructed.
@@ -234,9 +783,8 @@ impl UserQuery {
 
                         // If the element is an object opener boundary, then we need to set a
                         // key so that the recipient can properly refer to the nested object.
-                        QueryElement::ObjectOpeningBoundary { key } => {
-                            selections.push(format!("'{key}', json_build_object("))
-                        }
+                        QueryElement::ObjectOpeningBoundary { key } => selections
+                            .push(format!("'{key}', {}(", json_object_fn(db_type))),
 
                         QueryElement::ObjectClosingBoundary => {
                             selections.push(")".to_string());
@@ -265,7 +813,7 @@ impl UserQuery {
         let mut selections = Vec::new();
 
         match db_type {
-            DbType::Postgres => {
+            DbType::Postgres | DbType::Sqlite => {
                 let mut peekable_elements = self.elements.iter().peekable();
                 let mut nesting_level = 0;
 
@@ -349,11 +897,85 @@ impl UserQuery {
     }
 }
 
+/// Returns a clause only keeping the records that meet all of `conditions`.
+fn where_clause(conditions: &[String]) -> String {
+    match conditions.is_empty() {
+        true => String::new(),
+        false => format!("WHERE {}", conditions.join(" AND ")),
+    }
+}
+
+/// Returns a condition keeping the records past `cursor` in a connection
+/// ordered by `sort`, then by `id`, in `direction`: the records after the
+/// cursor if `forward` is set, and those before it otherwise.
+///
+/// Records where the sorted field is null come last, and the condition is
+/// never null itself, so that it can be negated.
+fn seek_condition(
+    cursor: &Cursor,
+    sort: &Option<(String, SortOrder)>,
+    id: &str,
+    direction: &SortOrder,
+    forward: bool,
+    db_type: &DbType,
+) -> Result<String, GraphqlError> {
+    let op = match (direction, forward) {
+        (SortOrder::Asc, true) | (SortOrder::Desc, false) => ">",
+        (SortOrder::Asc, false) | (SortOrder::Desc, true) => "<",
+    };
+    let cursor_id = ParsedValue::UInt8(cursor.id).to_sql(db_type);
+
+    match (sort, &cursor.key) {
+        (Some((column, _)), Some(Some(key))) => {
+            let key = key.to_sql(db_type);
+            let seek = format!(
+                "{column} {op} {key} OR ({column} = {key} AND {id} {op} {cursor_id})"
+            );
+            match forward {
+                true => Ok(format!("({column} IS NULL OR {seek})")),
+                false => Ok(format!("({column} IS NOT NULL AND ({seek}))")),
+            }
+        }
+        (Some((column, _)), Some(None)) => match forward {
+            true => Ok(format!("({column} IS NULL AND {id} {op} {cursor_id})")),
+            false => Ok(format!("({column} IS NOT NULL OR {id} {op} {cursor_id})")),
+        },
+        (None, None) => Ok(format!("({id} {op} {cursor_id})")),
+        _ => Err(GraphqlError::InvalidCursor(
+            "Cursor doesn't match the order of the query".to_string(),
+        )),
+    }
+}
+
+/// Returns the decimal text of an unsigned 64-bit integer that SQLite stores
+/// offset by 2^63, or null. Flipping the top bit undoes the offset, and
+/// `printf` reads the bits back as unsigned.
+fn sqlite_u64_text(value: &str) -> String {
+    format!(
+        "CASE WHEN {value} IS NULL THEN NULL ELSE printf('%u', ({value} | (1 << 63)) & ~({value} & (1 << 63))) END"
+    )
+}
+
+/// Returns an unsigned 64-bit integer that SQLite stores offset by 2^63 as an
+/// exact JSON number, since SQLite's own numbers are signed.
+fn sqlite_u64_json(value: &str) -> String {
+    format!("json(COALESCE({}, 'null'))", sqlite_u64_text(value))
+}
+
+/// Returns the name of the function that builds a JSON object from a list of
+/// keys and values.
+fn json_object_fn(db_type: &DbType) -> &'static str {
+    match db_type {
+        DbType::Postgres => "json_build_object",
+        DbType::Sqlite => "json_object",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::arguments::{Filter, FilterType, ParsedValue};
+    use crate::arguments::{Filter, FilterType, ParsedValue, Sort, SortOrder};
 
     #[test]
     fn test_user_query_parse_query_elements() {
@@ -378,6 +1000,10 @@ mod tests {
             entity_name: "".to_string(),
             query_params: QueryParams::default(),
             alias: None,
+            versioned: HashSet::new(),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::new(),
         };
 
         let expected = vec![
@@ -459,12 +1085,167 @@ mod tests {
                 sorts: vec![],
                 offset: None,
                 limit: None,
+                block: None,
+                group_by: vec![],
+                after: None,
+                before: None,
+                last: None,
             },
             alias: None,
+            versioned: HashSet::new(),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::new(),
         };
 
         let expected = "SELECT json_build_object('hash', name_ident.block.hash, 'tx', json_build_object('hash', name_ident.tx.hash), 'height', name_ident.block.height) FROM name_ident.entity_name INNER JOIN name_ident.block ON name_ident.tx.block = name_ident.block.id WHERE  name_ident.entity_name.id = 1 "
             .to_string();
         assert_eq!(expected, uq.to_sql(&DbType::Postgres).unwrap());
     }
+    #[test]
+    fn test_user_query_at_block_to_sql() {
+        let join = JoinCondition {
+            referencing_key_table: "balance".to_string(),
+            referencing_key_col: "owner".to_string(),
+            primary_key_table: "account".to_string(),
+            primary_key_col: "id".to_string(),
+        };
+
+        let mut uq = UserQuery {
+            elements: vec![
+                QueryElement::Field {
+                    key: "amount".to_string(),
+                    value: "balance.amount".to_string(),
+                },
+                QueryElement::ObjectOpeningBoundary {
+                    key: "owner".to_string(),
+                },
+                QueryElement::Field {
+                    key: "id".to_string(),
+                    value: "account.id".to_string(),
+                },
+                QueryElement::ObjectClosingBoundary,
+            ],
+            joins: HashMap::from([
+                (
+                    "account".to_string(),
+                    QueryJoinNode {
+                        dependencies: HashMap::new(),
+                        dependents: HashMap::from([(
+                            "balance".to_string(),
+                            join.clone(),
+                        )]),
+                    },
+                ),
+                (
+                    "balance".to_string(),
+                    QueryJoinNode {
+                        dependents: HashMap::new(),
+                        dependencies: HashMap::from([("account".to_string(), join)]),
+                    },
+                ),
+            ]),
+            namespace_identifier: "name_ident".to_string(),
+            entity_name: "balance".to_string(),
+            query_params: QueryParams {
+                block: Some(5),
+                ..QueryParams::default()
+            },
+            alias: None,
+            versioned: HashSet::from(["balance".to_string()]),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::new(),
+        };
+
+        let expected = "SELECT json_build_object('amount', balance.amount, 'owner', json_build_object('id', account.id)) FROM (SELECT * FROM name_ident.balance_history WHERE valid_from <= 5 AND (valid_to IS NULL OR valid_to > 5)) AS balance INNER JOIN name_ident.account AS account ON balance.owner = account.id  "
+            .to_string();
+        assert_eq!(expected, uq.to_sql(&DbType::Postgres).unwrap());
+    }
+
+    #[test]
+    fn test_user_query_to_sql_for_sqlite() {
+        let mut uq = UserQuery {
+            elements: vec![
+                QueryElement::Field {
+                    key: "amount".to_string(),
+                    value: "name_ident__balance.amount".to_string(),
+                },
+                QueryElement::ObjectOpeningBoundary {
+                    key: "owner".to_string(),
+                },
+                QueryElement::Field {
+                    key: "id".to_string(),
+                    value: "name_ident__account.id".to_string(),
+                },
+                QueryElement::ObjectClosingBoundary,
+            ],
+            joins: HashMap::new(),
+            namespace_identifier: "name_ident".to_string(),
+            entity_name: "balance".to_string(),
+            query_params: QueryParams::default(),
+            alias: None,
+            versioned: HashSet::new(),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::new(),
+        };
+
+        let expected = "SELECT json_object('amount', name_ident__balance.amount, 'owner', json_object('id', name_ident__account.id)) FROM name_ident__balance   "
+            .to_string();
+        assert_eq!(expected, uq.to_sql(&DbType::Sqlite).unwrap());
+
+        uq.query_params.limit = Some(10);
+        uq.query_params.sorts = vec![Sort {
+            fully_qualified_table_name: "name_ident__balance.amount".to_string(),
+            order: SortOrder::Asc,
+        }];
+
+        let sql = uq.to_sql(&DbType::Sqlite).unwrap();
+        assert!(sql.contains("ORDER BY name_ident__balance.amount ASC"));
+        assert!(sql.contains("SELECT json_group_array(json(balance))"));
+        assert!(!sql.contains("json_build_object"));
+    }
+
+    #[test]
+    fn test_aggregate_query_to_sql_for_sqlite() {
+        let mut uq = UserQuery {
+            elements: vec![],
+            joins: HashMap::new(),
+            namespace_identifier: "name_ident".to_string(),
+            entity_name: "transfer".to_string(),
+            query_params: QueryParams::default(),
+            alias: None,
+            versioned: HashSet::new(),
+            aggregates: vec![
+                AggregateElement::Count {
+                    key: "count".to_string(),
+                },
+                AggregateElement::Function {
+                    key: "avg".to_string(),
+                    function: AggregateFunction::Avg,
+                    fields: vec![("amount".to_string(), "amount".to_string())],
+                }
+                .qualify("name_ident__transfer"),
+            ],
+            connection: None,
+            u64_columns: HashSet::new(),
+        };
+
+        let expected = "SELECT json_object('count', COUNT(*), 'avg', json_object('amount', AVG(name_ident__transfer.amount))) FROM name_ident__transfer  "
+            .to_string();
+        assert_eq!(expected, uq.to_sql(&DbType::Sqlite).unwrap());
+
+        uq.query_params.group_by = vec!["name_ident__transfer.asset".to_string()];
+        uq.aggregates.push(AggregateElement::GroupKey {
+            key: "asset".to_string(),
+            value: "name_ident__transfer.asset".to_string(),
+        });
+
+        let sql = uq.to_sql(&DbType::Sqlite).unwrap();
+        assert!(sql.contains("'asset', name_ident__transfer.asset)"));
+        assert!(sql.ends_with(
+            "GROUP BY name_ident__transfer.asset ORDER BY name_ident__transfer.asset"
+        ));
+    }
 }
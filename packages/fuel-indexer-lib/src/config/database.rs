@@ -25,6 +25,10 @@ pub enum DatabaseConfig {
         database: String,
         verbose: String,
     },
+    Sqlite {
+        path: String,
+        verbose: String,
+    },
 }
 
 impl Env for DatabaseConfig {
@@ -58,6 +62,11 @@ impl Env for DatabaseConfig {
                     *database = std::env::var(trim_opt_env_key(database))?;
                 }
             }
+            DatabaseConfig::Sqlite { path, .. } => {
+                if is_opt_env_var(path) {
+                    *path = std::env::var(trim_opt_env_key(path))?;
+                }
+            }
         }
         Ok(())
     }
@@ -81,6 +90,9 @@ impl ToString for DatabaseConfig {
                     .join("&");
                 format!("postgres://{user}:{password}@{host}:{port}/{database}?{params}")
             }
+            DatabaseConfig::Sqlite { path, verbose } => {
+                format!("sqlite://{path}?verbose={verbose}")
+            }
         }
     }
 }
@@ -106,6 +118,13 @@ impl Debug for DatabaseConfig {
                     .field("verbose", &verbose)
                     .finish();
             }
+            DatabaseConfig::Sqlite { path, verbose } => {
+                let _ = f
+                    .debug_struct("SqliteConfig")
+                    .field("path", &path)
+                    .field("verbose", &verbose)
+                    .finish();
+            }
         }
 
         Ok(())
@@ -161,6 +180,17 @@ impl FromStr for DatabaseConfig {
                     verbose: value,
                 })
             }
+            "sqlite" => {
+                let path = db_url
+                    .trim_start_matches("sqlite://")
+                    .trim_start_matches("sqlite:");
+                let path = path.split('?').next().unwrap_or_default();
+
+                Ok(DatabaseConfig::Sqlite {
+                    path: path.to_string(),
+                    verbose: value,
+                })
+            }
             _ => {
                 unimplemented!("Unsupported database. Please check your database URL.")
             }
@@ -55,6 +55,8 @@ pub enum EnvVar {
     PostgresPort,
     #[strum(serialize = "POSTGRES_USER")]
     PostgresUser,
+    #[strum(serialize = "SQLITE_DATABASE")]
+    SqliteDatabase,
     #[strum(serialize = "JWT_SECRET")]
     JwtSecret,
 }
@@ -113,7 +115,7 @@ pub struct IndexerArgs {
     pub graphql_api_port: String,
 
     /// Database type.
-    #[clap(long, help = "Database type.", default_value = defaults::DATABASE, value_parser(["postgres"]))]
+    #[clap(long, help = "Database type.", default_value = defaults::DATABASE, value_parser(["postgres", "sqlite"]))]
     pub database: String,
 
     /// Max body size for GraphQL API requests.
@@ -140,6 +142,10 @@ pub struct IndexerArgs {
     #[clap(long, help = "Postgres port.")]
     pub postgres_port: Option<String>,
 
+    /// SQLite database file.
+    #[clap(long, help = "SQLite database file.")]
+    pub sqlite_database: Option<String>,
+
     /// Run database migrations before starting service.
     #[clap(long, help = "Run database migrations before starting service.")]
     pub run_migrations: bool,
@@ -199,6 +205,63 @@ pub struct IndexerArgs {
     /// Allow network configuration via indexer manifests.
     #[clap(long, help = "Allow network configuration via indexer manifests.")]
     pub indexer_net_config: bool,
+
+    /// Max number of pages of blocks to fetch from the Fuel node at once.
+    #[clap(
+        long,
+        help = "Max number of pages of blocks to fetch from the Fuel node at once.",
+        default_value_t = defaults::BLOCK_FETCH_CONCURRENCY
+    )]
+    pub block_fetch_concurrency: usize,
+
+    /// Write every block fetched from the Fuel node to an archive file.
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with = "replay-blocks",
+        help = "Write every block fetched from the Fuel node to an archive file."
+    )]
+    pub record_blocks: Option<PathBuf>,
+
+    /// Index blocks from an archive file instead of a Fuel node.
+    #[clap(
+        long,
+        value_name = "FILE",
+        help = "Index blocks from an archive file instead of a Fuel node."
+    )]
+    pub replay_blocks: Option<PathBuf>,
+
+    /// Clear what each indexer has indexed, and replay blocks from its start_block.
+    #[clap(
+        long,
+        requires = "replay-blocks",
+        help = "Clear what each indexer has indexed, and replay blocks from its start_block."
+    )]
+    pub replay_from_start: bool,
+
+    /// Max number of WASM instructions an indexer may execute per batch of blocks. 0 disables the limit.
+    #[clap(
+        long,
+        help = "Max number of WASM instructions an indexer may execute per batch of blocks. 0 disables the limit.",
+        default_value_t = defaults::WASM_INSTRUCTION_LIMIT
+    )]
+    pub wasm_instruction_limit: u64,
+
+    /// Max size of an indexer's WASM linear memory, in MiB. 0 disables the limit.
+    #[clap(
+        long,
+        help = "Max size of an indexer's WASM linear memory, in MiB. 0 disables the limit.",
+        default_value_t = defaults::WASM_MEMORY_LIMIT_MB
+    )]
+    pub wasm_memory_limit_mb: u64,
+
+    /// Max number of seconds an indexer may spend on a batch of blocks. 0 disables the limit.
+    #[clap(
+        long,
+        help = "Max number of seconds an indexer may spend on a batch of blocks. 0 disables the limit.",
+        default_value_t = defaults::WASM_TIMEOUT_SECS
+    )]
+    pub wasm_timeout_secs: u64,
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -237,7 +300,7 @@ pub struct ApiServerArgs {
     pub graphql_api_port: String,
 
     /// Database type.
-    #[clap(long, help = "Database type.", default_value = defaults::DATABASE, value_parser(["postgres"]))]
+    #[clap(long, help = "Database type.", default_value = defaults::DATABASE, value_parser(["postgres", "sqlite"]))]
     pub database: String,
 
     /// Max body size for GraphQL API requests.
@@ -268,6 +331,10 @@ pub struct ApiServerArgs {
     #[clap(long, help = "Postgres port.")]
     pub postgres_port: Option<String>,
 
+    /// SQLite database file.
+    #[clap(long, help = "SQLite database file.")]
+    pub sqlite_database: Option<String>,
+
     /// Use Prometheus metrics reporting.
     #[clap(long, help = "Use Prometheus metrics reporting.")]
     pub metrics: bool,
@@ -320,6 +387,7 @@ impl Default for IndexerArgs {
             postgres_password: None,
             postgres_host: None,
             postgres_port: None,
+            sqlite_database: None,
             run_migrations: true,
             metrics: false,
             stop_idle_indexers: false,
@@ -332,6 +400,13 @@ impl Default for IndexerArgs {
             verbose: false,
             local_fuel_node: false,
             indexer_net_config: false,
+            block_fetch_concurrency: defaults::BLOCK_FETCH_CONCURRENCY,
+            record_blocks: None,
+            replay_blocks: None,
+            replay_from_start: false,
+            wasm_instruction_limit: defaults::WASM_INSTRUCTION_LIMIT,
+            wasm_memory_limit_mb: defaults::WASM_MEMORY_LIMIT_MB,
+            wasm_timeout_secs: defaults::WASM_TIMEOUT_SECS,
         }
     }
 }
@@ -359,6 +434,20 @@ pub struct IndexerConfig {
     pub stop_idle_indexers: bool,
     pub run_migrations: bool,
     pub authentication: AuthenticationConfig,
+    #[serde(default)]
+    pub block_fetch_concurrency: usize,
+    #[serde(default)]
+    pub record_blocks: Option<PathBuf>,
+    #[serde(default)]
+    pub replay_blocks: Option<PathBuf>,
+    #[serde(default)]
+    pub replay_from_start: bool,
+    #[serde(default)]
+    pub wasm_instruction_limit: u64,
+    #[serde(default)]
+    pub wasm_memory_limit_mb: u64,
+    #[serde(default)]
+    pub wasm_timeout_secs: u64,
 }
 
 impl From<IndexerArgs> for IndexerConfig {
@@ -397,6 +486,15 @@ impl From<IndexerArgs> for IndexerConfig {
                 }),
                 verbose: args.verbose.to_string(),
             },
+            "sqlite" => DatabaseConfig::Sqlite {
+                path: args.sqlite_database.unwrap_or_else(|| {
+                    env_or_default(
+                        EnvVar::SqliteDatabase,
+                        defaults::SQLITE_DATABASE.to_string(),
+                    )
+                }),
+                verbose: args.verbose.to_string(),
+            },
             _ => {
                 panic!("Unrecognized database type in options.");
             }
@@ -428,6 +526,13 @@ impl From<IndexerArgs> for IndexerConfig {
                 jwt_issuer: args.jwt_issuer,
                 jwt_expiry: args.jwt_expiry,
             },
+            block_fetch_concurrency: args.block_fetch_concurrency,
+            record_blocks: args.record_blocks,
+            replay_blocks: args.replay_blocks,
+            replay_from_start: args.replay_from_start,
+            wasm_instruction_limit: args.wasm_instruction_limit,
+            wasm_memory_limit_mb: args.wasm_memory_limit_mb,
+            wasm_timeout_secs: args.wasm_timeout_secs,
         };
 
         config
@@ -474,6 +579,15 @@ impl From<ApiServerArgs> for IndexerConfig {
                 }),
                 verbose: args.verbose.to_string(),
             },
+            "sqlite" => DatabaseConfig::Sqlite {
+                path: args.sqlite_database.unwrap_or_else(|| {
+                    env_or_default(
+                        EnvVar::SqliteDatabase,
+                        defaults::SQLITE_DATABASE.to_string(),
+                    )
+                }),
+                verbose: args.verbose.to_string(),
+            },
             _ => {
                 panic!("Unrecognized database type in options.");
             }
@@ -505,6 +619,13 @@ impl From<ApiServerArgs> for IndexerConfig {
                 jwt_issuer: args.jwt_issuer,
                 jwt_expiry: args.jwt_expiry,
             },
+            block_fetch_concurrency: defaults::BLOCK_FETCH_CONCURRENCY,
+            record_blocks: None,
+            replay_blocks: None,
+            replay_from_start: false,
+            wasm_instruction_limit: defaults::WASM_INSTRUCTION_LIMIT,
+            wasm_memory_limit_mb: defaults::WASM_MEMORY_LIMIT_MB,
+            wasm_timeout_secs: defaults::WASM_TIMEOUT_SECS,
         };
 
         config
@@ -534,6 +655,16 @@ impl IndexerConfig {
         let local_fuel_node_key = serde_yaml::Value::String("local_fuel_node".into());
         let indexer_net_config_key =
             serde_yaml::Value::String("indexer_net_config".into());
+        let block_fetch_concurrency_key =
+            serde_yaml::Value::String("block_fetch_concurrency".into());
+        let record_blocks_key = serde_yaml::Value::String("record_blocks".into());
+        let replay_blocks_key = serde_yaml::Value::String("replay_blocks".into());
+        let replay_from_start_key = serde_yaml::Value::String("replay_from_start".into());
+        let wasm_instruction_limit_key =
+            serde_yaml::Value::String("wasm_instruction_limit".into());
+        let wasm_memory_limit_mb_key =
+            serde_yaml::Value::String("wasm_memory_limit_mb".into());
+        let wasm_timeout_secs_key = serde_yaml::Value::String("wasm_timeout_secs".into());
 
         if let Some(metrics) = content.get(metrics_key) {
             config.metrics = metrics.as_bool().unwrap();
@@ -559,6 +690,38 @@ impl IndexerConfig {
             config.indexer_net_config = indexer_net_config.as_bool().unwrap();
         }
 
+        config.block_fetch_concurrency = content
+            .get(block_fetch_concurrency_key)
+            .map(|c| c.as_u64().unwrap() as usize)
+            .unwrap_or(defaults::BLOCK_FETCH_CONCURRENCY);
+
+        if let Some(record_blocks) = content.get(record_blocks_key) {
+            config.record_blocks = Some(PathBuf::from(record_blocks.as_str().unwrap()));
+        }
+
+        if let Some(replay_blocks) = content.get(replay_blocks_key) {
+            config.replay_blocks = Some(PathBuf::from(replay_blocks.as_str().unwrap()));
+        }
+
+        if let Some(replay_from_start) = content.get(replay_from_start_key) {
+            config.replay_from_start = replay_from_start.as_bool().unwrap();
+        }
+
+        config.wasm_instruction_limit = content
+            .get(wasm_instruction_limit_key)
+            .map(|l| l.as_u64().unwrap())
+            .unwrap_or(defaults::WASM_INSTRUCTION_LIMIT);
+
+        config.wasm_memory_limit_mb = content
+            .get(wasm_memory_limit_mb_key)
+            .map(|l| l.as_u64().unwrap())
+            .unwrap_or(defaults::WASM_MEMORY_LIMIT_MB);
+
+        config.wasm_timeout_secs = content
+            .get(wasm_timeout_secs_key)
+            .map(|l| l.as_u64().unwrap())
+            .unwrap_or(defaults::WASM_TIMEOUT_SECS);
+
         let fuel_config_key = serde_yaml::Value::String("fuel_node".into());
         let graphql_config_key = serde_yaml::Value::String("graphql_api".into());
         let database_config_key = serde_yaml::Value::String("database".into());
@@ -646,6 +809,23 @@ impl IndexerConfig {
                     verbose: config.verbose.to_string(),
                 };
             }
+
+            let sqlite_section = section.get("sqlite");
+
+            if let Some(sqlite_section) = sqlite_section {
+                let mut sqlite_path = defaults::SQLITE_DATABASE.to_string();
+
+                let sqlite_path_value =
+                    sqlite_section.get(&serde_yaml::Value::String("path".into()));
+                if let Some(sqlite_path_value) = sqlite_path_value {
+                    sqlite_path = sqlite_path_value.as_str().unwrap().to_string();
+                }
+
+                config.database = DatabaseConfig::Sqlite {
+                    path: sqlite_path,
+                    verbose: config.verbose.to_string(),
+                };
+            }
         }
 
         if let Some(section) = content.get(auth_config_key) {
@@ -719,7 +899,9 @@ mod tests {
         assert!(!config.run_migrations);
         assert!(!config.verbose);
 
-        let DatabaseConfig::Postgres { verbose, .. } = config.database;
+        let DatabaseConfig::Postgres { verbose, .. } = config.database else {
+            panic!("Expected the default Postgres config.");
+        };
         assert_eq!(verbose.as_str(), "false");
 
         fs::remove_file(FILE).unwrap();
@@ -778,6 +960,31 @@ mod tests {
 
                 fs::remove_file(FILE).unwrap();
             }
+            config => panic!("Expected a Postgres config, got {config:?}."),
+        }
+    }
+
+    #[test]
+    fn test_indexer_config_reads_sqlite_database_section() {
+        let file = "sqlite.yaml";
+        let config_str = r#"
+        database:
+          sqlite:
+            path: /var/lib/fuel/indexer.db
+        "#;
+
+        fs::write(file, config_str).unwrap();
+        let config = IndexerConfig::from_file(file).unwrap();
+        fs::remove_file(file).unwrap();
+
+        let url = config.database.to_string();
+        assert_eq!(url, "sqlite:///var/lib/fuel/indexer.db?verbose=false");
+
+        match url.parse::<DatabaseConfig>().unwrap() {
+            DatabaseConfig::Sqlite { path, .. } => {
+                assert_eq!(path, "/var/lib/fuel/indexer.db".to_string());
+            }
+            config => panic!("Expected a SQLite config, got {config:?}."),
         }
     }
 }
@@ -11,6 +11,8 @@ pub const POSTGRES_HOST: &str = "localhost";
 pub const POSTGRES_PORT: &str = "5432";
 pub const POSTGRES_PASSWORD: &str = "postgres";
 
+pub const SQLITE_DATABASE: &str = "fuel_indexer.db";
+
 pub const INDEX_FAILED_CALLS: usize = 10;
 pub const STOP_IDLE_INDEXERS: bool = false;
 
@@ -24,7 +26,10 @@ pub const INITIAL_RETRY_DELAY_SECS: u64 = 2;
 pub const MAX_EMPTY_BLOCK_REQUESTS: usize = 10;
 
 pub const DELAY_FOR_SERVICE_ERR: u64 = 5;
+pub const INITIAL_RETRY_BACKOFF_MS: u64 = DELAY_FOR_SERVICE_ERR * 1000;
+pub const MAX_RETRY_BACKOFF_MS: u64 = 300_000; // 5 minutes
 pub const DELAY_FOR_EMPTY_PAGE: u64 = 1;
+pub const STOP_EXECUTOR_POLL_MS: u64 = 100;
 
 pub const RUN_MIGRATIONS: bool = true;
 pub const USE_METRICS: bool = true;
@@ -49,7 +54,35 @@ pub const VERBOSE_LOGGING: bool = false;
 pub const VERBOSE_DB_LOGGING: &str = "false";
 
 pub const NODE_GRAPHQL_PAGE_SIZE: usize = 10;
+pub const BLOCK_FETCH_CONCURRENCY: usize = 4;
+pub const BLOCK_CACHE_SIZE: usize = 1000;
+pub const MAX_REORG_DEPTH: u64 = 100;
+pub const BACKFILL_RANGE_SIZE: u64 = 100;
+
+pub const WASM_INSTRUCTION_LIMIT: u64 = 10_000_000_000;
+pub const WASM_MEMORY_LIMIT_MB: u64 = 512;
+pub const WASM_TIMEOUT_SECS: u64 = 60;
 
 pub const LOCAL_FUEL_NODE: bool = false;
 
 pub const INDEXER_NET_CONFIG: bool = false;
+
+/// Appended to an indexer's identifier for the tables and progress of a
+/// blue/green deployment that hasn't replaced it yet.
+pub const SHADOW_IDENTIFIER_SUFFIX: &str = "__shadow";
+
+/// Postgres channel that indexers notify each time they commit a transaction,
+/// with the `namespace.identifier` of the indexer as the payload.
+pub const COMMIT_NOTIFICATION_CHANNEL: &str = "fuel_indexer_commits";
+/// How often subscriptions check for new commits when the database can't
+/// notify them, as with SQLite.
+pub const SUBSCRIPTION_POLL_INTERVAL_MS: u64 = 1000;
+/// Commit notifications buffered for each subscription before the oldest ones
+/// are dropped.
+pub const SUBSCRIPTION_CHANNEL_SIZE: usize = 100;
+/// Answers buffered for the subscriptions of a WebSocket before a subscriber
+/// that reads too slowly is sent only its latest answer.
+pub const SUBSCRIPTION_OUTGOING_SIZE: usize = 16;
+/// Most entities a subscription may select, since it keeps every entity it has
+/// sent in memory to tell which ones changed.
+pub const SUBSCRIPTION_MAX_RECORDS: usize = 10_000;
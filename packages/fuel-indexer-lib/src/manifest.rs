@@ -1,3 +1,4 @@
+use crate::defaults;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -5,6 +6,7 @@ use std::{
     io::{Read, Write},
     path::{Path, PathBuf},
     str::FromStr,
+    time::Duration,
 };
 use thiserror::Error;
 
@@ -47,6 +49,75 @@ impl ToString for Module {
     }
 }
 
+/// What an indexer does once its handler has failed on a batch of blocks
+/// more times than its `RetryPolicy` allows.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureAction {
+    /// Stop the indexer.
+    #[default]
+    Halt,
+    /// Index the batch a block at a time, skipping the blocks that still fail
+    /// and recording them in the dead-letter table.
+    Skip,
+    /// Keep retrying, waiting the maximum backoff between attempts.
+    Retry,
+}
+
+/// How an indexer retries a batch of blocks that its handler failed to index.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Number of times a failed batch is retried before `on_failure` applies.
+    pub max_retries: usize,
+
+    /// Delay before the first retry, doubled after each further failure.
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound on the delay between retries.
+    pub max_backoff_ms: u64,
+
+    pub on_failure: FailureAction,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: defaults::INDEX_FAILED_CALLS,
+            initial_backoff_ms: defaults::INITIAL_RETRY_BACKOFF_MS,
+            max_backoff_ms: defaults::MAX_RETRY_BACKOFF_MS,
+            on_failure: FailureAction::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retrying after `attempt` consecutive failures.
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 1u64.checked_shl(attempt as u32).unwrap_or(u64::MAX);
+        let millis = self
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(self.max_backoff_ms);
+        Duration::from_millis(millis)
+    }
+}
+
+/// Per-indexer limits on WASM execution, which can only tighten the limits set
+/// by the indexer service.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct WasmLimits {
+    /// Max number of WASM instructions executed per batch of blocks.
+    pub instructions: Option<u64>,
+
+    /// Max size of the indexer's linear memory, in MiB.
+    pub memory_mb: Option<u64>,
+
+    /// Max number of seconds spent on a batch of blocks.
+    pub timeout_secs: Option<u64>,
+}
+
 /// Represents the indexer manifest file.
 ///
 /// This manifest file is a simple YAML file that is read and passed
@@ -69,7 +140,11 @@ pub struct Manifest {
     pub contract_id: ContractIds,
     pub start_block: Option<u64>,
     #[serde(default)]
-    pub resumable: Option<bool>,
+    pub end_block: Option<u64>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub limits: WasmLimits,
 }
 
 impl Manifest {
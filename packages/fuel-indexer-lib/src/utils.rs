@@ -75,9 +75,18 @@ pub struct IndexRevertRequest {
     pub identifier: String,
 }
 
+/// Start indexing a new version of an indexer into shadow tables, to replace
+/// the running version once it has caught up with the chain.
+#[derive(Debug)]
+pub struct ShadowDeployRequest {
+    pub namespace: String,
+    pub identifier: String,
+}
+
 #[derive(Debug)]
 pub enum ServiceRequest {
     AssetReload(AssetReloadRequest),
+    ShadowDeploy(ShadowDeployRequest),
     IndexStop(IndexStopRequest),
     IndexRevert(IndexRevertRequest),
 }
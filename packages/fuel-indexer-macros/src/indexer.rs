@@ -258,6 +258,17 @@ fn process_fn_items(
         None => quote! {},
     };
 
+    let end_block = match manifest.end_block {
+        Some(end_block) => {
+            quote! {
+                if block.height > #end_block {
+                    continue;
+                }
+            }
+        }
+        None => quote! {},
+    };
+
     let contracts = match &manifest.contract_id {
         ContractIds::Single(contract_id) => match contract_id {
             Some(contract_id) => {
@@ -461,6 +472,8 @@ fn process_fn_items(
 
                 #start_block
 
+                #end_block
+
                 let mut decoder = Decoders::default();
 
                 let ty_id = abi::BlockData::type_id();
@@ -12,12 +12,15 @@ pub fn handler_block_native(
         async fn handle_events(blocks: Vec<BlockData>, db_conn: Arc<Mutex<Database>>) -> IndexerResult<()> {
 
             unsafe {
-                db = Some(db_conn);
+                db = Some(db_conn.clone());
             }
 
             #handler_block
 
-            Ok(())
+            match db_conn.lock().await.take_handler_error() {
+                Some(e) => Err(e),
+                None => Ok(()),
+            }
 
         }
     }
@@ -5,8 +5,9 @@ use fuel_indexer_graphql_parser::schema::{
 };
 use fuel_indexer_lib::utils::local_repository_root;
 use fuel_indexer_schema::utils::{
-    build_schema_fields_and_types_map, build_schema_objects_set, get_join_directive_info,
-    inject_native_entities_into_schema, schema_version, BASE_SCHEMA,
+    build_schema_fields_and_types_map, build_schema_objects_set, get_index_directive,
+    get_join_directive_info, get_unique_directive, inject_native_entities_into_schema,
+    schema_version, BASE_SCHEMA,
 };
 use fuel_indexer_types::type_id;
 use lazy_static::lazy_static;
@@ -161,6 +162,9 @@ fn process_type_def(
             let mut row_extractors = quote! {};
             let mut construction = quote! {};
             let mut flattened = quote! {};
+            let mut has_id = false;
+            let mut filter_fields = quote! {};
+            let mut filter_columns = quote! {};
 
             for field in &obj.fields {
                 let (mut type_name, mut field_name, mut ext) =
@@ -182,12 +186,38 @@ fn process_type_def(
 
                 processed.insert(column_type_name_str.clone());
 
+                if field_name == "id" && column_type_name_str == "ID" && !is_nullable {
+                    has_id = true;
+                }
+
                 let decoder = if is_nullable {
                     quote! { FtColumn::#column_type_name(self.#field_name), }
                 } else {
                     quote! { FtColumn::#column_type_name(Some(self.#field_name.clone())), }
                 };
 
+                let directives::Unique(unique) = get_unique_directive(field);
+                if unique || get_index_directive(field).is_some() {
+                    let column_name = &field.name;
+                    let value = if is_nullable {
+                        quote! { FtColumn::#column_type_name(value) }
+                    } else {
+                        quote! { FtColumn::#column_type_name(Some(value)) }
+                    };
+
+                    filter_fields = quote! {
+                        #filter_fields
+                        pub #field_name: Option<#type_name>,
+                    };
+
+                    filter_columns = quote! {
+                        #filter_columns
+                        if let Some(value) = self.#field_name {
+                            columns.push((String::from(#column_name), #value));
+                        }
+                    };
+                }
+
                 block = quote! {
                     #block
                     #field_name: #type_name,
@@ -211,9 +241,68 @@ fn process_type_def(
             }
             let strct = format_ident! {"{}", name};
 
+            // Entities are deleted by their `ID` column, which not every
+            // entity has.
+            let no_id_error = format!("{name} has no ID field and can't be deleted.");
+
             processed.insert(strct.to_string());
 
+            // Entities can be found by their `@indexed` and `@unique` fields.
+            let filter = format_ident! {"{}Filter", name};
+            let filter_struct = quote! {
+                #[derive(Debug, Default, Clone)]
+                pub struct #filter {
+                    #filter_fields
+                }
+
+                impl #filter {
+                    fn entity_filter(self, limit: Option<u64>) -> EntityFilter {
+                        let mut columns = Vec::new();
+                        #filter_columns
+                        EntityFilter { columns, limit }
+                    }
+                }
+            };
+            let has_filter = !filter_fields.is_empty();
+
             if is_native {
+                let finders = if has_filter {
+                    quote! {
+                        #filter_struct
+
+                        impl #strct {
+                            pub async fn find_many(filter: #filter) -> Vec<Self> {
+                                Self::find(filter.entity_filter(None)).await
+                            }
+
+                            pub async fn find_one(filter: #filter) -> Option<Self> {
+                                Self::find(filter.entity_filter(Some(1))).await.pop()
+                            }
+                        }
+                    }
+                } else {
+                    quote! {}
+                };
+
+                let native_delete = if has_id {
+                    quote! {
+                        unsafe {
+                            match &db {
+                                Some(d) => {
+                                    let mut d = d.lock().await;
+                                    if let Err(e) = d.delete_object(Self::TYPE_ID, self.id).await {
+                                        Logger::error(&format!("Failed to delete object: {}", e));
+                                        d.fail_handler(e);
+                                    }
+                                }
+                                None => {},
+                            }
+                        }
+                    }
+                } else {
+                    quote! { Logger::error(#no_id_error); }
+                };
+
                 Some(quote! {
                     #[derive(Debug, PartialEq, Eq, Hash)]
                     pub struct #strct {
@@ -241,13 +330,19 @@ fn process_type_def(
                             unsafe {
                                 match &db {
                                     Some(d) => {
-                                        match d.lock().await.get_object(Self::TYPE_ID, id).await {
-                                            Some(bytes) => {
+                                        let mut d = d.lock().await;
+                                        match d.get_object(Self::TYPE_ID, id).await {
+                                            Ok(Some(bytes)) => {
                                                 let columns: Vec<FtColumn> = bincode::deserialize(&bytes).expect("Serde error.");
                                                 let obj = Self::from_row(columns);
                                                 Some(obj)
                                             },
-                                            None => None,
+                                            Ok(None) => None,
+                                            Err(e) => {
+                                                Logger::error(&format!("Failed to load object: {}", e));
+                                                d.fail_handler(e);
+                                                None
+                                            }
                                         }
                                     }
                                     None => None,
@@ -259,19 +354,76 @@ fn process_type_def(
                             unsafe {
                                 match &db {
                                     Some(d) => {
-                                        d.lock().await.put_object(
+                                        let mut d = d.lock().await;
+                                        if let Err(e) = d.put_object(
                                             Self::TYPE_ID,
                                             self.to_row(),
                                             serialize(&self.to_row())
-                                        ).await;
+                                        ).await {
+                                            Logger::error(&format!("Failed to save object: {}", e));
+                                            d.fail_handler(e);
+                                        }
                                     }
                                     None => {},
                                 }
                             }
                         }
+
+                        async fn find(filter: EntityFilter) -> Vec<Self> {
+                            unsafe {
+                                match &db {
+                                    Some(d) => {
+                                        match d.lock().await.find_objects(Self::TYPE_ID, filter).await {
+                                            Ok(rows) => rows
+                                                .iter()
+                                                .map(|row| {
+                                                    let columns: Vec<FtColumn> = bincode::deserialize(row).expect("Serde error.");
+                                                    Self::from_row(columns)
+                                                })
+                                                .collect(),
+                                            Err(e) => {
+                                                Logger::error(&format!("Failed to find objects: {}", e));
+                                                Vec::new()
+                                            }
+                                        }
+                                    }
+                                    None => Vec::new(),
+                                }
+                            }
+                        }
+
+                        async fn delete(&self) {
+                            #native_delete
+                        }
                     }
+
+                    #finders
                 })
             } else {
+                let finders = if has_filter {
+                    quote! {
+                        #filter_struct
+
+                        impl #strct {
+                            pub fn find_many(filter: #filter) -> Vec<Self> {
+                                Self::find(filter.entity_filter(None))
+                            }
+
+                            pub fn find_one(filter: #filter) -> Option<Self> {
+                                Self::find(filter.entity_filter(Some(1))).pop()
+                            }
+                        }
+                    }
+                } else {
+                    quote! {}
+                };
+
+                let object_id = if has_id {
+                    quote! { Some(self.id) }
+                } else {
+                    quote! { None }
+                };
+
                 Some(quote! {
                     #[derive(Debug, PartialEq, Eq, Hash)]
                     pub struct #strct {
@@ -293,7 +445,13 @@ fn process_type_def(
                                 #flattened
                             ]
                         }
+
+                        fn object_id(&self) -> Option<u64> {
+                            #object_id
+                        }
                     }
+
+                    #finders
                 })
             }
         }
@@ -1,4 +1,4 @@
-use crate::db::Postgres;
+use crate::db::{Postgres, Sqlite};
 use lazy_static::lazy_static;
 use prometheus::{self, register_int_counter, IntCounter};
 use prometheus_client::{
@@ -27,12 +27,14 @@ pub struct Database {
     pub bytes_written: IntCounter,
     pub bytes_read: IntCounter,
     pub postgres: Postgres,
+    pub sqlite: Sqlite,
 }
 
 impl Metric for Database {
     fn init() -> Self {
         Self {
             postgres: Postgres::init(),
+            sqlite: Sqlite::init(),
             write_ops: register_int_counter!("write_ops", "Count of write operations.")
                 .unwrap(),
             read_ops: register_int_counter!("read_ops", "Count of read operations.")
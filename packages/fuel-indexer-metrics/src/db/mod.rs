@@ -1,2 +1,4 @@
 mod postgres;
+mod sqlite;
 pub use postgres::Postgres;
+pub use sqlite::Sqlite;
@@ -22,8 +22,9 @@ pub struct Postgres {
     pub new_column_insert_calls: IntCounter,
     pub list_column_by_id_calls: IntCounter,
     pub columns_get_schema_calls: IntCounter,
-    pub put_object_calls: IntCounter,
+    pub put_objects_calls: IntCounter,
     pub get_object_calls: IntCounter,
+    pub find_objects_calls: IntCounter,
     pub run_query_calls: IntCounter,
     pub execute_query_calls: IntCounter,
     pub root_columns_list_by_id_calls: IntCounter,
@@ -35,7 +36,6 @@ pub struct Postgres {
     pub register_index_asset_calls: IntCounter,
     pub latest_asset_for_index_calls: IntCounter,
     pub latest_assets_for_index_calls: IntCounter,
-    pub last_block_height_for_indexer_calls: IntCounter,
     pub asset_already_exists_calls: IntCounter,
     pub index_id_for_calls: IntCounter,
     pub penultimate_asset_for_index_calls: IntCounter,
@@ -45,6 +45,35 @@ pub struct Postgres {
     pub run_migration_calls: IntCounter,
     pub remove_asset_by_version_calls: IntCounter,
     pub remove_indexer: IntCounter,
+    pub put_rollback_journal_entries_calls: IntCounter,
+    pub rollback_journal_entries_calls: IntCounter,
+    pub remove_rollback_journal_entries_calls: IntCounter,
+    pub prune_rollback_journal_calls: IntCounter,
+    pub delete_object_calls: IntCounter,
+    pub remove_schema_metadata_calls: IntCounter,
+    pub new_schema_migration_calls: IntCounter,
+    pub list_objects_calls: IntCounter,
+    pub set_object_bytes_calls: IntCounter,
+    pub put_shadow_deployment_calls: IntCounter,
+    pub shadow_deployment_for_calls: IntCounter,
+    pub set_shadow_caught_up_calls: IntCounter,
+    pub remove_shadow_deployment_calls: IntCounter,
+    pub remove_indexer_state_calls: IntCounter,
+    pub rename_indexer_state_calls: IntCounter,
+    pub lock_indexer_calls: IntCounter,
+    pub foreign_keys_referencing_calls: IntCounter,
+    pub put_rollback_journal_entries_referencing_calls: IntCounter,
+    pub put_object_versions_calls: IntCounter,
+    pub close_object_versions_calls: IntCounter,
+    pub rollback_object_versions_calls: IntCounter,
+    pub notify_commit_calls: IntCounter,
+    pub set_index_completed_calls: IntCounter,
+    pub put_checkpoint_calls: IntCounter,
+    pub checkpoint_for_indexer_calls: IntCounter,
+    pub remove_checkpoint_calls: IntCounter,
+    pub set_index_status_calls: IntCounter,
+    pub put_dead_letter_calls: IntCounter,
+    pub dead_letters_calls: IntCounter,
     pub registry: Registry,
     requests: Family<Label, Histogram>,
 }
@@ -105,9 +134,9 @@ impl Metric for Postgres {
                 "Count of calls to postgres columns_get_schema_calls."
             )
             .unwrap(),
-            put_object_calls: register_int_counter!(
-                "postgres_put_object_calls",
-                "Count of calls to postgres put_object_calls."
+            put_objects_calls: register_int_counter!(
+                "postgres_put_objects_calls",
+                "Count of calls to postgres put_objects_calls."
             )
             .unwrap(),
             get_object_calls: register_int_counter!(
@@ -115,6 +144,11 @@ impl Metric for Postgres {
                 "Count of calls to postgres get_object_calls."
             )
             .unwrap(),
+            find_objects_calls: register_int_counter!(
+                "postgres_find_objects_calls",
+                "Count of calls to postgres find_objects_calls."
+            )
+            .unwrap(),
             run_query_calls: register_int_counter!(
                 "postgres_run_query_calls",
                 "Count of calls to postgres run_query_calls."
@@ -170,11 +204,6 @@ impl Metric for Postgres {
                 "Count of calls to postgres latest_assets_for_index_calls."
             )
             .unwrap(),
-            last_block_height_for_indexer_calls: register_int_counter!(
-                "postgres_last_block_height_for_indexer_calls",
-                "Count of calls to postgres last_block_height_for_indexer_calls."
-            )
-            .unwrap(),
             asset_already_exists_calls: register_int_counter!(
                 "postgres_asset_already_exists_calls",
                 "Count of calls to postgres asset_already_exists_calls."
@@ -220,6 +249,151 @@ impl Metric for Postgres {
                 "Count of calls to postgres remove_asset_by_version."
             )
             .unwrap(),
+            put_rollback_journal_entries_calls: register_int_counter!(
+                "postgres_put_rollback_journal_entries_calls",
+                "Count of calls to postgres put_rollback_journal_entries_calls."
+            )
+            .unwrap(),
+            rollback_journal_entries_calls: register_int_counter!(
+                "postgres_rollback_journal_entries_calls",
+                "Count of calls to postgres rollback_journal_entries_calls."
+            )
+            .unwrap(),
+            remove_rollback_journal_entries_calls: register_int_counter!(
+                "postgres_remove_rollback_journal_entries_calls",
+                "Count of calls to postgres remove_rollback_journal_entries_calls."
+            )
+            .unwrap(),
+            prune_rollback_journal_calls: register_int_counter!(
+                "postgres_prune_rollback_journal_calls",
+                "Count of calls to postgres prune_rollback_journal_calls."
+            )
+            .unwrap(),
+            delete_object_calls: register_int_counter!(
+                "postgres_delete_object_calls",
+                "Count of calls to postgres delete_object_calls."
+            )
+            .unwrap(),
+            remove_schema_metadata_calls: register_int_counter!(
+                "postgres_remove_schema_metadata_calls",
+                "Count of calls to postgres remove_schema_metadata_calls."
+            )
+            .unwrap(),
+            new_schema_migration_calls: register_int_counter!(
+                "postgres_new_schema_migration_calls",
+                "Count of calls to postgres new_schema_migration_calls."
+            )
+            .unwrap(),
+            list_objects_calls: register_int_counter!(
+                "postgres_list_objects_calls",
+                "Count of calls to postgres list_objects_calls."
+            )
+            .unwrap(),
+            set_object_bytes_calls: register_int_counter!(
+                "postgres_set_object_bytes_calls",
+                "Count of calls to postgres set_object_bytes_calls."
+            )
+            .unwrap(),
+            put_shadow_deployment_calls: register_int_counter!(
+                "postgres_put_shadow_deployment_calls",
+                "Count of calls to postgres put_shadow_deployment_calls."
+            )
+            .unwrap(),
+            shadow_deployment_for_calls: register_int_counter!(
+                "postgres_shadow_deployment_for_calls",
+                "Count of calls to postgres shadow_deployment_for_calls."
+            )
+            .unwrap(),
+            set_shadow_caught_up_calls: register_int_counter!(
+                "postgres_set_shadow_caught_up_calls",
+                "Count of calls to postgres set_shadow_caught_up_calls."
+            )
+            .unwrap(),
+            remove_shadow_deployment_calls: register_int_counter!(
+                "postgres_remove_shadow_deployment_calls",
+                "Count of calls to postgres remove_shadow_deployment_calls."
+            )
+            .unwrap(),
+            remove_indexer_state_calls: register_int_counter!(
+                "postgres_remove_indexer_state_calls",
+                "Count of calls to postgres remove_indexer_state_calls."
+            )
+            .unwrap(),
+            rename_indexer_state_calls: register_int_counter!(
+                "postgres_rename_indexer_state_calls",
+                "Count of calls to postgres rename_indexer_state_calls."
+            )
+            .unwrap(),
+            lock_indexer_calls: register_int_counter!(
+                "postgres_lock_indexer_calls",
+                "Count of calls to postgres lock_indexer_calls."
+            )
+            .unwrap(),
+            foreign_keys_referencing_calls: register_int_counter!(
+                "postgres_foreign_keys_referencing_calls",
+                "Count of calls to postgres foreign_keys_referencing_calls."
+            )
+            .unwrap(),
+            put_rollback_journal_entries_referencing_calls: register_int_counter!(
+                "postgres_put_rollback_journal_entries_referencing_calls",
+                "Count of calls to postgres put_rollback_journal_entries_referencing_calls."
+            )
+            .unwrap(),
+            put_object_versions_calls: register_int_counter!(
+                "postgres_put_object_versions_calls",
+                "Count of calls to postgres put_object_versions_calls."
+            )
+            .unwrap(),
+            close_object_versions_calls: register_int_counter!(
+                "postgres_close_object_versions_calls",
+                "Count of calls to postgres close_object_versions_calls."
+            )
+            .unwrap(),
+            rollback_object_versions_calls: register_int_counter!(
+                "postgres_rollback_object_versions_calls",
+                "Count of calls to postgres rollback_object_versions_calls."
+            )
+            .unwrap(),
+            notify_commit_calls: register_int_counter!(
+                "postgres_notify_commit_calls",
+                "Count of calls to postgres notify_commit_calls."
+            )
+            .unwrap(),
+            set_index_completed_calls: register_int_counter!(
+                "postgres_set_index_completed_calls",
+                "Count of calls to postgres set_index_completed_calls."
+            )
+            .unwrap(),
+            put_checkpoint_calls: register_int_counter!(
+                "postgres_put_checkpoint_calls",
+                "Count of calls to postgres put_checkpoint_calls."
+            )
+            .unwrap(),
+            checkpoint_for_indexer_calls: register_int_counter!(
+                "postgres_checkpoint_for_indexer_calls",
+                "Count of calls to postgres checkpoint_for_indexer_calls."
+            )
+            .unwrap(),
+            remove_checkpoint_calls: register_int_counter!(
+                "postgres_remove_checkpoint_calls",
+                "Count of calls to postgres remove_checkpoint_calls."
+            )
+            .unwrap(),
+            set_index_status_calls: register_int_counter!(
+                "postgres_set_index_status_calls",
+                "Count of calls to postgres set_index_status_calls."
+            )
+            .unwrap(),
+            put_dead_letter_calls: register_int_counter!(
+                "postgres_put_dead_letter_calls",
+                "Count of calls to postgres put_dead_letter_calls."
+            )
+            .unwrap(),
+            dead_letters_calls: register_int_counter!(
+                "postgres_dead_letters_calls",
+                "Count of calls to postgres dead_letters_calls."
+            )
+            .unwrap(),
         }
     }
 }
@@ -0,0 +1,378 @@
+use crate::core::Metric;
+use prometheus::{register_int_counter, IntCounter};
+
+pub struct Sqlite {
+    pub graph_root_latest_calls: IntCounter,
+    pub new_graph_root_calls: IntCounter,
+    pub type_id_list_by_name_calls: IntCounter,
+    pub type_id_latest_calls: IntCounter,
+    pub type_id_insert_calls: IntCounter,
+    pub schema_exists_calls: IntCounter,
+    pub new_column_insert_calls: IntCounter,
+    pub list_column_by_id_calls: IntCounter,
+    pub columns_get_schema_calls: IntCounter,
+    pub put_objects_calls: IntCounter,
+    pub get_object_calls: IntCounter,
+    pub find_objects_calls: IntCounter,
+    pub run_query_calls: IntCounter,
+    pub execute_query_calls: IntCounter,
+    pub root_columns_list_by_id_calls: IntCounter,
+    pub new_root_columns_calls: IntCounter,
+    pub index_is_registered_calls: IntCounter,
+    pub register_index_calls: IntCounter,
+    pub registered_indices_calls: IntCounter,
+    pub index_asset_version_calls: IntCounter,
+    pub register_index_asset_calls: IntCounter,
+    pub latest_asset_for_index_calls: IntCounter,
+    pub latest_assets_for_index_calls: IntCounter,
+    pub asset_already_exists_calls: IntCounter,
+    pub index_id_for_calls: IntCounter,
+    pub penultimate_asset_for_index_calls: IntCounter,
+    pub start_transaction_calls: IntCounter,
+    pub commit_transaction_calls: IntCounter,
+    pub revert_transaction_calls: IntCounter,
+    pub run_migration_calls: IntCounter,
+    pub remove_asset_by_version_calls: IntCounter,
+    pub remove_indexer: IntCounter,
+    pub put_rollback_journal_entries_calls: IntCounter,
+    pub rollback_journal_entries_calls: IntCounter,
+    pub remove_rollback_journal_entries_calls: IntCounter,
+    pub prune_rollback_journal_calls: IntCounter,
+    pub delete_object_calls: IntCounter,
+    pub remove_schema_metadata_calls: IntCounter,
+    pub new_schema_migration_calls: IntCounter,
+    pub list_objects_calls: IntCounter,
+    pub set_object_bytes_calls: IntCounter,
+    pub put_shadow_deployment_calls: IntCounter,
+    pub shadow_deployment_for_calls: IntCounter,
+    pub set_shadow_caught_up_calls: IntCounter,
+    pub remove_shadow_deployment_calls: IntCounter,
+    pub remove_indexer_state_calls: IntCounter,
+    pub rename_indexer_state_calls: IntCounter,
+    pub lock_indexer_calls: IntCounter,
+    pub foreign_keys_referencing_calls: IntCounter,
+    pub put_rollback_journal_entries_referencing_calls: IntCounter,
+    pub put_object_versions_calls: IntCounter,
+    pub close_object_versions_calls: IntCounter,
+    pub rollback_object_versions_calls: IntCounter,
+    pub notify_commit_calls: IntCounter,
+    pub set_index_completed_calls: IntCounter,
+    pub put_checkpoint_calls: IntCounter,
+    pub checkpoint_for_indexer_calls: IntCounter,
+    pub remove_checkpoint_calls: IntCounter,
+    pub set_index_status_calls: IntCounter,
+    pub put_dead_letter_calls: IntCounter,
+    pub dead_letters_calls: IntCounter,
+}
+
+impl Metric for Sqlite {
+    fn init() -> Self {
+        Self {
+            graph_root_latest_calls: register_int_counter!(
+                "sqlite_graph_root_latest_calls",
+                "Count of calls to sqlite graph_root_latest_calls."
+            )
+            .unwrap(),
+            new_graph_root_calls: register_int_counter!(
+                "sqlite_new_graph_root_calls",
+                "Count of calls to sqlite new_graph_root_calls."
+            )
+            .unwrap(),
+            type_id_list_by_name_calls: register_int_counter!(
+                "sqlite_type_id_list_by_name_calls",
+                "Count of calls to sqlite type_id_list_by_name_calls"
+            )
+            .unwrap(),
+            type_id_latest_calls: register_int_counter!(
+                "sqlite_type_id_latest_calls",
+                "Count of calls to sqlite type_id_latest_calls."
+            )
+            .unwrap(),
+            type_id_insert_calls: register_int_counter!(
+                "sqlite_type_id_insert_calls",
+                "Count of calls to sqlite type_id_insert_calls."
+            )
+            .unwrap(),
+            schema_exists_calls: register_int_counter!(
+                "sqlite_schema_exists_calls",
+                "Count of calls to sqlite schema_exists_calls."
+            )
+            .unwrap(),
+            new_column_insert_calls: register_int_counter!(
+                "sqlite_new_column_insert_calls",
+                "Count of calls to sqlite new_column_insert_calls."
+            )
+            .unwrap(),
+            list_column_by_id_calls: register_int_counter!(
+                "sqlite_list_column_by_id_calls",
+                "Count of calls to sqlite list_column_by_id_calls."
+            )
+            .unwrap(),
+            columns_get_schema_calls: register_int_counter!(
+                "sqlite_columns_get_schema_calls",
+                "Count of calls to sqlite columns_get_schema_calls."
+            )
+            .unwrap(),
+            put_objects_calls: register_int_counter!(
+                "sqlite_put_objects_calls",
+                "Count of calls to sqlite put_objects_calls."
+            )
+            .unwrap(),
+            get_object_calls: register_int_counter!(
+                "sqlite_get_object_calls",
+                "Count of calls to sqlite get_object_calls."
+            )
+            .unwrap(),
+            find_objects_calls: register_int_counter!(
+                "sqlite_find_objects_calls",
+                "Count of calls to sqlite find_objects_calls."
+            )
+            .unwrap(),
+            run_query_calls: register_int_counter!(
+                "sqlite_run_query_calls",
+                "Count of calls to sqlite run_query_calls."
+            )
+            .unwrap(),
+            execute_query_calls: register_int_counter!(
+                "sqlite_execute_query_calls",
+                "Count of calls to sqlite execute_query_calls."
+            )
+            .unwrap(),
+            root_columns_list_by_id_calls: register_int_counter!(
+                "sqlite_root_columns_list_by_id_calls",
+                "Count of calls to sqlite root_columns_list_by_id_calls."
+            )
+            .unwrap(),
+            new_root_columns_calls: register_int_counter!(
+                "sqlite_new_root_columns_calls",
+                "Count of calls to sqlite new_root_columns_calls."
+            )
+            .unwrap(),
+            index_is_registered_calls: register_int_counter!(
+                "sqlite_index_is_registered_calls",
+                "Count of calls to sqlite index_is_registered_calls."
+            )
+            .unwrap(),
+            register_index_calls: register_int_counter!(
+                "sqlite_register_index_calls",
+                "Count of calls to sqlite register_index_calls."
+            )
+            .unwrap(),
+            registered_indices_calls: register_int_counter!(
+                "sqlite_registered_indices_calls",
+                "Count of calls to sqlite registered_indices_calls."
+            )
+            .unwrap(),
+            index_asset_version_calls: register_int_counter!(
+                "sqlite_index_asset_version_calls",
+                "Count of calls to sqlite index_asset_version_calls."
+            )
+            .unwrap(),
+            register_index_asset_calls: register_int_counter!(
+                "sqlite_register_index_asset_calls",
+                "Count of calls to sqlite register_index_asset_calls."
+            )
+            .unwrap(),
+            latest_asset_for_index_calls: register_int_counter!(
+                "sqlite_latest_asset_for_index_calls",
+                "Count of calls to sqlite latest_asset_for_index_calls."
+            )
+            .unwrap(),
+            latest_assets_for_index_calls: register_int_counter!(
+                "sqlite_latest_assets_for_index_calls",
+                "Count of calls to sqlite latest_assets_for_index_calls."
+            )
+            .unwrap(),
+            asset_already_exists_calls: register_int_counter!(
+                "sqlite_asset_already_exists_calls",
+                "Count of calls to sqlite asset_already_exists_calls."
+            )
+            .unwrap(),
+            index_id_for_calls: register_int_counter!(
+                "sqlite_index_id_for_calls",
+                "Count of calls to sqlite index_id_for_calls."
+            )
+            .unwrap(),
+            penultimate_asset_for_index_calls: register_int_counter!(
+                "sqlite_penultimate_asset_id_for_calls",
+                "Count of calls to sqlite penultimate_index_id_for_calls."
+            )
+            .unwrap(),
+            start_transaction_calls: register_int_counter!(
+                "sqlite_start_transaction_calls",
+                "Count of calls to sqlite start_transaction_calls."
+            )
+            .unwrap(),
+            commit_transaction_calls: register_int_counter!(
+                "sqlite_commit_transaction_calls",
+                "Count of calls to sqlite commit_transaction_calls."
+            )
+            .unwrap(),
+            revert_transaction_calls: register_int_counter!(
+                "sqlite_revert_transaction_calls",
+                "Count of calls to sqlite revert_transaction_calls."
+            )
+            .unwrap(),
+            run_migration_calls: register_int_counter!(
+                "sqlite_run_migration_calls",
+                "Count of calls to sqlite run_migration_calls."
+            )
+            .unwrap(),
+            remove_indexer: register_int_counter!(
+                "sqlite_remove_indexer",
+                "Count of calls to sqlite remove_indexer."
+            )
+            .unwrap(),
+            remove_asset_by_version_calls: register_int_counter!(
+                "sqlite_remove_asset_by_version",
+                "Count of calls to sqlite remove_asset_by_version."
+            )
+            .unwrap(),
+            put_rollback_journal_entries_calls: register_int_counter!(
+                "sqlite_put_rollback_journal_entries_calls",
+                "Count of calls to sqlite put_rollback_journal_entries_calls."
+            )
+            .unwrap(),
+            rollback_journal_entries_calls: register_int_counter!(
+                "sqlite_rollback_journal_entries_calls",
+                "Count of calls to sqlite rollback_journal_entries_calls."
+            )
+            .unwrap(),
+            remove_rollback_journal_entries_calls: register_int_counter!(
+                "sqlite_remove_rollback_journal_entries_calls",
+                "Count of calls to sqlite remove_rollback_journal_entries_calls."
+            )
+            .unwrap(),
+            prune_rollback_journal_calls: register_int_counter!(
+                "sqlite_prune_rollback_journal_calls",
+                "Count of calls to sqlite prune_rollback_journal_calls."
+            )
+            .unwrap(),
+            delete_object_calls: register_int_counter!(
+                "sqlite_delete_object_calls",
+                "Count of calls to sqlite delete_object_calls."
+            )
+            .unwrap(),
+            remove_schema_metadata_calls: register_int_counter!(
+                "sqlite_remove_schema_metadata_calls",
+                "Count of calls to sqlite remove_schema_metadata_calls."
+            )
+            .unwrap(),
+            new_schema_migration_calls: register_int_counter!(
+                "sqlite_new_schema_migration_calls",
+                "Count of calls to sqlite new_schema_migration_calls."
+            )
+            .unwrap(),
+            list_objects_calls: register_int_counter!(
+                "sqlite_list_objects_calls",
+                "Count of calls to sqlite list_objects_calls."
+            )
+            .unwrap(),
+            set_object_bytes_calls: register_int_counter!(
+                "sqlite_set_object_bytes_calls",
+                "Count of calls to sqlite set_object_bytes_calls."
+            )
+            .unwrap(),
+            put_shadow_deployment_calls: register_int_counter!(
+                "sqlite_put_shadow_deployment_calls",
+                "Count of calls to sqlite put_shadow_deployment_calls."
+            )
+            .unwrap(),
+            shadow_deployment_for_calls: register_int_counter!(
+                "sqlite_shadow_deployment_for_calls",
+                "Count of calls to sqlite shadow_deployment_for_calls."
+            )
+            .unwrap(),
+            set_shadow_caught_up_calls: register_int_counter!(
+                "sqlite_set_shadow_caught_up_calls",
+                "Count of calls to sqlite set_shadow_caught_up_calls."
+            )
+            .unwrap(),
+            remove_shadow_deployment_calls: register_int_counter!(
+                "sqlite_remove_shadow_deployment_calls",
+                "Count of calls to sqlite remove_shadow_deployment_calls."
+            )
+            .unwrap(),
+            remove_indexer_state_calls: register_int_counter!(
+                "sqlite_remove_indexer_state_calls",
+                "Count of calls to sqlite remove_indexer_state_calls."
+            )
+            .unwrap(),
+            rename_indexer_state_calls: register_int_counter!(
+                "sqlite_rename_indexer_state_calls",
+                "Count of calls to sqlite rename_indexer_state_calls."
+            )
+            .unwrap(),
+            lock_indexer_calls: register_int_counter!(
+                "sqlite_lock_indexer_calls",
+                "Count of calls to sqlite lock_indexer_calls."
+            )
+            .unwrap(),
+            foreign_keys_referencing_calls: register_int_counter!(
+                "sqlite_foreign_keys_referencing_calls",
+                "Count of calls to sqlite foreign_keys_referencing_calls."
+            )
+            .unwrap(),
+            put_rollback_journal_entries_referencing_calls: register_int_counter!(
+                "sqlite_put_rollback_journal_entries_referencing_calls",
+                "Count of calls to sqlite put_rollback_journal_entries_referencing_calls."
+            )
+            .unwrap(),
+            put_object_versions_calls: register_int_counter!(
+                "sqlite_put_object_versions_calls",
+                "Count of calls to sqlite put_object_versions_calls."
+            )
+            .unwrap(),
+            close_object_versions_calls: register_int_counter!(
+                "sqlite_close_object_versions_calls",
+                "Count of calls to sqlite close_object_versions_calls."
+            )
+            .unwrap(),
+            rollback_object_versions_calls: register_int_counter!(
+                "sqlite_rollback_object_versions_calls",
+                "Count of calls to sqlite rollback_object_versions_calls."
+            )
+            .unwrap(),
+            notify_commit_calls: register_int_counter!(
+                "sqlite_notify_commit_calls",
+                "Count of calls to sqlite notify_commit_calls."
+            )
+            .unwrap(),
+            set_index_completed_calls: register_int_counter!(
+                "sqlite_set_index_completed_calls",
+                "Count of calls to sqlite set_index_completed_calls."
+            )
+            .unwrap(),
+            put_checkpoint_calls: register_int_counter!(
+                "sqlite_put_checkpoint_calls",
+                "Count of calls to sqlite put_checkpoint_calls."
+            )
+            .unwrap(),
+            checkpoint_for_indexer_calls: register_int_counter!(
+                "sqlite_checkpoint_for_indexer_calls",
+                "Count of calls to sqlite checkpoint_for_indexer_calls."
+            )
+            .unwrap(),
+            remove_checkpoint_calls: register_int_counter!(
+                "sqlite_remove_checkpoint_calls",
+                "Count of calls to sqlite remove_checkpoint_calls."
+            )
+            .unwrap(),
+            set_index_status_calls: register_int_counter!(
+                "sqlite_set_index_status_calls",
+                "Count of calls to sqlite set_index_status_calls."
+            )
+            .unwrap(),
+            put_dead_letter_calls: register_int_counter!(
+                "sqlite_put_dead_letter_calls",
+                "Count of calls to sqlite put_dead_letter_calls."
+            )
+            .unwrap(),
+            dead_letters_calls: register_int_counter!(
+                "sqlite_dead_letters_calls",
+                "Count of calls to sqlite dead_letters_calls."
+            )
+            .unwrap(),
+        }
+    }
+}
@@ -7,7 +7,7 @@ pub mod wasm;
 extern crate alloc;
 
 pub mod types {
-    pub use fuel_indexer_schema::FtColumn;
+    pub use fuel_indexer_schema::{EntityFilter, FtColumn};
     pub use fuel_indexer_types::*;
 }
 
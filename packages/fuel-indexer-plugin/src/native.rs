@@ -16,7 +16,7 @@ pub use fuel_indexer_lib::{
     manifest::Manifest,
     utils::{init_logging, ServiceRequest},
 };
-use fuel_indexer_schema::FtColumn;
+use fuel_indexer_schema::{EntityFilter, FtColumn};
 pub use sha2;
 pub use std::collections::HashMap;
 pub use std::collections::HashSet;
@@ -65,4 +65,8 @@ pub trait Entity: Sized + PartialEq + Eq + std::fmt::Debug {
     async fn load(id: u64) -> Option<Self>;
 
     async fn save(&self);
+
+    async fn find(filter: EntityFilter) -> Vec<Self>;
+
+    async fn delete(&self);
 }
@@ -2,7 +2,7 @@ extern crate alloc;
 use alloc::vec::Vec;
 use fuel_indexer_schema::{
     utils::{deserialize, serialize},
-    FtColumn,
+    EntityFilter, FtColumn,
 };
 
 use fuel_indexer_types::ffi::{
@@ -20,6 +20,13 @@ extern "C" {
     // TODO: error codes? or just panic and let the runtime handle it?
     fn ff_get_object(type_id: i64, ptr: *const u8, len: *mut u8) -> *mut u8;
     fn ff_put_object(type_id: i64, ptr: *const u8, len: u32);
+    fn ff_find_objects(
+        type_id: i64,
+        ptr: *const u8,
+        len: u32,
+        len_ptr: *mut u8,
+    ) -> *mut u8;
+    fn ff_delete_object(type_id: i64, ptr: *const u8);
     fn ff_log_data(ptr: *const u8, len: u32, log_level: u32);
 }
 
@@ -59,6 +66,9 @@ pub trait Entity: Sized + PartialEq + Eq + std::fmt::Debug {
         Self::TYPE_ID
     }
 
+    /// The `ID` of this entity, which entities without an `id` field lack.
+    fn object_id(&self) -> Option<u64>;
+
     fn load(id: u64) -> Option<Self> {
         unsafe {
             let buf = id.to_le_bytes();
@@ -84,6 +94,47 @@ pub trait Entity: Sized + PartialEq + Eq + std::fmt::Debug {
             ff_put_object(Self::TYPE_ID, buf.as_ptr(), buf.len() as u32)
         }
     }
+
+    fn find(filter: EntityFilter) -> Vec<Self> {
+        unsafe {
+            let buf = serialize(&filter);
+            let mut buflen = 0u32.to_le_bytes();
+
+            let ptr = ff_find_objects(
+                Self::TYPE_ID,
+                buf.as_ptr(),
+                buf.len() as u32,
+                buflen.as_mut_ptr(),
+            );
+
+            if !ptr.is_null() {
+                let len = u32::from_le_bytes(buflen) as usize;
+                let bytes = Vec::from_raw_parts(ptr, len, len);
+                let rows: Vec<Vec<u8>> = deserialize(&bytes).expect("Bad serialization.");
+
+                rows.iter()
+                    .map(|row| {
+                        Self::from_row(deserialize(row).expect("Bad serialization."))
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    fn delete(&self) {
+        match self.object_id() {
+            Some(id) => unsafe {
+                let buf = id.to_le_bytes();
+                ff_delete_object(Self::TYPE_ID, buf.as_ptr())
+            },
+            None => Logger::error(&format!(
+                "{} has no ID field and can't be deleted.",
+                core::any::type_name::<Self>()
+            )),
+        }
+    }
 }
 
 #[no_mangle]
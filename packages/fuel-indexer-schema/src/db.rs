@@ -1,7 +1,8 @@
 pub mod manager;
+pub mod migration;
 pub mod tables;
 
-use fuel_indexer_database::IndexerDatabaseError;
+use fuel_indexer_database::{DbType, IndexerDatabaseError};
 use thiserror::Error;
 
 pub type IndexerSchemaResult<T> = core::result::Result<T, IndexerSchemaError>;
@@ -12,6 +13,14 @@ pub enum IndexerSchemaError {
     SqlxError(#[from] sqlx::Error),
     #[error("Database error: {0:?}")]
     DatabaseError(#[from] IndexerDatabaseError),
+    #[error("Schema changes would lose indexed data, and have to be forced: {0}")]
+    DestructiveMigration(String),
+    #[error("Could not migrate entity objects: {0}")]
+    MigrationError(String),
+    #[error("Invalid schema: {0}")]
+    InvalidSchema(String),
+    #[error("Not supported by the {0:?} backend: {1}")]
+    NotSupported(DbType, String),
     #[error("Generic error")]
     Generic,
 }
@@ -1,11 +1,17 @@
 use crate::{
     db::{
+        migration::SchemaMigration,
         tables::{Schema, SchemaBuilder},
-        IndexerSchemaResult,
+        IndexerSchemaError, IndexerSchemaResult,
     },
     utils::{inject_native_entities_into_schema, schema_version},
 };
-use fuel_indexer_database::{queries, IndexerConnection, IndexerConnectionPool};
+use fuel_indexer_database::{
+    queries,
+    types::{history_table_name, NewSchemaMigration},
+    DbType, IndexerConnection, IndexerConnectionPool,
+};
+use fuel_indexer_lib::defaults::SHADOW_IDENTIFIER_SUFFIX;
 
 pub struct SchemaManager {
     pool: IndexerConnectionPool,
@@ -16,6 +22,15 @@ impl SchemaManager {
         SchemaManager { pool }
     }
 
+    /// Fail unless the database keeps each indexer's tables in a schema of
+    /// their own, which `feature` swaps out.
+    fn require_schemas(&self, feature: &str) -> IndexerSchemaResult<()> {
+        match self.pool.database_type() {
+            DbType::Postgres => Ok(()),
+            db_type => Err(IndexerSchemaError::NotSupported(db_type, feature.into())),
+        }
+    }
+
     pub async fn new_schema(
         &self,
         namespace: &str,
@@ -23,23 +38,221 @@ impl SchemaManager {
         schema: &str,
         conn: &mut IndexerConnection,
     ) -> IndexerSchemaResult<()> {
+        self.migrate_schema(namespace, identifier, schema, false, conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Create the tables of an indexer's schema, or, if the indexer already has
+    /// tables for another version of its schema, migrate them to this one.
+    ///
+    /// Migrations that would lose indexed data are rejected unless `force` is
+    /// set. Callers should run this in a transaction, so that a migration that
+    /// fails part way through is rolled back.
+    pub async fn migrate_schema(
+        &self,
+        namespace: &str,
+        identifier: &str,
+        schema: &str,
+        force: bool,
+        conn: &mut IndexerConnection,
+    ) -> IndexerSchemaResult<Option<SchemaMigration>> {
         // Schema is built in serveral different places so we add default entities here
         let schema = inject_native_entities_into_schema(schema);
 
-        // TODO: Not doing much with version, but might be useful if we do graph schema upgrades
         let version = schema_version(&schema);
 
-        if !queries::schema_exists(conn, namespace, identifier, &version).await? {
-            let _db_schema = SchemaBuilder::new(
-                namespace,
-                identifier,
-                &version,
-                self.pool.database_type(),
-            )
-            .build(&schema)
-            .commit_metadata(conn)
-            .await?;
+        if queries::schema_exists(conn, namespace, identifier, &version).await? {
+            return Ok(None);
+        }
+
+        let builder = SchemaBuilder::new(
+            namespace,
+            identifier,
+            &version,
+            self.pool.database_type(),
+        )
+        .build(&schema)?;
+
+        let root = match queries::graph_root_latest(conn, namespace, identifier).await {
+            Ok(root) => root,
+            Err(sqlx::Error::RowNotFound) => {
+                let _db_schema = builder.commit_metadata(conn).await?;
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let previous = SchemaBuilder::new(
+            namespace,
+            identifier,
+            &root.version,
+            self.pool.database_type(),
+        )
+        .build(&root.schema)?;
+
+        let migration = SchemaMigration::new(&previous, &builder);
+
+        let db_type = self.pool.database_type();
+        let unsupported = migration
+            .changes
+            .iter()
+            .filter(|c| !c.is_supported_by(&db_type))
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        if !unsupported.is_empty() {
+            return Err(IndexerSchemaError::NotSupported(
+                db_type,
+                unsupported.join(", "),
+            ));
         }
+
+        if migration.is_destructive() && !force {
+            let changes = migration
+                .changes
+                .iter()
+                .filter(|c| c.is_destructive())
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(IndexerSchemaError::DestructiveMigration(changes));
+        }
+
+        migration.apply(conn).await?;
+
+        queries::remove_schema_metadata(conn, namespace, identifier, &version).await?;
+        let _db_schema = builder.register_metadata(conn).await?;
+
+        // Rows removed from some tables would otherwise never be indexed again,
+        // as the indexer resumes from its checkpoint.
+        if migration.clears_rows() {
+            self.clear_indexed_data(namespace, identifier, conn).await?;
+        }
+
+        queries::new_schema_migration(
+            conn,
+            NewSchemaMigration {
+                schema_name: namespace.to_string(),
+                schema_identifier: identifier.to_string(),
+                from_version: migration.from_version.clone(),
+                to_version: migration.to_version.clone(),
+                statements: migration.statements().to_vec(),
+                forced: force && migration.is_destructive(),
+            },
+        )
+        .await?;
+
+        Ok(Some(migration))
+    }
+
+    /// Create fresh tables for a blue/green deployment of an indexer, alongside
+    /// those of its running version, replacing any left by an earlier one.
+    ///
+    /// The tables aren't recorded in the registry until they're promoted.
+    pub async fn new_shadow_schema(
+        &self,
+        namespace: &str,
+        identifier: &str,
+        schema: &str,
+        conn: &mut IndexerConnection,
+    ) -> IndexerSchemaResult<()> {
+        self.require_schemas("blue/green deployments")?;
+
+        let schema = inject_native_entities_into_schema(schema);
+        let version = schema_version(&schema);
+        let shadow_identifier = format!("{identifier}{SHADOW_IDENTIFIER_SUFFIX}");
+
+        queries::execute_query(
+            conn,
+            format!("DROP SCHEMA IF EXISTS {namespace}_{shadow_identifier} CASCADE"),
+        )
+        .await?;
+
+        SchemaBuilder::new(
+            namespace,
+            &shadow_identifier,
+            &version,
+            self.pool.database_type(),
+        )
+        .build(&schema)?
+        .create_tables(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace an indexer's tables with those of its blue/green deployment, and
+    /// register them in their place.
+    ///
+    /// Readers of the indexer's tables are locked out until the open
+    /// transaction commits, so they see either version, but never both.
+    pub async fn promote_shadow_schema(
+        &self,
+        namespace: &str,
+        identifier: &str,
+        schema: &str,
+        conn: &mut IndexerConnection,
+    ) -> IndexerSchemaResult<()> {
+        self.require_schemas("blue/green deployments")?;
+
+        let schema = inject_native_entities_into_schema(schema);
+        let version = schema_version(&schema);
+
+        queries::lock_indexer(conn, namespace, identifier, true).await?;
+
+        for statement in [
+            format!("DROP SCHEMA IF EXISTS {namespace}_{identifier} CASCADE"),
+            format!(
+                "ALTER SCHEMA {namespace}_{identifier}{SHADOW_IDENTIFIER_SUFFIX} RENAME TO {namespace}_{identifier}"
+            ),
+        ] {
+            queries::execute_query(conn, statement).await?;
+        }
+
+        queries::remove_schema_metadata(conn, namespace, identifier, &version).await?;
+        let _db_schema = SchemaBuilder::new(
+            namespace,
+            identifier,
+            &version,
+            self.pool.database_type(),
+        )
+        .build(&schema)?
+        .register_metadata(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove everything an indexer has indexed into the tables of its latest
+    /// schema, along with its checkpoint and rollback journal, so that it
+    /// indexes the chain again from its start block.
+    pub async fn clear_indexed_data(
+        &self,
+        namespace: &str,
+        identifier: &str,
+        conn: &mut IndexerConnection,
+    ) -> IndexerSchemaResult<()> {
+        let db_type = self.pool.database_type();
+        let root = queries::graph_root_latest(conn, namespace, identifier).await?;
+        let type_ids =
+            queries::type_id_list_by_name(conn, namespace, &root.version, identifier)
+                .await?;
+
+        for typ in type_ids {
+            let table =
+                db_type.table_name(&format!("{namespace}_{identifier}"), &typ.table_name);
+            queries::execute_query(conn, format!("DELETE FROM {table}")).await?;
+            if typ.versioned {
+                queries::execute_query(
+                    conn,
+                    format!("DELETE FROM {}", history_table_name(&table)),
+                )
+                .await?;
+            }
+        }
+
+        queries::remove_indexer_state(conn, namespace, identifier).await?;
+
         Ok(())
     }
 
@@ -0,0 +1,793 @@
+use crate::{
+    db::{
+        tables::{create_history_table_statement, create_table_statement, SchemaBuilder},
+        IndexerSchemaError, IndexerSchemaResult,
+    },
+    utils::{deserialize, serialize},
+    FtColumn,
+};
+use fuel_indexer_database::{
+    queries,
+    types::{history_table_name, ColumnType, CreateStatement, DbType, NewColumn},
+    IndexerConnection,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Number of entity rows read at a time while their objects are rewritten.
+const REWRITE_BATCH_SIZE: i64 = 1000;
+
+/// A change to an indexer's tables between two versions of its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// A new entity, stored in a new table.
+    CreateTable {
+        table: String,
+    },
+    /// A removed entity. Its table and rows are dropped.
+    DropTable {
+        table: String,
+    },
+    /// A new field, stored in a new column. Existing rows can't have a value
+    /// for a field that isn't nullable, so they're removed.
+    AddColumn {
+        table: String,
+        column: String,
+        nullable: bool,
+    },
+    /// A removed field. Its column and values are dropped.
+    DropColumn {
+        table: String,
+        column: String,
+    },
+    /// A field whose type changed, or that became required or unique. Its
+    /// column is replaced, and the values it held are lost.
+    ReplaceColumn {
+        table: String,
+        column: String,
+    },
+    /// A field that is no longer required or unique.
+    RelaxColumn {
+        table: String,
+        column: String,
+    },
+    CreateIndex {
+        table: String,
+        column: String,
+    },
+    DropIndex {
+        table: String,
+        column: String,
+    },
+}
+
+impl SchemaChange {
+    /// Whether making this change loses data that is already indexed.
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            SchemaChange::DropTable { .. }
+            | SchemaChange::DropColumn { .. }
+            | SchemaChange::ReplaceColumn { .. } => true,
+            SchemaChange::AddColumn { nullable, .. } => !nullable,
+            _ => false,
+        }
+    }
+
+    /// Whether the database can make this change in place. SQLite can only
+    /// add nullable columns to existing tables, and can't change or drop them.
+    pub fn is_supported_by(&self, db_type: &DbType) -> bool {
+        match db_type {
+            DbType::Postgres => true,
+            DbType::Sqlite => match self {
+                SchemaChange::DropColumn { .. }
+                | SchemaChange::ReplaceColumn { .. }
+                | SchemaChange::RelaxColumn { .. } => false,
+                SchemaChange::AddColumn { nullable, .. } => *nullable,
+                _ => true,
+            },
+        }
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::CreateTable { table } => write!(f, "create table {table}"),
+            SchemaChange::DropTable { table } => write!(f, "drop table {table}"),
+            SchemaChange::AddColumn {
+                table,
+                column,
+                nullable,
+            } => match nullable {
+                true => write!(f, "add column {table}.{column}"),
+                false => write!(f, "add required column {table}.{column}"),
+            },
+            SchemaChange::DropColumn { table, column } => {
+                write!(f, "drop column {table}.{column}")
+            }
+            SchemaChange::ReplaceColumn { table, column } => {
+                write!(f, "replace column {table}.{column}")
+            }
+            SchemaChange::RelaxColumn { table, column } => {
+                write!(f, "relax column {table}.{column}")
+            }
+            SchemaChange::CreateIndex { table, column } => {
+                write!(f, "create index on {table}.{column}")
+            }
+            SchemaChange::DropIndex { table, column } => {
+                write!(f, "drop index on {table}.{column}")
+            }
+        }
+    }
+}
+
+/// Where a column of an entity's serialized object comes from after a
+/// migration.
+#[derive(Debug)]
+enum ObjectColumn {
+    /// The column at this position before the migration.
+    Kept(usize),
+    /// A new column, without a value.
+    Null(ColumnType),
+}
+
+/// How the serialized objects of a table's rows are rearranged to match its
+/// new columns, so that handlers can still load them.
+#[derive(Debug)]
+struct ObjectRewrite {
+    table: String,
+    columns: Vec<ObjectColumn>,
+}
+
+/// The changes that bring an indexer's tables from one version of its schema
+/// to another, and the statements that make them.
+#[derive(Debug)]
+pub struct SchemaMigration {
+    pub from_version: String,
+    pub to_version: String,
+    pub changes: Vec<SchemaChange>,
+    statements: Vec<String>,
+    rewrites: Vec<ObjectRewrite>,
+    clears_rows: bool,
+}
+
+/// Columns of a type, in position order, without the object column.
+fn entity_columns(builder: &SchemaBuilder, type_id: i64) -> Vec<&NewColumn> {
+    let mut columns: Vec<_> = builder
+        .columns
+        .iter()
+        .filter(|c| c.type_id == type_id && c.column_type != "Object")
+        .collect();
+    columns.sort_by_key(|c| c.column_position);
+    columns
+}
+
+impl SchemaMigration {
+    /// Compare the tables built for two versions of a schema.
+    pub fn new(from: &SchemaBuilder, to: &SchemaBuilder) -> Self {
+        let namespace = format!("{}_{}", to.namespace, to.identifier);
+        let sql_table = |table: &str| to.db_type.table_name(&namespace, table);
+        let cascade = match to.db_type {
+            DbType::Postgres => " CASCADE",
+            DbType::Sqlite => "",
+        };
+
+        let old_tables: HashSet<i64> = from.type_ids.iter().map(|t| t.id).collect();
+        let old_versioned: HashSet<i64> = from
+            .type_ids
+            .iter()
+            .filter(|t| t.versioned)
+            .map(|t| t.id)
+            .collect();
+        let new_tables: HashSet<i64> = to.type_ids.iter().map(|t| t.id).collect();
+
+        let old_indices: HashMap<(&str, &str), String> = from
+            .indices
+            .iter()
+            .map(|i| {
+                (
+                    (i.table_name.as_str(), i.column_name.as_str()),
+                    i.create_statement(),
+                )
+            })
+            .collect();
+
+        let mut changes = Vec::new();
+        let mut rewrites = Vec::new();
+        let mut clears_rows = false;
+
+        // Statements are grouped so that everything is dropped before anything
+        // is created in its place, and constraints are added last.
+        let mut drop_indices = Vec::new();
+        let mut clear_tables = Vec::new();
+        let mut drop_columns = Vec::new();
+        let mut drop_tables = Vec::new();
+        let mut create_tables = Vec::new();
+        let mut add_columns = Vec::new();
+        let mut relax_columns = Vec::new();
+        let mut add_foreign_keys = Vec::new();
+        let mut create_indices = Vec::new();
+
+        // Columns that were added or replaced, which need their foreign keys
+        // and indices created.
+        let mut new_columns: HashSet<(String, String)> = HashSet::new();
+
+        for typ in from.type_ids.iter().filter(|t| !new_tables.contains(&t.id)) {
+            let table = sql_table(&typ.table_name);
+            drop_tables.push(format!("DROP TABLE IF EXISTS {table}{cascade}"));
+            if typ.versioned {
+                drop_tables.push(format!(
+                    "DROP TABLE IF EXISTS {}",
+                    history_table_name(&table)
+                ));
+            }
+            changes.push(SchemaChange::DropTable { table });
+        }
+
+        for typ in &to.type_ids {
+            let table = sql_table(&typ.table_name);
+
+            let mut columns: Vec<_> =
+                to.columns.iter().filter(|c| c.type_id == typ.id).collect();
+            columns.sort_by_key(|c| c.column_position);
+
+            if !old_tables.contains(&typ.id) {
+                let fragments = columns
+                    .iter()
+                    .map(|c| to.column_sql(&typ.table_name, c))
+                    .collect::<Vec<_>>()
+                    .join(",\n");
+
+                create_tables.push(create_table_statement(&table, &fragments));
+                if typ.versioned {
+                    create_tables.push(create_history_table_statement(
+                        &table,
+                        &columns,
+                        &to.db_type,
+                    ));
+                }
+                for column in entity_columns(to, typ.id) {
+                    new_columns
+                        .insert((typ.table_name.clone(), column.column_name.clone()));
+                }
+                changes.push(SchemaChange::CreateTable { table });
+                continue;
+            }
+
+            // The history of a table that stays versioned follows its columns,
+            // while a table that starts or stops keeping history gains or loses
+            // its history table.
+            let history = history_table_name(&table);
+            let keeps_history = match (old_versioned.contains(&typ.id), typ.versioned) {
+                (true, true) => true,
+                (false, true) => {
+                    create_tables.push(create_history_table_statement(
+                        &table,
+                        &columns,
+                        &to.db_type,
+                    ));
+                    changes.push(SchemaChange::CreateTable {
+                        table: history.clone(),
+                    });
+                    false
+                }
+                (true, false) => {
+                    drop_tables.push(format!("DROP TABLE IF EXISTS {history}"));
+                    changes.push(SchemaChange::DropTable {
+                        table: history.clone(),
+                    });
+                    false
+                }
+                (false, false) => false,
+            };
+
+            let old_columns = entity_columns(from, typ.id);
+            let mut object_columns = Vec::new();
+            let mut cleared = false;
+
+            for column in entity_columns(to, typ.id) {
+                let name = column.column_name.clone();
+                let old = old_columns
+                    .iter()
+                    .position(|c| c.column_name == column.column_name);
+
+                let old = match old {
+                    Some(position) => (position, old_columns[position]),
+                    None => {
+                        add_columns.push(format!(
+                            "ALTER TABLE {table} ADD COLUMN {}",
+                            to.column_sql(&typ.table_name, column)
+                        ));
+                        if keeps_history {
+                            add_columns.push(format!(
+                                "ALTER TABLE {history} ADD COLUMN {}",
+                                column.history_sql_fragment(&to.db_type)
+                            ));
+                        }
+                        cleared |= !column.nullable;
+                        new_columns.insert((typ.table_name.clone(), name.clone()));
+                        object_columns.push(ObjectColumn::Null(ColumnType::from(
+                            column.column_type.as_str(),
+                        )));
+                        changes.push(SchemaChange::AddColumn {
+                            table: table.clone(),
+                            column: name,
+                            nullable: column.nullable,
+                        });
+                        continue;
+                    }
+                };
+
+                let (position, old) = old;
+                let same_type = old.column_type == column.column_type
+                    && old.graphql_type == column.graphql_type;
+
+                if same_type
+                    && old.nullable == column.nullable
+                    && old.unique == column.unique
+                {
+                    object_columns.push(ObjectColumn::Kept(position));
+                } else if same_type
+                    && (column.nullable || !old.nullable)
+                    && (old.unique || !column.unique)
+                {
+                    if column.nullable && !old.nullable {
+                        relax_columns.push(format!(
+                            "ALTER TABLE {table} ALTER COLUMN {name} DROP NOT NULL"
+                        ));
+                    }
+                    if old.unique && !column.unique {
+                        relax_columns.push(format!(
+                            "ALTER TABLE {table} DROP CONSTRAINT IF EXISTS {}_{name}_key",
+                            typ.table_name
+                        ));
+                    }
+                    object_columns.push(ObjectColumn::Kept(position));
+                    changes.push(SchemaChange::RelaxColumn {
+                        table: table.clone(),
+                        column: name,
+                    });
+                } else {
+                    drop_columns.push(format!(
+                        "ALTER TABLE {table} DROP COLUMN IF EXISTS {name} CASCADE"
+                    ));
+                    add_columns.push(format!(
+                        "ALTER TABLE {table} ADD COLUMN {}",
+                        to.column_sql(&typ.table_name, column)
+                    ));
+                    if keeps_history {
+                        drop_columns.push(format!(
+                            "ALTER TABLE {history} DROP COLUMN IF EXISTS {name}"
+                        ));
+                        add_columns.push(format!(
+                            "ALTER TABLE {history} ADD COLUMN {}",
+                            column.history_sql_fragment(&to.db_type)
+                        ));
+                    }
+                    cleared |= !column.nullable;
+                    new_columns.insert((typ.table_name.clone(), name.clone()));
+                    object_columns.push(ObjectColumn::Null(ColumnType::from(
+                        column.column_type.as_str(),
+                    )));
+                    changes.push(SchemaChange::ReplaceColumn {
+                        table: table.clone(),
+                        column: name,
+                    });
+                }
+            }
+
+            for old in &old_columns {
+                if !entity_columns(to, typ.id)
+                    .iter()
+                    .any(|c| c.column_name == old.column_name)
+                {
+                    drop_columns.push(format!(
+                        "ALTER TABLE {table} DROP COLUMN IF EXISTS {} CASCADE",
+                        old.column_name
+                    ));
+                    if keeps_history {
+                        drop_columns.push(format!(
+                            "ALTER TABLE {history} DROP COLUMN IF EXISTS {}",
+                            old.column_name
+                        ));
+                    }
+                    changes.push(SchemaChange::DropColumn {
+                        table: table.clone(),
+                        column: old.column_name.clone(),
+                    });
+                }
+            }
+
+            let unchanged = object_columns.len() == old_columns.len()
+                && object_columns
+                    .iter()
+                    .enumerate()
+                    .all(|(i, c)| matches!(c, ObjectColumn::Kept(p) if *p == i));
+
+            if cleared {
+                clears_rows = true;
+                clear_tables.push(format!("DELETE FROM {table}"));
+                if keeps_history {
+                    clear_tables.push(format!("DELETE FROM {history}"));
+                }
+            } else if !unchanged {
+                rewrites.push(ObjectRewrite {
+                    table: table.clone(),
+                    columns: object_columns,
+                });
+            }
+        }
+
+        // SQLite foreign keys are declared with the new columns instead.
+        for fk in to
+            .foreign_keys
+            .iter()
+            .filter(|fk| fk.db_type == DbType::Postgres)
+        {
+            if new_columns.contains(&(fk.table_name.clone(), fk.column_name.clone())) {
+                add_foreign_keys.push(fk.create_statement());
+            }
+        }
+
+        let new_indices: HashMap<(&str, &str), String> = to
+            .indices
+            .iter()
+            .map(|i| {
+                (
+                    (i.table_name.as_str(), i.column_name.as_str()),
+                    i.create_statement(),
+                )
+            })
+            .collect();
+
+        // Indices of new and dropped tables are created and dropped with them.
+        let retained: HashSet<&str> = to
+            .type_ids
+            .iter()
+            .filter(|t| old_tables.contains(&t.id))
+            .map(|t| t.table_name.as_str())
+            .collect();
+
+        for index in &from.indices {
+            let key = (index.table_name.as_str(), index.column_name.as_str());
+            let changed = new_indices.get(&key) != old_indices.get(&key);
+            let replaced = new_columns
+                .contains(&(index.table_name.clone(), index.column_name.clone()));
+
+            if retained.contains(key.0) && (changed || replaced) {
+                drop_indices.push(format!(
+                    "DROP INDEX IF EXISTS {}",
+                    index.db_type.table_name(&index.namespace, &index.name())
+                ));
+                if changed {
+                    changes.push(SchemaChange::DropIndex {
+                        table: sql_table(&index.table_name),
+                        column: index.column_name.clone(),
+                    });
+                }
+            }
+        }
+
+        for index in &to.indices {
+            let key = (index.table_name.as_str(), index.column_name.as_str());
+            let changed = new_indices.get(&key) != old_indices.get(&key);
+            let replaced = new_columns
+                .contains(&(index.table_name.clone(), index.column_name.clone()));
+
+            if changed || replaced {
+                create_indices.push(index.create_statement());
+                if changed && retained.contains(key.0) {
+                    changes.push(SchemaChange::CreateIndex {
+                        table: sql_table(&index.table_name),
+                        column: index.column_name.clone(),
+                    });
+                }
+            }
+        }
+
+        let statements = [
+            drop_indices,
+            clear_tables,
+            drop_columns,
+            drop_tables,
+            create_tables,
+            add_columns,
+            relax_columns,
+            add_foreign_keys,
+            create_indices,
+        ]
+        .concat();
+
+        SchemaMigration {
+            from_version: from.version.clone(),
+            to_version: to.version.clone(),
+            changes,
+            statements,
+            rewrites,
+            clears_rows,
+        }
+    }
+
+    /// Whether any of the changes loses data that is already indexed.
+    pub fn is_destructive(&self) -> bool {
+        self.changes.iter().any(|c| c.is_destructive())
+    }
+
+    /// Whether the rows of any table are removed, so that the indexer has to
+    /// index the chain again to fill them.
+    pub fn clears_rows(&self) -> bool {
+        self.clears_rows
+    }
+
+    pub fn statements(&self) -> &[String] {
+        &self.statements
+    }
+
+    /// Run the migration's statements, then rewrite the serialized objects of
+    /// tables whose columns changed.
+    pub async fn apply(&self, conn: &mut IndexerConnection) -> IndexerSchemaResult<()> {
+        for statement in &self.statements {
+            queries::execute_query(conn, statement.to_owned()).await?;
+        }
+
+        for rewrite in &self.rewrites {
+            let mut after = None;
+
+            loop {
+                let objects = queries::list_objects(
+                    conn,
+                    &rewrite.table,
+                    after,
+                    REWRITE_BATCH_SIZE,
+                )
+                .await?;
+
+                let last = match objects.last() {
+                    Some((id, _)) => *id,
+                    None => break,
+                };
+
+                for (id, bytes) in objects {
+                    let old: Vec<FtColumn> = deserialize(&bytes)
+                        .map_err(IndexerSchemaError::MigrationError)?;
+
+                    let new = rewrite
+                        .columns
+                        .iter()
+                        .map(|column| match column {
+                            ObjectColumn::Kept(position) => {
+                                old.get(*position).cloned().ok_or_else(|| {
+                                    IndexerSchemaError::MigrationError(format!(
+                                        "Object {id} in {} is missing column {position}.",
+                                        rewrite.table
+                                    ))
+                                })
+                            }
+                            ObjectColumn::Null(ty) => Ok(FtColumn::null(*ty)),
+                        })
+                        .collect::<IndexerSchemaResult<Vec<_>>>()?;
+
+                    queries::set_object_bytes(conn, &rewrite.table, id, serialize(&new))
+                        .await?;
+                }
+
+                after = Some(last);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fuel_indexer_database::DbType;
+
+    const SCHEMA: &str = r#"
+        schema {
+            query: QueryRoot
+        }
+
+        type QueryRoot {
+            thing1: Thing1
+        }
+
+        type Thing1 {
+            id: ID!
+            account: Address!
+        }
+    "#;
+
+    fn builder(version: &str, schema: &str) -> SchemaBuilder {
+        SchemaBuilder::new("test_namespace", "index1", version, DbType::Postgres)
+            .build(schema)
+            .expect("Bad schema")
+    }
+
+    #[test]
+    fn test_schema_migration_adds_tables_and_nullable_columns() {
+        let new_schema = r#"
+        schema {
+            query: QueryRoot
+        }
+
+        type QueryRoot {
+            thing1: Thing1
+            thing2: Thing2
+        }
+
+        type Thing1 {
+            id: ID!
+            account: Address!
+            memo: Charfield
+        }
+
+        type Thing2 {
+            id: ID!
+            hash: Bytes32!
+        }
+    "#;
+
+        let migration =
+            SchemaMigration::new(&builder("v1", SCHEMA), &builder("v2", new_schema));
+
+        assert!(!migration.is_destructive());
+        assert!(!migration.clears_rows());
+        assert_eq!(
+            migration.changes,
+            vec![
+                SchemaChange::AddColumn {
+                    table: "test_namespace_index1.thing1".to_string(),
+                    column: "memo".to_string(),
+                    nullable: true,
+                },
+                SchemaChange::CreateTable {
+                    table: "test_namespace_index1.thing2".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            migration.statements(),
+            &[
+                concat!(
+                    "CREATE TABLE IF NOT EXISTS\n",
+                    " test_namespace_index1.thing2 (\n",
+                    " id numeric(20, 0) primary key not null,\n",
+                    "hash varchar(64) not null,\n",
+                    "object bytea not null\n",
+                    ")"
+                )
+                .to_string(),
+                "ALTER TABLE test_namespace_index1.thing1 ADD COLUMN memo varchar(255)"
+                    .to_string(),
+            ]
+        );
+        assert_eq!(migration.rewrites.len(), 1);
+    }
+
+    #[test]
+    fn test_schema_migration_detects_destructive_changes() {
+        let new_schema = r#"
+        schema {
+            query: QueryRoot
+        }
+
+        type QueryRoot {
+            thing1: Thing1
+        }
+
+        type Thing1 {
+            id: ID!
+            hash: Bytes32!
+        }
+    "#;
+
+        let migration =
+            SchemaMigration::new(&builder("v1", SCHEMA), &builder("v2", new_schema));
+
+        assert!(migration.is_destructive());
+        assert!(migration.clears_rows());
+        assert_eq!(
+            migration.statements(),
+            &[
+                "DELETE FROM test_namespace_index1.thing1",
+                "ALTER TABLE test_namespace_index1.thing1 DROP COLUMN IF EXISTS account CASCADE",
+                "ALTER TABLE test_namespace_index1.thing1 ADD COLUMN hash varchar(64) not null",
+            ]
+        );
+        assert!(migration.rewrites.is_empty());
+    }
+
+    #[test]
+    fn test_schema_migration_creates_history_of_newly_versioned_types() {
+        let new_schema = r#"
+        schema {
+            query: QueryRoot
+        }
+
+        type QueryRoot {
+            thing1: Thing1
+        }
+
+        type Thing1 @versioned {
+            id: ID!
+            account: Address!
+            memo: Charfield
+        }
+    "#;
+
+        let migration =
+            SchemaMigration::new(&builder("v1", SCHEMA), &builder("v2", new_schema));
+
+        assert!(!migration.is_destructive());
+        assert_eq!(
+            migration.changes[0],
+            SchemaChange::CreateTable {
+                table: "test_namespace_index1.thing1_history".to_string(),
+            }
+        );
+
+        let migration = SchemaMigration::new(
+            &builder("v2", new_schema),
+            &builder("v3", &new_schema.replace("memo: Charfield", "")),
+        );
+
+        assert!(migration.is_destructive());
+        assert_eq!(
+            migration.statements(),
+            &[
+                "ALTER TABLE test_namespace_index1.thing1 DROP COLUMN IF EXISTS memo CASCADE",
+                "ALTER TABLE test_namespace_index1.thing1_history DROP COLUMN IF EXISTS memo",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_migration_of_unchanged_schema_is_empty() {
+        let migration =
+            SchemaMigration::new(&builder("v1", SCHEMA), &builder("v1", SCHEMA));
+
+        assert!(migration.changes.is_empty());
+        assert!(migration.statements().is_empty());
+    }
+
+    #[test]
+    fn test_schema_migration_on_sqlite_adds_columns_with_their_foreign_keys() {
+        let sqlite_builder = |version: &str, schema: &str| {
+            SchemaBuilder::new("test_namespace", "index1", version, DbType::Sqlite)
+                .build(schema)
+                .expect("Bad schema")
+        };
+        let new_schema = SCHEMA
+            .replace("thing1: Thing1", "thing1: Thing1\n thing2: Thing2")
+            .replace(
+                "account: Address!",
+                "account: Address!\n thing2: Thing2\n }\n type Thing2 {\n id: ID!",
+            );
+
+        let migration = SchemaMigration::new(
+            &sqlite_builder("v1", SCHEMA),
+            &sqlite_builder("v2", &new_schema),
+        );
+
+        assert!(migration
+            .changes
+            .iter()
+            .all(|c| c.is_supported_by(&DbType::Sqlite)));
+        assert_eq!(
+            migration.statements()[1],
+            "ALTER TABLE test_namespace_index1__thing1 ADD COLUMN thing2 numeric REFERENCES test_namespace_index1__thing2(id) ON DELETE NO ACTION ON UPDATE NO ACTION DEFERRABLE INITIALLY DEFERRED"
+        );
+
+        let migration = SchemaMigration::new(
+            &sqlite_builder("v2", &new_schema),
+            &sqlite_builder("v3", SCHEMA),
+        );
+
+        assert!(!migration
+            .changes
+            .iter()
+            .all(|c| c.is_supported_by(&DbType::Sqlite)));
+    }
+}
@@ -1,7 +1,11 @@
-use crate::utils::{
-    build_schema_fields_and_types_map, build_schema_objects_set, field_type_table_name,
-    get_index_directive, get_join_directive_info, get_unique_directive,
-    normalize_field_type_name, BASE_SCHEMA,
+use crate::{
+    db::{IndexerSchemaError, IndexerSchemaResult},
+    utils::{
+        build_schema_fields_and_types_map, build_schema_objects_set,
+        field_type_table_name, get_index_directive, get_join_directive_info,
+        get_unique_directive, is_versioned, normalize_field_type_name, type_name,
+        BASE_SCHEMA, JOIN_DIRECTIVE_NAME,
+    },
 };
 use fuel_indexer_database::{
     queries,
@@ -17,15 +21,15 @@ use std::collections::{HashMap, HashSet};
 
 #[derive(Default)]
 pub struct SchemaBuilder {
-    db_type: DbType,
+    pub(crate) db_type: DbType,
     statements: Vec<String>,
-    type_ids: Vec<TypeId>,
-    columns: Vec<NewColumn>,
-    foreign_keys: Vec<ForeignKey>,
-    indices: Vec<ColumnIndex>,
-    namespace: String,
-    identifier: String,
-    version: String,
+    pub(crate) type_ids: Vec<TypeId>,
+    pub(crate) columns: Vec<NewColumn>,
+    pub(crate) foreign_keys: Vec<ForeignKey>,
+    pub(crate) indices: Vec<ColumnIndex>,
+    pub(crate) namespace: String,
+    pub(crate) identifier: String,
+    pub(crate) version: String,
     schema: String,
     types: HashSet<String>,
     fields: HashMap<String, HashMap<String, String>>,
@@ -59,7 +63,7 @@ impl SchemaBuilder {
         }
     }
 
-    pub fn build(mut self, schema: &str) -> Self {
+    pub fn build(mut self, schema: &str) -> IndexerSchemaResult<Self> {
         if DbType::Postgres == self.db_type {
             let create = format!(
                 "CREATE SCHEMA IF NOT EXISTS {}_{}",
@@ -68,10 +72,9 @@ impl SchemaBuilder {
             self.statements.push(create);
         }
 
-        let ast = match parse_schema::<String>(schema) {
-            Ok(ast) => ast,
-            Err(e) => panic!("Error parsing graphql schema {e:?}",),
-        };
+        let ast = parse_schema::<String>(schema).map_err(|e| {
+            IndexerSchemaError::InvalidSchema(format!("Could not parse schema: {e}"))
+        })?;
 
         let query = ast
             .definitions
@@ -86,8 +89,11 @@ impl SchemaBuilder {
             })
             .next();
 
-        // TODO: Add error enum here
-        let query = query.cloned().expect("TODO: this needs to be error type");
+        let query = query.cloned().ok_or_else(|| {
+            IndexerSchemaError::InvalidSchema("No query root is defined.".to_string())
+        })?;
+
+        self.check_definitions(&ast, &query)?;
 
         let types_map = build_schema_fields_and_types_map(&ast);
 
@@ -100,21 +106,153 @@ impl SchemaBuilder {
         self.query = query;
         self.schema = schema.to_string();
 
-        self
+        Ok(self)
+    }
+
+    /// Check that tables can be generated for every type of the schema, which
+    /// otherwise fails part way through.
+    fn check_definitions(
+        &self,
+        ast: &Document<String>,
+        query: &str,
+    ) -> IndexerSchemaResult<()> {
+        let invalid = |details: String| Err(IndexerSchemaError::InvalidSchema(details));
+
+        let mut objects = Vec::new();
+        for def in ast.definitions.iter() {
+            if let Definition::TypeDefinition(typ) = def {
+                match typ {
+                    TypeDefinition::Object(o) => objects.push(o),
+                    o => {
+                        return invalid(format!(
+                            "'{}' is not an object type",
+                            type_name(o)
+                        ))
+                    }
+                }
+            }
+        }
+
+        let fields: HashSet<String> = objects
+            .iter()
+            .flat_map(|o| o.fields.iter().map(|f| format!("{}.{}", o.name, f.name)))
+            .collect();
+
+        for obj in objects.iter().filter(|o| o.name != query) {
+            for field in obj.fields.iter() {
+                let mut field_type = &field.field_type;
+                let name = loop {
+                    match field_type {
+                        Type::NamedType(t) => break t,
+                        Type::NonNullType(t) => field_type = t,
+                        Type::ListType(_) => {
+                            return invalid(format!(
+                                "List types are not supported: '{}.{}'",
+                                obj.name, field.name
+                            ))
+                        }
+                    }
+                };
+
+                if self.primitives.contains(name.as_str()) {
+                    if ColumnType::from_name(name).is_none() {
+                        return invalid(format!(
+                            "'{name}' can't be stored: '{}.{}'",
+                            obj.name, field.name
+                        ));
+                    }
+                    continue;
+                }
+
+                let reference_field_name = match field.directives.as_slice() {
+                    [directive] => match directive.arguments.last() {
+                        Some((_, name)) if directive.name == JOIN_DIRECTIVE_NAME => {
+                            name.to_string()
+                        }
+                        _ => {
+                            return invalid(format!(
+                                "Foreign key '{}.{}' only takes a @join directive",
+                                obj.name, field.name
+                            ))
+                        }
+                    },
+                    _ => IdCol::to_lowercase_string(),
+                };
+
+                if !fields.contains(&format!("{name}.{reference_field_name}")) {
+                    return invalid(format!(
+                        "Foreign key field '{name}.{reference_field_name}' is not defined in the schema"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
     }
 
     pub async fn commit_metadata(
         self,
         conn: &mut IndexerConnection,
+    ) -> sqlx::Result<Schema> {
+        self.create_tables(conn).await?;
+        self.register_metadata(conn).await
+    }
+
+    /// Create the schema's tables, with their foreign keys and indices,
+    /// without recording them in the registry.
+    pub async fn create_tables(&self, conn: &mut IndexerConnection) -> sqlx::Result<()> {
+        for query in &self.statements {
+            queries::execute_query(conn, query.to_owned()).await?;
+        }
+
+        // SQLite foreign keys are declared with their columns.
+        if self.db_type == DbType::Postgres {
+            for fk in &self.foreign_keys {
+                queries::execute_query(conn, fk.create_statement()).await?;
+            }
+        }
+
+        for idx in &self.indices {
+            queries::execute_query(conn, idx.create_statement()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Columns of the schema's tables, ordered as they would be listed from
+    /// the registry once the schema is committed.
+    pub fn column_info(&self) -> Vec<ColumnInfo> {
+        let tables: HashMap<i64, &TypeId> =
+            self.type_ids.iter().map(|t| (t.id, t)).collect();
+
+        let mut columns: Vec<_> = self
+            .columns
+            .iter()
+            .map(|c| ColumnInfo {
+                type_id: c.type_id,
+                table_name: tables[&c.type_id].table_name.clone(),
+                column_position: c.column_position,
+                column_name: c.column_name.clone(),
+                column_type: c.column_type.clone(),
+                versioned: tables[&c.type_id].versioned,
+            })
+            .collect();
+        columns.sort_by_key(|c| (c.type_id, c.column_position));
+
+        columns
+    }
+
+    /// Record the schema's graph root, types and columns in the registry,
+    /// without touching its tables.
+    pub(crate) async fn register_metadata(
+        self,
+        conn: &mut IndexerConnection,
     ) -> sqlx::Result<Schema> {
         #[allow(unused_variables)]
         let SchemaBuilder {
             version,
-            statements,
             type_ids,
             columns,
-            foreign_keys,
-            indices,
             namespace,
             identifier,
             types,
@@ -122,7 +260,6 @@ impl SchemaBuilder {
             query,
             query_fields,
             schema,
-            db_type,
             ..
         } = self;
 
@@ -150,17 +287,11 @@ impl SchemaBuilder {
 
         queries::new_root_columns(conn, cols).await?;
 
-        for query in statements {
-            queries::execute_query(conn, query).await?;
-        }
-
-        for fk in foreign_keys {
-            queries::execute_query(conn, fk.create_statement()).await?;
-        }
-
-        for idx in indices {
-            queries::execute_query(conn, idx.create_statement()).await?;
-        }
+        let versioned = type_ids
+            .iter()
+            .filter(|t| t.versioned)
+            .map(|t| t.table_name.clone())
+            .collect();
 
         queries::type_id_insert(conn, type_ids).await?;
         queries::new_column_insert(conn, columns).await?;
@@ -173,6 +304,7 @@ impl SchemaBuilder {
             types,
             fields,
             foreign_keys: HashMap::new(),
+            versioned,
         })
     }
 
@@ -235,9 +367,9 @@ impl SchemaBuilder {
                     unique,
                 };
 
-                fragments.push(column.sql_fragment());
-                self.columns.push(column);
                 self.foreign_keys.push(fk);
+                fragments.push(self.column_sql(table_name, &column));
+                self.columns.push(column);
 
                 continue;
             }
@@ -267,7 +399,7 @@ impl SchemaBuilder {
                 });
             }
 
-            fragments.push(column.sql_fragment());
+            fragments.push(column.sql_fragment(&self.db_type));
             self.columns.push(column);
         }
 
@@ -282,7 +414,7 @@ impl SchemaBuilder {
             unique: false,
         };
 
-        fragments.push(object_column.sql_fragment());
+        fragments.push(object_column.sql_fragment(&self.db_type));
         self.columns.push(object_column);
 
         fragments.join(",\n")
@@ -292,6 +424,22 @@ impl SchemaBuilder {
         format!("{}_{}", self.namespace, self.identifier)
     }
 
+    /// The definition of `column` in `table_name`. SQLite can't add foreign
+    /// keys to existing tables, so there they're declared with the column.
+    pub(crate) fn column_sql(&self, table_name: &str, column: &NewColumn) -> String {
+        let fragment = column.sql_fragment(&self.db_type);
+
+        match self.db_type {
+            DbType::Postgres => fragment,
+            DbType::Sqlite => match self.foreign_keys.iter().find(|fk| {
+                fk.table_name == table_name && fk.column_name == column.column_name
+            }) {
+                Some(fk) => format!("{fragment} {}", fk.column_constraint()),
+                None => fragment,
+            },
+        }
+    }
+
     fn generate_table_sql(
         &mut self,
         root: &str,
@@ -324,10 +472,23 @@ impl SchemaBuilder {
 
                 let sql_table = self.db_type.table_name(&self.namespace(), &table_name);
 
-                let create =
-                    format!("CREATE TABLE IF NOT EXISTS\n {sql_table} (\n {columns}\n)",);
+                self.statements
+                    .push(create_table_statement(&sql_table, &columns));
+
+                let versioned = is_versioned(o);
+                if versioned {
+                    let columns: Vec<_> = self
+                        .columns
+                        .iter()
+                        .filter(|c| c.type_id == type_id)
+                        .collect();
+                    self.statements.push(create_history_table_statement(
+                        &sql_table,
+                        &columns,
+                        &self.db_type,
+                    ));
+                }
 
-                self.statements.push(create);
                 self.type_ids.push(TypeId {
                     id: type_id,
                     schema_version: self.version.to_string(),
@@ -335,12 +496,36 @@ impl SchemaBuilder {
                     schema_identifier: self.identifier.to_string(),
                     graphql_name: o.name.to_string(),
                     table_name,
+                    versioned,
                 });
             }
             o => panic!("Got a non-object type: '{o:?}'"),
         }
     }
 }
+
+pub(crate) fn create_table_statement(sql_table: &str, columns: &str) -> String {
+    format!("CREATE TABLE IF NOT EXISTS\n {sql_table} (\n {columns}\n)",)
+}
+
+/// The table keeping one row per version of each entity in `sql_table`, valid
+/// from the block height it was written at until the next one.
+pub(crate) fn create_history_table_statement(
+    sql_table: &str,
+    columns: &[&NewColumn],
+    db_type: &DbType,
+) -> String {
+    let mut fragments: Vec<_> = columns
+        .iter()
+        .map(|c| c.history_sql_fragment(db_type))
+        .collect();
+    fragments.push("valid_from numeric(20, 0) not null".to_string());
+    fragments.push("valid_to numeric(20, 0)".to_string());
+    fragments.push("primary key (id, valid_from)".to_string());
+
+    create_table_statement(&history_table_name(sql_table), &fragments.join(",\n"))
+}
+
 #[derive(Debug)]
 pub struct Schema {
     pub version: String,
@@ -350,6 +535,8 @@ pub struct Schema {
     pub types: HashSet<String>,
     pub fields: HashMap<String, HashMap<String, String>>,
     pub foreign_keys: HashMap<String, HashMap<String, (String, String)>>,
+    /// Tables keeping the history of their entities.
+    pub versioned: HashSet<String>,
 }
 
 impl Schema {
@@ -371,6 +558,7 @@ impl Schema {
 
         let mut types = HashSet::new();
         let mut fields = HashMap::new();
+        let mut versioned = HashSet::new();
 
         types.insert(root.query.clone());
         fields.insert(
@@ -382,6 +570,9 @@ impl Schema {
         );
         for tid in typeids {
             types.insert(tid.graphql_name.clone());
+            if tid.versioned {
+                versioned.insert(tid.table_name.clone());
+            }
 
             let columns = queries::list_column_by_id(&mut conn, tid.id).await?;
             fields.insert(
@@ -403,6 +594,7 @@ impl Schema {
             types,
             fields,
             foreign_keys,
+            versioned,
         })
     }
 
@@ -410,6 +602,12 @@ impl Schema {
         self.types.contains(type_name)
     }
 
+    /// Whether the entities of a type keep their history.
+    pub fn is_versioned(&self, type_name: &str) -> bool {
+        self.versioned
+            .contains(&normalize_field_type_name(type_name).to_lowercase())
+    }
+
     pub fn field_type(&self, cond: &str, name: &str) -> Option<&String> {
         match self.fields.get(cond) {
             Some(fieldset) => fieldset.get(name),
@@ -422,6 +620,32 @@ impl Schema {
             }
         }
     }
+
+    /// Whether the values of a field are unsigned 64-bit integers: `ID`s,
+    /// `UInt8`s, and references to other types joined on either. Types can
+    /// be named by their table names.
+    pub fn has_u64_values(&self, cond: &str, name: &str) -> bool {
+        let table = normalize_field_type_name(cond).to_lowercase();
+        let field_type = |table: &str, name: &str| {
+            self.fields
+                .iter()
+                .find(|(ty, _)| ty.to_lowercase() == table)
+                .and_then(|(_, fields)| fields.get(name))
+                .map(|ty| normalize_field_type_name(ty))
+        };
+        let is_u64 = |ty: &str| matches!(ty, "ID" | "UInt8");
+
+        match field_type(&table, name) {
+            Some(ty) if is_u64(&ty) => true,
+            Some(_) => self
+                .foreign_keys
+                .get(&table)
+                .and_then(|fields| fields.get(name))
+                .and_then(|(table, column)| field_type(table, column))
+                .map_or(false, |ty| is_u64(&ty)),
+            None => false,
+        }
+    }
 }
 
 fn get_foreign_keys(schema: &str) -> HashMap<String, HashMap<String, (String, String)>> {
@@ -564,7 +788,8 @@ mod tests {
             DbType::Postgres,
         );
 
-        let SchemaBuilder { statements, .. } = sb.build(graphql_schema);
+        let SchemaBuilder { statements, .. } =
+            sb.build(graphql_schema).expect("Bad schema");
 
         assert_eq!(statements[0], create_schema);
         assert_eq!(statements[1], create_thing1_schmea);
@@ -622,13 +847,63 @@ mod tests {
             DbType::Postgres,
         );
 
-        let SchemaBuilder { statements, .. } = sb.build(graphql_schema);
+        let SchemaBuilder { statements, .. } =
+            sb.build(graphql_schema).expect("Bad schema");
 
         assert_eq!(statements[0], create_schema);
         assert_eq!(statements[1], create_thing1_schmea);
         assert_eq!(statements[2], create_thing2_schema);
     }
 
+    #[test]
+    fn test_schema_builder_for_postgres_versioned_type_returns_history_create_sql() {
+        let graphql_schema: &str = r#"
+        schema {
+            query: QueryRoot
+        }
+
+        type QueryRoot {
+            balance: Balance
+        }
+
+        type Balance @versioned {
+            id: ID!
+            owner: Address! @indexed
+            amount: UInt8!
+        }
+    "#;
+
+        let create_balance_history_schema: &str = concat!(
+            "CREATE TABLE IF NOT EXISTS\n",
+            " test_namespace_index1.balance_history (\n",
+            " id numeric(20, 0) not null,\n",
+            "owner varchar(64),\n",
+            "amount numeric(20, 0),\n",
+            "object bytea,\n",
+            "valid_from numeric(20, 0) not null,\n",
+            "valid_to numeric(20, 0),\n",
+            "primary key (id, valid_from)\n",
+            ")"
+        );
+
+        let sb = SchemaBuilder::new(
+            "test_namespace",
+            "index1",
+            "a_version_string",
+            DbType::Postgres,
+        );
+
+        let SchemaBuilder {
+            statements,
+            type_ids,
+            ..
+        } = sb.build(graphql_schema).expect("Bad schema");
+
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[2], create_balance_history_schema);
+        assert!(type_ids[0].versioned);
+    }
+
     #[test]
     fn test_schema_builder_for_postgres_indices_returns_proper_create_sql() {
         let graphql_schema: &str = r#"
@@ -655,7 +930,7 @@ mod tests {
 
         let sb = SchemaBuilder::new("namespace", "index1", "v1", DbType::Postgres);
 
-        let SchemaBuilder { indices, .. } = sb.build(graphql_schema);
+        let SchemaBuilder { indices, .. } = sb.build(graphql_schema).expect("Bad schema");
 
         assert_eq!(indices.len(), 2);
         assert_eq!(
@@ -705,13 +980,61 @@ mod tests {
 
         let sb = SchemaBuilder::new("namespace", "index1", "v1", DbType::Postgres);
 
-        let SchemaBuilder { foreign_keys, .. } = sb.build(graphql_schema);
+        let SchemaBuilder { foreign_keys, .. } =
+            sb.build(graphql_schema).expect("Bad schema");
 
         assert_eq!(foreign_keys.len(), 2);
         assert_eq!(foreign_keys[0].create_statement(), "ALTER TABLE namespace_index1.lender ADD CONSTRAINT fk_lender_borrower__borrower_id FOREIGN KEY (borrower) REFERENCES namespace_index1.borrower(id) ON DELETE NO ACTION ON UPDATE NO ACTION INITIALLY DEFERRED;".to_string());
         assert_eq!(foreign_keys[1].create_statement(), "ALTER TABLE namespace_index1.auditor ADD CONSTRAINT fk_auditor_borrower__borrower_id FOREIGN KEY (borrower) REFERENCES namespace_index1.borrower(id) ON DELETE NO ACTION ON UPDATE NO ACTION INITIALLY DEFERRED;".to_string());
     }
 
+    #[test]
+    fn test_schema_builder_for_sqlite_schema_returns_proper_create_sql() {
+        let graphql_schema: &str = r#"
+        schema {
+            query: QueryRoot
+        }
+
+        type QueryRoot {
+            borrower: Borrower
+            lender: Lender
+        }
+
+        type Borrower {
+            id: ID!
+            account: Address! @indexed
+        }
+
+        type Lender @versioned {
+            id: ID!
+            amount: UInt8!
+            borrower: Borrower!
+        }
+    "#;
+
+        let SchemaBuilder {
+            statements,
+            indices,
+            ..
+        } = SchemaBuilder::new("namespace", "index1", "v1", DbType::Sqlite)
+            .build(graphql_schema)
+            .expect("Bad schema");
+
+        // No schema is created, and foreign keys are declared with their columns.
+        assert_eq!(
+            statements,
+            vec![
+                "CREATE TABLE IF NOT EXISTS\n namespace_index1__borrower (\n id numeric primary key not null,\naccount text not null,\nobject blob not null\n)".to_string(),
+                "CREATE TABLE IF NOT EXISTS\n namespace_index1__lender (\n id numeric primary key not null,\namount numeric not null,\nborrower numeric not null REFERENCES namespace_index1__borrower(id) ON DELETE NO ACTION ON UPDATE NO ACTION DEFERRABLE INITIALLY DEFERRED,\nobject blob not null\n)".to_string(),
+                "CREATE TABLE IF NOT EXISTS\n namespace_index1__lender_history (\n id numeric not null,\namount numeric,\nborrower numeric,\nobject blob,\nvalid_from numeric(20, 0) not null,\nvalid_to numeric(20, 0),\nprimary key (id, valid_from)\n)".to_string(),
+            ]
+        );
+        assert_eq!(
+            indices[0].create_statement(),
+            "CREATE INDEX namespace_index1__borrower_account_idx ON namespace_index1__borrower (account);"
+        );
+    }
+
     #[test]
     fn test_schema_builder_for_postgres_foreign_keys_with_directive_returns_proper_create_sql(
     ) {
@@ -745,7 +1068,8 @@ mod tests {
 
         let sb = SchemaBuilder::new("namespace", "index1", "v1", DbType::Postgres);
 
-        let SchemaBuilder { foreign_keys, .. } = sb.build(graphql_schema);
+        let SchemaBuilder { foreign_keys, .. } =
+            sb.build(graphql_schema).expect("Bad schema");
 
         assert_eq!(foreign_keys.len(), 2);
         assert_eq!(foreign_keys[0].create_statement(), "ALTER TABLE namespace_index1.lender ADD CONSTRAINT fk_lender_borrower__borrower_account FOREIGN KEY (borrower) REFERENCES namespace_index1.borrower(account) ON DELETE NO ACTION ON UPDATE NO ACTION INITIALLY DEFERRED;".to_string());
@@ -778,13 +1102,49 @@ mod tests {
 
         let sb = SchemaBuilder::new("namespace", "index1", "v1", DbType::Postgres);
 
-        let SchemaBuilder { foreign_keys, .. } = sb.build(graphql_schema);
+        let SchemaBuilder { foreign_keys, .. } =
+            sb.build(graphql_schema).expect("Bad schema");
 
         assert_eq!(foreign_keys.len(), 2);
         assert_eq!(foreign_keys[0].create_statement(), "ALTER TABLE namespace_index1.message ADD CONSTRAINT fk_message_sender__account_id FOREIGN KEY (sender) REFERENCES namespace_index1.account(id) ON DELETE NO ACTION ON UPDATE NO ACTION INITIALLY DEFERRED;".to_string());
         assert_eq!(foreign_keys[1].create_statement(), "ALTER TABLE namespace_index1.message ADD CONSTRAINT fk_message_receiver__account_id FOREIGN KEY (receiver) REFERENCES namespace_index1.account(id) ON DELETE NO ACTION ON UPDATE NO ACTION INITIALLY DEFERRED;".to_string());
     }
 
+    #[test]
+    fn test_schema_builder_rejects_schemas_it_cannot_build_tables_for() {
+        let build = |types: &str| {
+            let graphql_schema = format!(
+                "schema {{ query: QueryRoot }} type QueryRoot {{ thing: Thing }} {types}"
+            );
+            SchemaBuilder::new("namespace", "index1", "v1", DbType::Postgres)
+                .build(&graphql_schema)
+        };
+
+        for types in [
+            "type Thing { id: ID!",
+            "type Thing { id: ID! things: [Thing] }",
+            "type Thing { id: ID! color: Color! }",
+            "type Thing { id: ID! other: Other! }",
+            "type Thing { id: ID! other: Other! @join(on:missing) } type Other { id: ID! }",
+            "type Thing { id: ID! } enum Kind { One, Two }",
+        ] {
+            assert!(
+                matches!(build(types), Err(IndexerSchemaError::InvalidSchema(_))),
+                "{types}"
+            );
+        }
+
+        assert!(build("type Thing { id: ID! }").is_ok());
+
+        let no_query_root =
+            SchemaBuilder::new("namespace", "index1", "v1", DbType::Postgres)
+                .build("type Thing { id: ID! }");
+        assert!(matches!(
+            no_query_root,
+            Err(IndexerSchemaError::InvalidSchema(_))
+        ));
+    }
+
     #[test]
     fn test_get_implicit_foreign_keys_for_schema() {
         let implicit_fk_graphql_schema: &str = r#"
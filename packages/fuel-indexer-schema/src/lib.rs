@@ -1,7 +1,7 @@
 #![deny(unused_crate_dependencies)]
 
 extern crate alloc;
-use crate::sql_types::ColumnType;
+use crate::sql_types::{BindValue, ColumnType};
 use core::convert::TryInto;
 use fuel_indexer_types::{
     try_from_bytes, Address, AssetId, Blob, Bytes32, Bytes4, Bytes8, ContractId,
@@ -168,6 +168,105 @@ impl FtColumn {
         }
     }
 
+    /// A column of type `ty` without a value.
+    pub fn null(ty: ColumnType) -> FtColumn {
+        match ty {
+            ColumnType::ID => FtColumn::ID(None),
+            ColumnType::Address => FtColumn::Address(None),
+            ColumnType::AssetId => FtColumn::AssetId(None),
+            ColumnType::Bytes4 => FtColumn::Bytes4(None),
+            ColumnType::Bytes8 => FtColumn::Bytes8(None),
+            ColumnType::Bytes32 => FtColumn::Bytes32(None),
+            ColumnType::ContractId => FtColumn::ContractId(None),
+            ColumnType::Salt => FtColumn::Salt(None),
+            ColumnType::Int4 => FtColumn::Int4(None),
+            ColumnType::Int8 => FtColumn::Int8(None),
+            ColumnType::Int16 => FtColumn::Int16(None),
+            ColumnType::UInt4 => FtColumn::UInt4(None),
+            ColumnType::UInt8 => FtColumn::UInt8(None),
+            ColumnType::UInt16 => FtColumn::UInt16(None),
+            ColumnType::Timestamp => FtColumn::Timestamp(None),
+            ColumnType::Blob => FtColumn::Blob(None),
+            ColumnType::Json => FtColumn::Json(None),
+            ColumnType::MessageId => FtColumn::MessageId(None),
+            ColumnType::Charfield => FtColumn::Charfield(None),
+            ColumnType::Identity => FtColumn::Identity(None),
+            ColumnType::Boolean => FtColumn::Boolean(None),
+            ColumnType::ForeignKey => {
+                panic!("ForeignKey not supported for FtColumn.");
+            }
+            ColumnType::Object => {
+                panic!("Object not supported for FtColumn.");
+            }
+        }
+    }
+
+    /// A column of the same type as this one, without a value.
+    pub fn to_null(&self) -> FtColumn {
+        match self {
+            FtColumn::ID(_) => FtColumn::ID(None),
+            FtColumn::Address(_) => FtColumn::Address(None),
+            FtColumn::AssetId(_) => FtColumn::AssetId(None),
+            FtColumn::Bytes4(_) => FtColumn::Bytes4(None),
+            FtColumn::Bytes8(_) => FtColumn::Bytes8(None),
+            FtColumn::Bytes32(_) => FtColumn::Bytes32(None),
+            FtColumn::ContractId(_) => FtColumn::ContractId(None),
+            FtColumn::Int4(_) => FtColumn::Int4(None),
+            FtColumn::Int8(_) => FtColumn::Int8(None),
+            FtColumn::Int16(_) => FtColumn::Int16(None),
+            FtColumn::UInt4(_) => FtColumn::UInt4(None),
+            FtColumn::UInt8(_) => FtColumn::UInt8(None),
+            FtColumn::UInt16(_) => FtColumn::UInt16(None),
+            FtColumn::Timestamp(_) => FtColumn::Timestamp(None),
+            FtColumn::Salt(_) => FtColumn::Salt(None),
+            FtColumn::Json(_) => FtColumn::Json(None),
+            FtColumn::MessageId(_) => FtColumn::MessageId(None),
+            FtColumn::Charfield(_) => FtColumn::Charfield(None),
+            FtColumn::Identity(_) => FtColumn::Identity(None),
+            FtColumn::Boolean(_) => FtColumn::Boolean(None),
+            FtColumn::Blob(_) => FtColumn::Blob(None),
+        }
+    }
+
+    /// The value of this column as a query parameter.
+    pub fn bind_value(&self) -> BindValue {
+        match self {
+            FtColumn::ID(value) => match value {
+                Some(val) => BindValue::UInt8(Some(*val)),
+                None => panic!("Schema fields of type ID cannot be nullable"),
+            },
+            FtColumn::Address(value) => BindValue::Text(value.map(|v| format!("{v:x}"))),
+            FtColumn::AssetId(value) => BindValue::Text(value.map(|v| format!("{v:x}"))),
+            FtColumn::Bytes4(value) => BindValue::Text(value.map(|v| format!("{v:x}"))),
+            FtColumn::Bytes8(value) => BindValue::Text(value.map(|v| format!("{v:x}"))),
+            FtColumn::Bytes32(value) => BindValue::Text(value.map(|v| format!("{v:x}"))),
+            FtColumn::ContractId(value) => {
+                BindValue::Text(value.map(|v| format!("{v:x}")))
+            }
+            FtColumn::Salt(value) => BindValue::Text(value.map(|v| format!("{v:x}"))),
+            FtColumn::MessageId(value) => {
+                BindValue::Text(value.map(|v| format!("{v:x}")))
+            }
+            FtColumn::Identity(value) => {
+                BindValue::Text(value.as_ref().map(|v| match v {
+                    Identity::Address(v) => format!("{v:x}"),
+                    Identity::ContractId(v) => format!("{v:x}"),
+                }))
+            }
+            FtColumn::Blob(value) => BindValue::Text(value.as_ref().map(hex::encode)),
+            FtColumn::Int4(value) => BindValue::Integer(*value),
+            FtColumn::Int8(value) => BindValue::BigInt(*value),
+            FtColumn::UInt4(value) => BindValue::BigInt(value.map(i64::from)),
+            FtColumn::Int16(value) => BindValue::Numeric(value.map(|v| v.to_string())),
+            FtColumn::UInt8(value) => BindValue::UInt8(*value),
+            FtColumn::UInt16(value) => BindValue::Numeric(value.map(|v| v.to_string())),
+            FtColumn::Timestamp(value) => BindValue::Timestamp(*value),
+            FtColumn::Json(value) => BindValue::Json(value.as_ref().map(|v| v.0.clone())),
+            FtColumn::Charfield(value) => BindValue::Text(value.clone()),
+            FtColumn::Boolean(value) => BindValue::Boolean(*value),
+        }
+    }
+
     pub fn query_fragment(&self) -> String {
         match self {
             FtColumn::ID(value) => {
@@ -270,6 +369,16 @@ impl FtColumn {
     }
 }
 
+/// Selects the entities of one type whose columns all have the given values,
+/// for finding entities by fields other than their ID from inside handlers.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Default)]
+pub struct EntityFilter {
+    /// Column names and the values they must have.
+    pub columns: Vec<(String, FtColumn)>,
+    /// Return at most this many entities.
+    pub limit: Option<u64>,
+}
+
 mod tests {
     #[test]
     fn test_fragments_some_types() {
@@ -370,6 +479,38 @@ mod tests {
         insta::assert_yaml_snapshot!(identity_none.query_fragment());
     }
 
+    #[test]
+    fn test_bind_values() {
+        use super::*;
+
+        assert_eq!(
+            FtColumn::ID(Some(u64::MAX)).bind_value(),
+            BindValue::UInt8(Some(u64::MAX))
+        );
+        assert_eq!(
+            FtColumn::Bytes4(Some(Bytes4::try_from([0xF0; 4]).expect("Bad bytes")))
+                .bind_value(),
+            BindValue::Text(Some("f0f0f0f0".to_string()))
+        );
+        assert_eq!(
+            FtColumn::Charfield(Some("it's".to_string())).bind_value(),
+            BindValue::Text(Some("it's".to_string()))
+        );
+        assert_eq!(
+            FtColumn::UInt4(Some(u32::MAX)).bind_value(),
+            BindValue::BigInt(Some(u32::MAX as i64))
+        );
+        assert_eq!(
+            FtColumn::Int16(Some(i128::MIN)).bind_value(),
+            BindValue::Numeric(Some(i128::MIN.to_string()))
+        );
+        assert_eq!(FtColumn::Json(None).bind_value(), BindValue::Json(None));
+        assert_eq!(
+            FtColumn::Boolean(Some(false)).bind_value(),
+            BindValue::Boolean(Some(false))
+        );
+    }
+
     #[test]
     #[should_panic(expected = "Schema fields of type ID cannot be nullable")]
     fn test_panic_on_none_id_fragment() {
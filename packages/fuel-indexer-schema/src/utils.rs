@@ -14,6 +14,7 @@ pub const BASE_SCHEMA: &str = include_str!("./base.graphql");
 pub const JOIN_DIRECTIVE_NAME: &str = "join";
 pub const UNIQUE_DIRECTIVE_NAME: &str = "unique";
 pub const INDEX_DIRECTIVE_NAME: &str = "indexed";
+pub const VERSIONED_DIRECTIVE_NAME: &str = "versioned";
 
 pub fn inject_native_entities_into_schema(schema: &str) -> String {
     format!("{}{}", schema, IndexMetadata::schema_fragment())
@@ -86,6 +87,13 @@ pub fn get_unique_directive(field: &Field<String>) -> sql_types::directives::Uni
     sql_types::directives::Unique(false)
 }
 
+/// Whether the object type keeps the history of its entities.
+pub fn is_versioned(obj: &ObjectType<String>) -> bool {
+    obj.directives
+        .iter()
+        .any(|d| d.name == VERSIONED_DIRECTIVE_NAME)
+}
+
 pub fn get_join_directive_info<'a>(
     field: &Field<'a, String>,
     obj: &ObjectType<'a, String>,
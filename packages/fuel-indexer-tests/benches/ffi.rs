@@ -0,0 +1,242 @@
+//! Measures the cost of the host functions a WASM indexer calls to load and
+//! save entities, and compares running each database call on a new tokio
+//! runtime (as the host functions used to) with running it on a handle to the
+//! indexer's runtime (as they do now).
+//!
+//! Requires a running Postgres, at `DATABASE_URL` if set. Run with
+//! `cargo bench -p fuel-indexer-tests --bench ffi`.
+use fuel_indexer::{
+    Database, Executor, FtColumn, IndexerConfig, Manifest, WasmIndexExecutor,
+};
+use fuel_indexer_database::{queries, IndexerConnectionPool};
+use fuel_indexer_lib::{config::DatabaseConfig, manifest::Module};
+use fuel_indexer_schema::{
+    db::manager::SchemaManager,
+    utils::{inject_native_entities_into_schema, schema_version, serialize},
+};
+use fuel_indexer_tests::{
+    assets::{SIMPLE_WASM_MANIFEST, SIMPLE_WASM_SCHEMA},
+    defaults,
+};
+use fuel_indexer_types::{abi::BlockData, type_id, Bytes32};
+use fuels::prelude::Address;
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    runtime::{Handle, Runtime},
+    sync::Mutex,
+    task::spawn_blocking,
+};
+
+const NAMESPACE: &str = "ffi_bench";
+const IDENTIFIER: &str = "simple_wasm";
+
+/// Number of batches handled.
+const BATCHES: u64 = 10;
+
+/// Number of `put_object`/`get_object` pairs per batch.
+const CALLS_PER_BATCH: u32 = 1_000;
+
+/// Number of `put_object`/`get_object` pairs run outside of WASM for each way
+/// of blocking on a database call.
+const BASELINE_CALLS: u32 = 1_000;
+
+/// Bytes as a WAT string literal.
+fn wat_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("\\{b:02x}")).collect()
+}
+
+/// An indexer module whose handler saves and then loads the same `Thing1`
+/// `CALLS_PER_BATCH` times.
+fn bench_module(version: &str, object: &[u8]) -> String {
+    let thing1 = type_id(&format!("{NAMESPACE}_{IDENTIFIER}"), "Thing1");
+    let object_id = wat_bytes(&4u64.to_le_bytes());
+
+    format!(
+        r#"(module
+            (import "env" "ff_put_object" (func $put (param i64 i32 i32)))
+            (import "env" "ff_get_object" (func $get (param i64 i32 i32) (result i32)))
+            (memory (export "memory") 64)
+            (data (i32.const 0) "{NAMESPACE}")
+            (data (i32.const 32) "{IDENTIFIER}")
+            (data (i32.const 64) "{object_id}")
+            (data (i32.const 128) "{version}")
+            (data (i32.const 512) "{object}")
+            (global $heap (mut i32) (i32.const 4096))
+            (func (export "get_namespace_ptr") (result i32) i32.const 0)
+            (func (export "get_namespace_len") (result i32) i32.const {ns_len})
+            (func (export "get_identifier_ptr") (result i32) i32.const 32)
+            (func (export "get_identifier_len") (result i32) i32.const {id_len})
+            (func (export "get_version_ptr") (result i32) i32.const 128)
+            (func (export "get_version_len") (result i32) i32.const {version_len})
+            (func (export "alloc_fn") (param $size i32) (result i32)
+                global.get $heap
+                global.get $heap
+                local.get $size
+                i32.add
+                global.set $heap)
+            (func (export "dealloc_fn") (param i32 i32))
+            (func (export "handle_events") (param i32 i32) (local $i i32)
+                (loop $calls
+                    (call $put (i64.const {thing1}) (i32.const 512) (i32.const {object_len}))
+                    (drop (call $get (i64.const {thing1}) (i32.const 64) (i32.const 96)))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br_if $calls (i32.lt_u (local.get $i) (i32.const {CALLS_PER_BATCH}))))))"#,
+        ns_len = NAMESPACE.len(),
+        id_len = IDENTIFIER.len(),
+        version_len = version.len(),
+        object = wat_bytes(object),
+        object_len = object.len(),
+    )
+}
+
+/// Runs one `put_object`/`get_object` pair to completion from a blocking thread.
+type BlockOn = fn(&Handle, &Arc<Mutex<Database>>, i64, Vec<FtColumn>, Vec<u8>);
+
+/// Run `BASELINE_CALLS` `put_object`/`get_object` pairs from a blocking thread,
+/// the way host functions do, blocking on each call with `block_on`.
+async fn time_blocking_calls(
+    db: Arc<Mutex<Database>>,
+    type_id: i64,
+    columns: Vec<FtColumn>,
+    object: Vec<u8>,
+    block_on: BlockOn,
+) -> Duration {
+    spawn_blocking(move || {
+        let handle = Handle::current();
+        let start = Instant::now();
+        for _ in 0..BASELINE_CALLS {
+            block_on(&handle, &db, type_id, columns.clone(), object.clone());
+        }
+        start.elapsed()
+    })
+    .await
+    .expect("Baseline failed")
+}
+
+async fn put_and_get(
+    db: &Arc<Mutex<Database>>,
+    type_id: i64,
+    columns: Vec<FtColumn>,
+    object: Vec<u8>,
+) {
+    let mut db = db.lock().await;
+    db.put_object(type_id, columns, object).await.unwrap();
+    db.get_object(type_id, 4).await.unwrap();
+}
+
+fn print_result(name: &str, calls: u32, elapsed: Duration) {
+    println!(
+        "{name:<24} {calls:>6} calls in {elapsed:>12?} ({:?}/call)",
+        elapsed / calls
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let database_url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| defaults::POSTGRES_URL.to_string());
+
+    let pool = IndexerConnectionPool::connect(&database_url)
+        .await
+        .expect("Connection pool error");
+    let mut conn = pool.acquire().await.expect("Failed to acquire connection");
+    queries::run_migration(&mut conn)
+        .await
+        .expect("Failed to run migrations");
+    SchemaManager::new(pool.clone())
+        .new_schema(NAMESPACE, IDENTIFIER, SIMPLE_WASM_SCHEMA, &mut conn)
+        .await
+        .expect("Failed to create schema");
+
+    let version = schema_version(&inject_native_entities_into_schema(SIMPLE_WASM_SCHEMA));
+    let columns = vec![
+        FtColumn::ID(Some(4)),
+        FtColumn::Address(Some(Address::from([0x04; 32]))),
+    ];
+    let object = serialize(&columns);
+
+    let config = IndexerConfig {
+        database: DatabaseConfig::from_str(&database_url).unwrap(),
+        ..IndexerConfig::default()
+    };
+    let manifest = Manifest::try_from(SIMPLE_WASM_MANIFEST).unwrap();
+    let mut executor =
+        WasmIndexExecutor::new(&config, &manifest, bench_module(&version, &object))
+            .await
+            .expect("Failed to create executor");
+
+    let start = Instant::now();
+    for height in 1..=BATCHES {
+        let block = BlockData {
+            height,
+            id: Bytes32::from([height as u8; 32]),
+            producer: None,
+            time: 1,
+            transactions: vec![],
+        };
+        executor
+            .handle_events(vec![block])
+            .await
+            .expect("Handler failed");
+    }
+    let elapsed = start.elapsed();
+
+    print_result(
+        "wasm host calls",
+        BATCHES as u32 * CALLS_PER_BATCH * 2,
+        elapsed,
+    );
+
+    let mut db = Database::new(&database_url)
+        .await
+        .expect("Failed to connect to database");
+    let native_manifest = Manifest {
+        namespace: NAMESPACE.to_string(),
+        identifier: IDENTIFIER.to_string(),
+        module: Module::Native,
+        ..manifest
+    };
+    db.load_schema(&native_manifest, None)
+        .await
+        .expect("Failed to load schema");
+    db.start_transaction()
+        .await
+        .expect("Failed to start transaction");
+    let db = Arc::new(Mutex::new(db));
+    let thing1 = type_id(&format!("{NAMESPACE}_{IDENTIFIER}"), "Thing1");
+
+    let new_runtime = time_blocking_calls(
+        db.clone(),
+        thing1,
+        columns.clone(),
+        object.clone(),
+        |_, db, type_id, columns, object| {
+            let rt = Runtime::new().expect("Could not create tokio runtime.");
+            rt.block_on(put_and_get(db, type_id, columns, object));
+        },
+    )
+    .await;
+    let shared_handle = time_blocking_calls(
+        db.clone(),
+        thing1,
+        columns,
+        object,
+        |handle, db, type_id, columns, object| {
+            handle.block_on(put_and_get(db, type_id, columns, object));
+        },
+    )
+    .await;
+
+    db.lock()
+        .await
+        .revert_transaction()
+        .await
+        .expect("Failed to revert transaction");
+
+    print_result("new runtime per call", BASELINE_CALLS * 2, new_runtime);
+    print_result("shared runtime handle", BASELINE_CALLS * 2, shared_handle);
+}
@@ -641,6 +641,48 @@ mod fuel_indexer_test {
         f3.save();
     }
 
+    fn fuel_indexer_test_find_entities(_ping: Ping) {
+        Logger::info("fuel_indexer_test_find_entities handling a Ping event.");
+
+        FindEntity {
+            id: 1,
+            owner: "alice".to_string(),
+            label: "one".to_string(),
+        }
+        .save();
+
+        FindEntity {
+            id: 2,
+            owner: "bob".to_string(),
+            label: "two".to_string(),
+        }
+        .save();
+
+        FindEntity {
+            id: 3,
+            owner: "alice".to_string(),
+            label: "three".to_string(),
+        }
+        .save();
+
+        let owned = FindEntity::find_many(FindEntityFilter {
+            owner: Some("alice".to_string()),
+            ..Default::default()
+        });
+
+        let labelled = FindEntity::find_one(FindEntityFilter {
+            label: Some("three".to_string()),
+            ..Default::default()
+        });
+
+        FoundEntity {
+            id: 1,
+            owner_matches: owned.len() as u64,
+            label_match: labelled.map(|entity| entity.id),
+        }
+        .save();
+    }
+
     fn fuel_indexer_triger_enum_error(enum_error: abi::Revert) {
         Logger::info("fuel_indexer_triger_enum_error handling trigger_enum_error event.");
 
@@ -26,7 +26,7 @@ use sqlx::{Connection, Executor};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use tokio::sync::mpsc::{channel, Receiver};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tracing_subscriber::filter::EnvFilter;
 
 use crate::{defaults, TestError, WORKSPACE_ROOT};
@@ -66,7 +66,10 @@ impl TestPostgresDb {
             host,
             port,
             ..
-        } = connection_config;
+        } = connection_config
+        else {
+            panic!("DATABASE_URL must point at a Postgres server.");
+        };
         let test_db_config = DatabaseConfig::Postgres {
             user,
             password,
@@ -94,6 +97,9 @@ impl TestPostgresDb {
                         fuel_indexer_postgres::run_migration(&mut conn).await?;
                         p
                     }
+                    IndexerConnectionPool::Sqlite(_) => {
+                        unreachable!("Test databases are created on Postgres.")
+                    }
                 },
                 Err(e) => return Err(TestError::PoolCreationError(e)),
             };
@@ -291,6 +297,13 @@ pub async fn api_server_app_postgres(
         stop_idle_indexers: true,
         run_migrations: false,
         authentication: AuthenticationConfig::default(),
+        block_fetch_concurrency: config_defaults::BLOCK_FETCH_CONCURRENCY,
+        record_blocks: None,
+        replay_blocks: None,
+        replay_from_start: false,
+        wasm_instruction_limit: config_defaults::WASM_INSTRUCTION_LIMIT,
+        wasm_memory_limit_mb: config_defaults::WASM_MEMORY_LIMIT_MB,
+        wasm_timeout_secs: config_defaults::WASM_TIMEOUT_SECS,
     };
 
     let pool = IndexerConnectionPool::connect(&config.database.to_string())
@@ -328,6 +341,13 @@ pub async fn authenticated_api_server_app_postgres(database_url: Option<&str>) -
             jwt_issuer: Some("FuelLabs".to_string()),
             jwt_expiry: Some(config_defaults::JWT_EXPIRY_SECS)
         },
+        block_fetch_concurrency: config_defaults::BLOCK_FETCH_CONCURRENCY,
+        record_blocks: None,
+        replay_blocks: None,
+        replay_from_start: false,
+        wasm_instruction_limit: config_defaults::WASM_INSTRUCTION_LIMIT,
+        wasm_memory_limit_mb: config_defaults::WASM_MEMORY_LIMIT_MB,
+        wasm_timeout_secs: config_defaults::WASM_TIMEOUT_SECS,
     };
 
     let (tx, _rx) = channel::<ServiceRequest>(SERVICE_REQUEST_CHANNEL_SIZE);
@@ -340,12 +360,19 @@ pub async fn authenticated_api_server_app_postgres(database_url: Option<&str>) -
 }
 
 pub async fn indexer_service_postgres(database_url: Option<&str>) -> IndexerService {
+    let (service, _tx) =
+        indexer_service_from_config(indexer_config_postgres(database_url)).await;
+
+    service
+}
+
+pub fn indexer_config_postgres(database_url: Option<&str>) -> IndexerConfig {
     let database: DatabaseConfig = database_url
         .map_or(DatabaseConfig::default(), |url| {
             DatabaseConfig::from_str(url).unwrap()
         });
 
-    let config = IndexerConfig {
+    IndexerConfig {
         verbose: true,
         local_fuel_node: false,
         indexer_net_config: false,
@@ -356,15 +383,27 @@ pub async fn indexer_service_postgres(database_url: Option<&str>) -> IndexerServ
         stop_idle_indexers: true,
         run_migrations: false,
         authentication: AuthenticationConfig::default(),
-    };
+        block_fetch_concurrency: config_defaults::BLOCK_FETCH_CONCURRENCY,
+        record_blocks: None,
+        replay_blocks: None,
+        replay_from_start: false,
+        wasm_instruction_limit: config_defaults::WASM_INSTRUCTION_LIMIT,
+        wasm_memory_limit_mb: config_defaults::WASM_MEMORY_LIMIT_MB,
+        wasm_timeout_secs: config_defaults::WASM_TIMEOUT_SECS,
+    }
+}
 
-    let (_tx, rx) = channel::<ServiceRequest>(SERVICE_REQUEST_CHANNEL_SIZE);
+/// An indexer service, along with the sender its requests are handled from.
+pub async fn indexer_service_from_config(
+    config: IndexerConfig,
+) -> (IndexerService, Sender<ServiceRequest>) {
+    let (tx, rx) = channel::<ServiceRequest>(SERVICE_REQUEST_CHANNEL_SIZE);
 
     let pool = IndexerConnectionPool::connect(&config.database.to_string())
         .await
         .expect("Failed to create connection pool");
 
-    IndexerService::new(config, pool, rx).await.unwrap()
+    (IndexerService::new(config, pool, rx).await.unwrap(), tx)
 }
 
 pub async fn connect_to_deployed_contract(
@@ -866,3 +866,249 @@ async fn test_can_return_query_response_with_alias_and_ascending_offset_and_limi
     assert_eq!(data["aliased_entities"][0]["foola"].as_str(), Some("blorp"));
     assert_eq!(data["page_info"]["pages"].as_i64(), Some(3));
 }
+
+#[actix_web::test]
+#[cfg(all(feature = "e2e", feature = "postgres"))]
+async fn test_can_return_introspection_response_postgres() {
+    let (fuel_node_handle, _test_db, mut srvc, api_app) = setup_test_components().await;
+    fuel_node_handle.abort();
+
+    let server = axum::Server::bind(&GraphQLConfig::default().into())
+        .serve(api_app.into_make_service());
+
+    let server_handle = tokio::spawn(server);
+    let mut manifest: Manifest =
+        serde_yaml::from_str(assets::FUEL_INDEXER_TEST_MANIFEST).expect("Bad yaml file.");
+
+    update_test_manifest_asset_paths(&mut manifest);
+
+    srvc.register_index_from_manifest(manifest)
+        .await
+        .expect("Failed to initialize indexer.");
+
+    let client = http_client();
+    let resp = client
+        .post("http://127.0.0.1:29987/api/graph/fuel_indexer_test/index1")
+        .header(CONTENT_TYPE, "application/graphql".to_owned())
+        .body(
+            r#"{"query": "query { __schema { queryType { name } } __type(name: \"Tx\") { kind fields { name type { kind name } } } }" }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    server_handle.abort();
+
+    let body = resp.text().await.unwrap();
+    let v: Value = serde_json::from_str(&body).unwrap();
+    let data = v["data"].as_object().expect("data is not an object");
+
+    assert_eq!(
+        data["__schema"]["queryType"]["name"].as_str(),
+        Some("QueryRoot")
+    );
+    assert_eq!(data["__type"]["kind"].as_str(), Some("OBJECT"));
+
+    let fields = data["__type"]["fields"].as_array().unwrap();
+    let block = fields.iter().find(|f| f["name"] == "block").unwrap();
+    assert_eq!(block["type"]["kind"].as_str(), Some("OBJECT"));
+    assert_eq!(block["type"]["name"].as_str(), Some("Block"));
+}
+
+#[actix_web::test]
+#[cfg(all(feature = "e2e", feature = "postgres"))]
+async fn test_can_return_query_response_with_variables_postgres() {
+    let (fuel_node_handle, _test_db, mut srvc, api_app) = setup_test_components().await;
+
+    let server = axum::Server::bind(&GraphQLConfig::default().into())
+        .serve(api_app.into_make_service());
+
+    let server_handle = tokio::spawn(server);
+    let mut manifest: Manifest =
+        serde_yaml::from_str(assets::FUEL_INDEXER_TEST_MANIFEST).expect("Bad yaml file.");
+
+    update_test_manifest_asset_paths(&mut manifest);
+
+    srvc.register_index_from_manifest(manifest)
+        .await
+        .expect("Failed to initialize indexer.");
+
+    let contract = connect_to_deployed_contract().await.unwrap();
+    let app = test::init_service(app(contract)).await;
+    let req = test::TestRequest::post().uri("/ping").to_request();
+    let _ = app.call(req).await;
+
+    sleep(Duration::from_secs(defaults::INDEXED_EVENT_WAIT)).await;
+    fuel_node_handle.abort();
+
+    let client = http_client();
+    let resp = client
+        .post("http://127.0.0.1:29987/api/graph/fuel_indexer_test/index1")
+        .header(CONTENT_TYPE, "application/graphql".to_owned())
+        .body(
+            r#"{
+                "query": "query Other { block { id } } query Filtered($filter: FilterEntityFilter) { filterentity(filter: $filter) { id foola } }",
+                "operationName": "Filtered",
+                "variables": { "filter": { "foola": { "equals": "beep" } } }
+            }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    server_handle.abort();
+
+    let body = resp.text().await.unwrap();
+    let v: Value = serde_json::from_str(&body).unwrap();
+    let data = v["data"].as_array().expect("data is not an array");
+
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["id"].as_i64(), Some(1));
+    assert_eq!(data[0]["foola"].as_str(), Some("beep"));
+}
+
+#[actix_web::test]
+#[cfg(all(feature = "e2e", feature = "postgres"))]
+async fn test_can_return_aggregate_query_response_postgres() {
+    let (fuel_node_handle, _test_db, mut srvc, api_app) = setup_test_components().await;
+
+    let server = axum::Server::bind(&GraphQLConfig::default().into())
+        .serve(api_app.into_make_service());
+
+    let server_handle = tokio::spawn(server);
+    let mut manifest: Manifest =
+        serde_yaml::from_str(assets::FUEL_INDEXER_TEST_MANIFEST).expect("Bad yaml file.");
+
+    update_test_manifest_asset_paths(&mut manifest);
+
+    srvc.register_index_from_manifest(manifest)
+        .await
+        .expect("Failed to initialize indexer.");
+
+    let contract = connect_to_deployed_contract().await.unwrap();
+    let app = test::init_service(app(contract)).await;
+    let req = test::TestRequest::post().uri("/ping").to_request();
+    let _ = app.call(req).await;
+
+    sleep(Duration::from_secs(defaults::INDEXED_EVENT_WAIT)).await;
+    fuel_node_handle.abort();
+
+    let client = http_client();
+    let resp = client
+        .post("http://127.0.0.1:29987/api/graph/fuel_indexer_test/index1")
+        .header(CONTENT_TYPE, "application/graphql".to_owned())
+        .body(r#"{ "query": "query { filterentityAggregate(filter: { bazoo: { lt: 1000 } }) { count sum { bazoo } max { bazoo } } }" }"#)
+        .send()
+        .await
+        .unwrap();
+
+    server_handle.abort();
+
+    let body = resp.text().await.unwrap();
+    let v: Value = serde_json::from_str(&body).unwrap();
+    let data = v["data"].as_array().expect("data is not an array");
+
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["count"].as_i64(), Some(2));
+    assert_eq!(data[0]["sum"]["bazoo"].as_i64(), Some(6));
+    assert_eq!(data[0]["max"]["bazoo"].as_i64(), Some(5));
+}
+
+#[actix_web::test]
+#[cfg(all(feature = "e2e", feature = "postgres"))]
+async fn test_can_return_connection_query_response_postgres() {
+    let (fuel_node_handle, _test_db, mut srvc, api_app) = setup_test_components().await;
+
+    let server = axum::Server::bind(&GraphQLConfig::default().into())
+        .serve(api_app.into_make_service());
+
+    let server_handle = tokio::spawn(server);
+    let mut manifest: Manifest =
+        serde_yaml::from_str(assets::FUEL_INDEXER_TEST_MANIFEST).expect("Bad yaml file.");
+
+    update_test_manifest_asset_paths(&mut manifest);
+
+    srvc.register_index_from_manifest(manifest)
+        .await
+        .expect("Failed to initialize indexer.");
+
+    let contract = connect_to_deployed_contract().await.unwrap();
+    let app = test::init_service(app(contract)).await;
+    let req = test::TestRequest::post().uri("/ping").to_request();
+    let _ = app.call(req).await;
+
+    sleep(Duration::from_secs(defaults::INDEXED_EVENT_WAIT)).await;
+    fuel_node_handle.abort();
+
+    let client = http_client();
+    let resp = client
+        .post("http://127.0.0.1:29987/api/graph/fuel_indexer_test/index1")
+        .header(CONTENT_TYPE, "application/graphql".to_owned())
+        .body(r#"{ "query": "query { filterentityConnection(first: 2, order: { asc: bazoo }) { totalCount pageInfo { hasNextPage endCursor } nodes { id bazoo } } }" }"#)
+        .send()
+        .await
+        .unwrap();
+
+    server_handle.abort();
+
+    let body = resp.text().await.unwrap();
+    let v: Value = serde_json::from_str(&body).unwrap();
+    let connection = &v["data"]["filterentityConnection"];
+
+    assert_eq!(connection["totalCount"].as_i64(), Some(3));
+    assert_eq!(connection["pageInfo"]["hasNextPage"].as_bool(), Some(true));
+    assert!(connection["pageInfo"]["endCursor"].is_string());
+
+    let nodes = connection["nodes"]
+        .as_array()
+        .expect("nodes is not an array");
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0]["bazoo"].as_i64(), Some(1));
+    assert_eq!(nodes[1]["bazoo"].as_i64(), Some(5));
+}
+
+#[actix_web::test]
+#[cfg(all(feature = "e2e", feature = "postgres"))]
+async fn test_can_find_entities_by_indexed_and_unique_fields_postgres() {
+    let (fuel_node_handle, _test_db, mut srvc, api_app) = setup_test_components().await;
+
+    let server = axum::Server::bind(&GraphQLConfig::default().into())
+        .serve(api_app.into_make_service());
+
+    let server_handle = tokio::spawn(server);
+    let mut manifest: Manifest =
+        serde_yaml::from_str(assets::FUEL_INDEXER_TEST_MANIFEST).expect("Bad yaml file.");
+
+    update_test_manifest_asset_paths(&mut manifest);
+
+    srvc.register_index_from_manifest(manifest)
+        .await
+        .expect("Failed to initialize indexer.");
+
+    let contract = connect_to_deployed_contract().await.unwrap();
+    let app = test::init_service(app(contract)).await;
+    let req = test::TestRequest::post().uri("/ping").to_request();
+    let _ = app.call(req).await;
+
+    sleep(Duration::from_secs(defaults::INDEXED_EVENT_WAIT)).await;
+    fuel_node_handle.abort();
+
+    let client = http_client();
+    let resp = client
+        .post("http://127.0.0.1:29987/api/graph/fuel_indexer_test/index1")
+        .header(CONTENT_TYPE, "application/graphql".to_owned())
+        .body(r#"{ "query": "query { foundentity(id: 1) { id owner_matches label_match } }" }"#)
+        .send()
+        .await
+        .unwrap();
+
+    server_handle.abort();
+
+    let body = resp.text().await.unwrap();
+    let v: Value = serde_json::from_str(&body).unwrap();
+    let data = v["data"].as_array().expect("data is not an array");
+
+    assert_eq!(data[0]["id"].as_i64(), Some(1));
+    assert_eq!(data[0]["owner_matches"].as_i64(), Some(2));
+    assert_eq!(data[0]["label_match"].as_i64(), Some(3));
+}
@@ -0,0 +1,262 @@
+use async_trait::async_trait;
+use fuel_core::service::{Config, FuelService};
+use fuel_core_client::client::FuelClient;
+use fuel_indexer::{
+    block_source::BlockPage, BlockArchive, BlockSource, CachedBlockSource,
+    FileBlockSource, FuelNodeBlockSource, IndexerConfig, IndexerResult, Manifest,
+};
+use fuel_indexer_lib::defaults::NODE_GRAPHQL_PAGE_SIZE;
+use fuel_indexer_schema::utils::serialize;
+use fuel_indexer_tests::assets::SIMPLE_WASM_MANIFEST;
+use fuel_indexer_types::{abi::BlockData, Bytes32};
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+fn block(height: u64) -> BlockData {
+    BlockData {
+        height,
+        id: Bytes32::from([height as u8; 32]),
+        producer: None,
+        time: 1,
+        transactions: vec![],
+    }
+}
+
+/// Serves a chain of blocks two at a time, counting the pages it serves.
+struct CountingBlockSource {
+    blocks: Mutex<Vec<BlockData>>,
+    pages: AtomicUsize,
+}
+
+impl CountingBlockSource {
+    fn new(heights: std::ops::RangeInclusive<u64>) -> Self {
+        Self {
+            blocks: Mutex::new(heights.map(block).collect()),
+            pages: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl BlockSource for &CountingBlockSource {
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage> {
+        self.pages.fetch_add(1, Ordering::SeqCst);
+        let after = cursor.map(|c| c.parse::<u64>().unwrap());
+        let blocks: Vec<BlockData> = self
+            .blocks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|b| after.map_or(true, |after| b.height > after))
+            .take(2)
+            .cloned()
+            .collect();
+        let cursor = blocks.last().map(|b| b.height.to_string());
+
+        Ok(BlockPage { blocks, cursor })
+    }
+
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>> {
+        Ok(self
+            .blocks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|b| b.height == height)
+            .map(|b| b.id))
+    }
+}
+
+#[tokio::test]
+async fn test_file_block_source_serves_pages_in_height_order() {
+    let path = std::env::temp_dir().join("test_file_block_source.blocks");
+    let mut file = std::fs::File::create(&path).unwrap();
+    for page in [vec![block(3)], vec![block(1), block(2)]] {
+        let bytes = serialize(&page);
+        file.write_all(&(bytes.len() as u32).to_le_bytes()).unwrap();
+        file.write_all(&bytes).unwrap();
+    }
+
+    let source = FileBlockSource::from_file(&path).unwrap();
+
+    let page = source.next_page(None).await.unwrap();
+    let heights: Vec<u64> = page.blocks.iter().map(|b| b.height).collect();
+    assert_eq!(heights, vec![1, 2, 3]);
+    assert_eq!(page.cursor, Some("3".to_string()));
+
+    let page = source.next_page(Some("1".to_string())).await.unwrap();
+    assert_eq!(page.blocks.len(), 2);
+
+    let page = source.next_page(Some("3".to_string())).await.unwrap();
+    assert!(page.blocks.is_empty());
+    assert_eq!(page.cursor, None);
+
+    assert_eq!(
+        source.block_id(2).await.unwrap(),
+        Some(Bytes32::from([2u8; 32]))
+    );
+    assert_eq!(source.block_id(4).await.unwrap(), None);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_block_archive_can_be_replayed() {
+    let path = std::env::temp_dir().join("test_block_archive.blocks");
+    let _ = std::fs::remove_file(&path);
+
+    let archive = BlockArchive::create(&path).unwrap();
+    archive.append(&[block(1), block(2)]).unwrap();
+    // Already archived blocks aren't written twice.
+    archive.append(&[block(2), block(3)]).unwrap();
+    // A block that replaced one at an archived height supersedes it.
+    let mut reorged = block(3);
+    reorged.id = Bytes32::from([9u8; 32]);
+    archive.append(&[reorged]).unwrap();
+
+    let source = FileBlockSource::from_file(&path).unwrap();
+
+    let page = source.next_page(None).await.unwrap();
+    let heights: Vec<u64> = page.blocks.iter().map(|b| b.height).collect();
+    assert_eq!(heights, vec![1, 2, 3]);
+    assert_eq!(
+        source.block_id(3).await.unwrap(),
+        Some(Bytes32::from([9u8; 32]))
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_cached_block_source_fetches_each_block_once() {
+    let node = CountingBlockSource::new(0..=3);
+    let source = CachedBlockSource::new(&node, 100);
+
+    // The first executor pages through the chain, the second follows it
+    // from its own cursor.
+    for _ in 0..2 {
+        let mut cursor = None;
+        let mut heights = Vec::new();
+        loop {
+            let page = source.next_page(cursor.clone()).await.unwrap();
+            if page.blocks.is_empty() {
+                break;
+            }
+            heights.extend(page.blocks.iter().map(|b| b.height));
+            cursor = page.cursor;
+        }
+        assert_eq!(heights, vec![0, 1, 2, 3]);
+    }
+
+    // Two pages of blocks, plus an empty page for each executor at the tip.
+    assert_eq!(node.pages.load(Ordering::SeqCst), 4);
+
+    // After a reorg, blocks from the fork onwards are fetched again.
+    let mut reorged = block(2);
+    reorged.id = Bytes32::from([9u8; 32]);
+    node.blocks.lock().unwrap()[2] = reorged;
+
+    assert_eq!(
+        source.block_id(2).await.unwrap(),
+        Some(Bytes32::from([9u8; 32]))
+    );
+    let page = source.next_page(Some("1".to_string())).await.unwrap();
+    assert_eq!(page.blocks[0].id, Bytes32::from([9u8; 32]));
+    assert_eq!(node.pages.load(Ordering::SeqCst), 5);
+}
+
+/// Start a node with `count` blocks after genesis, returning a block source
+/// reading from it for each of the given fetch concurrencies.
+async fn fuel_node_block_sources(
+    count: u64,
+    concurrencies: &[usize],
+) -> (FuelService, Vec<FuelNodeBlockSource>) {
+    let node = FuelService::new_node(Config {
+        manual_blocks_enabled: true,
+        ..Config::local_node()
+    })
+    .await
+    .unwrap();
+    FuelClient::from(node.bound_address)
+        .produce_blocks(count, None)
+        .await
+        .unwrap();
+
+    let manifest = Manifest::try_from(SIMPLE_WASM_MANIFEST).unwrap();
+    let sources = concurrencies
+        .iter()
+        .map(|&concurrency| {
+            let config = IndexerConfig {
+                fuel_node: node.bound_address.into(),
+                block_fetch_concurrency: concurrency,
+                ..IndexerConfig::default()
+            };
+            FuelNodeBlockSource::new(&config, &manifest).unwrap()
+        })
+        .collect();
+
+    (node, sources)
+}
+
+fn heights(page: &BlockPage) -> Vec<u64> {
+    page.blocks.iter().map(|b| b.height).collect()
+}
+
+#[tokio::test]
+async fn test_fuel_node_block_source_fetches_following_pages_concurrently() {
+    let page_size = NODE_GRAPHQL_PAGE_SIZE as u64;
+    // Genesis plus three and a half pages of blocks.
+    let head = 3 * page_size + page_size / 2;
+    let (_node, sources) = fuel_node_block_sources(head, &[1, 2, 3, 8]).await;
+    let (sequential, concurrent) = sources.split_first().unwrap();
+
+    let mut expected = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = sequential.next_page(cursor.clone()).await.unwrap();
+        assert!(heights(&page).len() <= NODE_GRAPHQL_PAGE_SIZE);
+        if page.blocks.is_empty() {
+            assert!(page.cursor.is_none());
+            break;
+        }
+        expected.push(page);
+        cursor = expected.last().unwrap().cursor.clone();
+    }
+    assert_eq!(expected.len(), 4);
+    assert_eq!(
+        expected.iter().flat_map(heights).collect::<Vec<_>>(),
+        (0..=head).collect::<Vec<_>>()
+    );
+
+    // Two and three pages at once: each following page starts where the one
+    // before it ends, and the cursor is carried forward to the last of them.
+    let page = concurrent[0].next_page(None).await.unwrap();
+    assert_eq!(heights(&page), (0..2 * page_size).collect::<Vec<_>>());
+    assert_eq!(page.cursor, expected[1].cursor);
+
+    let page = concurrent[1].next_page(None).await.unwrap();
+    assert_eq!(heights(&page), (0..3 * page_size).collect::<Vec<_>>());
+    assert_eq!(page.cursor, expected[2].cursor);
+
+    let page = concurrent[1].next_page(page.cursor).await.unwrap();
+    assert_eq!(heights(&page), heights(&expected[3]));
+    assert_eq!(page.cursor, expected[3].cursor);
+
+    // Eight pages at once: fetching stops at the first page that isn't full,
+    // and the empty pages past the head don't clear the cursor.
+    let page = concurrent[2].next_page(None).await.unwrap();
+    assert_eq!(heights(&page), (0..=head).collect::<Vec<_>>());
+    assert_eq!(page.cursor, Some(head.to_string()));
+
+    let page = concurrent[2]
+        .next_page(Some((head - 1).to_string()))
+        .await
+        .unwrap();
+    assert_eq!(heights(&page), vec![head]);
+    assert_eq!(page.cursor, Some(head.to_string()));
+}
@@ -1,9 +1,11 @@
+use fuel_indexer_database::DbType;
 use fuel_indexer_graphql::{
     arguments::{Comparison, Filter, FilterType, ParsedValue, QueryParams},
     graphql::*,
     queries::{QueryElement, UserQuery},
 };
 use fuel_indexer_schema::db::tables::Schema;
+use serde_json::json;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 
@@ -46,6 +48,7 @@ fn generate_schema() -> Schema {
         types,
         fields,
         foreign_keys: HashMap::new(),
+        versioned: HashSet::from(["thing1".to_string()]),
     }
 }
 
@@ -75,18 +78,29 @@ fn test_query_builder_parses_correctly() {
             }
         }
 
-        {
+        query GetThing1 {
             thing1(id: 4321, filter: { huge_number: { equals: 340282366920938463463374607431768211455 } } ) { account }
         }
     "#;
 
     let schema = generate_schema();
 
+    // Documents with several operations have to name the one to run.
     let query = GraphqlQueryBuilder::new(&schema, good_query);
     assert!(query.is_ok());
-    let q = query.expect("It's ok here").build();
-    assert!(q.is_ok());
-    let q = q.expect("It's ok");
+    match query.expect("It's ok here").build() {
+        Err(GraphqlError::OperationNameRequired) => {}
+        o => panic!("Should have gotten OperationNameRequired, got {o:?}",),
+    }
+
+    let parse = |name: &str| {
+        GraphqlQueryBuilder::new(&schema, good_query)
+            .expect("It's ok here")
+            .operation_name(Some(name.to_string()))
+            .build()
+            .expect("It's ok")
+            .parse(&schema, &DbType::Postgres)
+    };
 
     let expected = vec![
         UserQuery {
@@ -107,13 +121,22 @@ fn test_query_builder_parses_correctly() {
                 filters: vec![Filter {
                     fully_qualified_table_name: "test_namespace_index1.thing2"
                         .to_string(),
-                    filter_type: FilterType::IdSelection(ParsedValue::Number(1234)),
+                    filter_type: FilterType::IdSelection(ParsedValue::UInt8(1234)),
                 }],
                 sorts: vec![],
                 offset: None,
                 limit: None,
+                block: None,
+                group_by: vec![],
+                after: None,
+                before: None,
+                last: None,
             },
             alias: None,
+            versioned: HashSet::new(),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::new(),
         },
         UserQuery {
             elements: vec![
@@ -137,13 +160,22 @@ fn test_query_builder_parses_correctly() {
                 filters: vec![Filter {
                     fully_qualified_table_name: "test_namespace_index1.thing2"
                         .to_string(),
-                    filter_type: FilterType::IdSelection(ParsedValue::Number(84848)),
+                    filter_type: FilterType::IdSelection(ParsedValue::UInt8(84848)),
                 }],
                 sorts: vec![],
                 offset: None,
                 limit: None,
+                block: None,
+                group_by: vec![],
+                after: None,
+                before: None,
+                last: None,
             },
             alias: None,
+            versioned: HashSet::new(),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::from(["test_namespace_index1.thing2.id".to_string()]),
         },
         UserQuery {
             elements: vec![QueryElement::Field {
@@ -158,7 +190,7 @@ fn test_query_builder_parses_correctly() {
                     Filter {
                         fully_qualified_table_name: "test_namespace_index1.thing1"
                             .to_string(),
-                        filter_type: FilterType::IdSelection(ParsedValue::Number(4321)),
+                        filter_type: FilterType::IdSelection(ParsedValue::UInt8(4321)),
                     },
                     Filter {
                         fully_qualified_table_name: "test_namespace_index1.thing1"
@@ -174,12 +206,25 @@ fn test_query_builder_parses_correctly() {
                 sorts: vec![],
                 offset: None,
                 limit: None,
+                block: None,
+                group_by: vec![],
+                after: None,
+                before: None,
+                last: None,
             },
             alias: None,
+            versioned: HashSet::new(),
+            aggregates: Vec::new(),
+            connection: None,
+            u64_columns: HashSet::new(),
         },
     ];
 
-    assert_eq!(q.parse(&schema), expected);
+    let parsed: Vec<UserQuery> = ["GetThing2", "OtherQuery", "GetThing1"]
+        .into_iter()
+        .flat_map(parse)
+        .collect();
+    assert_eq!(parsed, expected);
 
     let bad_query = r#"
         fragment frag1 on BadType{
@@ -200,3 +245,457 @@ fn test_query_builder_parses_correctly() {
         o => panic!("Should have gotten Unrecognized type, got {o:?}",),
     }
 }
+
+#[test]
+fn test_query_builder_parses_block_argument() {
+    let schema = generate_schema();
+
+    let query = r#"
+        {
+            thing1(id: 4321, block: 7) { account }
+        }
+    "#;
+
+    let q = GraphqlQueryBuilder::new(&schema, query)
+        .expect("It's ok here")
+        .build()
+        .expect("It's ok");
+
+    let expected = vec![UserQuery {
+        elements: vec![QueryElement::Field {
+            key: "account".to_string(),
+            value: "thing1.account".to_string(),
+        }],
+        joins: HashMap::new(),
+        namespace_identifier: "test_namespace_index1".to_string(),
+        entity_name: "thing1".to_string(),
+        query_params: QueryParams {
+            filters: vec![Filter {
+                fully_qualified_table_name: "thing1".to_string(),
+                filter_type: FilterType::IdSelection(ParsedValue::UInt8(4321)),
+            }],
+            sorts: vec![],
+            offset: None,
+            limit: None,
+            block: Some(7),
+            group_by: vec![],
+            after: None,
+            before: None,
+            last: None,
+        },
+        alias: None,
+        versioned: HashSet::from(["thing1".to_string()]),
+        aggregates: Vec::new(),
+        connection: None,
+        u64_columns: HashSet::new(),
+    }];
+
+    assert_eq!(q.parse(&schema, &DbType::Postgres), expected);
+
+    let unversioned_query = r#"
+        {
+            thing2(id: 1234, block: 7) { account }
+        }
+    "#;
+
+    match GraphqlQueryBuilder::new(&schema, unversioned_query).and_then(|q| q.build()) {
+        Err(GraphqlError::UnversionedEntity(_)) => (),
+        o => panic!("Should have gotten UnversionedEntity, got {o:?}",),
+    }
+}
+
+#[test]
+fn test_query_builder_substitutes_variables_of_named_operation() {
+    let schema = generate_schema();
+
+    let query = r#"
+        query GetThing1($id: ID!, $filter: Thing1Filter, $block: Int = 7) {
+            thing1(id: $id, filter: $filter, block: $block) { account }
+        }
+
+        query GetThing2 {
+            thing2(id: 1234) { account }
+        }
+    "#;
+
+    let variables = HashMap::from([
+        ("id".to_string(), json!(4321)),
+        ("filter".to_string(), json!({ "huge_number": { "gt": 10 } })),
+    ]);
+
+    let q = GraphqlQueryBuilder::new(&schema, query)
+        .expect("It's ok here")
+        .variables(variables)
+        .operation_name(Some("GetThing1".to_string()))
+        .build()
+        .expect("It's ok");
+
+    let expected = vec![UserQuery {
+        elements: vec![QueryElement::Field {
+            key: "account".to_string(),
+            value: "thing1.account".to_string(),
+        }],
+        joins: HashMap::new(),
+        namespace_identifier: "test_namespace_index1".to_string(),
+        entity_name: "thing1".to_string(),
+        query_params: QueryParams {
+            filters: vec![
+                Filter {
+                    fully_qualified_table_name: "thing1".to_string(),
+                    filter_type: FilterType::IdSelection(ParsedValue::UInt8(4321)),
+                },
+                Filter {
+                    fully_qualified_table_name: "thing1".to_string(),
+                    filter_type: FilterType::Comparison(Comparison::Greater(
+                        "huge_number".to_string(),
+                        ParsedValue::Number(10),
+                    )),
+                },
+            ],
+            sorts: vec![],
+            offset: None,
+            limit: None,
+            block: Some(7),
+            group_by: vec![],
+            after: None,
+            before: None,
+            last: None,
+        },
+        alias: None,
+        versioned: HashSet::from(["thing1".to_string()]),
+        aggregates: Vec::new(),
+        connection: None,
+        u64_columns: HashSet::new(),
+    }];
+
+    assert_eq!(q.parse(&schema, &DbType::Postgres), expected);
+}
+
+#[test]
+fn test_query_builder_substitutes_negative_int_variables() {
+    let schema = generate_schema();
+
+    let query = r#"
+        query GetThing1($min: Int, $first: Int) {
+            thing1(filter: { huge_number: { gt: $min } }, first: $first) { account }
+        }
+    "#;
+
+    let build = |variables: serde_json::Value| {
+        GraphqlQueryBuilder::new(&schema, query)?
+            .variables(serde_json::from_value(variables).unwrap())
+            .build()
+    };
+
+    let q = build(json!({ "min": -5, "first": 10 })).expect("It's ok");
+    let filters = q
+        .parse(&schema, &DbType::Postgres)
+        .remove(0)
+        .query_params
+        .filters;
+    assert_eq!(
+        filters,
+        vec![Filter {
+            fully_qualified_table_name: "test_namespace_index1.thing1".to_string(),
+            filter_type: FilterType::Comparison(Comparison::Greater(
+                "huge_number".to_string(),
+                ParsedValue::SignedNumber(-5),
+            )),
+        }]
+    );
+
+    match build(json!({ "min": 0, "first": -1 })) {
+        Err(GraphqlError::UnsupportedValueType(value)) => assert_eq!(value, "-1"),
+        o => panic!("Should have gotten UnsupportedValueType, got {o:?}",),
+    }
+}
+
+#[test]
+fn test_query_builder_rejects_invalid_variables_and_operations() {
+    let schema = generate_schema();
+
+    let query = r#"
+        query GetThing2($id: ID!, $first: Int, $filter: Thing2Filter) {
+            thing2(id: $id, first: $first, filter: $filter) { account }
+        }
+    "#;
+
+    let build = |variables: serde_json::Value, operation_name: Option<&str>| {
+        GraphqlQueryBuilder::new(&schema, query)?
+            .variables(serde_json::from_value(variables).unwrap())
+            .operation_name(operation_name.map(str::to_string))
+            .build()
+    };
+
+    match build(json!({}), None) {
+        Err(GraphqlError::InvalidVariableValue(name, ty, _)) => {
+            assert_eq!((name.as_str(), ty.as_str()), ("id", "ID!"))
+        }
+        o => panic!("Should have gotten InvalidVariableValue, got {o:?}",),
+    }
+
+    match build(json!({ "id": 1, "first": "ten" }), None) {
+        Err(GraphqlError::InvalidVariableValue(name, ty, _)) => {
+            assert_eq!((name.as_str(), ty.as_str()), ("first", "Int"))
+        }
+        o => panic!("Should have gotten InvalidVariableValue, got {o:?}",),
+    }
+
+    match build(
+        json!({ "id": 1, "filter": { "nope": { "equals": 1 } } }),
+        None,
+    ) {
+        Err(GraphqlError::UnrecognizedArgument(ty, field)) => {
+            assert_eq!((ty.as_str(), field.as_str()), ("Thing2Filter", "nope"))
+        }
+        o => panic!("Should have gotten UnrecognizedArgument, got {o:?}",),
+    }
+
+    match build(json!({ "id": 1 }), Some("GetThing1")) {
+        Err(GraphqlError::UnknownOperation(name)) => assert_eq!(name, "GetThing1"),
+        o => panic!("Should have gotten UnknownOperation, got {o:?}",),
+    }
+
+    let undeclared = "query { thing2(id: $id) { account } }";
+    match GraphqlQueryBuilder::new(&schema, undeclared).and_then(|q| q.build()) {
+        Err(GraphqlError::UndefinedVariable(name)) => assert_eq!(name, "id"),
+        o => panic!("Should have gotten UndefinedVariable, got {o:?}",),
+    }
+}
+
+#[test]
+fn test_query_builder_applies_inline_fragments_and_directives() {
+    let schema = generate_schema();
+
+    let query = r#"
+        query GetThing2($withHash: Boolean!) {
+            thing2(id: 1234) {
+                ... on Thing2 {
+                    account
+                    hash @include(if: $withHash)
+                }
+                ... @skip(if: true) {
+                    hash
+                }
+                id @skip(if: false)
+            }
+            thing1(id: 4321) @include(if: false) {
+                account
+            }
+        }
+    "#;
+
+    let q = GraphqlQueryBuilder::new(&schema, query)
+        .expect("It's ok here")
+        .variables(HashMap::from([("withHash".to_string(), json!(false))]))
+        .build()
+        .expect("It's ok");
+
+    let expected = vec![UserQuery {
+        elements: vec![
+            QueryElement::Field {
+                key: "account".to_string(),
+                value: "test_namespace_index1.thing2.account".to_string(),
+            },
+            QueryElement::Field {
+                key: "id".to_string(),
+                value: "test_namespace_index1.thing2.id".to_string(),
+            },
+        ],
+        joins: HashMap::new(),
+        namespace_identifier: "test_namespace_index1".to_string(),
+        entity_name: "thing2".to_string(),
+        query_params: QueryParams {
+            filters: vec![Filter {
+                fully_qualified_table_name: "test_namespace_index1.thing2".to_string(),
+                filter_type: FilterType::IdSelection(ParsedValue::UInt8(1234)),
+            }],
+            sorts: vec![],
+            offset: None,
+            limit: None,
+            block: None,
+            group_by: vec![],
+            after: None,
+            before: None,
+            last: None,
+        },
+        alias: None,
+        versioned: HashSet::new(),
+        aggregates: Vec::new(),
+        connection: None,
+        u64_columns: HashSet::from(["test_namespace_index1.thing2.id".to_string()]),
+    }];
+
+    assert_eq!(q.parse(&schema, &DbType::Postgres), expected);
+
+    let wrong_type = "query { thing2(id: 1) { ... on Thing1 { account } } }";
+    match GraphqlQueryBuilder::new(&schema, wrong_type).and_then(|q| q.build()) {
+        Err(GraphqlError::InvalidInlineFragment(cond, _)) => assert_eq!(cond, "Thing1"),
+        o => panic!("Should have gotten InvalidInlineFragment, got {o:?}",),
+    }
+
+    let unknown_directive = "query { thing2(id: 1) { account @deprecated } }";
+    match GraphqlQueryBuilder::new(&schema, unknown_directive).and_then(|q| q.build()) {
+        Err(GraphqlError::UnsupportedDirective(name)) => assert_eq!(name, "deprecated"),
+        o => panic!("Should have gotten UnsupportedDirective, got {o:?}",),
+    }
+}
+
+#[test]
+fn test_query_builder_parses_aggregate_queries() {
+    let schema = generate_schema();
+
+    let query = r#"
+        query {
+            thing1Aggregate(filter: { huge_number: { gt: 1 } }, groupBy: [account]) {
+                account
+                count
+                total: sum { huge_number }
+                max { huge_number id }
+            }
+        }
+    "#;
+
+    let q = GraphqlQueryBuilder::new(&schema, query)
+        .expect("It's ok here")
+        .build()
+        .expect("It's ok");
+
+    assert_eq!(
+        q.as_sql(&schema, DbType::Postgres).unwrap(),
+        vec!["SELECT json_build_object('account', test_namespace_index1.thing1.account, 'count', COUNT(*), 'total', json_build_object('huge_number', SUM(test_namespace_index1.thing1.huge_number)), 'max', json_build_object('huge_number', MAX(test_namespace_index1.thing1.huge_number), 'id', MAX(test_namespace_index1.thing1.id))) FROM test_namespace_index1.thing1 WHERE  test_namespace_index1.thing1.huge_number > 1  GROUP BY test_namespace_index1.thing1.account ORDER BY test_namespace_index1.thing1.account".to_string()]
+    );
+    let sql = q.as_sql(&schema, DbType::Sqlite).unwrap().join("");
+    assert!(sql.contains("'id', json(COALESCE(CASE WHEN MAX(test_namespace_index1__thing1.id) IS NULL THEN NULL ELSE printf("));
+
+    let not_numeric = "query { thing2Aggregate { sum { account } } }";
+    match GraphqlQueryBuilder::new(&schema, not_numeric).and_then(|q| q.build()) {
+        Err(GraphqlError::InvalidAggregate(function, field)) => {
+            assert_eq!((function.as_str(), field.as_str()), ("SUM", "account"))
+        }
+        o => panic!("Should have gotten InvalidAggregate, got {o:?}",),
+    }
+
+    let ungrouped = "query { thing2Aggregate { hash count } }";
+    match GraphqlQueryBuilder::new(&schema, ungrouped).and_then(|q| q.build()) {
+        Err(GraphqlError::UngroupedField(field)) => assert_eq!(field, "hash"),
+        o => panic!("Should have gotten UngroupedField, got {o:?}",),
+    }
+
+    let paginated = "query { thing2Aggregate(first: 10) { count } }";
+    match GraphqlQueryBuilder::new(&schema, paginated).and_then(|q| q.build()) {
+        Err(GraphqlError::UnrecognizedArgument(field, arg)) => {
+            assert_eq!((field.as_str(), arg.as_str()), ("thing2Aggregate", "first"))
+        }
+        o => panic!("Should have gotten UnrecognizedArgument, got {o:?}",),
+    }
+}
+
+#[test]
+fn test_query_builder_parses_connection_queries() {
+    let schema = generate_schema();
+
+    // The cursor of the entity with ID 3 and hash "y".
+    let query = r#"
+        query {
+            things: thing2Connection(first: 2, after: "5b2279222c202233225d", order: { desc: hash }) {
+                totalCount
+                pageInfo { hasNextPage endCursor }
+                edges { cursor node { id } }
+                nodes { id hash }
+            }
+        }
+    "#;
+
+    let q = GraphqlQueryBuilder::new(&schema, query)
+        .expect("It's ok here")
+        .build()
+        .expect("It's ok");
+    assert!(q.is_connection());
+
+    let sql = q.as_sql(&schema, DbType::Postgres).unwrap().join("");
+    assert!(sql.contains("SELECT json_build_object('id', test_namespace_index1.thing2.id, 'hash', test_namespace_index1.thing2.hash) AS node"));
+    assert!(sql.contains("WHERE (test_namespace_index1.thing2.hash IS NULL OR test_namespace_index1.thing2.hash < 'y' OR (test_namespace_index1.thing2.hash = 'y' AND test_namespace_index1.thing2.id < 3))"));
+    assert!(sql.contains("ORDER BY test_namespace_index1.thing2.hash DESC NULLS LAST, test_namespace_index1.thing2.id DESC"));
+    assert!(sql.contains("LIMIT 3"));
+    assert!(sql
+        .contains("SELECT json_build_object('things', json_build_object('totalCount', "));
+
+    let sql = q.as_sql(&schema, DbType::Sqlite).unwrap().join("");
+    assert!(sql.contains("lower(hex(json_array(CAST(test_namespace_index1__thing2.hash AS TEXT), CASE WHEN test_namespace_index1__thing2.id IS NULL THEN NULL ELSE printf('%u', (test_namespace_index1__thing2.id | (1 << 63)) & ~(test_namespace_index1__thing2.id & (1 << 63))) END)))"));
+    // SQLite stores IDs offset by 2^63, which cursors and results undo.
+    assert!(sql.contains("test_namespace_index1__thing2.id < -9223372036854775805)"));
+    assert!(sql.contains("'id', json(COALESCE(CASE WHEN test_namespace_index1__thing2.id IS NULL THEN NULL ELSE printf("));
+    assert!(!sql.contains("json_build_object"));
+
+    // The cursor of the entity with ID 4 and no hash.
+    let null_key = r#"query { thing2Connection(before: "5b6e756c6c2c2234225d", order: { asc: hash }) { totalCount } }"#;
+    let q = GraphqlQueryBuilder::new(&schema, null_key)
+        .expect("It's ok here")
+        .build()
+        .expect("It's ok");
+    let sql = q.as_sql(&schema, DbType::Postgres).unwrap().join("");
+    assert!(sql.contains("WHERE (test_namespace_index1.thing2.hash IS NOT NULL OR test_namespace_index1.thing2.id < 4)"));
+
+    let bad_cursor = r#"query { thing2Connection(after: "nope") { totalCount } }"#;
+    match GraphqlQueryBuilder::new(&schema, bad_cursor).and_then(|q| q.build()) {
+        Err(GraphqlError::InvalidCursor(cursor)) => assert_eq!(cursor, "nope"),
+        o => panic!("Should have gotten InvalidCursor, got {o:?}",),
+    }
+
+    // Cursors hold values of the type of the field they're ordered by.
+    let numeric_key = r#"query { thing1Connection(after: "5b223132222c202233225d", order: { asc: huge_number }) { totalCount } }"#;
+    let q = GraphqlQueryBuilder::new(&schema, numeric_key)
+        .expect("It's ok here")
+        .build()
+        .expect("It's ok");
+    let sql = q.as_sql(&schema, DbType::Postgres).unwrap().join("");
+    assert!(sql.contains("test_namespace_index1.thing1.huge_number > 12 OR (test_namespace_index1.thing1.huge_number = 12 AND test_namespace_index1.thing1.id > 3)"));
+
+    // The cursor of the entity with ID 3 and huge_number "1' OR 1=1 --".
+    let bad_key = r#"query { thing1Connection(after: "5b223127204f5220313d31202d2d222c202233225d", order: { asc: huge_number }) { totalCount } }"#;
+    match GraphqlQueryBuilder::new(&schema, bad_key).and_then(|q| q.build()) {
+        Err(GraphqlError::InvalidCursor(_)) => {}
+        o => panic!("Should have gotten InvalidCursor, got {o:?}",),
+    }
+
+    // The cursor of the entity with ID 3 and hash "y' OR 'a'='a".
+    let quoted_key = r#"query { thing2Connection(after: "5b227927204f52202761273d2761222c202233225d", order: { asc: hash }) { totalCount } }"#;
+    let q = GraphqlQueryBuilder::new(&schema, quoted_key)
+        .expect("It's ok here")
+        .build()
+        .expect("It's ok");
+    let sql = q.as_sql(&schema, DbType::Postgres).unwrap().join("");
+    assert!(sql.contains("test_namespace_index1.thing2.hash > 'y'' OR ''a''=''a'"));
+
+    // Cursors of an ordered connection can't be used in an unordered one.
+    let unordered =
+        r#"query { thing2Connection(after: "5b2279222c202233225d") { totalCount } }"#;
+    let q = GraphqlQueryBuilder::new(&schema, unordered)
+        .and_then(|q| q.build())
+        .expect("It's ok");
+    match q.as_sql(&schema, DbType::Postgres) {
+        Err(GraphqlError::InvalidCursor(_)) => {}
+        o => panic!("Should have gotten InvalidCursor, got {o:?}",),
+    }
+
+    let both_ends = "query { thing2Connection(first: 1, last: 1) { totalCount } }";
+    let q = GraphqlQueryBuilder::new(&schema, both_ends)
+        .and_then(|q| q.build())
+        .expect("It's ok");
+    match q.as_sql(&schema, DbType::Postgres) {
+        Err(GraphqlError::ConflictingPagination) => {}
+        o => panic!("Should have gotten ConflictingPagination, got {o:?}",),
+    }
+
+    let offset = "query { thing2Connection(offset: 1) { totalCount } }";
+    match GraphqlQueryBuilder::new(&schema, offset).and_then(|q| q.build()) {
+        Err(GraphqlError::UnrecognizedArgument(field, arg)) => {
+            assert_eq!(
+                (field.as_str(), arg.as_str()),
+                ("thing2Connection", "offset")
+            )
+        }
+        o => panic!("Should have gotten UnrecognizedArgument, got {o:?}",),
+    }
+}
@@ -1,4 +1,6 @@
+mod block_source;
 mod database;
+mod executor;
 mod graphql_schema;
 mod service;
 mod web_api_postgres;
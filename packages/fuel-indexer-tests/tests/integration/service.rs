@@ -1,6 +1,28 @@
 extern crate alloc;
-use fuel_indexer_lib::manifest::Manifest;
-use fuel_indexer_tests::{defaults, fixtures::indexer_service_postgres};
+use fuel_indexer::{
+    prelude::{Arc, Mutex},
+    BlockArchive, Database, FtColumn, IndexerResult,
+};
+use fuel_indexer_database::{
+    queries,
+    types::{IndexAssetType, IndexerStatus},
+    IndexerConnectionPool,
+};
+use fuel_indexer_lib::{
+    defaults::SHADOW_IDENTIFIER_SUFFIX,
+    manifest::{Manifest, Module},
+    utils::{IndexStopRequest, ServiceRequest, ShadowDeployRequest},
+};
+use fuel_indexer_schema::{db::manager::SchemaManager, utils::serialize};
+use fuel_indexer_tests::{
+    assets::{SIMPLE_WASM_SCHEMA, SIMPLE_WASM_WASM},
+    defaults,
+    fixtures::{
+        indexer_config_postgres, indexer_service_from_config, indexer_service_postgres,
+        TestPostgresDb,
+    },
+};
+use fuel_indexer_types::{abi::BlockData, type_id, Bytes32};
 use fuels::prelude::{LoadConfiguration, TxParameters};
 use fuels::{
     macros::abigen,
@@ -10,6 +32,8 @@ use fuels::{
     },
 };
 use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
 
 const SIMPLE_WASM_MANIFEST: &str =
     include_str!("./../../components/indices/simple-wasm/simple_wasm.yaml");
@@ -74,3 +98,430 @@ async fn test_can_trigger_event_from_contract_and_index_emited_event_in_postgres
 
     srvc.run().await;
 }
+
+fn block(height: u64) -> BlockData {
+    BlockData {
+        height,
+        id: Bytes32::from([height as u8; 32]),
+        producer: None,
+        time: 1,
+        transactions: vec![],
+    }
+}
+
+/// The height and last update of the checkpoint recorded under `identifier`.
+async fn checkpoint(test_db: &TestPostgresDb, identifier: &str) -> Option<(i64, String)> {
+    sqlx::query_as(
+        "SELECT block_height, updated_at::text FROM index_checkpoints
+        WHERE namespace = 'test_namespace' AND identifier = $1",
+    )
+    .bind(identifier)
+    .fetch_optional(&test_db.pool)
+    .await
+    .unwrap()
+}
+
+async fn thing1_columns(test_db: &TestPostgresDb, schema: &str) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT column_name::text FROM information_schema.columns
+        WHERE table_schema = $1 AND table_name = 'thing1' ORDER BY column_name",
+    )
+    .bind(schema)
+    .fetch_all(&test_db.pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_service_promotes_blue_green_deployment_once_caught_up_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+    let shadow_identifier = format!("simple_wasm_executor{SHADOW_IDENTIFIER_SUFFIX}");
+
+    let path = std::env::temp_dir().join("test_service_blue_green.blocks");
+    let _ = std::fs::remove_file(&path);
+    BlockArchive::create(&path)
+        .unwrap()
+        .append(&(1..=8).map(block).collect::<Vec<_>>())
+        .unwrap();
+
+    let mut config = indexer_config_postgres(Some(&test_db.url));
+    config.replay_blocks = Some(path.clone());
+    // The running version only stops when it's replaced.
+    config.stop_idle_indexers = false;
+    let (mut service, tx) = indexer_service_from_config(config).await;
+
+    let pool = IndexerConnectionPool::connect(&test_db.url).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    let manifest = Manifest::try_from(SIMPLE_WASM_MANIFEST).unwrap();
+    SchemaManager::new(pool.clone())
+        .new_schema(
+            "test_namespace",
+            "simple_wasm_executor",
+            SIMPLE_WASM_SCHEMA,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+    for (asset_type, bytes) in [
+        (IndexAssetType::Wasm, SIMPLE_WASM_WASM.to_vec()),
+        (
+            IndexAssetType::Manifest,
+            SIMPLE_WASM_MANIFEST.as_bytes().to_vec(),
+        ),
+        (
+            IndexAssetType::Schema,
+            SIMPLE_WASM_SCHEMA.as_bytes().to_vec(),
+        ),
+    ] {
+        queries::register_index_asset(
+            &mut conn,
+            &manifest.namespace,
+            &manifest.identifier,
+            bytes,
+            asset_type,
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    service.register_indices_from_registry().await.unwrap();
+    let service = tokio::spawn(service.run());
+
+    while checkpoint(&test_db, "simple_wasm_executor")
+        .await
+        .map(|c| c.0)
+        != Some(8)
+    {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Only a restarted executor marks the indexer as running again.
+    queries::set_index_status(
+        &mut conn,
+        "test_namespace",
+        "simple_wasm_executor",
+        IndexerStatus::Halted,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let shadow_schema = SIMPLE_WASM_SCHEMA.replace(
+        "    account: Address!\n}",
+        "    account: Address!\n    label: Charfield\n}",
+    );
+    assert_ne!(shadow_schema, SIMPLE_WASM_SCHEMA);
+    let index =
+        queries::index_is_registered(&mut conn, "test_namespace", "simple_wasm_executor")
+            .await
+            .unwrap()
+            .unwrap();
+    queries::put_shadow_deployment(
+        &mut conn,
+        &index.id,
+        SIMPLE_WASM_WASM.to_vec(),
+        SIMPLE_WASM_MANIFEST.as_bytes().to_vec(),
+        shadow_schema.as_bytes().to_vec(),
+    )
+    .await
+    .unwrap();
+    tx.send(ServiceRequest::ShadowDeploy(ShadowDeployRequest {
+        namespace: "test_namespace".to_string(),
+        identifier: "simple_wasm_executor".to_string(),
+    }))
+    .await
+    .unwrap();
+
+    // The blue/green deployment stops once it has caught up with the chain.
+    let shadow_checkpoint = tokio::time::timeout(Duration::from_secs(120), async {
+        loop {
+            let shadow = queries::shadow_deployment_for(
+                &mut conn,
+                "test_namespace",
+                "simple_wasm_executor",
+            )
+            .await
+            .unwrap();
+            if let Some(shadow) = shadow {
+                if shadow.caught_up {
+                    return checkpoint(&test_db, &shadow_identifier).await.unwrap();
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Blue/green deployment didn't catch up.");
+    assert_eq!(shadow_checkpoint.0, 8);
+
+    // It replaces the running version, which has to stop first.
+    tokio::time::timeout(Duration::from_secs(120), async {
+        while queries::shadow_deployment_for(
+            &mut conn,
+            "test_namespace",
+            "simple_wasm_executor",
+        )
+        .await
+        .unwrap()
+        .is_some()
+        {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Blue/green deployment wasn't promoted.");
+
+    let assets = queries::latest_assets_for_index(&mut conn, &index.id)
+        .await
+        .unwrap();
+    assert_eq!(assets.schema.bytes, shadow_schema.as_bytes());
+    assert_eq!(assets.schema.version, 2);
+
+    assert_eq!(
+        thing1_columns(&test_db, "test_namespace_simple_wasm_executor").await,
+        vec!["account", "id", "label", "object"]
+    );
+    assert!(
+        thing1_columns(&test_db, &format!("test_namespace_{shadow_identifier}"))
+            .await
+            .is_empty()
+    );
+
+    // The indexer carries on from where its blue/green deployment stopped,
+    // rather than indexing the chain again.
+    tokio::time::timeout(Duration::from_secs(120), async {
+        loop {
+            let index = queries::index_is_registered(
+                &mut conn,
+                "test_namespace",
+                "simple_wasm_executor",
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            if index.status == IndexerStatus::Running {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Indexer wasn't restarted.");
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(
+        checkpoint(&test_db, "simple_wasm_executor").await,
+        Some(shadow_checkpoint)
+    );
+    assert_eq!(checkpoint(&test_db, &shadow_identifier).await, None);
+
+    tx.send(ServiceRequest::IndexStop(IndexStopRequest {
+        namespace: "test_namespace".to_string(),
+        identifier: "simple_wasm_executor".to_string(),
+    }))
+    .await
+    .unwrap();
+    service.abort();
+    std::fs::remove_file(&path).unwrap();
+}
+
+const REINDEXED_SCHEMA: &str = r#"
+schema {
+    query: QueryRoot
+}
+
+type QueryRoot {
+    height: Height
+}
+
+type Height {
+    id: ID!
+    value: UInt8!
+}
+"#;
+
+async fn index_block_heights(
+    blocks: Vec<BlockData>,
+    db: Arc<Mutex<Database>>,
+) -> IndexerResult<()> {
+    let mut db = db.lock().await;
+    for block in blocks {
+        let columns = vec![
+            FtColumn::ID(Some(block.height)),
+            FtColumn::UInt8(Some(block.height)),
+        ];
+        let bytes = serialize(&columns);
+        db.put_object(
+            type_id("test_namespace_reindexed", "Height"),
+            columns,
+            bytes,
+        )
+        .await
+        .unwrap();
+    }
+    Ok(())
+}
+
+async fn indexed_heights(test_db: &TestPostgresDb) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM test_namespace_reindexed.height")
+        .fetch_one(&test_db.pool)
+        .await
+        .unwrap()
+}
+
+async fn stop_reindexed(tx: &Sender<ServiceRequest>) {
+    tx.send(ServiceRequest::IndexStop(IndexStopRequest {
+        namespace: "test_namespace".to_string(),
+        identifier: "reindexed".to_string(),
+    }))
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_service_reindexes_rows_cleared_by_forced_migration_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+
+    let path = std::env::temp_dir().join("test_service_reindex.blocks");
+    let _ = std::fs::remove_file(&path);
+    BlockArchive::create(&path)
+        .unwrap()
+        .append(&(1..=8).map(block).collect::<Vec<_>>())
+        .unwrap();
+    let schema_path = std::env::temp_dir().join("test_service_reindex.graphql");
+    std::fs::write(&schema_path, REINDEXED_SCHEMA).unwrap();
+
+    let mut manifest = Manifest::try_from(SIMPLE_WASM_MANIFEST).unwrap();
+    manifest.identifier = "reindexed".to_string();
+    manifest.module = Module::Native;
+    manifest.graphql_schema = schema_path.to_str().unwrap().to_string();
+
+    let mut config = indexer_config_postgres(Some(&test_db.url));
+    config.replay_blocks = Some(path.clone());
+    config.stop_idle_indexers = false;
+
+    let wait_for_heights = || async {
+        tokio::time::timeout(Duration::from_secs(120), async {
+            while checkpoint(&test_db, "reindexed").await.map(|c| c.0) != Some(8) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("Indexer didn't catch up.");
+    };
+
+    let (mut service, tx) = indexer_service_from_config(config.clone()).await;
+    service
+        .register_native_index(manifest.clone(), index_block_heights)
+        .await
+        .unwrap();
+    let running = tokio::spawn(service.run());
+    wait_for_heights().await;
+    stop_reindexed(&tx).await;
+    running.abort();
+    assert_eq!(indexed_heights(&test_db).await, 8);
+
+    // Making a column unique replaces it, which removes every row.
+    let forced_schema = REINDEXED_SCHEMA.replace("UInt8!", "UInt8! @unique");
+    let pool = IndexerConnectionPool::connect(&test_db.url).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    let migration = SchemaManager::new(pool.clone())
+        .migrate_schema(
+            "test_namespace",
+            "reindexed",
+            &forced_schema,
+            true,
+            &mut conn,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(migration.clears_rows());
+    assert_eq!(indexed_heights(&test_db).await, 0);
+    assert_eq!(checkpoint(&test_db, "reindexed").await, None);
+
+    // The indexer starts over, rather than from where it had got to.
+    std::fs::write(&schema_path, &forced_schema).unwrap();
+    let (mut service, tx) = indexer_service_from_config(config).await;
+    service
+        .register_native_index(manifest, index_block_heights)
+        .await
+        .unwrap();
+    let running = tokio::spawn(service.run());
+    wait_for_heights().await;
+    stop_reindexed(&tx).await;
+    running.abort();
+    assert_eq!(indexed_heights(&test_db).await, 8);
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&schema_path).unwrap();
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_service_replays_blocks_from_start_block_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+
+    let path = std::env::temp_dir().join("test_service_replay_from_start.blocks");
+    let _ = std::fs::remove_file(&path);
+    BlockArchive::create(&path)
+        .unwrap()
+        .append(&(1..=8).map(block).collect::<Vec<_>>())
+        .unwrap();
+    let schema_path = std::env::temp_dir().join("test_service_replay_from_start.graphql");
+    std::fs::write(&schema_path, REINDEXED_SCHEMA).unwrap();
+
+    let mut manifest = Manifest::try_from(SIMPLE_WASM_MANIFEST).unwrap();
+    manifest.identifier = "reindexed".to_string();
+    manifest.module = Module::Native;
+    manifest.graphql_schema = schema_path.to_str().unwrap().to_string();
+
+    let mut config = indexer_config_postgres(Some(&test_db.url));
+    config.replay_blocks = Some(path.clone());
+    config.stop_idle_indexers = false;
+
+    let wait_for_heights = || async {
+        tokio::time::timeout(Duration::from_secs(120), async {
+            while checkpoint(&test_db, "reindexed").await.map(|c| c.0) != Some(8) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("Indexer didn't catch up.");
+    };
+
+    let (mut service, tx) = indexer_service_from_config(config.clone()).await;
+    service
+        .register_native_index(manifest.clone(), index_block_heights)
+        .await
+        .unwrap();
+    let running = tokio::spawn(service.run());
+    wait_for_heights().await;
+    stop_reindexed(&tx).await;
+    running.abort();
+
+    // A row that only replaying the blocks from the start brings back.
+    sqlx::query("DELETE FROM test_namespace_reindexed.height WHERE id = 3")
+        .execute(&test_db.pool)
+        .await
+        .unwrap();
+    assert_eq!(indexed_heights(&test_db).await, 7);
+
+    config.replay_from_start = true;
+    let (mut service, tx) = indexer_service_from_config(config).await;
+    service
+        .register_native_index(manifest, index_block_heights)
+        .await
+        .unwrap();
+    let running = tokio::spawn(service.run());
+    wait_for_heights().await;
+    stop_reindexed(&tx).await;
+    running.abort();
+    assert_eq!(indexed_heights(&test_db).await, 8);
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&schema_path).unwrap();
+}
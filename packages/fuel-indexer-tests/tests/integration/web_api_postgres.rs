@@ -1,5 +1,11 @@
-use fuel_indexer_lib::config::GraphQLConfig;
+use fuel_indexer::{Database, FtColumn};
+use fuel_indexer_database::{types::IndexAssetType, IndexerConnectionPool};
+use fuel_indexer_lib::{
+    config::GraphQLConfig,
+    manifest::{Manifest, Module as ManifestModule},
+};
 use fuel_indexer_postgres as postgres;
+use fuel_indexer_schema::{db::manager::SchemaManager, utils::serialize};
 use fuel_indexer_tests::assets::{
     SIMPLE_WASM_MANIFEST, SIMPLE_WASM_SCHEMA, SIMPLE_WASM_WASM,
 };
@@ -7,10 +13,19 @@ use fuel_indexer_tests::fixtures::{
     api_server_app_postgres, authenticated_api_server_app_postgres, http_client,
     indexer_service_postgres, TestPostgresDb,
 };
+use fuel_indexer_types::type_id;
+use futures::{SinkExt, StreamExt};
 use hyper::header::CONTENT_TYPE;
 use reqwest::multipart;
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::{net::TcpStream, time::timeout};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, Message as WsMessage},
+    MaybeTlsStream, WebSocketStream,
+};
 
 const SIGNATURE: &str = "cb19384361af5dd7fec2a0052ca49d289f997238ea90590baf47f16ff0a33fb20170a43bd20208ce16daf443bad06dd66c1d1bf73f48b5ae53de682a5731d7d9";
 const NONCE: &str = "ea35be0c98764e7ca06d02067982e3b4";
@@ -81,18 +96,22 @@ async fn test_database_postgres_metrics_properly_increments_counts_when_queries_
         .unwrap();
 
     let categories = resp.split('\n').collect::<Vec<&str>>();
+    let start = categories
+        .iter()
+        .position(|c| c.starts_with("# HELP postgres_execute_query_calls"))
+        .unwrap();
 
     assert_eq!(
-        categories[15],
+        categories[start],
         "# HELP postgres_execute_query_calls Count of calls to postgres execute_query_calls."
     );
     assert_eq!(
-        categories[16],
+        categories[start + 1],
         "# TYPE postgres_execute_query_calls counter"
     );
 
     assert!(
-        categories[17].split(' ').collect::<Vec<&str>>()[1]
+        categories[start + 2].split(' ').collect::<Vec<&str>>()[1]
             .to_string()
             .parse::<i64>()
             .unwrap()
@@ -157,6 +176,271 @@ async fn test_asset_upload_endpoint_properly_adds_assets_to_database_postgres()
     assert!(is_index_registered.is_some());
 }
 
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_asset_upload_endpoint_reverts_assets_of_rejected_migration_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+    let (app, _rx) = api_server_app_postgres(Some(&test_db.url)).await;
+
+    // Bound to its own port, since this test's results depend on its database.
+    let server = axum::Server::bind(&"127.0.0.1:29990".parse().unwrap())
+        .serve(app.into_make_service());
+
+    let srv = tokio::spawn(server);
+
+    let upload = |schema: String| {
+        // The schema comes last, so the other assets are written before it's
+        // migrated.
+        let form = multipart::Form::new()
+            .part(
+                "manifest",
+                multipart::Part::stream(SIMPLE_WASM_MANIFEST)
+                    .file_name("simple_wasm.yaml"),
+            )
+            .part(
+                "wasm",
+                multipart::Part::stream(SIMPLE_WASM_WASM).file_name("simple_wasm.wasm"),
+            )
+            .part(
+                "schema",
+                multipart::Part::stream(schema).file_name("simple_wasm.graphql"),
+            );
+
+        http_client()
+            .post("http://localhost:29990/api/index/test_namespace/simple_wasm_executor")
+            .multipart(form)
+            .header(CONTENT_TYPE, "multipart/form-data".to_owned())
+            .send()
+    };
+
+    let resp = upload(SIMPLE_WASM_SCHEMA.to_string()).await.unwrap();
+    assert!(resp.status().is_success());
+
+    let destructive = SIMPLE_WASM_SCHEMA.replace("    hash: Bytes32! @indexed\n", "");
+    let resp = upload(destructive).await.unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    srv.abort();
+
+    // No connection is left holding the rejected assets in an open transaction.
+    let open_transactions: i64 = sqlx::query_scalar(
+        "SELECT count(*) FROM pg_stat_activity
+        WHERE datname = $1 AND state LIKE 'idle in transaction%'",
+    )
+    .bind(&test_db.db_name)
+    .fetch_one(&test_db.pool)
+    .await
+    .unwrap();
+    assert_eq!(open_transactions, 0);
+
+    let mut conn = test_db.pool.acquire().await.unwrap();
+    let index = postgres::index_is_registered(
+        &mut conn,
+        "test_namespace",
+        "simple_wasm_executor",
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let version =
+        postgres::index_asset_version(&mut conn, &index.id, &IndexAssetType::Wasm)
+            .await
+            .unwrap();
+    assert_eq!(version, 1);
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_asset_upload_endpoint_rejects_invalid_blue_green_deployment_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+    let (app, _rx) = api_server_app_postgres(Some(&test_db.url)).await;
+
+    // Bound to its own port, since this test's results depend on its database.
+    let server = axum::Server::bind(&"127.0.0.1:29991".parse().unwrap())
+        .serve(app.into_make_service());
+
+    let srv = tokio::spawn(server);
+
+    let upload = |query: &'static str, manifest: String, schema: String| {
+        let form = multipart::Form::new()
+            .part(
+                "manifest",
+                multipart::Part::stream(manifest).file_name("simple_wasm.yaml"),
+            )
+            .part(
+                "schema",
+                multipart::Part::stream(schema).file_name("simple_wasm.graphql"),
+            )
+            .part(
+                "wasm",
+                multipart::Part::stream(SIMPLE_WASM_WASM).file_name("simple_wasm.wasm"),
+            );
+
+        http_client()
+            .post(format!(
+                "http://localhost:29991/api/index/test_namespace/simple_wasm_executor{query}"
+            ))
+            .multipart(form)
+            .header(CONTENT_TYPE, "multipart/form-data".to_owned())
+            .send()
+    };
+
+    let resp = upload(
+        "",
+        SIMPLE_WASM_MANIFEST.to_string(),
+        SIMPLE_WASM_SCHEMA.to_string(),
+    )
+    .await
+    .unwrap();
+    assert!(resp.status().is_success());
+
+    let unsupported_schema = SIMPLE_WASM_SCHEMA.replace(
+        "    account: Address!\n}",
+        "    account: Address!\n    accounts: [Address!]\n}",
+    );
+    assert_ne!(unsupported_schema, SIMPLE_WASM_SCHEMA);
+    let resp = upload(
+        "?blue_green=true",
+        SIMPLE_WASM_MANIFEST.to_string(),
+        unsupported_schema,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let resp = upload(
+        "?blue_green=true",
+        "namespace: [".to_string(),
+        SIMPLE_WASM_SCHEMA.to_string(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    srv.abort();
+
+    let mut conn = test_db.pool.acquire().await.unwrap();
+    let shadow = postgres::shadow_deployment_for(
+        &mut conn,
+        "test_namespace",
+        "simple_wasm_executor",
+    )
+    .await
+    .unwrap();
+    assert!(shadow.is_none());
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_graph_query_waits_for_blue_green_swap_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+    let (app, _rx) = api_server_app_postgres(Some(&test_db.url)).await;
+
+    // Bound to its own port, since this test's results depend on its database.
+    let server = axum::Server::bind(&"127.0.0.1:29992".parse().unwrap())
+        .serve(app.into_make_service());
+
+    let srv = tokio::spawn(server);
+
+    let form = multipart::Form::new()
+        .part(
+            "manifest",
+            multipart::Part::stream(SIMPLE_WASM_MANIFEST).file_name("simple_wasm.yaml"),
+        )
+        .part(
+            "schema",
+            multipart::Part::stream(SIMPLE_WASM_SCHEMA).file_name("simple_wasm.graphql"),
+        )
+        .part(
+            "wasm",
+            multipart::Part::stream(SIMPLE_WASM_WASM).file_name("simple_wasm.wasm"),
+        );
+    let resp = http_client()
+        .post("http://localhost:29992/api/index/test_namespace/simple_wasm_executor")
+        .multipart(form)
+        .header(CONTENT_TYPE, "multipart/form-data".to_owned())
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    // Hold the lock taken while an indexer's tables are swapped.
+    let mut swap = test_db.pool.begin().await.unwrap();
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind("test_namespace_simple_wasm_executor")
+        .execute(&mut swap)
+        .await
+        .unwrap();
+
+    let query = tokio::spawn(
+        http_client()
+            .post("http://localhost:29992/api/graph/test_namespace/simple_wasm_executor")
+            .json(&json!({ "query": "query { thing1(id: 1) { id account } }" }))
+            .send(),
+    );
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(!query.is_finished());
+
+    swap.commit().await.unwrap();
+
+    let resp = timeout(Duration::from_secs(10), query)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    srv.abort();
+
+    assert!(resp.status().is_success());
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_status_endpoint_reports_indexer_checkpoint_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+    let (app, _rx) = api_server_app_postgres(Some(&test_db.url)).await;
+
+    // Bound to its own port, since this test's results depend on its database.
+    let server = axum::Server::bind(&"127.0.0.1:29988".parse().unwrap())
+        .serve(app.into_make_service());
+
+    let srv = tokio::spawn(server);
+
+    let mut conn = test_db.pool.acquire().await.unwrap();
+    postgres::register_index(&mut conn, "test_namespace", "simple_wasm_executor", None)
+        .await
+        .unwrap();
+    postgres::put_checkpoint(
+        &mut conn,
+        "test_namespace",
+        "simple_wasm_executor",
+        42,
+        Some("0101010101010101010101010101010101010101010101010101010101010101"),
+        Some("42"),
+    )
+    .await
+    .unwrap();
+
+    let client = http_client();
+    let resp: serde_json::Value = client
+        .get("http://localhost:29988/api/status/test_namespace/simple_wasm_executor")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    srv.abort();
+
+    assert_eq!(resp["block_height"], 42);
+    assert_eq!(resp["cursor"], "42");
+    assert_eq!(resp["completed"], false);
+    assert_eq!(resp["status"], "running");
+    assert_eq!(resp["dead_letters"], serde_json::json!([]));
+}
+
 #[derive(Serialize, Debug)]
 struct SignatureRequest {
     signature: String,
@@ -217,3 +501,276 @@ async fn test_signature_route_validates_signature_expires_nonce_and_creates_jwt(
         .await
         .unwrap();
 }
+
+const SUBSCRIPTION_TEST_SCHEMA: &str = r#"
+schema {
+    query: QueryRoot
+}
+
+type QueryRoot {
+    note: Note
+}
+
+type Note {
+    id: ID!
+    text: Charfield!
+}
+"#;
+
+type TestSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn put_notes(db: &mut Database, notes: &[(u64, &str)]) {
+    db.start_transaction().await.unwrap();
+    for (id, text) in notes {
+        let columns = vec![
+            FtColumn::ID(Some(*id)),
+            FtColumn::Charfield(Some(text.to_string())),
+        ];
+        let bytes = serialize(&columns);
+        db.put_object(type_id("sub_test_notes", "Note"), columns, bytes)
+            .await
+            .unwrap();
+    }
+    db.commit_transaction().await.unwrap();
+}
+
+async fn send_message(socket: &mut TestSocket, message: serde_json::Value) {
+    socket
+        .send(WsMessage::Text(message.to_string()))
+        .await
+        .unwrap();
+}
+
+async fn receive_message(socket: &mut TestSocket) -> serde_json::Value {
+    let message = timeout(Duration::from_secs(10), socket.next())
+        .await
+        .expect("Timed out waiting for a message")
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(&message.into_text().unwrap()).unwrap()
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_subscription_receives_new_and_changed_records_after_commits_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+    let (app, _rx) = api_server_app_postgres(Some(&test_db.url)).await;
+
+    // Bound to its own port, since this test's results depend on its database.
+    let server = axum::Server::bind(&"127.0.0.1:29989".parse().unwrap())
+        .serve(app.into_make_service());
+
+    let srv = tokio::spawn(server);
+
+    let pool = IndexerConnectionPool::connect(&test_db.url).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    SchemaManager::new(pool.clone())
+        .new_schema("sub_test", "notes", SUBSCRIPTION_TEST_SCHEMA, &mut conn)
+        .await
+        .unwrap();
+
+    let mut manifest = Manifest::try_from(SIMPLE_WASM_MANIFEST).unwrap();
+    manifest.namespace = "sub_test".to_string();
+    manifest.identifier = "notes".to_string();
+    manifest.module = ManifestModule::Native;
+
+    let mut db = Database::new(&test_db.url).await.unwrap();
+    db.load_schema(&manifest, None).await.unwrap();
+
+    // Subscriptions can't be answered by a single response.
+    let resp = http_client()
+        .post("http://localhost:29989/api/graph/sub_test/notes")
+        .header(CONTENT_TYPE, "application/graphql".to_owned())
+        .body(r#"{ "query": "subscription { note { id text } }" }"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let mut request = "ws://localhost:29989/api/graph/sub_test/notes"
+        .into_client_request()
+        .unwrap();
+    request.headers_mut().insert(
+        "Sec-WebSocket-Protocol",
+        "graphql-transport-ws".parse().unwrap(),
+    );
+    let (mut socket, response) = connect_async(request).await.unwrap();
+    assert_eq!(
+        response.headers()["Sec-WebSocket-Protocol"],
+        "graphql-transport-ws"
+    );
+
+    send_message(&mut socket, json!({ "type": "connection_init" })).await;
+    assert_eq!(
+        receive_message(&mut socket).await,
+        json!({ "type": "connection_ack" })
+    );
+
+    send_message(
+        &mut socket,
+        json!({
+            "type": "subscribe",
+            "id": "1",
+            "payload": { "query": "subscription { note(order: { asc: id }) { id text } }" },
+        }),
+    )
+    .await;
+    assert_eq!(
+        receive_message(&mut socket).await,
+        json!({ "type": "next", "id": "1", "payload": { "data": [] } })
+    );
+
+    put_notes(&mut db, &[(1, "first"), (2, "second")]).await;
+    assert_eq!(
+        receive_message(&mut socket).await,
+        json!({
+            "type": "next",
+            "id": "1",
+            "payload": { "data": [
+                { "id": 1, "text": "first" },
+                { "id": 2, "text": "second" },
+            ] },
+        })
+    );
+
+    put_notes(&mut db, &[(1, "first"), (2, "changed"), (3, "third")]).await;
+    assert_eq!(
+        receive_message(&mut socket).await,
+        json!({
+            "type": "next",
+            "id": "1",
+            "payload": { "data": [
+                { "id": 2, "text": "changed" },
+                { "id": 3, "text": "third" },
+            ] },
+        })
+    );
+
+    srv.abort();
+}
+
+const NULLABLE_SORT_TEST_SCHEMA: &str = r#"
+schema {
+    query: QueryRoot
+}
+
+type QueryRoot {
+    note: Note
+}
+
+type Note {
+    id: ID!
+    text: Charfield
+}
+"#;
+
+/// Read a whole connection of notes two at a time, following the cursors of
+/// each page in the direction given by `first` or `last`.
+async fn page_through_notes(order: &str, pagination: &str) -> Vec<u64> {
+    let (size, cursor_arg, cursor_field, more_field) = match pagination {
+        "first" => ("first: 2", "after", "endCursor", "hasNextPage"),
+        _ => ("last: 2", "before", "startCursor", "hasPreviousPage"),
+    };
+
+    let mut ids = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let cursor_arg = cursor
+            .as_ref()
+            .map(|c| format!(", {cursor_arg}: \"{c}\""))
+            .unwrap_or_default();
+        let query = format!(
+            "query {{ noteConnection({size}{cursor_arg}, order: {{ {order}: text }}) {{ pageInfo {{ {more_field} {cursor_field} }} nodes {{ id }} }} }}"
+        );
+        let resp: serde_json::Value = http_client()
+            .post("http://localhost:29993/api/graph/null_test/notes")
+            .json(&json!({ "query": query }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        let connection = &resp["data"]["noteConnection"];
+        let mut page: Vec<u64> = connection["nodes"]
+            .as_array()
+            .unwrap_or_else(|| panic!("Unexpected response: {resp}"))
+            .iter()
+            .map(|node| node["id"].as_u64().unwrap())
+            .collect();
+        match pagination {
+            "first" => ids.extend(page),
+            _ => {
+                page.extend(ids);
+                ids = page;
+            }
+        }
+
+        if connection["pageInfo"][more_field] != json!(true) {
+            return ids;
+        }
+        cursor = connection["pageInfo"][cursor_field]
+            .as_str()
+            .map(str::to_string);
+    }
+}
+
+#[tokio::test]
+#[cfg(all(feature = "postgres"))]
+async fn test_connection_pages_through_records_without_sorted_field_postgres() {
+    let test_db = TestPostgresDb::new().await.unwrap();
+    let (app, _rx) = api_server_app_postgres(Some(&test_db.url)).await;
+
+    // Bound to its own port, since this test's results depend on its database.
+    let server = axum::Server::bind(&"127.0.0.1:29993".parse().unwrap())
+        .serve(app.into_make_service());
+
+    let srv = tokio::spawn(server);
+
+    let pool = IndexerConnectionPool::connect(&test_db.url).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    SchemaManager::new(pool.clone())
+        .new_schema("null_test", "notes", NULLABLE_SORT_TEST_SCHEMA, &mut conn)
+        .await
+        .unwrap();
+
+    let mut manifest = Manifest::try_from(SIMPLE_WASM_MANIFEST).unwrap();
+    manifest.namespace = "null_test".to_string();
+    manifest.identifier = "notes".to_string();
+    manifest.module = ManifestModule::Native;
+
+    let mut db = Database::new(&test_db.url).await.unwrap();
+    db.load_schema(&manifest, None).await.unwrap();
+
+    db.start_transaction().await.unwrap();
+    for (id, text) in [
+        (1, Some("b")),
+        (2, None),
+        (3, Some("a")),
+        (4, None),
+        (5, Some("b")),
+        (6, Some("c")),
+    ] {
+        let columns = vec![
+            FtColumn::ID(Some(id)),
+            FtColumn::Charfield(text.map(str::to_string)),
+        ];
+        let bytes = serialize(&columns);
+        db.put_object(type_id("null_test_notes", "Note"), columns, bytes)
+            .await
+            .unwrap();
+    }
+    db.commit_transaction().await.unwrap();
+
+    // Notes without any text come last, whichever way they're ordered.
+    let ascending = vec![3, 1, 5, 6, 2, 4];
+    assert_eq!(page_through_notes("asc", "first").await, ascending);
+    assert_eq!(page_through_notes("asc", "last").await, ascending);
+
+    let descending = vec![6, 5, 1, 3, 4, 2];
+    assert_eq!(page_through_notes("desc", "first").await, descending);
+    assert_eq!(page_through_notes("desc", "last").await, descending);
+
+    srv.abort();
+}
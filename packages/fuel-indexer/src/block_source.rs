@@ -0,0 +1,637 @@
+use crate::{
+    full_block::FullBlockClient, IndexerConfig, IndexerError, IndexerResult, Manifest,
+};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use fuel_core_client::client::{
+    types::{TransactionResponse, TransactionStatus as GqlTransactionStatus},
+    FuelClient,
+};
+use fuel_indexer_lib::defaults::{MAX_REORG_DEPTH, NODE_GRAPHQL_PAGE_SIZE};
+use fuel_indexer_types::{
+    abi::{BlockData, TransactionData},
+    tx::{Receipt, TransactionStatus, TxId},
+    Bytes32,
+};
+use futures::future::try_join_all;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::info;
+
+/// A page of blocks, along with the cursor from which the following page
+/// should be requested. A `None` cursor means there were no new blocks.
+#[derive(Debug, Default)]
+pub struct BlockPage {
+    pub blocks: Vec<BlockData>,
+    pub cursor: Option<String>,
+}
+
+/// Anything that can feed blocks to an executor.
+///
+/// Cursors are block heights encoded as strings, the same as the cursors
+/// returned by the Fuel node's `blocks` query.
+#[async_trait]
+pub trait BlockSource: Send + Sync {
+    /// Fetch the page of blocks following `cursor`.
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage>;
+
+    /// Fetch at most `limit` blocks following `cursor`, in a single request.
+    ///
+    /// Unlike `next_page`, this never fetches further pages concurrently, so
+    /// callers that fetch in parallel can bound the requests in flight.
+    async fn page(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> IndexerResult<BlockPage> {
+        let mut page = self.next_page(cursor).await?;
+        if page.blocks.len() > limit {
+            page.blocks.truncate(limit);
+            page.cursor = page.blocks.last().map(|b| b.height.to_string());
+        }
+        Ok(page)
+    }
+
+    /// Return the id of the canonical block at `height`, if there is one.
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>>;
+
+    /// Return the height of the latest block, if it is known.
+    async fn head_height(&self) -> IndexerResult<Option<u64>> {
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl<T: BlockSource + ?Sized> BlockSource for Box<T> {
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage> {
+        (**self).next_page(cursor).await
+    }
+
+    async fn page(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> IndexerResult<BlockPage> {
+        (**self).page(cursor, limit).await
+    }
+
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>> {
+        (**self).block_id(height).await
+    }
+
+    async fn head_height(&self) -> IndexerResult<Option<u64>> {
+        (**self).head_height().await
+    }
+}
+
+#[async_trait]
+impl<T: BlockSource + ?Sized> BlockSource for Arc<T> {
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage> {
+        (**self).next_page(cursor).await
+    }
+
+    async fn page(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> IndexerResult<BlockPage> {
+        (**self).page(cursor, limit).await
+    }
+
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>> {
+        (**self).block_id(height).await
+    }
+
+    async fn head_height(&self) -> IndexerResult<Option<u64>> {
+        (**self).head_height().await
+    }
+}
+
+/// Polls a Fuel node's GraphQL API for blocks.
+///
+/// Each page of blocks is fetched, along with every transaction, status, and
+/// receipt, in a single query. While catching up, up to `concurrency` pages
+/// are fetched at once.
+pub struct FuelNodeBlockSource {
+    client: FuelClient,
+    full_blocks: FullBlockClient,
+    page_size: usize,
+    concurrency: usize,
+}
+
+impl FuelNodeBlockSource {
+    /// The address of the Fuel node an indexer reads from.
+    pub fn fuel_node_addr(config: &IndexerConfig, manifest: &Manifest) -> String {
+        if config.indexer_net_config {
+            manifest
+                .fuel_client
+                .clone()
+                .unwrap_or(config.fuel_node.to_string())
+        } else {
+            config.fuel_node.to_string()
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn new(config: &IndexerConfig, manifest: &Manifest) -> IndexerResult<Self> {
+        let fuel_node_addr = Self::fuel_node_addr(config, manifest);
+
+        info!("Subscribing to Fuel node at {fuel_node_addr}");
+
+        let client = FuelClient::from_str(&fuel_node_addr)
+            .map_err(|e| IndexerError::NodeConnectionError(e.to_string()))?;
+        let full_blocks = FullBlockClient::new(&fuel_node_addr)?;
+
+        Ok(Self {
+            client,
+            full_blocks,
+            page_size: NODE_GRAPHQL_PAGE_SIZE,
+            concurrency: config.block_fetch_concurrency.max(1),
+        })
+    }
+
+    async fn fetch_page(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> IndexerResult<BlockPage> {
+        let (results, cursor) = self
+            .full_blocks
+            .full_blocks(cursor, limit.min(self.page_size))
+            .await?;
+
+        let mut blocks = Vec::with_capacity(results.len());
+        for block in results.into_iter() {
+            let (block, full_transactions) = block.into_parts();
+            let producer = block.block_producer().map(|pk| pk.hash());
+
+            let mut transactions = Vec::with_capacity(full_transactions.len());
+            for transaction in full_transactions {
+                let (id, mut transaction) = transaction.into_parts();
+
+                let receipts = transaction
+                    .receipts
+                    .take()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Receipt::try_from)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| IndexerError::BlockSourceError(e.to_string()))?;
+
+                let TransactionResponse {
+                    transaction,
+                    status,
+                } = TransactionResponse::try_from(transaction)
+                    .map_err(|e| IndexerError::BlockSourceError(e.to_string()))?;
+
+                transactions.push(TransactionData {
+                    receipts,
+                    status: transaction_status(status),
+                    transaction,
+                    id: TxId::from(id),
+                });
+            }
+
+            blocks.push(BlockData {
+                height: block.header.height.0,
+                id: Bytes32::from(block.id),
+                producer,
+                time: block.header.time.0.to_unix(),
+                transactions,
+            });
+        }
+
+        Ok(BlockPage { blocks, cursor })
+    }
+}
+
+#[async_trait]
+impl BlockSource for FuelNodeBlockSource {
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage> {
+        let mut page = self.fetch_page(cursor, self.page_size).await?;
+
+        // A full page means we're behind the chain head, so fetch the pages
+        // that follow it concurrently. Cursors are block heights, so the
+        // cursor for each of those pages is known up front.
+        let behind = page.blocks.len() == self.page_size && self.concurrency > 1;
+        let after = page.cursor.as_deref().and_then(|c| c.parse::<u64>().ok());
+        if let (true, Some(after)) = (behind, after) {
+            let pages = try_join_all((0..self.concurrency - 1).map(|i| {
+                let cursor = after + (i * self.page_size) as u64;
+                self.fetch_page(Some(cursor.to_string()), self.page_size)
+            }))
+            .await?;
+
+            // Stop at the first page that isn't full, so blocks that were
+            // produced while these were in flight can't leave a gap.
+            for next in pages {
+                let is_full = next.blocks.len() == self.page_size;
+                page.blocks.extend(next.blocks);
+                if next.cursor.is_some() {
+                    page.cursor = next.cursor;
+                }
+                if !is_full {
+                    break;
+                }
+            }
+        }
+
+        Ok(page)
+    }
+
+    async fn page(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> IndexerResult<BlockPage> {
+        self.fetch_page(cursor, limit).await
+    }
+
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>> {
+        Ok(self
+            .client
+            .block_by_height(height)
+            .await?
+            .map(|block| Bytes32::from(block.id)))
+    }
+
+    async fn head_height(&self) -> IndexerResult<Option<u64>> {
+        let chain = self.client.chain_info().await?;
+        Ok(Some(chain.latest_block.header.height.0))
+    }
+}
+
+// NOTE: https://github.com/FuelLabs/fuel-indexer/issues/286
+fn transaction_status(status: GqlTransactionStatus) -> TransactionStatus {
+    match status {
+        GqlTransactionStatus::Success { block_id, time, .. } => {
+            TransactionStatus::Success {
+                block_id,
+                time: Utc.timestamp_opt(time.to_unix(), 0).single().unwrap(),
+            }
+        }
+        GqlTransactionStatus::Failure {
+            block_id,
+            time,
+            reason,
+            ..
+        } => TransactionStatus::Failure {
+            block_id,
+            time: Utc.timestamp_opt(time.to_unix(), 0).single().unwrap(),
+            reason,
+        },
+        GqlTransactionStatus::Submitted { submitted_at } => {
+            TransactionStatus::Submitted {
+                submitted_at: Utc
+                    .timestamp_opt(submitted_at.to_unix(), 0)
+                    .single()
+                    .unwrap(),
+            }
+        }
+        GqlTransactionStatus::SqueezedOut { reason } => {
+            TransactionStatus::SqueezedOut { reason }
+        }
+    }
+}
+
+/// Serves blocks that were previously written to a file.
+///
+/// The file is a sequence of pages, each a little-endian `u32` length
+/// followed by a bincode-serialized `Vec<BlockData>`.
+pub struct FileBlockSource {
+    blocks: Vec<BlockData>,
+    page_size: usize,
+}
+
+impl FileBlockSource {
+    #[allow(clippy::result_large_err)]
+    pub fn from_file(path: impl AsRef<Path>) -> IndexerResult<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut blocks: Vec<BlockData> = Vec::new();
+        let mut rest = bytes.as_slice();
+        while !rest.is_empty() {
+            if rest.len() < 4 {
+                return Err(IndexerError::BlockSourceError(
+                    "Truncated page length.".to_string(),
+                ));
+            }
+            let (len, tail) = rest.split_at(4);
+            let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+            if tail.len() < len {
+                return Err(IndexerError::BlockSourceError(
+                    "Truncated page.".to_string(),
+                ));
+            }
+            let (page, tail) = tail.split_at(len);
+            let page: Vec<BlockData> = bincode::deserialize(page)
+                .map_err(|e| IndexerError::BlockSourceError(e.to_string()))?;
+            blocks.extend(page);
+            rest = tail;
+        }
+
+        // If a height was written more than once, the last write wins.
+        blocks.reverse();
+        blocks.sort_by_key(|b| b.height);
+        blocks.dedup_by_key(|b| b.height);
+
+        Ok(Self {
+            blocks,
+            page_size: NODE_GRAPHQL_PAGE_SIZE,
+        })
+    }
+}
+
+#[async_trait]
+impl BlockSource for FileBlockSource {
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage> {
+        let after = match cursor {
+            Some(c) => c
+                .parse::<u64>()
+                .map_err(|e| IndexerError::BlockSourceError(e.to_string()))?,
+            None => 0,
+        };
+
+        let start = self.blocks.partition_point(|b| b.height <= after);
+        let blocks: Vec<BlockData> = self.blocks[start..]
+            .iter()
+            .take(self.page_size)
+            .cloned()
+            .collect();
+        let cursor = blocks.last().map(|b| b.height.to_string());
+
+        Ok(BlockPage { blocks, cursor })
+    }
+
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>> {
+        Ok(self
+            .blocks
+            .binary_search_by_key(&height, |b| b.height)
+            .ok()
+            .map(|i| self.blocks[i].id))
+    }
+
+    async fn head_height(&self) -> IndexerResult<Option<u64>> {
+        Ok(self.blocks.last().map(|b| b.height))
+    }
+}
+
+/// An append-only file of block pages, in the format read by [`FileBlockSource`].
+///
+/// A single archive can be shared by every executor in a service. Blocks that
+/// were recently written with the same id are skipped, so executors indexing
+/// the same range don't duplicate them; a block at an already written height
+/// but with a new id (i.e. after a reorg) is written again and supersedes the
+/// earlier one on replay.
+pub struct BlockArchive {
+    file: Mutex<File>,
+    recent: Mutex<BTreeMap<u64, Bytes32>>,
+}
+
+impl BlockArchive {
+    #[allow(clippy::result_large_err)]
+    pub fn create(path: impl AsRef<Path>) -> IndexerResult<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+            recent: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Append the blocks not already in the archive as a single page.
+    #[allow(clippy::result_large_err)]
+    pub fn append(&self, blocks: &[BlockData]) -> IndexerResult<()> {
+        let mut recent = self.recent.lock().expect("Archive lock poisoned.");
+        let page: Vec<&BlockData> = blocks
+            .iter()
+            .filter(|b| recent.get(&b.height) != Some(&b.id))
+            .collect();
+
+        if page.is_empty() {
+            return Ok(());
+        }
+
+        let bytes = bincode::serialize(&page)
+            .map_err(|e| IndexerError::BlockSourceError(e.to_string()))?;
+        let len = u32::try_from(bytes.len()).map_err(|_| {
+            IndexerError::BlockSourceError("Page too large to archive.".to_string())
+        })?;
+
+        {
+            let mut file = self.file.lock().expect("Archive lock poisoned.");
+            file.write_all(&len.to_le_bytes())?;
+            file.write_all(&bytes)?;
+            file.flush()?;
+        }
+
+        for block in page {
+            recent.insert(block.height, block.id);
+        }
+        if let Some(&tip) = recent.keys().next_back() {
+            let floor = tip.saturating_sub(MAX_REORG_DEPTH);
+            *recent = recent.split_off(&floor);
+        }
+
+        Ok(())
+    }
+}
+
+/// Wraps another source and writes every page it serves to a [`BlockArchive`].
+pub struct RecordingBlockSource<S> {
+    inner: S,
+    archive: Arc<BlockArchive>,
+}
+
+impl<S: BlockSource> RecordingBlockSource<S> {
+    pub fn new(inner: S, archive: Arc<BlockArchive>) -> Self {
+        Self { inner, archive }
+    }
+}
+
+#[async_trait]
+impl<S: BlockSource> BlockSource for RecordingBlockSource<S> {
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage> {
+        let page = self.inner.next_page(cursor).await?;
+        if !page.blocks.is_empty() {
+            self.archive.append(&page.blocks)?;
+        }
+        Ok(page)
+    }
+
+    async fn page(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> IndexerResult<BlockPage> {
+        let page = self.inner.page(cursor, limit).await?;
+        if !page.blocks.is_empty() {
+            self.archive.append(&page.blocks)?;
+        }
+        Ok(page)
+    }
+
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>> {
+        self.inner.block_id(height).await
+    }
+
+    async fn head_height(&self) -> IndexerResult<Option<u64>> {
+        self.inner.head_height().await
+    }
+}
+
+/// Shares the blocks fetched from one source between many executors.
+///
+/// Recently fetched blocks are cached, so executors reading the same range
+/// from their own cursors only cause it to be fetched once. Concurrent fetches
+/// from the same cursor are coalesced, so executors at the same height don't
+/// race to fetch the same page, while fetches from different cursors still run
+/// in parallel.
+pub struct CachedBlockSource<S> {
+    inner: S,
+    cache: Mutex<BTreeMap<u64, BlockData>>,
+    fetching: Mutex<HashMap<Option<String>, Arc<AsyncMutex<()>>>>,
+    capacity: usize,
+    page_size: usize,
+}
+
+impl<S: BlockSource> CachedBlockSource<S> {
+    pub fn new(inner: S, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(BTreeMap::new()),
+            fetching: Mutex::new(HashMap::new()),
+            capacity,
+            page_size: NODE_GRAPHQL_PAGE_SIZE,
+        }
+    }
+
+    /// At most `limit` of the cached blocks directly following `cursor`, if
+    /// there are any.
+    #[allow(clippy::result_large_err)]
+    fn cached_page(
+        &self,
+        cursor: &Option<String>,
+        limit: usize,
+    ) -> IndexerResult<Option<BlockPage>> {
+        let start = match cursor {
+            Some(c) => {
+                c.parse::<u64>()
+                    .map_err(|e| IndexerError::BlockSourceError(e.to_string()))?
+                    + 1
+            }
+            None => 0,
+        };
+
+        let cache = self.cache.lock().expect("Block cache lock poisoned.");
+        let blocks: Vec<BlockData> = cache
+            .range(start..)
+            .zip(start..)
+            .take_while(|((height, _), expected)| *height == expected)
+            .take(self.page_size.min(limit))
+            .map(|((_, block), _)| block.clone())
+            .collect();
+
+        if blocks.is_empty() {
+            return Ok(None);
+        }
+
+        let cursor = blocks.last().map(|b| b.height.to_string());
+        Ok(Some(BlockPage { blocks, cursor }))
+    }
+
+    fn insert(&self, blocks: &[BlockData]) {
+        let mut cache = self.cache.lock().expect("Block cache lock poisoned.");
+        for block in blocks {
+            if matches!(cache.get(&block.height), Some(cached) if cached.id != block.id) {
+                // The chain was reorganized, so anything cached above is stale.
+                cache.split_off(&block.height);
+            }
+            cache.insert(block.height, block.clone());
+        }
+
+        while cache.len() > self.capacity {
+            cache.pop_first();
+        }
+    }
+
+    /// Serve at most `limit` blocks following `cursor` from the cache, or
+    /// fetch them from the inner source: as `page` does if `single_page` is
+    /// set, and as `next_page` does otherwise.
+    async fn fetch(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+        single_page: bool,
+    ) -> IndexerResult<BlockPage> {
+        if let Some(page) = self.cached_page(&cursor, limit)? {
+            return Ok(page);
+        }
+
+        let fetch = self
+            .fetching
+            .lock()
+            .expect("Block cache lock poisoned.")
+            .entry(cursor.clone())
+            .or_default()
+            .clone();
+        let _fetching = fetch.lock().await;
+
+        // Another executor may have fetched this page while we waited.
+        if let Some(page) = self.cached_page(&cursor, limit)? {
+            return Ok(page);
+        }
+
+        let page = match single_page {
+            true => self.inner.page(cursor.clone(), limit).await,
+            false => self.inner.next_page(cursor.clone()).await,
+        };
+        if let Ok(page) = &page {
+            self.insert(&page.blocks);
+        }
+        self.fetching
+            .lock()
+            .expect("Block cache lock poisoned.")
+            .remove(&cursor);
+
+        page
+    }
+}
+
+#[async_trait]
+impl<S: BlockSource> BlockSource for CachedBlockSource<S> {
+    async fn next_page(&self, cursor: Option<String>) -> IndexerResult<BlockPage> {
+        self.fetch(cursor, usize::MAX, false).await
+    }
+
+    async fn page(
+        &self,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> IndexerResult<BlockPage> {
+        self.fetch(cursor, limit, true).await
+    }
+
+    async fn block_id(&self, height: u64) -> IndexerResult<Option<Bytes32>> {
+        let id = self.inner.block_id(height).await?;
+
+        let mut cache = self.cache.lock().expect("Block cache lock poisoned.");
+        if matches!(cache.get(&height), Some(cached) if Some(cached.id) != id) {
+            cache.split_off(&height);
+        }
+
+        Ok(id)
+    }
+
+    async fn head_height(&self) -> IndexerResult<Option<u64>> {
+        self.inner.head_height().await
+    }
+}
@@ -1,23 +1,88 @@
 use crate::ffi;
 use crate::{IndexerError, IndexerResult, Manifest};
 use fuel_indexer_database::{
-    queries, types::IdCol, IndexerConnection, IndexerConnectionPool,
+    queries,
+    types::{BindValue, ColumnInfo, IdCol, IndexerStatus, OnDelete},
+    DbType, IndexerConnection, IndexerConnectionPool,
 };
-use fuel_indexer_schema::FtColumn;
-use std::collections::HashMap;
+use fuel_indexer_lib::defaults::SHADOW_IDENTIFIER_SUFFIX;
+use fuel_indexer_schema::{
+    db::tables::SchemaBuilder,
+    utils::{deserialize, inject_native_entities_into_schema, schema_version, serialize},
+    EntityFilter, FtColumn,
+};
+use fuel_indexer_types::Bytes32;
+use std::collections::{HashMap, HashSet};
 use tracing::error;
 use wasmer::Instance;
 
+/// An entity saved in the open transaction but not yet written.
+#[derive(Clone, Debug)]
+struct PendingObject {
+    columns: Vec<FtColumn>,
+    bytes: Vec<u8>,
+}
+
+/// Entities saved in the open transaction, by type and ID, to be written in
+/// bulk when the transaction commits. Saving an entity that's already pending
+/// replaces it, so each entity is written at most once per transaction.
+type EntityCache = HashMap<i64, HashMap<u64, PendingObject>>;
+
+/// Set `column` to null in the serialized objects of the rows of `table` with
+/// `object_ids`, which the database has set to null in the column itself.
+async fn null_object_column(
+    conn: &mut IndexerConnection,
+    table: &str,
+    columns: &[String],
+    column: &str,
+    object_ids: &[u64],
+) -> IndexerResult<()> {
+    let position = match columns.iter().position(|c| c == column) {
+        Some(position) => position,
+        None => return Ok(()),
+    };
+
+    for &object_id in object_ids {
+        let bytes = queries::get_object(conn, table, object_id).await?;
+        let mut object: Vec<FtColumn> =
+            deserialize(&bytes).map_err(IndexerError::InvalidObject)?;
+        if let Some(value) = object.get_mut(position) {
+            *value = value.to_null();
+        }
+        queries::set_object_bytes(conn, table, object_id, serialize(&object)).await?;
+    }
+
+    Ok(())
+}
+
+/// Rows of each table that deleting an entity removes, or updates through
+/// `ON DELETE SET NULL` foreign keys, along with the column set to null.
+#[derive(Debug, Default)]
+struct DeletedRows {
+    deleted: Vec<(String, Vec<u64>)>,
+    nulled: Vec<(String, String, Vec<u64>)>,
+}
+
 /// Database for an executor instance, with schema info.
 #[derive(Debug)]
 pub struct Database {
     pub pool: IndexerConnectionPool,
     stashed: Option<IndexerConnection>,
     pub namespace: String,
+    /// Identifier that the indexer's tables and progress are kept under.
     pub identifier: String,
     pub version: String,
     pub schema: HashMap<String, Vec<String>>,
     pub tables: HashMap<i64, String>,
+    /// Tables that keep the history of their entities.
+    versioned: HashSet<String>,
+    block_height: Option<u64>,
+    cache: EntityCache,
+    /// Whether this is a blue/green deployment, indexing into shadow tables.
+    shadow: bool,
+    /// An entity operation of a native handler that failed in the open
+    /// transaction, which fails the batch once the handler returns.
+    handler_error: Option<IndexerError>,
 }
 
 // TODO: Use mutex
@@ -36,27 +101,108 @@ impl Database {
             version: Default::default(),
             schema: Default::default(),
             tables: Default::default(),
+            versioned: Default::default(),
+            block_height: None,
+            cache: Default::default(),
+            shadow: false,
+            handler_error: None,
         })
     }
 
     pub async fn start_transaction(&mut self) -> IndexerResult<usize> {
         let mut conn = self.pool.acquire().await?;
-        let result = queries::execute_query(&mut conn, "BEGIN".into()).await?;
+        // A SQLite transaction that starts by reading fails when it comes to
+        // write after another connection has, so it takes the write lock first.
+        let begin = match self.pool.database_type() {
+            DbType::Postgres => "BEGIN",
+            DbType::Sqlite => "BEGIN IMMEDIATE",
+        };
+        let result = queries::execute_query(&mut conn, begin.into()).await?;
 
         self.stashed = Some(conn);
+        self.cache.clear();
+        self.handler_error = None;
 
         Ok(result)
     }
 
+    /// Fail the batch of the open transaction once the native handler running
+    /// it returns, as a failed entity operation traps a WASM handler.
+    pub fn fail_handler(&mut self, error: IndexerError) {
+        self.handler_error.get_or_insert(error);
+    }
+
+    /// The error that failed the native handler of the open transaction, if
+    /// any.
+    pub fn take_handler_error(&mut self) -> Option<IndexerError> {
+        self.handler_error.take()
+    }
+
     pub async fn commit_transaction(&mut self) -> IndexerResult<usize> {
+        if let Err(e) = self.flush_objects().await {
+            self.revert_transaction().await?;
+            return Err(e);
+        }
+
         let mut conn = self
             .stashed
             .take()
             .ok_or(IndexerError::NoTransactionError)?;
-        Ok(queries::execute_query(&mut conn, "COMMIT".into()).await?)
+        let result = queries::execute_query(&mut conn, "COMMIT".into()).await?;
+
+        // The commit already succeeded, so subscribers missing a notification
+        // isn't worth failing the batch over.
+        if let Err(e) =
+            queries::notify_commit(&mut conn, &self.namespace, &self.identifier).await
+        {
+            error!("Failed to notify commit: {e:?}");
+        }
+
+        Ok(result)
+    }
+
+    /// Checkpoint the last block of the batch being indexed and commit, so the
+    /// checkpoint only ever covers writes that were actually committed.
+    pub async fn commit_batch(
+        &mut self,
+        last: Option<(u64, Bytes32)>,
+    ) -> IndexerResult<usize> {
+        if let Some((height, id)) = last {
+            if let Err(e) = self.put_checkpoint(height, Some(id)).await {
+                self.revert_transaction().await?;
+                return Err(e);
+            }
+        }
+
+        self.commit_transaction().await
+    }
+
+    async fn put_checkpoint(
+        &mut self,
+        block_height: u64,
+        block_id: Option<Bytes32>,
+    ) -> IndexerResult<usize> {
+        let conn = self
+            .stashed
+            .as_mut()
+            .ok_or(IndexerError::NoTransactionError)?;
+
+        let block_id = block_id.map(|id| id.to_string());
+        let cursor = block_height.to_string();
+
+        Ok(queries::put_checkpoint(
+            conn,
+            &self.namespace,
+            &self.identifier,
+            block_height,
+            block_id.as_deref(),
+            Some(&cursor),
+        )
+        .await?)
     }
 
     pub async fn revert_transaction(&mut self) -> IndexerResult<usize> {
+        self.cache.clear();
         let mut conn = self
             .stashed
             .take()
@@ -64,88 +210,530 @@ impl Database {
         Ok(queries::execute_query(&mut conn, "ROLLBACK".into()).await?)
     }
 
-    fn upsert_query(
-        &self,
-        table: &str,
-        columns: &[String],
-        inserts: Vec<String>,
-        updates: Vec<String>,
-    ) -> String {
-        format!(
-            "INSERT INTO {}
-                ({})
-             VALUES
-                ({}, $1)
-             ON CONFLICT(id)
-             DO UPDATE SET {}",
-            table,
-            columns.join(", "),
-            inserts.join(", "),
-            updates.join(", "),
-        )
+    /// Set the height of the first block in the batch currently being
+    /// indexed. Writes made while a height is set are journaled so that
+    /// the batch can be undone by `rollback` if the chain reorganizes.
+    pub fn set_block_height(&mut self, height: Option<u64>) {
+        self.block_height = height;
     }
 
     fn namespace(&self) -> String {
         format!("{}_{}", self.namespace, self.identifier)
     }
 
-    fn get_query(&self, table: &str, object_id: u64) -> String {
-        format!("SELECT object from {table} where id = {object_id}")
+    /// Whether any of the indexer's tables keeps the history of its entities,
+    /// which needs every block to be committed at its own height.
+    pub fn keeps_history(&self) -> bool {
+        !self.versioned.is_empty()
     }
 
+    /// Save an entity in the open transaction. It's written to the database
+    /// when the transaction commits, or before a query that could select it.
     pub async fn put_object(
         &mut self,
         type_id: i64,
         columns: Vec<FtColumn>,
         bytes: Vec<u8>,
-    ) {
+    ) -> IndexerResult<()> {
+        if self.stashed.is_none() {
+            return Err(IndexerError::NoTransactionError);
+        }
+
         let table = match self.tables.get(&type_id) {
             Some(t) => t,
             None => {
                 error!("TypeId({}) not found in tables: {:?}", type_id, self.tables,);
-                return;
+                return Ok(());
             }
         };
 
-        let inserts: Vec<_> = columns.iter().map(|col| col.query_fragment()).collect();
-        let updates: Vec<_> = self.schema[table]
+        let object_id =
+            self.schema[table]
+                .iter()
+                .zip(columns.iter())
+                .find_map(|(colname, value)| match value {
+                    FtColumn::ID(Some(id))
+                        if colname == &IdCol::to_lowercase_string() =>
+                    {
+                        Some(*id)
+                    }
+                    _ => None,
+                });
+
+        let object_id = match object_id {
+            Some(id) => id,
+            None => {
+                error!("TypeId({}) object is missing an ID.", type_id);
+                return Ok(());
+            }
+        };
+
+        self.cache
+            .entry(type_id)
+            .or_default()
+            .insert(object_id, PendingObject { columns, bytes });
+
+        Ok(())
+    }
+
+    /// Write every entity saved in the open transaction, journaling the rows
+    /// they replace if a block height is set.
+    async fn flush_objects(&mut self) -> IndexerResult<()> {
+        let pending = std::mem::take(&mut self.cache);
+        self.write_objects(pending).await
+    }
+
+    async fn write_objects(&mut self, pending: EntityCache) -> IndexerResult<()> {
+        for (type_id, objects) in pending {
+            let (object_ids, objects): (Vec<u64>, Vec<PendingObject>) =
+                objects.into_iter().unzip();
+            let table = &self.tables[&type_id];
+            let columns = &self.schema[table];
+            let conn = self
+                .stashed
+                .as_mut()
+                .ok_or(IndexerError::NoTransactionError)?;
+
+            if let Some(height) = self.block_height {
+                queries::put_rollback_journal_entries(
+                    conn,
+                    &self.namespace,
+                    &self.identifier,
+                    table,
+                    height,
+                    type_id,
+                    &object_ids,
+                )
+                .await?;
+            }
+
+            let rows = objects
+                .into_iter()
+                .map(|object| {
+                    let mut values: Vec<_> =
+                        object.columns.iter().map(|c| c.bind_value()).collect();
+                    values.push(BindValue::Bytes(Some(object.bytes)));
+                    values
+                })
+                .collect();
+
+            queries::put_objects(conn, table, columns, rows).await?;
+
+            if let (true, Some(height)) =
+                (self.versioned.contains(table), self.block_height)
+            {
+                queries::put_object_versions(conn, table, columns, &object_ids, height)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load an entity in the open transaction, if it exists.
+    pub async fn get_object(
+        &mut self,
+        type_id: i64,
+        object_id: u64,
+    ) -> IndexerResult<Option<Vec<u8>>> {
+        let conn = self
+            .stashed
+            .as_mut()
+            .ok_or(IndexerError::NoTransactionError)?;
+
+        if let Some(object) = self
+            .cache
+            .get(&type_id)
+            .and_then(|objects| objects.get(&object_id))
+        {
+            return Ok(Some(object.bytes.clone()));
+        }
+
+        let table = &self.tables[&type_id];
+        match queries::get_object(conn, table, object_id).await {
+            Ok(v) => Ok(Some(v)),
+            Err(_e) => Ok(None),
+        }
+    }
+
+    /// Find the entities of a type whose columns have the values in `filter`,
+    /// including entities saved earlier in the open transaction.
+    pub async fn find_objects(
+        &mut self,
+        type_id: i64,
+        filter: EntityFilter,
+    ) -> IndexerResult<Vec<Vec<u8>>> {
+        let table = self.tables.get(&type_id).ok_or_else(|| {
+            IndexerError::InvalidEntityFilter(format!("TypeId({type_id}) not found."))
+        })?;
+
+        let columns = filter
+            .columns
             .iter()
-            .zip(columns.iter())
-            .filter_map(|(colname, value)| {
-                if colname == &IdCol::to_lowercase_string() {
-                    None
-                } else {
-                    Some(format!("{} = {}", colname, value.query_fragment()))
+            .map(
+                |(column, value)| match self.schema[table].contains(column) {
+                    true => Ok((column.to_owned(), value.bind_value())),
+                    false => Err(IndexerError::InvalidEntityFilter(format!(
+                        "{table} has no column {column:?}."
+                    ))),
+                },
+            )
+            .collect::<IndexerResult<Vec<_>>>()?;
+        let table = table.to_owned();
+
+        // Pending entities stay cached until they're written, so that they're
+        // not lost if writing them fails.
+        if let Some(pending) = self.cache.get(&type_id).cloned() {
+            self.write_objects(HashMap::from([(type_id, pending)]))
+                .await?;
+            self.cache.remove(&type_id);
+        }
+
+        let conn = self
+            .stashed
+            .as_mut()
+            .ok_or(IndexerError::NoTransactionError)?;
+
+        Ok(queries::find_objects(conn, &table, columns, filter.limit).await?)
+    }
+
+    /// Delete an entity in the open transaction. Entities saved earlier in the
+    /// transaction are written first, so that the `ON DELETE` action of every
+    /// foreign key referencing this entity applies to them too.
+    pub async fn delete_object(
+        &mut self,
+        type_id: i64,
+        object_id: u64,
+    ) -> IndexerResult<usize> {
+        let table = match self.tables.get(&type_id) {
+            Some(t) => t.to_owned(),
+            None => {
+                error!("TypeId({}) not found in tables: {:?}", type_id, self.tables,);
+                return Ok(0);
+            }
+        };
+
+        self.flush_objects().await?;
+
+        let rows = match self.block_height {
+            Some(height) => {
+                self.journal_delete(height, type_id, &table, object_id)
+                    .await?
+            }
+            None => DeletedRows::default(),
+        };
+
+        let conn = self
+            .stashed
+            .as_mut()
+            .ok_or(IndexerError::NoTransactionError)?;
+
+        let count = queries::delete_object(conn, &table, object_id).await?;
+
+        if let Some(height) = self.block_height {
+            for (table, object_ids) in &rows.deleted {
+                if self.versioned.contains(table) {
+                    queries::close_object_versions(conn, table, object_ids, height)
+                        .await?;
                 }
-            })
-            .collect();
+            }
 
-        let columns = self.schema[table].clone();
+            for (table, column, object_ids) in &rows.nulled {
+                null_object_column(conn, table, &self.schema[table], column, object_ids)
+                    .await?;
+
+                if self.versioned.contains(table) {
+                    queries::put_object_versions(
+                        conn,
+                        table,
+                        &self.schema[table],
+                        object_ids,
+                        height,
+                    )
+                    .await?;
+                }
+            }
+        }
 
-        let query_text = self.upsert_query(table, &columns, inserts, updates);
+        Ok(count)
+    }
 
+    /// Journal an entity about to be deleted, along with every row the delete
+    /// will remove or update through `ON DELETE CASCADE` and `ON DELETE SET NULL`
+    /// foreign keys, so that `rollback` can restore all of them. Returns the
+    /// rows the delete will touch.
+    async fn journal_delete(
+        &mut self,
+        height: u64,
+        type_id: i64,
+        table: &str,
+        object_id: u64,
+    ) -> IndexerResult<DeletedRows> {
         let conn = self
             .stashed
             .as_mut()
-            .expect("No transaction has been opened.");
+            .ok_or(IndexerError::NoTransactionError)?;
 
-        queries::put_object(conn, query_text, bytes)
-            .await
-            .expect("Failed to put object.");
+        queries::put_rollback_journal_entries(
+            conn,
+            &self.namespace,
+            &self.identifier,
+            table,
+            height,
+            type_id,
+            &[object_id],
+        )
+        .await?;
+
+        let type_ids: HashMap<&String, i64> =
+            self.tables.iter().map(|(k, v)| (v, *k)).collect();
+        let mut journaled = HashSet::from([(table.to_string(), object_id)]);
+        let mut deleted = vec![(table.to_string(), vec![object_id])];
+        let mut rows = DeletedRows::default();
+
+        while let Some((table, object_ids)) = deleted.pop() {
+            for fk in queries::foreign_keys_referencing(conn, &table).await? {
+                let referencing = self
+                    .pool
+                    .database_type()
+                    .table_name(&fk.namespace, &fk.table_name);
+                let referencing_type_id = match (fk.on_delete, type_ids.get(&referencing))
+                {
+                    (OnDelete::NoAction, _) | (_, None) => continue,
+                    (_, Some(type_id)) => *type_id,
+                };
+
+                let ids = queries::put_rollback_journal_entries_referencing(
+                    conn,
+                    &self.namespace,
+                    &self.identifier,
+                    &fk,
+                    height,
+                    referencing_type_id,
+                    &object_ids,
+                )
+                .await?;
+
+                if let OnDelete::Cascade = fk.on_delete {
+                    let ids: Vec<u64> = ids
+                        .into_iter()
+                        .filter(|id| journaled.insert((referencing.clone(), *id)))
+                        .collect();
+
+                    if !ids.is_empty() {
+                        deleted.push((referencing, ids));
+                    }
+                } else if !ids.is_empty() {
+                    rows.nulled.push((referencing, fk.column_name.clone(), ids));
+                }
+            }
+
+            rows.deleted.push((table, object_ids));
+        }
+
+        Ok(rows)
     }
 
-    pub async fn get_object(&mut self, type_id: i64, object_id: u64) -> Option<Vec<u8>> {
-        let table = &self.tables[&type_id];
-        let query = self.get_query(table, object_id);
+    /// Undo every journaled write made by batches starting at or above
+    /// `from_height`, restoring entity rows to their prior state.
+    pub async fn rollback(&mut self, from_height: u64) -> IndexerResult<usize> {
+        let block_height = self.block_height.take();
+
+        self.start_transaction().await?;
+        let result = match self.rollback_journaled_writes(from_height).await {
+            Ok(count) => self.commit_transaction().await.map(|_| count),
+            Err(e) => self.revert_transaction().await.and(Err(e)),
+        };
+
+        // Restored only now, so that committing doesn't journal the restored
+        // entities as writes of the current batch.
+        self.block_height = block_height;
+
+        result
+    }
+
+    async fn rollback_journaled_writes(
+        &mut self,
+        from_height: u64,
+    ) -> IndexerResult<usize> {
+        let conn = self
+            .stashed
+            .as_mut()
+            .ok_or(IndexerError::NoTransactionError)?;
+
+        let entries = queries::rollback_journal_entries(
+            conn,
+            &self.namespace,
+            &self.identifier,
+            from_height,
+        )
+        .await?;
+
+        let count = entries.len();
+
+        for entry in entries {
+            match entry.object {
+                Some(bytes) => {
+                    let columns: Vec<FtColumn> =
+                        deserialize(&bytes).map_err(IndexerError::RollbackError)?;
+                    self.put_object(entry.type_id, columns, bytes).await?;
+                }
+                None => {
+                    let table = self
+                        .tables
+                        .get(&entry.type_id)
+                        .ok_or_else(|| {
+                            IndexerError::RollbackError(format!(
+                                "TypeId({}) not found in tables.",
+                                entry.type_id
+                            ))
+                        })?
+                        .to_owned();
+                    if let Some(objects) = self.cache.get_mut(&entry.type_id) {
+                        objects.remove(&entry.object_id);
+                    }
+                    let conn = self
+                        .stashed
+                        .as_mut()
+                        .ok_or(IndexerError::NoTransactionError)?;
+                    queries::delete_object(conn, &table, entry.object_id).await?;
+                }
+            }
+        }
+
         let conn = self
             .stashed
             .as_mut()
-            .expect("No transaction has been opened.");
+            .ok_or(IndexerError::NoTransactionError)?;
+
+        for table in &self.versioned {
+            queries::rollback_object_versions(conn, table, from_height).await?;
+        }
+
+        queries::remove_rollback_journal_entries(
+            conn,
+            &self.namespace,
+            &self.identifier,
+            from_height,
+        )
+        .await?;
+
+        // Resume from just below the rolled back batches. The id of that block
+        // isn't known here, but it'll be filled in by the next commit.
+        if from_height > 1 {
+            self.put_checkpoint(from_height - 1, None).await?;
+        } else {
+            let conn = self
+                .stashed
+                .as_mut()
+                .ok_or(IndexerError::NoTransactionError)?;
+            queries::remove_checkpoint(conn, &self.namespace, &self.identifier).await?;
+        }
+
+        Ok(count)
+    }
+
+    /// Discard journaled writes for batches below `below_height`, which are
+    /// deeper than any reorg we are willing to undo.
+    pub async fn prune_rollback_journal(
+        &mut self,
+        below_height: u64,
+    ) -> IndexerResult<usize> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(queries::prune_rollback_journal(
+            &mut conn,
+            &self.namespace,
+            &self.identifier,
+            below_height,
+        )
+        .await?)
+    }
+
+    /// Flag this indexer as having reached its manifest's `end_block`, so it is
+    /// not restarted from the registry.
+    pub async fn mark_completed(&mut self) -> IndexerResult<usize> {
+        if self.shadow {
+            return self.mark_caught_up().await;
+        }
+
+        let mut conn = self.pool.acquire().await?;
+        Ok(queries::set_index_completed(
+            &mut conn,
+            &self.namespace,
+            &self.identifier,
+            true,
+        )
+        .await?)
+    }
+
+    /// Called once the indexer has caught up with the chain. Returns whether it
+    /// should stop here, which a blue/green deployment does so that it can
+    /// replace the running version of its indexer.
+    pub async fn caught_up(&mut self) -> IndexerResult<bool> {
+        if !self.shadow {
+            return Ok(false);
+        }
+
+        self.mark_caught_up().await?;
+        Ok(true)
+    }
+
+    async fn mark_caught_up(&mut self) -> IndexerResult<usize> {
+        let identifier = self
+            .identifier
+            .strip_suffix(SHADOW_IDENTIFIER_SUFFIX)
+            .unwrap_or(&self.identifier);
+
+        let mut conn = self.pool.acquire().await?;
+        Ok(queries::set_shadow_caught_up(&mut conn, &self.namespace, identifier).await?)
+    }
+
+    /// Record whether the indexer's handler is currently succeeding.
+    pub async fn set_status(
+        &mut self,
+        status: IndexerStatus,
+        message: Option<&str>,
+    ) -> IndexerResult<usize> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(queries::set_index_status(
+            &mut conn,
+            &self.namespace,
+            &self.identifier,
+            status,
+            message,
+        )
+        .await?)
+    }
 
-        match queries::get_object(conn, query).await {
-            Ok(v) => Some(v),
-            Err(_e) => None,
+    /// Record a block the handler failed on in the dead-letter table, and move
+    /// the checkpoint past it.
+    pub async fn skip_block(
+        &mut self,
+        block_height: u64,
+        block_id: Bytes32,
+        error: &str,
+    ) -> IndexerResult<usize> {
+        self.start_transaction().await?;
+
+        let conn = self
+            .stashed
+            .as_mut()
+            .ok_or(IndexerError::NoTransactionError)?;
+        if let Err(e) = queries::put_dead_letter(
+            conn,
+            &self.namespace,
+            &self.identifier,
+            block_height,
+            &block_id.to_string(),
+            error,
+        )
+        .await
+        {
+            self.revert_transaction().await?;
+            return Err(e.into());
         }
+
+        self.commit_batch(Some((block_height, block_id))).await
     }
 
     pub async fn load_schema(
@@ -153,38 +741,16 @@ impl Database {
         manifest: &Manifest,
         instance: Option<&Instance>,
     ) -> IndexerResult<()> {
+        let mut conn = self.pool.acquire().await?;
+
         match manifest.is_native() {
             true => {
                 self.namespace = manifest.namespace.clone();
                 self.identifier = manifest.identifier.clone();
 
-                let mut conn = self.pool.acquire().await?;
                 self.version =
                     queries::type_id_latest(&mut conn, &self.namespace, &self.identifier)
                         .await?;
-
-                let results = queries::columns_get_schema(
-                    &mut conn,
-                    &self.namespace,
-                    &self.identifier,
-                    &self.version,
-                )
-                .await?;
-
-                for column in results {
-                    let table = &format!("{}.{}", self.namespace(), &column.table_name);
-
-                    self.tables
-                        .entry(column.type_id)
-                        .or_insert_with(|| table.to_string());
-
-                    let columns = self
-                        .schema
-                        .entry(table.to_string())
-                        .or_insert_with(Vec::new);
-
-                    columns.push(column.column_name);
-                }
             }
             false => {
                 let instance = instance.unwrap();
@@ -192,33 +758,84 @@ impl Database {
                 self.namespace = ffi::get_namespace(instance)?;
                 self.identifier = ffi::get_identifier(instance)?;
                 self.version = ffi::get_version(instance)?;
+            }
+        }
 
-                let mut conn = self.pool.acquire().await?;
-                let results = queries::columns_get_schema(
-                    &mut conn,
-                    &self.namespace,
-                    &self.identifier,
-                    &self.version,
-                )
-                .await?;
+        let results = queries::columns_get_schema(
+            &mut conn,
+            &self.namespace,
+            &self.identifier,
+            &self.version,
+        )
+        .await?;
 
-                for column in results {
-                    let table = &format!("{}.{}", self.namespace(), &column.table_name);
+        self.load_columns(results);
 
-                    self.tables
-                        .entry(column.type_id)
-                        .or_insert_with(|| table.to_string());
+        Ok(())
+    }
 
-                    let columns = self
-                        .schema
-                        .entry(table.to_string())
-                        .or_insert_with(Vec::new);
+    /// Load the schema of a blue/green deployment, whose tables aren't in the
+    /// registry yet, so that it indexes into its shadow tables.
+    pub async fn load_shadow_schema(
+        &mut self,
+        manifest: &Manifest,
+        instance: Option<&Instance>,
+        schema: &str,
+    ) -> IndexerResult<()> {
+        let schema = inject_native_entities_into_schema(schema);
 
-                    columns.push(column.column_name);
-                }
+        match instance {
+            Some(instance) if !manifest.is_native() => {
+                self.namespace = ffi::get_namespace(instance)?;
+                self.identifier = ffi::get_identifier(instance)?;
+                self.version = ffi::get_version(instance)?;
+            }
+            _ => {
+                self.namespace = manifest.namespace.clone();
+                self.identifier = manifest.identifier.clone();
+                self.version = schema_version(&schema);
             }
         }
 
+        // Type IDs are those of the indexer itself, since they're compiled
+        // into its handlers, but its tables are the shadow ones.
+        let columns = SchemaBuilder::new(
+            &self.namespace,
+            &self.identifier,
+            &self.version,
+            self.pool.database_type(),
+        )
+        .build(&schema)?
+        .column_info();
+
+        self.identifier = format!("{}{SHADOW_IDENTIFIER_SUFFIX}", self.identifier);
+        self.shadow = true;
+        self.load_columns(columns);
+
         Ok(())
     }
+
+    fn load_columns(&mut self, columns: Vec<ColumnInfo>) {
+        for column in columns {
+            let table = &self
+                .pool
+                .database_type()
+                .table_name(&self.namespace(), &column.table_name);
+
+            self.tables
+                .entry(column.type_id)
+                .or_insert_with(|| table.to_string());
+
+            let columns = self
+                .schema
+                .entry(table.to_string())
+                .or_insert_with(Vec::new);
+
+            if column.versioned {
+                self.versioned.insert(table.to_string());
+            }
+
+            columns.push(column.column_name);
+        }
+    }
 }
@@ -1,38 +1,41 @@
-use crate::{database::Database, ffi, IndexerConfig, IndexerError, IndexerResult};
+use crate::{
+    block_source::{BlockPage, BlockSource},
+    database::Database,
+    ffi,
+    limits::{ExecutionLimits, LimitingTunables, Metering, INTERRUPT_GRACE_PERIOD},
+    IndexerConfig, IndexerError, IndexerResult,
+};
 use async_std::{
     fs::File,
     io::ReadExt,
     sync::{Arc, Mutex},
 };
 use async_trait::async_trait;
-use chrono::{TimeZone, Utc};
-use fuel_core_client::client::{
-    types::{TransactionResponse, TransactionStatus as GqlTransactionStatus},
-    FuelClient, PageDirection, PaginatedResult, PaginationRequest,
+use fuel_indexer_database::types::IndexerStatus;
+use fuel_indexer_lib::{
+    defaults::*,
+    manifest::{FailureAction, Manifest, RetryPolicy},
 };
-use fuel_indexer_lib::{defaults::*, manifest::Manifest};
 use fuel_indexer_schema::utils::serialize;
-use fuel_indexer_types::{
-    abi::{BlockData, TransactionData},
-    tx::{TransactionStatus, TxId},
-    Bytes32,
-};
-use futures::Future;
+use fuel_indexer_types::{abi::BlockData, Bytes32};
+use futures::{stream, Future, StreamExt};
 use std::{
+    collections::VecDeque,
     marker::{Send, Sync},
     path::Path,
-    str::FromStr,
     sync::atomic::{AtomicBool, Ordering},
 };
 use thiserror::Error;
 use tokio::{
+    runtime::Handle,
+    sync::mpsc,
     task::{spawn_blocking, JoinHandle},
     time::{sleep, Duration},
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use wasmer::{
-    imports, Instance, LazyInit, Memory, Module, NativeFunc, RuntimeError, Store,
-    WasmerEnv,
+    imports, BaseTunables, CompilerConfig, Instance, LazyInit, Memory, Module,
+    NativeFunc, RuntimeError, Store, Target, WasmerEnv,
 };
 use wasmer_compiler_cranelift::Cranelift;
 use wasmer_engine_universal::Universal;
@@ -65,23 +68,331 @@ impl ExecutorSource {
     }
 }
 
-pub fn run_executor<T: 'static + Executor + Send + Sync>(
+/// Ids of recently indexed blocks, used to detect when the node's chain no
+/// longer agrees with what has already been indexed.
+#[derive(Debug, Default)]
+pub(crate) struct ReorgTracker {
+    /// `(height, block_id, batch_start)` for each indexed block, oldest first.
+    blocks: VecDeque<(u64, Bytes32, u64)>,
+}
+
+impl ReorgTracker {
+    /// Remember the `(height, block_id)` pairs of a committed batch.
+    pub fn record(&mut self, blocks: &[(u64, Bytes32)]) {
+        if let Some((batch_start, _)) = blocks.first() {
+            for (height, id) in blocks {
+                self.blocks.push_back((*height, *id, *batch_start));
+            }
+        }
+    }
+
+    /// Forget batches that start below `height`.
+    pub fn prune(&mut self, height: u64) {
+        while matches!(self.blocks.front(), Some((_, _, start)) if *start < height) {
+            self.blocks.pop_front();
+        }
+    }
+
+    /// Blocks that can still be checked against the node, newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &(u64, Bytes32, u64)> {
+        self.blocks.iter().rev()
+    }
+
+    pub fn tip(&self) -> Option<(u64, Bytes32)> {
+        self.blocks.back().map(|(height, id, _)| (*height, *id))
+    }
+
+    /// Drop every batch containing a block above `fork_height`, returning the
+    /// height from which those batches need to be re-indexed.
+    pub fn rewind(&mut self, fork_height: u64) -> Option<u64> {
+        let from = self
+            .blocks
+            .iter()
+            .find(|(height, _, _)| *height > fork_height)
+            .map(|(_, _, start)| *start)?;
+
+        while matches!(self.blocks.back(), Some((_, _, start)) if *start >= from) {
+            self.blocks.pop_back();
+        }
+
+        Some(from)
+    }
+}
+
+/// Walk back through recently indexed blocks until one matches the node's
+/// canonical chain, returning that height.
+async fn find_fork_height<S: BlockSource>(
+    source: &S,
+    tracker: &ReorgTracker,
+) -> Option<u64> {
+    let mut oldest = None;
+    for (height, id, _) in tracker.recent() {
+        match source.block_id(*height).await {
+            Ok(Some(block_id)) if block_id == *id => return Some(*height),
+            Ok(_) => oldest = Some(*height),
+            Err(e) => {
+                error!("Failed to retrieve block at height {height}: {e}");
+                return None;
+            }
+        }
+    }
+
+    warn!("Chain reorganization is deeper than {MAX_REORG_DEPTH} blocks.");
+    oldest.map(|h| h.saturating_sub(1))
+}
+
+/// Remember a committed batch, forgetting anything indexed too long ago to be
+/// affected by a reorganization.
+async fn record_batch<T: Executor>(
+    executor: &mut T,
+    tracker: &mut ReorgTracker,
+    indexed: &[(u64, Bytes32)],
+) {
+    tracker.record(indexed);
+
+    if let Some((last, _)) = indexed.last() {
+        if *last > MAX_REORG_DEPTH {
+            let below = last - MAX_REORG_DEPTH;
+            tracker.prune(below);
+            if let Err(e) = executor.prune_rollback_journal(below).await {
+                error!("Failed to prune rollback journal: {e:?}");
+            }
+        }
+    }
+}
+
+/// Whether a batch ending at `last` reaches the manifest's `end_block`.
+fn reached_end_block(end_block: Option<u64>, last: Option<u64>) -> bool {
+    matches!((end_block, last), (Some(end), Some(last)) if last >= end)
+}
+
+/// Mark the indexer as completed once its `end_block` has been committed.
+async fn complete<T: Executor>(executor: &mut T) {
+    info!("Indexer reached its end_block, stopping.");
+    if let Err(e) = executor.mark_completed().await {
+        error!("Failed to mark indexer as completed: {e:?}");
+    }
+}
+
+/// Record the indexer's status, logging rather than failing if it can't be saved.
+async fn set_status<T: Executor>(
+    executor: &mut T,
+    status: IndexerStatus,
+    message: Option<String>,
+) {
+    if let Err(e) = executor.set_status(status, message).await {
+        error!("Failed to record indexer status: {e:?}");
+    }
+}
+
+/// Sleep for `delay`, waking early if `kill_switch` is set. Returns `false`
+/// if the indexer should stop.
+async fn sleep_unless_killed(delay: Duration, kill_switch: &AtomicBool) -> bool {
+    let poll = Duration::from_millis(STOP_EXECUTOR_POLL_MS);
+    let mut remaining = delay;
+    while !remaining.is_zero() {
+        if kill_switch.load(Ordering::SeqCst) {
+            return false;
+        }
+        let step = remaining.min(poll);
+        sleep(step).await;
+        remaining -= step;
+    }
+    !kill_switch.load(Ordering::SeqCst)
+}
+
+/// Index `blocks`, retrying with backoff according to `policy` when the
+/// handler fails. Returns `false` if the indexer should stop, including when
+/// `kill_switch` is set while retrying.
+async fn index_blocks<T: Executor>(
+    executor: &mut T,
+    policy: &RetryPolicy,
+    blocks: Vec<BlockData>,
+    kill_switch: &AtomicBool,
+) -> bool {
+    if blocks.len() > 1 && executor.keeps_history().await {
+        for block in blocks {
+            if !index_batch(executor, policy, vec![block], kill_switch).await {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    index_batch(executor, policy, blocks, kill_switch).await
+}
+
+/// Index `blocks` as a single batch, retrying as `index_blocks` does.
+async fn index_batch<T: Executor>(
+    executor: &mut T,
+    policy: &RetryPolicy,
+    blocks: Vec<BlockData>,
+    kill_switch: &AtomicBool,
+) -> bool {
+    let mut attempt = 0;
+    loop {
+        let e = match executor.handle_events(blocks.clone()).await {
+            Ok(()) => {
+                if attempt > 0 {
+                    set_status(executor, IndexerStatus::Running, None).await;
+                }
+                return true;
+            }
+            Err(e) => e,
+        };
+
+        if kill_switch.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        set_status(executor, IndexerStatus::Retrying, Some(e.to_string())).await;
+
+        if attempt >= policy.max_retries {
+            match policy.on_failure {
+                FailureAction::Halt => {
+                    error!("Indexer failed after retries, giving up. <('.')>");
+                    set_status(executor, IndexerStatus::Halted, Some(e.to_string()))
+                        .await;
+                    return false;
+                }
+                FailureAction::Skip => {
+                    return skip_failing_blocks(executor, blocks).await
+                }
+                FailureAction::Retry => {}
+            }
+        }
+
+        let delay = policy.backoff(attempt);
+        error!("Indexer executor failed {e:?}, retrying in {delay:?}.");
+        if !sleep_unless_killed(delay, kill_switch).await {
+            return false;
+        }
+        attempt += 1;
+    }
+}
+
+/// Index `blocks` one at a time, skipping any that the handler still fails on.
+/// Returns `false` if a skipped block could not be recorded.
+async fn skip_failing_blocks<T: Executor>(
+    executor: &mut T,
+    blocks: Vec<BlockData>,
+) -> bool {
+    for block in blocks {
+        let height = block.height;
+        if let Err(e) = executor.handle_events(vec![block.clone()]).await {
+            warn!("Skipping block {height}, which the indexer failed on: {e:?}");
+            if let Err(e) = executor.skip_block(&block, e.to_string()).await {
+                error!("Failed to record skipped block {height}: {e:?}. <('.')>");
+                set_status(executor, IndexerStatus::Halted, Some(e.to_string())).await;
+                return false;
+            }
+        }
+    }
+
+    set_status(executor, IndexerStatus::Running, None).await;
+    true
+}
+
+/// Fetch the blocks following `cursor`, up to and including `until`, a single
+/// page at a time.
+async fn fetch_range<S: BlockSource>(
+    source: Arc<S>,
+    mut cursor: Option<String>,
+    until: u64,
+) -> IndexerResult<Vec<BlockData>> {
+    let mut blocks = Vec::new();
+    loop {
+        let after = cursor
+            .as_deref()
+            .and_then(|c| c.parse::<u64>().ok())
+            .unwrap_or(0);
+        if after >= until {
+            return Ok(blocks);
+        }
+
+        let limit = usize::try_from(until - after).unwrap_or(usize::MAX);
+        let page = source.page(cursor, limit).await?;
+        if page.blocks.is_empty() {
+            return Ok(blocks);
+        }
+
+        for block in page.blocks {
+            if block.height > until {
+                return Ok(blocks);
+            }
+            blocks.push(block);
+        }
+
+        if page.cursor.is_none() {
+            return Ok(blocks);
+        }
+        cursor = page.cursor;
+    }
+}
+
+/// Fetch the blocks following `cursor`, up to and including `until`, in ranges
+/// of `BACKFILL_RANGE_SIZE` blocks.
+///
+/// Up to `concurrency` ranges are fetched and decoded in parallel, each a page
+/// at a time, so there are never more than `concurrency` requests to the
+/// source in flight. Ranges are delivered in block order, and delivery stops
+/// after the first error.
+fn backfill<S: 'static + BlockSource>(
+    source: Arc<S>,
+    cursor: Option<String>,
+    until: u64,
+    concurrency: usize,
+) -> mpsc::Receiver<IndexerResult<Vec<BlockData>>> {
+    let (tx, rx) = mpsc::channel(concurrency);
+
+    let after = cursor
+        .as_deref()
+        .and_then(|c| c.parse::<u64>().ok())
+        .unwrap_or(0);
+    let ranges = (after..until)
+        .step_by(BACKFILL_RANGE_SIZE as usize)
+        .map(move |start| {
+            let cursor = if start == after {
+                cursor.clone()
+            } else {
+                Some(start.to_string())
+            };
+            (cursor, (start + BACKFILL_RANGE_SIZE).min(until))
+        })
+        .collect::<Vec<_>>();
+
+    tokio::spawn(async move {
+        let mut ranges = stream::iter(ranges)
+            .map(|(cursor, until)| {
+                tokio::spawn(fetch_range(source.clone(), cursor, until))
+            })
+            .buffered(concurrency);
+
+        while let Some(result) = ranges.next().await {
+            let result = result.map_err(IndexerError::from).and_then(|r| r);
+            let failed = result.is_err();
+            if tx.send(result).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+pub fn run_executor<T: 'static + Executor + Send + Sync, S: 'static + BlockSource>(
     config: &IndexerConfig,
     manifest: &Manifest,
     mut executor: T,
+    source: S,
     kill_switch: Arc<AtomicBool>,
 ) -> impl Future<Output = ()> {
     let start_block = manifest.start_block.expect("Failed to detect start_block.");
+    let end_block = manifest.end_block;
+    let policy = manifest.retry.clone();
     let stop_idle_indexers = config.stop_idle_indexers;
-
-    let fuel_node_addr = if config.indexer_net_config {
-        manifest
-            .fuel_client
-            .clone()
-            .unwrap_or(config.fuel_node.to_string())
-    } else {
-        config.fuel_node.to_string()
-    };
+    let concurrency = config.block_fetch_concurrency.max(1);
+    let source = Arc::new(source);
 
     let mut next_cursor = if start_block > 1 {
         let decremented = start_block - 1;
@@ -89,14 +400,8 @@ pub fn run_executor<T: 'static + Executor + Send + Sync>(
     } else {
         None
     };
-    info!("Subscribing to Fuel node at {fuel_node_addr}");
-
-    let client = FuelClient::from_str(&fuel_node_addr)
-        .unwrap_or_else(|e| panic!("Node connection failed: {e}."));
 
     async move {
-        let mut retry_count = 0;
-
         // If we're testing or running on CI, we don't want indexers to run forever. But in production
         // let the index operators decide if they want to stop idle indexers. Maybe we can eventually
         // make this MAX_EMPTY_BLOCK_REQUESTS value configurable
@@ -106,132 +411,159 @@ pub fn run_executor<T: 'static + Executor + Send + Sync>(
             usize::MAX
         };
         let mut num_empty_block_reqs = 0;
+        let mut tracker = ReorgTracker::default();
 
-        loop {
-            debug!("Fetching paginated results from {next_cursor:?}",);
+        set_status(&mut executor, IndexerStatus::Running, None).await;
 
-            let PaginatedResult {
-                cursor, results, ..
-            } = client
-                .blocks(PaginationRequest {
-                    cursor: next_cursor.clone(),
-                    results: NODE_GRAPHQL_PAGE_SIZE,
-                    direction: PageDirection::Forward,
-                })
-                .await
-                .unwrap_or_else(|e| {
-                    error!("Failed to retrieve blocks: {e}",);
-                    PaginatedResult {
-                        cursor: None,
-                        results: vec![],
-                        has_next_page: false,
-                        has_previous_page: false,
+        if reached_end_block(end_block, Some(start_block.saturating_sub(1))) {
+            complete(&mut executor).await;
+            return;
+        }
+
+        // While the indexer is far behind the chain head, fetch blocks in
+        // parallel, up to the point where they could still be reorganized.
+        // Once it has caught up, tail the chain a page at a time.
+        'backfill: loop {
+            let after = next_cursor
+                .as_deref()
+                .and_then(|c| c.parse::<u64>().ok())
+                .unwrap_or(0);
+            let until = match source.head_height().await {
+                Ok(Some(head)) => head
+                    .saturating_sub(MAX_REORG_DEPTH)
+                    .min(end_block.unwrap_or(u64::MAX)),
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Failed to retrieve chain head: {e}");
+                    break;
+                }
+            };
+
+            if until < after + BACKFILL_RANGE_SIZE {
+                break;
+            }
+
+            info!("Backfilling blocks {after} to {until}.");
+
+            let mut ranges =
+                backfill(source.clone(), next_cursor.clone(), until, concurrency);
+            while let Some(result) = ranges.recv().await {
+                let blocks = match result {
+                    Ok(blocks) => blocks,
+                    Err(e) => {
+                        error!("Failed to backfill blocks: {e}");
+                        break 'backfill;
                     }
-                });
-
-            let mut block_info = Vec::new();
-            for block in results.into_iter() {
-                let producer = block.block_producer().map(|pk| pk.hash());
-
-                let mut transactions = Vec::new();
-
-                for trans in block.transactions {
-                    // TODO: https://github.com/FuelLabs/fuel-indexer/issues/288
-                    match client.transaction(&trans.id.to_string()).await {
-                        Ok(result) => {
-                            if let Some(TransactionResponse {
-                                transaction,
-                                status,
-                            }) = result
-                            {
-                                let receipts = client
-                                    .receipts(&trans.id.to_string())
-                                    .await
-                                    .unwrap_or_else(|e| {
-                                        error!("Client communication error fetching receipts: {e:?}");
-                                        Vec::new()
-                                    });
-
-                                // NOTE: https://github.com/FuelLabs/fuel-indexer/issues/286
-                                let status = match status {
-                                    GqlTransactionStatus::Success {
-                                        block_id,
-                                        time,
-                                        ..
-                                    } => TransactionStatus::Success {
-                                        block_id,
-                                        time: Utc
-                                            .timestamp_opt(time.to_unix(), 0)
-                                            .single()
-                                            .unwrap(),
-                                    },
-                                    GqlTransactionStatus::Failure {
-                                        block_id,
-                                        time,
-                                        reason,
-                                        ..
-                                    } => TransactionStatus::Failure {
-                                        block_id,
-                                        time: Utc
-                                            .timestamp_opt(time.to_unix(), 0)
-                                            .single()
-                                            .unwrap(),
-                                        reason,
-                                    },
-                                    GqlTransactionStatus::Submitted { submitted_at } => {
-                                        TransactionStatus::Submitted {
-                                            submitted_at: Utc
-                                                .timestamp_opt(submitted_at.to_unix(), 0)
-                                                .single()
-                                                .unwrap(),
-                                        }
-                                    }
-                                    GqlTransactionStatus::SqueezedOut { reason } => {
-                                        TransactionStatus::SqueezedOut { reason }
-                                    }
-                                };
-
-                                let tx_data = TransactionData {
-                                    receipts,
-                                    status,
-                                    transaction,
-                                    id: TxId::from(trans.id),
-                                };
-                                transactions.push(tx_data);
-                            }
-                        }
-                        Err(e) => {
-                            error!("Error fetching transactions: {e:?}.",)
-                        }
-                    };
+                };
+
+                let indexed: Vec<_> = blocks.iter().map(|b| (b.height, b.id)).collect();
+
+                if !index_blocks(&mut executor, &policy, blocks, &kill_switch).await {
+                    return;
+                }
+
+                record_batch(&mut executor, &mut tracker, &indexed).await;
+
+                if let Some((last, _)) = indexed.last() {
+                    next_cursor = Some(last.to_string());
                 }
 
-                let block = BlockData {
-                    height: block.header.height.0,
-                    id: Bytes32::from(block.id),
-                    producer,
-                    time: block.header.time.0.to_unix(),
-                    transactions,
+                if reached_end_block(end_block, indexed.last().map(|(h, _)| *h)) {
+                    complete(&mut executor).await;
+                    return;
+                }
+
+                if kill_switch.load(Ordering::SeqCst) {
+                    return;
+                }
+            }
+        }
+
+        loop {
+            if let Some((height, id)) = tracker.tip() {
+                let diverged = match source.block_id(height).await {
+                    Ok(Some(block_id)) => block_id != id,
+                    Ok(None) => true,
+                    Err(e) => {
+                        error!("Failed to retrieve block at height {height}: {e}");
+                        false
+                    }
                 };
 
-                block_info.push(block);
+                if diverged {
+                    warn!("Chain reorganization detected at height {height}.");
+
+                    if let Some(from) = find_fork_height(&source, &tracker)
+                        .await
+                        .and_then(|fork| tracker.rewind(fork))
+                    {
+                        info!("Rolling back indexer to height {from}.");
+
+                        if let Err(e) = executor.rollback(from).await {
+                            error!("Indexer failed to roll back: {e:?}. <('.')>");
+                            break;
+                        }
+
+                        next_cursor = if from > 1 {
+                            Some((from - 1).to_string())
+                        } else {
+                            None
+                        };
+                    }
+                }
             }
 
-            let result = executor.handle_events(block_info).await;
+            debug!("Fetching paginated results from {next_cursor:?}",);
 
-            if let Err(e) = result {
-                error!("Indexer executor failed {e:?}, retrying.");
-                sleep(Duration::from_secs(DELAY_FOR_SERVICE_ERR)).await;
-                retry_count += 1;
-                if retry_count < INDEX_FAILED_CALLS {
+            let BlockPage {
+                blocks: mut block_info,
+                cursor,
+            } = match source.next_page(next_cursor.clone()).await {
+                Ok(page) => page,
+                Err(e) => {
+                    error!(
+                        "Failed to retrieve blocks: {e}, retrying in {DELAY_FOR_SERVICE_ERR}s."
+                    );
+                    let delay = Duration::from_secs(DELAY_FOR_SERVICE_ERR);
+                    if !sleep_unless_killed(delay, &kill_switch).await {
+                        break;
+                    }
                     continue;
-                } else {
-                    error!("Indexer failed after retries, giving up. <('.')>");
-                    break;
                 }
+            };
+
+            if let Some(end) = end_block {
+                block_info.retain(|block| block.height <= end);
+            }
+
+            let indexed: Vec<_> = block_info.iter().map(|b| (b.height, b.id)).collect();
+
+            if !index_blocks(&mut executor, &policy, block_info, &kill_switch).await {
+                break;
+            }
+
+            record_batch(&mut executor, &mut tracker, &indexed).await;
+
+            if reached_end_block(end_block, indexed.last().map(|(h, _)| *h)) {
+                complete(&mut executor).await;
+                break;
             }
 
             if cursor.is_none() {
+                // Only an empty page means the indexer has reached the tip.
+                if indexed.is_empty() {
+                    match executor.caught_up().await {
+                        Ok(true) => {
+                            info!("Indexer caught up with the chain, stopping.");
+                            break;
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            error!("Failed to record that the indexer caught up: {e:?}")
+                        }
+                    }
+                }
+
                 info!("No new blocks to process, sleeping.");
                 sleep(Duration::from_secs(DELAY_FOR_EMPTY_PAGE)).await;
 
@@ -249,8 +581,6 @@ pub fn run_executor<T: 'static + Executor + Send + Sync>(
             if kill_switch.load(Ordering::SeqCst) {
                 break;
             }
-
-            retry_count = 0;
         }
     }
 }
@@ -261,6 +591,33 @@ where
     Self: Sized,
 {
     async fn handle_events(&mut self, blocks: Vec<BlockData>) -> IndexerResult<()>;
+
+    /// Undo everything indexed from `from_height` onwards.
+    async fn rollback(&mut self, from_height: u64) -> IndexerResult<()>;
+
+    async fn prune_rollback_journal(&mut self, below_height: u64) -> IndexerResult<()>;
+
+    /// Record that the indexer has committed its manifest's `end_block`.
+    async fn mark_completed(&mut self) -> IndexerResult<()>;
+
+    /// Called each time the indexer has caught up with the chain. Returns
+    /// whether it should stop indexing.
+    async fn caught_up(&mut self) -> IndexerResult<bool>;
+
+    /// Whether the indexer keeps the history of its entities, in which case
+    /// blocks are indexed one at a time so each version gets its own height.
+    async fn keeps_history(&mut self) -> bool;
+
+    /// Record whether the handler is currently succeeding.
+    async fn set_status(
+        &mut self,
+        status: IndexerStatus,
+        message: Option<String>,
+    ) -> IndexerResult<()>;
+
+    /// Record a block the handler failed on as skipped, so indexing can move past it.
+    async fn skip_block(&mut self, block: &BlockData, error: String)
+        -> IndexerResult<()>;
 }
 
 #[derive(Error, Debug)]
@@ -278,6 +635,8 @@ pub struct IndexEnv {
     #[wasmer(export(name = "dealloc_fn"))]
     dealloc: LazyInit<NativeFunc<(u32, u32), ()>>,
     pub db: Arc<Mutex<Database>>,
+    /// Runtime that host functions use to run database calls to completion.
+    pub(crate) runtime: Handle,
 }
 
 impl IndexEnv {
@@ -288,6 +647,7 @@ impl IndexEnv {
             alloc: Default::default(),
             dealloc: Default::default(),
             db,
+            runtime: Handle::current(),
         })
     }
 }
@@ -331,10 +691,14 @@ where
         })
     }
 
-    pub async fn create<T: Future<Output = IndexerResult<()>> + Send + 'static>(
+    pub async fn create<
+        T: Future<Output = IndexerResult<()>> + Send + 'static,
+        S: BlockSource + 'static,
+    >(
         config: &IndexerConfig,
         manifest: &Manifest,
         handle_events: fn(Vec<BlockData>, Arc<Mutex<Database>>) -> T,
+        source: S,
     ) -> IndexerResult<(JoinHandle<()>, ExecutorSource, Arc<AtomicBool>)> {
         let executor = NativeIndexExecutor::new(config, manifest, handle_events).await?;
         let kill_switch = Arc::new(AtomicBool::new(false));
@@ -342,6 +706,7 @@ where
             config,
             manifest,
             executor,
+            source,
             kill_switch.clone(),
         ));
         Ok((handle, ExecutorSource::Manifest, kill_switch))
@@ -354,26 +719,91 @@ where
     F: Future<Output = IndexerResult<()>> + Send,
 {
     async fn handle_events(&mut self, blocks: Vec<BlockData>) -> IndexerResult<()> {
-        self.db.lock().await.start_transaction().await?;
+        let height = blocks.first().map(|b| b.height);
+        let last = blocks.last().map(|b| (b.height, b.id));
+        {
+            let mut db = self.db.lock().await;
+            db.start_transaction().await?;
+            db.set_block_height(height);
+        }
         let res = (self.handle_events_fn)(blocks, self.db.clone()).await;
         if let Err(e) = res {
             error!("NativeIndexExecutor handle_events failed: {}.", e);
             self.db.lock().await.revert_transaction().await?;
             return Err(IndexerError::NativeExecutionRuntimeError);
         } else {
-            self.db.lock().await.commit_transaction().await?;
+            self.db.lock().await.commit_batch(last).await?;
         }
         Ok(())
     }
+
+    async fn rollback(&mut self, from_height: u64) -> IndexerResult<()> {
+        let count = self.db.lock().await.rollback(from_height).await?;
+        info!("Reverted {count} writes from height {from_height}.");
+        Ok(())
+    }
+
+    async fn prune_rollback_journal(&mut self, below_height: u64) -> IndexerResult<()> {
+        self.db
+            .lock()
+            .await
+            .prune_rollback_journal(below_height)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_completed(&mut self) -> IndexerResult<()> {
+        self.db.lock().await.mark_completed().await?;
+        Ok(())
+    }
+
+    async fn caught_up(&mut self) -> IndexerResult<bool> {
+        self.db.lock().await.caught_up().await
+    }
+
+    async fn keeps_history(&mut self) -> bool {
+        self.db.lock().await.keeps_history()
+    }
+
+    async fn set_status(
+        &mut self,
+        status: IndexerStatus,
+        message: Option<String>,
+    ) -> IndexerResult<()> {
+        self.db
+            .lock()
+            .await
+            .set_status(status, message.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    async fn skip_block(
+        &mut self,
+        block: &BlockData,
+        error: String,
+    ) -> IndexerResult<()> {
+        self.db
+            .lock()
+            .await
+            .skip_block(block.height, block.id, &error)
+            .await?;
+        Ok(())
+    }
 }
 
 /// Responsible for loading a single indexer module, triggering events.
 #[derive(Debug)]
 pub struct WasmIndexExecutor {
     instance: Instance,
-    _module: Module,
+    module: Module,
     _store: Store,
     db: Arc<Mutex<Database>>,
+    db_url: String,
+    manifest: Manifest,
+    shadow_schema: Option<String>,
+    limits: ExecutionLimits,
+    memory_exceeded: Arc<AtomicBool>,
 }
 
 impl WasmIndexExecutor {
@@ -381,25 +811,101 @@ impl WasmIndexExecutor {
         config: &IndexerConfig,
         manifest: &Manifest,
         wasm_bytes: impl AsRef<[u8]>,
+    ) -> IndexerResult<Self> {
+        Self::instantiate(config, manifest, wasm_bytes, None).await
+    }
+
+    /// Create an executor for a blue/green deployment of an indexer, which
+    /// indexes into the shadow tables created for `schema`.
+    pub async fn new_shadow(
+        config: &IndexerConfig,
+        manifest: &Manifest,
+        wasm_bytes: impl AsRef<[u8]>,
+        schema: &str,
+    ) -> IndexerResult<Self> {
+        Self::instantiate(config, manifest, wasm_bytes, Some(schema)).await
+    }
+
+    async fn instantiate(
+        config: &IndexerConfig,
+        manifest: &Manifest,
+        wasm_bytes: impl AsRef<[u8]>,
+        shadow_schema: Option<&str>,
     ) -> IndexerResult<Self> {
         let db_url = config.database.to_string();
-        let store = Store::new(&Universal::new(compiler()).engine());
+        let limits = ExecutionLimits::new(config, manifest);
+        let memory_exceeded = Arc::new(AtomicBool::new(false));
+
+        let mut compiler = compiler();
+        if limits.metered() {
+            compiler.push_middleware(std::sync::Arc::new(Metering::default()));
+        }
+        let tunables = LimitingTunables::new(
+            BaseTunables::for_target(&Target::default()),
+            limits.memory_pages(),
+            memory_exceeded.clone(),
+        );
+        let store =
+            Store::new_with_tunables(&Universal::new(compiler).engine(), tunables);
         let module = Module::new(&store, &wasm_bytes)?;
 
+        let (instance, db) = Self::new_instance(
+            &module,
+            &db_url,
+            manifest,
+            shadow_schema,
+            &limits,
+            &memory_exceeded,
+        )
+        .await?;
+
+        Ok(WasmIndexExecutor {
+            instance,
+            module,
+            _store: store,
+            db,
+            db_url,
+            manifest: manifest.clone(),
+            shadow_schema: shadow_schema.map(str::to_string),
+            limits,
+            memory_exceeded,
+        })
+    }
+
+    /// Instantiate the indexer's module, with a database of its own.
+    async fn new_instance(
+        module: &Module,
+        db_url: &str,
+        manifest: &Manifest,
+        shadow_schema: Option<&str>,
+        limits: &ExecutionLimits,
+        memory_exceeded: &AtomicBool,
+    ) -> IndexerResult<(Instance, Arc<Mutex<Database>>)> {
         let mut import_object = imports! {};
 
-        let mut env = IndexEnv::new(db_url).await?;
-        let exports = ffi::get_exports(&env, &store);
+        let mut env = IndexEnv::new(db_url.to_string()).await?;
+        let exports = ffi::get_exports(&env, module.store());
 
         import_object.register("env", exports);
 
-        let instance = Instance::new(&module, &import_object)?;
+        let instance = Instance::new(module, &import_object).map_err(|e| {
+            match (memory_exceeded.load(Ordering::SeqCst), limits.memory_mb) {
+                (true, Some(mb)) => IndexerError::MemoryLimitExceeded(mb),
+                _ => e.into(),
+            }
+        })?;
         env.init_with_instance(&instance)?;
-        env.db
-            .lock()
-            .await
-            .load_schema(manifest, Some(&instance))
-            .await?;
+        limits.reset(&instance)?;
+        {
+            let mut db = env.db.lock().await;
+            match shadow_schema {
+                Some(schema) => {
+                    db.load_shadow_schema(manifest, Some(&instance), schema)
+                        .await?
+                }
+                None => db.load_schema(manifest, Some(&instance)).await?,
+            }
+        }
 
         if !instance
             .exports
@@ -408,12 +914,38 @@ impl WasmIndexExecutor {
             return Err(IndexerError::MissingHandler);
         }
 
-        Ok(WasmIndexExecutor {
-            instance,
-            _module: module,
-            _store: store,
-            db: env.db.clone(),
-        })
+        Ok((instance, env.db.clone()))
+    }
+
+    /// Stop a handler that has run past its timeout. It's interrupted, but one
+    /// held up in a host call may carry on for a while, so the batch is
+    /// reverted and the instance it runs in is replaced either way.
+    async fn stop_timed_out(
+        &mut self,
+        handle: JoinHandle<Result<(), RuntimeError>>,
+        timeout: Duration,
+    ) -> IndexerResult<()> {
+        self.limits.interrupt(&self.instance);
+        match tokio::time::timeout(INTERRUPT_GRACE_PERIOD, handle).await {
+            Ok(Ok(Err(e))) => error!("WasmIndexExecutor timed out: {}.", e.message()),
+            _ => warn!("WasmIndexExecutor handler didn't stop after timing out."),
+        }
+
+        self.db.lock().await.revert_transaction().await?;
+
+        let (instance, db) = Self::new_instance(
+            &self.module,
+            &self.db_url,
+            &self.manifest,
+            self.shadow_schema.as_deref(),
+            &self.limits,
+            &self.memory_exceeded,
+        )
+        .await?;
+        self.instance = instance;
+        self.db = db;
+
+        Err(IndexerError::ExecutionTimeout(timeout))
     }
 
     /// Restore index from wasm file
@@ -427,10 +959,11 @@ impl WasmIndexExecutor {
         Self::new(&config, &manifest, bytes).await
     }
 
-    pub async fn create(
+    pub async fn create<S: BlockSource + 'static>(
         config: &IndexerConfig,
         manifest: &Manifest,
         exec_source: ExecutorSource,
+        source: S,
     ) -> IndexerResult<(JoinHandle<()>, ExecutorSource, Arc<AtomicBool>)> {
         let killer = Arc::new(AtomicBool::new(false));
 
@@ -447,6 +980,7 @@ impl WasmIndexExecutor {
                         config,
                         manifest,
                         executor,
+                        source,
                         killer.clone(),
                     ));
 
@@ -462,6 +996,7 @@ impl WasmIndexExecutor {
                     config,
                     manifest,
                     executor,
+                    source,
                     killer.clone(),
                 ));
 
@@ -469,12 +1004,38 @@ impl WasmIndexExecutor {
             }
         }
     }
+
+    /// Start indexing a blue/green deployment of an indexer, until it catches
+    /// up with the chain.
+    pub async fn create_shadow<S: BlockSource + 'static>(
+        config: &IndexerConfig,
+        manifest: &Manifest,
+        wasm_bytes: Vec<u8>,
+        schema: &str,
+        source: S,
+    ) -> IndexerResult<(JoinHandle<()>, Arc<AtomicBool>)> {
+        let killer = Arc::new(AtomicBool::new(false));
+        let executor =
+            WasmIndexExecutor::new_shadow(config, manifest, wasm_bytes, schema).await?;
+        let handle = tokio::spawn(run_executor(
+            config,
+            manifest,
+            executor,
+            source,
+            killer.clone(),
+        ));
+
+        Ok((handle, killer))
+    }
 }
 
 #[async_trait]
 impl Executor for WasmIndexExecutor {
     /// Trigger a WASM event handler, passing in a serialized event struct.
     async fn handle_events(&mut self, blocks: Vec<BlockData>) -> IndexerResult<()> {
+        self.limits.reset(&self.instance)?;
+        self.memory_exceeded.store(false, Ordering::SeqCst);
+
         let bytes = serialize(&blocks);
         let arg = ffi::WasmArg::new(&self.instance, bytes)?;
 
@@ -483,12 +1044,32 @@ impl Executor for WasmIndexExecutor {
             .exports
             .get_native_function::<(u32, u32), ()>(ffi::MODULE_ENTRYPOINT)?;
 
-        self.db.lock().await.start_transaction().await?;
+        {
+            let mut db = self.db.lock().await;
+            db.start_transaction().await?;
+            db.set_block_height(blocks.first().map(|b| b.height));
+        }
 
         let ptr = arg.get_ptr();
         let len = arg.get_len();
 
-        let res = spawn_blocking(move || fun.call(ptr, len)).await?;
+        let mut handle = spawn_blocking(move || fun.call(ptr, len));
+        let res = match self.limits.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, &mut handle).await {
+                Ok(res) => res?,
+                Err(_) => {
+                    // The argument can't be freed while the handler may still
+                    // be using it, and goes with the instance it was made in.
+                    std::mem::forget(arg);
+                    return self.stop_timed_out(handle, timeout).await;
+                }
+            },
+            None => handle.await?,
+        };
+
+        // Refill the budget so that freeing the argument can still run.
+        let exhausted = self.limits.exhausted(&self.instance);
+        self.limits.reset(&self.instance)?;
 
         if let Err(e) = res {
             error!("WasmIndexExecutor handle_events failed: {}.", e.message());
@@ -503,10 +1084,79 @@ impl Executor for WasmIndexExecutor {
             }
 
             self.db.lock().await.revert_transaction().await?;
-            return Err(IndexerError::RuntimeError(e));
+
+            let limits = self.limits;
+            return Err(match limits {
+                ExecutionLimits {
+                    instructions: Some(instructions),
+                    ..
+                } if exhausted => IndexerError::InstructionLimitExceeded(instructions),
+                ExecutionLimits {
+                    memory_mb: Some(mb),
+                    ..
+                } if self.memory_exceeded.load(Ordering::SeqCst) => {
+                    IndexerError::MemoryLimitExceeded(mb)
+                }
+                _ => IndexerError::RuntimeError(e),
+            });
         } else {
-            self.db.lock().await.commit_transaction().await?;
+            let last = blocks.last().map(|b| (b.height, b.id));
+            self.db.lock().await.commit_batch(last).await?;
         }
         Ok(())
     }
+
+    async fn rollback(&mut self, from_height: u64) -> IndexerResult<()> {
+        let count = self.db.lock().await.rollback(from_height).await?;
+        info!("Reverted {count} writes from height {from_height}.");
+        Ok(())
+    }
+
+    async fn prune_rollback_journal(&mut self, below_height: u64) -> IndexerResult<()> {
+        self.db
+            .lock()
+            .await
+            .prune_rollback_journal(below_height)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_completed(&mut self) -> IndexerResult<()> {
+        self.db.lock().await.mark_completed().await?;
+        Ok(())
+    }
+
+    async fn caught_up(&mut self) -> IndexerResult<bool> {
+        self.db.lock().await.caught_up().await
+    }
+
+    async fn keeps_history(&mut self) -> bool {
+        self.db.lock().await.keeps_history()
+    }
+
+    async fn set_status(
+        &mut self,
+        status: IndexerStatus,
+        message: Option<String>,
+    ) -> IndexerResult<()> {
+        self.db
+            .lock()
+            .await
+            .set_status(status, message.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    async fn skip_block(
+        &mut self,
+        block: &BlockData,
+        error: String,
+    ) -> IndexerResult<()> {
+        self.db
+            .lock()
+            .await
+            .skip_block(block.height, block.id, &error)
+            .await?;
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use fuel_indexer_schema::FtColumn;
+use fuel_indexer_schema::{EntityFilter, FtColumn};
 use fuel_indexer_types::ffi::{
     LOG_LEVEL_DEBUG, LOG_LEVEL_ERROR, LOG_LEVEL_INFO, LOG_LEVEL_TRACE, LOG_LEVEL_WARN,
 };
@@ -114,14 +114,22 @@ fn log_data(env: &IndexEnv, ptr: u32, len: u32, log_level: u32) {
     }
 }
 
-fn get_object(env: &IndexEnv, type_id: i64, ptr: u32, len_ptr: u32) -> u32 {
+fn get_object(
+    env: &IndexEnv,
+    type_id: i64,
+    ptr: u32,
+    len_ptr: u32,
+) -> Result<u32, RuntimeError> {
     let mem = env.memory_ref().expect("Memory uninitialized.");
 
     let id = get_object_id(mem, ptr);
 
-    // TODO: stash this thing somewhere??
-    let rt = tokio::runtime::Runtime::new().expect("Could not create tokio runtime.");
-    let bytes = rt.block_on(async { env.db.lock().await.get_object(type_id, id).await });
+    let bytes = env
+        .runtime
+        .block_on(async { env.db.lock().await.get_object(type_id, id).await })
+        .map_err(|e| {
+            RuntimeError::new(format!("Failed to get TypeId({type_id}) object {id}: {e}"))
+        })?;
 
     if let Some(bytes) = bytes {
         let alloc_fn = env.alloc_ref().expect("Alloc export is missing.");
@@ -139,13 +147,18 @@ fn get_object(env: &IndexEnv, type_id: i64, ptr: u32, len_ptr: u32) -> u32 {
             mem.data_unchecked_mut()[range].copy_from_slice(&bytes);
         }
 
-        result
+        Ok(result)
     } else {
-        0
+        Ok(0)
     }
 }
 
-fn put_object(env: &IndexEnv, type_id: i64, ptr: u32, len: u32) {
+fn put_object(
+    env: &IndexEnv,
+    type_id: i64,
+    ptr: u32,
+    len: u32,
+) -> Result<(), RuntimeError> {
     let mem = env.memory_ref().expect("Memory uninitialized.");
 
     let mut bytes = Vec::with_capacity(len as usize);
@@ -157,21 +170,85 @@ fn put_object(env: &IndexEnv, type_id: i64, ptr: u32, len: u32) {
 
     let columns: Vec<FtColumn> = bincode::deserialize(&bytes).expect("Serde error.");
 
-    // TODO: stash this??
-    let rt = tokio::runtime::Runtime::new().expect("Could not create tokio runtime.");
-    rt.block_on(async {
-        env.db
-            .lock()
-            .await
-            .put_object(type_id, columns, bytes)
-            .await
-    });
+    env.runtime
+        .block_on(async {
+            env.db
+                .lock()
+                .await
+                .put_object(type_id, columns, bytes)
+                .await
+        })
+        .map_err(|e| {
+            RuntimeError::new(format!("Failed to put TypeId({type_id}) object: {e}"))
+        })
+}
+
+fn find_objects(
+    env: &IndexEnv,
+    type_id: i64,
+    ptr: u32,
+    len: u32,
+    len_ptr: u32,
+) -> Result<u32, RuntimeError> {
+    let mem = env.memory_ref().expect("Memory uninitialized.");
+
+    let mut bytes = Vec::with_capacity(len as usize);
+    let range = ptr as usize..ptr as usize + len as usize;
+
+    unsafe {
+        bytes.extend_from_slice(&mem.data_unchecked()[range]);
+    }
+
+    let filter: EntityFilter = bincode::deserialize(&bytes).expect("Serde error.");
+
+    let objects = env
+        .runtime
+        .block_on(async { env.db.lock().await.find_objects(type_id, filter).await })
+        .map_err(|e| {
+            RuntimeError::new(format!("Failed to find TypeId({type_id}) objects: {e}"))
+        })?;
+
+    let bytes = bincode::serialize(&objects).expect("Serde error.");
+    let alloc_fn = env.alloc_ref().expect("Alloc export is missing.");
+
+    let size = bytes.len() as u32;
+    let result = alloc_fn.call(size).expect("Alloc failed.");
+    let range = result as usize..result as usize + size as usize;
+
+    WasmPtr::<u32>::new(len_ptr)
+        .deref(mem)
+        .expect("Failed to deref WasmPtr.")
+        .set(size);
+
+    unsafe {
+        mem.data_unchecked_mut()[range].copy_from_slice(&bytes);
+    }
+
+    Ok(result)
+}
+
+fn delete_object(env: &IndexEnv, type_id: i64, ptr: u32) -> Result<(), RuntimeError> {
+    let mem = env.memory_ref().expect("Memory uninitialized.");
+
+    let id = get_object_id(mem, ptr);
+
+    env.runtime
+        .block_on(async { env.db.lock().await.delete_object(type_id, id).await })
+        .map_err(|e| {
+            RuntimeError::new(format!(
+                "Failed to delete TypeId({type_id}) object {id}: {e}"
+            ))
+        })?;
+
+    Ok(())
 }
 
 pub fn get_exports(env: &IndexEnv, store: &Store) -> Exports {
     let mut exports = Exports::new();
     declare_export!(get_object, exports, store, env);
     declare_export!(put_object, exports, store, env);
+    declare_export!(find_objects, exports, store, env);
+    declare_export!(delete_object, exports, store, env);
     declare_export!(log_data, exports, store, env);
     exports
 }
@@ -0,0 +1,158 @@
+//! Fetches blocks along with their transactions, statuses, and receipts in a
+//! single GraphQL query, rather than one query per block plus two per transaction.
+use crate::{IndexerError, IndexerResult};
+use cynic::{http::ReqwestExt, QueryBuilder};
+use fuel_core_client::client::schema::{
+    block::{Block, Consensus, Header},
+    schema,
+    tx::{OpaqueReceipt, OpaqueTransaction, TransactionStatus},
+    BlockId, ConnectionArgs, HexString, PageInfo, TransactionId,
+};
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/fuel_core_schema.sdl",
+    graphql_type = "Query",
+    variables = "ConnectionArgs"
+)]
+pub(crate) struct FullBlocksQuery {
+    #[arguments(after: $after, before: $before, first: $first, last: $last)]
+    pub blocks: FullBlockConnection,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/fuel_core_schema.sdl",
+    graphql_type = "BlockConnection"
+)]
+pub(crate) struct FullBlockConnection {
+    pub nodes: Vec<FullBlock>,
+    pub page_info: PageInfo,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(schema_path = "./assets/fuel_core_schema.sdl", graphql_type = "Block")]
+pub(crate) struct FullBlock {
+    pub id: BlockId,
+    pub header: Header,
+    pub consensus: Consensus,
+    pub transactions: Vec<FullTransaction>,
+}
+
+impl FullBlock {
+    /// Split off the transactions, returning the rest of the block as the
+    /// client's `Block` type so the producer can be recovered from it.
+    pub fn into_parts(self) -> (Block, Vec<FullTransaction>) {
+        let FullBlock {
+            id,
+            header,
+            consensus,
+            transactions,
+        } = self;
+
+        let block = Block {
+            id,
+            header,
+            consensus,
+            transactions: Vec::new(),
+        };
+
+        (block, transactions)
+    }
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/fuel_core_schema.sdl",
+    graphql_type = "Transaction"
+)]
+pub(crate) struct FullTransaction {
+    pub id: TransactionId,
+    pub raw_payload: HexString,
+    pub receipts: Option<Vec<OpaqueReceipt>>,
+    pub status: Option<TransactionStatus>,
+}
+
+impl FullTransaction {
+    /// Split off the id, returning the rest as the client's `OpaqueTransaction`.
+    pub fn into_parts(self) -> (TransactionId, OpaqueTransaction) {
+        let FullTransaction {
+            id,
+            raw_payload,
+            receipts,
+            status,
+        } = self;
+
+        let transaction = OpaqueTransaction {
+            raw_payload,
+            receipts,
+            status,
+        };
+
+        (id, transaction)
+    }
+}
+
+/// A minimal GraphQL client for the full block query.
+pub(crate) struct FullBlockClient {
+    client: reqwest::Client,
+    url: reqwest::Url,
+}
+
+impl FullBlockClient {
+    #[allow(clippy::result_large_err)]
+    pub fn new(fuel_node_addr: &str) -> IndexerResult<Self> {
+        let mut raw_url = fuel_node_addr.to_string();
+        if !raw_url.starts_with("http") {
+            raw_url = format!("http://{raw_url}");
+        }
+
+        let mut url = reqwest::Url::parse(&raw_url)
+            .map_err(|e| IndexerError::NodeConnectionError(e.to_string()))?;
+        url.set_path("/graphql");
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url,
+        })
+    }
+
+    /// Fetch up to `first` blocks following the `after` cursor, returning them
+    /// along with the cursor of the last block.
+    pub async fn full_blocks(
+        &self,
+        after: Option<String>,
+        first: usize,
+    ) -> IndexerResult<(Vec<FullBlock>, Option<String>)> {
+        let query = FullBlocksQuery::build(ConnectionArgs {
+            after,
+            before: None,
+            first: Some(first as i32),
+            last: None,
+        });
+
+        let response = self
+            .client
+            .post(self.url.clone())
+            .run_graphql(query)
+            .await
+            .map_err(|e| IndexerError::NodeConnectionError(e.to_string()))?;
+
+        match (response.data, response.errors) {
+            (Some(data), _) => {
+                let FullBlockConnection { nodes, page_info } = data.blocks;
+                Ok((nodes, page_info.end_cursor))
+            }
+            (None, Some(errors)) => Err(IndexerError::BlockSourceError(
+                errors
+                    .into_iter()
+                    .map(|e| e.message)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            )),
+            (None, None) => Err(IndexerError::BlockSourceError(
+                "Invalid response from Fuel node.".to_string(),
+            )),
+        }
+    }
+}
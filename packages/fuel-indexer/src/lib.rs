@@ -1,11 +1,18 @@
 #![deny(unused_crate_dependencies)]
+pub mod block_source;
 pub mod cli;
 pub(crate) mod commands;
 mod database;
 pub mod executor;
 pub mod ffi;
+mod full_block;
+mod limits;
 mod service;
 
+pub use block_source::{
+    BlockArchive, BlockSource, CachedBlockSource, FileBlockSource, FuelNodeBlockSource,
+    RecordingBlockSource,
+};
 pub use database::Database;
 pub use executor::{Executor, IndexEnv, NativeIndexExecutor, WasmIndexExecutor};
 pub use fuel_indexer_database::IndexerDatabaseError;
@@ -20,9 +27,9 @@ use wasmer::{ExportError, HostEnvInitError, InstantiationError, RuntimeError};
 
 pub mod prelude {
     pub use super::{
-        Database, Executor, FtColumn, IndexEnv, IndexerConfig, IndexerError,
-        IndexerResult, IndexerService, Manifest, Module, NativeIndexExecutor,
-        WasmIndexExecutor,
+        BlockSource, Database, Executor, FileBlockSource, FtColumn, FuelNodeBlockSource,
+        IndexEnv, IndexerConfig, IndexerError, IndexerResult, IndexerService, Manifest,
+        Module, NativeIndexExecutor, WasmIndexExecutor,
     };
     pub use async_std::sync::{Arc, Mutex};
     pub use fuel_indexer_lib::config::{DatabaseConfig, FuelNodeConfig, GraphQLConfig};
@@ -77,4 +84,20 @@ pub enum IndexerError {
     NativeExecutionInstantiationError,
     #[error("Native execution runtime error.")]
     NativeExecutionRuntimeError,
+    #[error("Failed to roll back indexer after chain reorganization: {0:?}")]
+    RollbackError(String),
+    #[error("Node connection failed: {0:?}")]
+    NodeConnectionError(String),
+    #[error("Block source error: {0:?}")]
+    BlockSourceError(String),
+    #[error("Indexer exceeded its limit of {0} instructions per batch.")]
+    InstructionLimitExceeded(u64),
+    #[error("Indexer exceeded its memory limit of {0} MiB.")]
+    MemoryLimitExceeded(u64),
+    #[error("Indexer exceeded its execution time limit of {0:?}.")]
+    ExecutionTimeout(std::time::Duration),
+    #[error("Invalid entity filter: {0:?}")]
+    InvalidEntityFilter(String),
+    #[error("Invalid serialized entity: {0:?}")]
+    InvalidObject(String),
 }
@@ -0,0 +1,425 @@
+//! Limits on the instructions, linear memory, and time a WASM indexer may use
+//! while handling a batch of blocks.
+use crate::{IndexerConfig, IndexerError, Manifest};
+use loupe::{MemoryUsage, MemoryUsageTracker};
+use std::{
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicBool, AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use wasmer::{
+    vm::{
+        Memory, MemoryError, MemoryStyle, Table, TableStyle, VMMemoryDefinition,
+        VMTableDefinition,
+    },
+    wasmparser::{Operator, Type as WpType, TypeOrFuncType},
+    BaseTunables, ExportIndex, FunctionMiddleware, GlobalInit, GlobalType, Instance,
+    LocalFunctionIndex, MemoryType, MiddlewareError, MiddlewareReaderState,
+    ModuleMiddleware, Mutability, Pages, TableType, Tunables, Type, Value,
+    WASM_MAX_PAGES,
+};
+use wasmer_types::{GlobalIndex, ModuleInfo};
+
+/// Export holding the number of instructions the indexer may still execute.
+const REMAINING_POINTS_EXPORT: &str = "fuel_indexer_remaining_points";
+
+/// Export set to 1 when the indexer has run out of instructions.
+const POINTS_EXHAUSTED_EXPORT: &str = "fuel_indexer_points_exhausted";
+
+/// Export the service sets to 1 to stop the indexer the next time it's metered.
+/// Only the service writes it, so it can't be lost to a concurrent update.
+const INTERRUPTED_EXPORT: &str = "fuel_indexer_interrupted";
+
+/// How long an interrupted handler has to trap before it's abandoned, in case
+/// it's held up in a host call, where it isn't metered.
+pub(crate) const INTERRUPT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+const MIB: u64 = 1024 * 1024;
+
+/// The limits a WASM executor enforces, combining the service's limits with
+/// any tighter ones from the indexer's manifest.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ExecutionLimits {
+    pub instructions: Option<u64>,
+    pub memory_mb: Option<u64>,
+    pub timeout: Option<Duration>,
+}
+
+/// The tighter of two limits, where 0 or `None` means unlimited.
+fn tightest(service: u64, indexer: Option<u64>) -> Option<u64> {
+    let service = Some(service).filter(|l| *l > 0);
+    let indexer = indexer.filter(|l| *l > 0);
+    match (service, indexer) {
+        (Some(s), Some(i)) => Some(s.min(i)),
+        (s, i) => s.or(i),
+    }
+}
+
+impl ExecutionLimits {
+    pub fn new(config: &IndexerConfig, manifest: &Manifest) -> Self {
+        let limits = &manifest.limits;
+        Self {
+            instructions: tightest(config.wasm_instruction_limit, limits.instructions),
+            memory_mb: tightest(config.wasm_memory_limit_mb, limits.memory_mb),
+            timeout: tightest(config.wasm_timeout_secs, limits.timeout_secs)
+                .map(Duration::from_secs),
+        }
+    }
+
+    /// Whether executions need to be metered. A timeout is enforced by
+    /// interrupting the indexer where it's metered, so it needs metering too.
+    pub fn metered(&self) -> bool {
+        self.instructions.is_some() || self.timeout.is_some()
+    }
+
+    pub fn memory_pages(&self) -> Pages {
+        let max = WASM_MAX_PAGES as u64;
+        let pages = self.memory_mb.map_or(max, |mb| {
+            (mb * MIB / wasmer::WASM_PAGE_SIZE as u64).min(max)
+        });
+        Pages(pages as u32)
+    }
+
+    /// Reset the instruction budget before handling a batch of blocks.
+    #[allow(clippy::result_large_err)]
+    pub fn reset(&self, instance: &Instance) -> Result<(), IndexerError> {
+        if !self.metered() {
+            return Ok(());
+        }
+
+        let points = self.instructions.unwrap_or(u64::MAX);
+        instance
+            .exports
+            .get_global(REMAINING_POINTS_EXPORT)?
+            .set(Value::I64(points as i64))?;
+        for export in [POINTS_EXHAUSTED_EXPORT, INTERRUPTED_EXPORT] {
+            instance.exports.get_global(export)?.set(Value::I32(0))?;
+        }
+
+        Ok(())
+    }
+
+    /// Flag a handler running on another thread to trap the next time it's
+    /// metered.
+    pub fn interrupt(&self, instance: &Instance) {
+        if let Ok(global) = instance.exports.get_global(INTERRUPTED_EXPORT) {
+            // SAFETY: the global's definition lives in an `UnsafeCell` as long
+            // as the instance, and is aligned for any value type. The handler
+            // only ever reads it, so an atomic store is all it can observe.
+            unsafe {
+                let definition = global.get_vm_global().from.vmglobal();
+                let flag = &*(definition.as_ptr() as *const AtomicI32);
+                flag.store(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Whether the last execution trapped because it ran out of instructions.
+    pub fn exhausted(&self, instance: &Instance) -> bool {
+        instance
+            .exports
+            .get_global(POINTS_EXHAUSTED_EXPORT)
+            .map_or(false, |g| g.get() == Value::I32(1))
+    }
+}
+
+/// Compiler middleware that charges one point per WASM operator, trapping
+/// once the budget in `REMAINING_POINTS_EXPORT` runs out, or once the service
+/// sets `INTERRUPTED_EXPORT`.
+///
+/// A `Metering` instance may only be used to compile a single module.
+#[derive(Debug, Default)]
+pub(crate) struct Metering {
+    globals: Mutex<Option<(GlobalIndex, GlobalIndex, GlobalIndex)>>,
+}
+
+impl MemoryUsage for Metering {
+    fn size_of_val(&self, _: &mut dyn MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+impl ModuleMiddleware for Metering {
+    fn generate_function_middleware(
+        &self,
+        _: LocalFunctionIndex,
+    ) -> Box<dyn FunctionMiddleware> {
+        let (remaining, exhausted, interrupted) = self
+            .globals
+            .lock()
+            .expect("Metering globals lock poisoned.")
+            .expect("Metering globals are added before functions are compiled.");
+
+        Box::new(FunctionMetering {
+            remaining,
+            exhausted,
+            interrupted,
+            accumulated: 0,
+        })
+    }
+
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        let mut globals = self
+            .globals
+            .lock()
+            .expect("Metering globals lock poisoned.");
+        assert!(
+            globals.is_none(),
+            "Metering can only be used for one module."
+        );
+
+        let remaining = module_info
+            .globals
+            .push(GlobalType::new(Type::I64, Mutability::Var));
+        module_info
+            .global_initializers
+            .push(GlobalInit::I64Const(0));
+        module_info.exports.insert(
+            REMAINING_POINTS_EXPORT.to_string(),
+            ExportIndex::Global(remaining),
+        );
+
+        let exhausted = module_info
+            .globals
+            .push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info
+            .global_initializers
+            .push(GlobalInit::I32Const(0));
+        module_info.exports.insert(
+            POINTS_EXHAUSTED_EXPORT.to_string(),
+            ExportIndex::Global(exhausted),
+        );
+
+        let interrupted = module_info
+            .globals
+            .push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info
+            .global_initializers
+            .push(GlobalInit::I32Const(0));
+        module_info.exports.insert(
+            INTERRUPTED_EXPORT.to_string(),
+            ExportIndex::Global(interrupted),
+        );
+
+        *globals = Some((remaining, exhausted, interrupted));
+    }
+}
+
+#[derive(Debug)]
+struct FunctionMetering {
+    remaining: GlobalIndex,
+    exhausted: GlobalIndex,
+    interrupted: GlobalIndex,
+    accumulated: u64,
+}
+
+impl FunctionMiddleware for FunctionMetering {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        self.accumulated += 1;
+
+        // Charge for the operators seen so far at every point where control
+        // flow can leave the current basic block.
+        let ends_block = matches!(
+            operator,
+            Operator::Loop { .. }
+                | Operator::End
+                | Operator::Else
+                | Operator::Br { .. }
+                | Operator::BrTable { .. }
+                | Operator::BrIf { .. }
+                | Operator::Call { .. }
+                | Operator::CallIndirect { .. }
+                | Operator::Return
+        );
+
+        if ends_block && self.accumulated > 0 {
+            let remaining = self.remaining.as_u32();
+            let cost = self.accumulated as i64;
+            state.extend(&[
+                Operator::GlobalGet {
+                    global_index: self.interrupted.as_u32(),
+                },
+                Operator::If {
+                    ty: TypeOrFuncType::Type(WpType::EmptyBlockType),
+                },
+                Operator::Unreachable,
+                Operator::End,
+                Operator::GlobalGet {
+                    global_index: remaining,
+                },
+                Operator::I64Const { value: cost },
+                Operator::I64LtU,
+                Operator::If {
+                    ty: TypeOrFuncType::Type(WpType::EmptyBlockType),
+                },
+                Operator::I32Const { value: 1 },
+                Operator::GlobalSet {
+                    global_index: self.exhausted.as_u32(),
+                },
+                Operator::Unreachable,
+                Operator::End,
+                Operator::GlobalGet {
+                    global_index: remaining,
+                },
+                Operator::I64Const { value: cost },
+                Operator::I64Sub,
+                Operator::GlobalSet {
+                    global_index: remaining,
+                },
+            ]);
+            self.accumulated = 0;
+        }
+
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
+/// Tunables that cap the size of every linear memory, flagging any attempt to
+/// grow past the cap.
+pub(crate) struct LimitingTunables {
+    base: BaseTunables,
+    limit: Pages,
+    exceeded: Arc<AtomicBool>,
+}
+
+impl MemoryUsage for LimitingTunables {
+    fn size_of_val(&self, tracker: &mut dyn MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self) + self.base.size_of_val(tracker)
+    }
+}
+
+impl LimitingTunables {
+    pub fn new(base: BaseTunables, limit: Pages, exceeded: Arc<AtomicBool>) -> Self {
+        Self {
+            base,
+            limit,
+            exceeded,
+        }
+    }
+
+    fn adjust(&self, ty: &MemoryType) -> MemoryType {
+        let mut adjusted = *ty;
+        if ty.maximum.map_or(true, |max| max > self.limit) {
+            adjusted.maximum = Some(self.limit);
+        }
+        adjusted
+    }
+
+    fn validate(&self, ty: &MemoryType) -> Result<(), MemoryError> {
+        if ty.minimum > self.limit {
+            self.exceeded.store(true, Ordering::SeqCst);
+            return Err(MemoryError::Generic(format!(
+                "Initial memory of {:?} exceeds the limit of {:?}.",
+                ty.minimum, self.limit
+            )));
+        }
+        Ok(())
+    }
+
+    fn limited(&self, inner: Arc<dyn Memory>) -> Arc<dyn Memory> {
+        Arc::new(LimitedMemory {
+            inner,
+            limit: self.limit,
+            exceeded: self.exceeded.clone(),
+        })
+    }
+}
+
+impl Tunables for LimitingTunables {
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        self.base.memory_style(&self.adjust(memory))
+    }
+
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+    ) -> Result<Arc<dyn Memory>, MemoryError> {
+        let ty = self.adjust(ty);
+        self.validate(&ty)?;
+        Ok(self.limited(self.base.create_host_memory(&ty, style)?))
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<Arc<dyn Memory>, MemoryError> {
+        let ty = self.adjust(ty);
+        self.validate(&ty)?;
+        Ok(self.limited(self.base.create_vm_memory(
+            &ty,
+            style,
+            vm_definition_location,
+        )?))
+    }
+
+    fn create_host_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+    ) -> Result<Arc<dyn Table>, String> {
+        self.base.create_host_table(ty, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<Arc<dyn Table>, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
+
+/// A linear memory that records when it's asked to grow past its limit.
+#[derive(Debug)]
+struct LimitedMemory {
+    inner: Arc<dyn Memory>,
+    limit: Pages,
+    exceeded: Arc<AtomicBool>,
+}
+
+impl MemoryUsage for LimitedMemory {
+    fn size_of_val(&self, tracker: &mut dyn MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self) + self.inner.size_of_val(tracker)
+    }
+}
+
+impl Memory for LimitedMemory {
+    fn ty(&self) -> MemoryType {
+        self.inner.ty()
+    }
+
+    fn style(&self) -> &MemoryStyle {
+        self.inner.style()
+    }
+
+    fn size(&self) -> Pages {
+        self.inner.size()
+    }
+
+    fn grow(&self, delta: Pages) -> Result<Pages, MemoryError> {
+        if self.size().0.saturating_add(delta.0) > self.limit.0 {
+            self.exceeded.store(true, Ordering::SeqCst);
+        }
+        self.inner.grow(delta)
+    }
+
+    fn vmmemory(&self) -> NonNull<VMMemoryDefinition> {
+        self.inner.vmmemory()
+    }
+}
@@ -1,18 +1,22 @@
 use crate::{
     executor::{ExecutorSource, NativeIndexExecutor, WasmIndexExecutor},
-    Database, IndexerConfig, IndexerError, IndexerResult, Manifest,
+    BlockArchive, BlockSource, CachedBlockSource, Database, FileBlockSource,
+    FuelNodeBlockSource, IndexerConfig, IndexerError, IndexerResult, Manifest,
+    RecordingBlockSource,
 };
 use async_std::sync::{Arc, Mutex};
 use fuel_indexer_database::{
-    queries, types::IndexAssetType, IndexerConnection, IndexerConnectionPool,
+    queries,
+    types::{IndexAssetType, ShadowDeployment},
+    IndexerConnection, IndexerConnectionPool,
+};
+use fuel_indexer_lib::{
+    defaults::{self, SHADOW_IDENTIFIER_SUFFIX},
+    utils::ServiceRequest,
 };
-use fuel_indexer_lib::{defaults, utils::ServiceRequest};
 use fuel_indexer_schema::db::manager::SchemaManager;
 use fuel_indexer_types::abi::BlockData;
-use futures::{
-    stream::{FuturesUnordered, StreamExt},
-    Future,
-};
+use futures::Future;
 use std::collections::HashMap;
 use std::marker::Send;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -23,13 +27,126 @@ use tokio::{
 };
 use tracing::{debug, error, info, warn};
 
+/// Where the executors of a service get their blocks from.
+#[derive(Clone)]
+enum BlockSourceMode {
+    /// Fetch blocks from the Fuel node.
+    Node,
+    /// Fetch blocks from the Fuel node, writing them to an archive.
+    Record(Arc<BlockArchive>),
+    /// Serve blocks from a previously recorded archive.
+    Replay(Arc<FileBlockSource>),
+}
+
+type SharedBlockSource = Arc<CachedBlockSource<Box<dyn BlockSource>>>;
+
+/// Hands out block sources to executors.
+///
+/// Executors that index from the same Fuel node share a single cached source,
+/// so each block is fetched once however many indexers are registered. Each
+/// executor still keeps its own cursor, and handles its own failures.
+#[derive(Clone)]
+pub(crate) struct BlockFetcher {
+    mode: BlockSourceMode,
+    shared: Arc<std::sync::Mutex<HashMap<String, SharedBlockSource>>>,
+}
+
+impl BlockFetcher {
+    #[allow(clippy::result_large_err)]
+    fn from_config(config: &IndexerConfig) -> IndexerResult<Self> {
+        let mode = if let Some(path) = &config.replay_blocks {
+            info!("Replaying blocks from {}", path.display());
+            BlockSourceMode::Replay(Arc::new(FileBlockSource::from_file(path)?))
+        } else if let Some(path) = &config.record_blocks {
+            info!("Recording blocks to {}", path.display());
+            BlockSourceMode::Record(Arc::new(BlockArchive::create(path)?))
+        } else {
+            BlockSourceMode::Node
+        };
+
+        Ok(Self {
+            mode,
+            shared: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        })
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn source_for(
+        &self,
+        config: &IndexerConfig,
+        manifest: &Manifest,
+    ) -> IndexerResult<Box<dyn BlockSource>> {
+        if let BlockSourceMode::Replay(source) = &self.mode {
+            return Ok(Box::new(source.clone()));
+        }
+
+        let addr = FuelNodeBlockSource::fuel_node_addr(config, manifest);
+        let mut shared = self.shared.lock().expect("Block fetcher lock poisoned.");
+        if let Some(source) = shared.get(&addr) {
+            return Ok(Box::new(source.clone()));
+        }
+
+        let node = FuelNodeBlockSource::new(config, manifest)?;
+        let inner: Box<dyn BlockSource> = match &self.mode {
+            BlockSourceMode::Record(archive) => {
+                Box::new(RecordingBlockSource::new(node, archive.clone()))
+            }
+            _ => Box::new(node),
+        };
+
+        let source = Arc::new(CachedBlockSource::new(inner, defaults::BLOCK_CACHE_SIZE));
+        shared.insert(addr, source.clone());
+
+        Ok(Box::new(source))
+    }
+}
+
+/// A running executor, along with the kill switch that stops it.
+pub(crate) struct ExecutorHandle {
+    handle: JoinHandle<()>,
+    killer: Arc<AtomicBool>,
+}
+
+impl ExecutorHandle {
+    fn new(handle: JoinHandle<()>, killer: Arc<AtomicBool>) -> Self {
+        Self { handle, killer }
+    }
+
+    /// Whether the executor's task has finished, however it ended.
+    fn stopped(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Ask the executor to stop once it's done with the batch it's indexing.
+    fn kill(&self) {
+        self.killer.store(true, Ordering::SeqCst);
+    }
+
+    /// Stop the executor, waiting for it to finish the batch it's indexing.
+    async fn stop(self) {
+        self.kill();
+        if let Err(e) = self.handle.await {
+            error!("Executor failed while stopping: {e}");
+        }
+    }
+}
+
+/// The executor of a blue/green deployment, indexing into the shadow schema of
+/// an indexer until it catches up with the chain.
+pub(crate) struct ShadowExecutor {
+    namespace: String,
+    identifier: String,
+    executor: ExecutorHandle,
+}
+
 pub struct IndexerService {
     config: IndexerConfig,
+    blocks: BlockFetcher,
     pool: IndexerConnectionPool,
     manager: SchemaManager,
-    handles: HashMap<String, JoinHandle<()>>,
     rx: Receiver<ServiceRequest>,
-    killers: HashMap<String, Arc<AtomicBool>>,
+    executors: HashMap<String, ExecutorHandle>,
+    shadows: HashMap<String, ShadowExecutor>,
 }
 
 impl IndexerService {
@@ -39,13 +156,15 @@ impl IndexerService {
         rx: Receiver<ServiceRequest>,
     ) -> IndexerResult<IndexerService> {
         let manager = SchemaManager::new(pool.clone());
+        let blocks = BlockFetcher::from_config(&config)?;
 
         Ok(IndexerService {
             config,
+            blocks,
             pool,
             manager,
-            handles: HashMap::default(),
-            killers: HashMap::default(),
+            executors: HashMap::default(),
+            shadows: HashMap::default(),
             rx,
         })
     }
@@ -76,11 +195,22 @@ impl IndexerService {
             .await?;
 
         let mut conn = self.pool.acquire().await?;
-        let start_block = get_start_block(&mut conn, &manifest).await?;
+        queries::set_index_completed(
+            &mut conn,
+            &manifest.namespace,
+            &manifest.identifier,
+            false,
+        )
+        .await?;
+        let start_block = self.registered_start_block(&mut conn, &manifest).await?;
         manifest.start_block = Some(start_block);
-        let (handle, exec_source, killer) =
-            WasmIndexExecutor::create(&self.config, &manifest, ExecutorSource::Manifest)
-                .await?;
+        let (handle, exec_source, killer) = WasmIndexExecutor::create(
+            &self.config,
+            &manifest,
+            ExecutorSource::Manifest,
+            self.blocks.source_for(&self.config, &manifest)?,
+        )
+        .await?;
 
         let mut items = vec![
             (IndexAssetType::Wasm, exec_source.to_vec()),
@@ -112,31 +242,89 @@ impl IndexerService {
         }
 
         info!("Registered Indexer({})", &manifest.uid());
-        self.handles.insert(manifest.uid(), handle);
-        self.killers.insert(manifest.uid(), killer);
+        self.executors
+            .insert(manifest.uid(), ExecutorHandle::new(handle, killer));
 
         Ok(())
     }
 
+    /// Whether indexers replay blocks from their start block, rather than
+    /// resuming from their checkpoint.
+    fn replays_from_start(&self) -> bool {
+        self.config.replay_blocks.is_some() && self.config.replay_from_start
+    }
+
+    /// The block a registered indexer starts from. When replaying blocks from
+    /// the start, what it has indexed so far is cleared first.
+    async fn registered_start_block(
+        &self,
+        conn: &mut IndexerConnection,
+        manifest: &Manifest,
+    ) -> IndexerResult<u64> {
+        if self.replays_from_start() {
+            queries::start_transaction(conn).await?;
+            let cleared = self
+                .manager
+                .clear_indexed_data(&manifest.namespace, &manifest.identifier, conn)
+                .await;
+            if let Err(e) = cleared {
+                queries::revert_transaction(conn).await?;
+                return Err(e.into());
+            }
+            queries::set_index_completed(
+                conn,
+                &manifest.namespace,
+                &manifest.identifier,
+                false,
+            )
+            .await?;
+            queries::commit_transaction(conn).await?;
+            info!("Replaying Indexer({}) from its start block", manifest.uid());
+        }
+
+        get_start_block(conn, manifest).await
+    }
+
     pub async fn register_indices_from_registry(&mut self) -> IndexerResult<()> {
         let mut conn = self.pool.acquire().await?;
         let indices = queries::registered_indices(&mut conn).await?;
         for index in indices {
+            if let Some(shadow) = queries::shadow_deployment_for(
+                &mut conn,
+                &index.namespace,
+                &index.identifier,
+            )
+            .await?
+            {
+                let executor =
+                    start_shadow_executor(&self.config, &self.blocks, &mut conn, shadow)
+                        .await?;
+
+                info!("Resumed blue/green deployment of Indexer({})", index.uid());
+                self.shadows.insert(index.uid(), executor);
+            }
+
+            if index.completed && !self.replays_from_start() {
+                info!("Indexer({}) reached its end_block, skipping.", index.uid());
+                continue;
+            }
+
             let assets = queries::latest_assets_for_index(&mut conn, &index.id).await?;
             let mut manifest = Manifest::try_from(&assets.manifest.bytes)?;
 
-            let start_block = get_start_block(&mut conn, &manifest).await.unwrap_or(1);
+            let start_block = self.registered_start_block(&mut conn, &manifest).await?;
             manifest.start_block = Some(start_block);
             let (handle, _module_bytes, killer) = WasmIndexExecutor::create(
                 &self.config,
                 &manifest,
                 ExecutorSource::Registry(assets.wasm.bytes),
+                self.blocks.source_for(&self.config, &manifest)?,
             )
             .await?;
 
             info!("Registered Indexer({})", manifest.uid());
-            self.handles.insert(manifest.uid(), handle);
-            self.killers.insert(manifest.uid(), killer);
+            self.executors
+                .insert(manifest.uid(), ExecutorHandle::new(handle, killer));
         }
 
         Ok(())
@@ -169,45 +357,54 @@ impl IndexerService {
             )
             .await?;
 
-        let start_block = get_start_block(&mut conn, &manifest).await.unwrap_or(1);
+        queries::set_index_completed(
+            &mut conn,
+            &manifest.namespace,
+            &manifest.identifier,
+            false,
+        )
+        .await?;
+        let start_block = self.registered_start_block(&mut conn, &manifest).await?;
         manifest.start_block = Some(start_block);
         let uid = manifest.uid();
-        let (handle, _module_bytes, killer) =
-            NativeIndexExecutor::<T>::create(&self.config, &manifest, handle_events)
-                .await?;
+        let (handle, _module_bytes, killer) = NativeIndexExecutor::<T>::create(
+            &self.config,
+            &manifest,
+            handle_events,
+            self.blocks.source_for(&self.config, &manifest)?,
+        )
+        .await?;
 
         info!("Registered NativeIndex({})", uid);
 
-        self.handles.insert(uid.clone(), handle);
-        self.killers.insert(uid, killer);
+        self.executors
+            .insert(uid, ExecutorHandle::new(handle, killer));
         Ok(())
     }
 
     pub async fn run(self) {
         let IndexerService {
-            handles,
             rx,
             pool,
             config,
-            killers,
+            blocks,
+            mut executors,
+            mut shadows,
             ..
         } = self;
 
-        let futs = Arc::new(Mutex::new(FuturesUnordered::from_iter(
-            handles.into_values(),
-        )));
+        if let Err(e) =
+            create_service_task(rx, config, blocks, pool, &mut executors, &mut shadows)
+                .await
+        {
+            error!("Indexer service failed: {e}");
+        }
 
-        let _ = tokio::spawn(create_service_task(
-            rx,
-            config.clone(),
-            pool.clone(),
-            futs.clone(),
-            killers,
-        ))
-        .await
-        .unwrap();
-
-        while let Some(fut) = futs.lock().await.next().await {
+        let executors = executors
+            .into_values()
+            .chain(shadows.into_values().map(|shadow| shadow.executor));
+        for executor in executors {
+            let fut = executor.handle.await;
             info!("Retired a future {fut:?}");
         }
     }
@@ -216,12 +413,31 @@ impl IndexerService {
 async fn create_service_task(
     mut rx: Receiver<ServiceRequest>,
     config: IndexerConfig,
+    blocks: BlockFetcher,
     pool: IndexerConnectionPool,
-    futs: Arc<Mutex<FuturesUnordered<JoinHandle<()>>>>,
-    mut killers: HashMap<String, Arc<AtomicBool>>,
+    executors: &mut HashMap<String, ExecutorHandle>,
+    shadows: &mut HashMap<String, ShadowExecutor>,
 ) -> IndexerResult<()> {
     loop {
-        let futs = futs.lock().await;
+        // Blue/green deployments are promoted as soon as their executor stops,
+        // whether or not the service is busy with requests.
+        let stopped: Vec<String> = shadows
+            .iter()
+            .filter(|(_, shadow)| shadow.executor.stopped())
+            .map(|(uid, _)| uid.clone())
+            .collect();
+
+        for uid in stopped {
+            if let Some(shadow) = shadows.remove(&uid) {
+                if let Err(e) =
+                    promote_shadow_executor(&config, &blocks, &pool, executors, shadow)
+                        .await
+                {
+                    error!("Failed to replace Indexer({uid}) with its blue/green deployment: {e}");
+                }
+            }
+        }
+
         match rx.try_recv() {
             Ok(service_request) => match service_request {
                 ServiceRequest::AssetReload(request) => {
@@ -235,30 +451,16 @@ async fn create_service_task(
                     .await
                     {
                         Ok(id) => {
-                            let assets =
-                                queries::latest_assets_for_index(&mut conn, &id).await?;
-                            let mut manifest =
-                                Manifest::try_from(&assets.manifest.bytes)?;
-
-                            let start_block =
-                                get_start_block(&mut conn, &manifest).await?;
-                            manifest.start_block = Some(start_block);
-                            let (handle, _module_bytes, killer) =
-                                WasmIndexExecutor::create(
-                                    &config,
-                                    &manifest,
-                                    ExecutorSource::Registry(assets.wasm.bytes),
-                                )
-                                .await?;
-
-                            futs.push(handle);
-
-                            if let Some(killer_for_prev_executor) =
-                                killers.insert(manifest.uid(), killer)
+                            let (uid, executor) = start_registered_executor(
+                                &config, &blocks, &mut conn, &id,
+                            )
+                            .await?;
+
+                            if let Some(previous) =
+                                executors.insert(uid.clone(), executor)
                             {
-                                let uid = manifest.uid();
                                 info!("Indexer({uid}) was replaced. Stopping previous version of Indexer({uid}).");
-                                killer_for_prev_executor.store(true, Ordering::SeqCst);
+                                previous.kill();
                             }
                         }
                         Err(e) => {
@@ -271,11 +473,74 @@ async fn create_service_task(
                         }
                     }
                 }
+                ServiceRequest::ShadowDeploy(request) => {
+                    let uid = format!("{}.{}", request.namespace, request.identifier);
+
+                    if let Some(previous) = shadows.remove(&uid) {
+                        info!("Indexer({uid}) was redeployed. Stopping previous blue/green deployment of Indexer({uid}).");
+                        previous.executor.stop().await;
+                    }
+
+                    let mut conn = pool.acquire().await?;
+                    let shadow = match queries::shadow_deployment_for(
+                        &mut conn,
+                        &request.namespace,
+                        &request.identifier,
+                    )
+                    .await?
+                    {
+                        Some(shadow) => shadow,
+                        None => {
+                            warn!("Blue/green deployment of Indexer({uid}) not found.");
+                            continue;
+                        }
+                    };
+
+                    let schema = String::from_utf8_lossy(&shadow.schema).to_string();
+                    let shadow_identifier =
+                        format!("{}{SHADOW_IDENTIFIER_SUFFIX}", shadow.identifier);
+
+                    queries::start_transaction(&mut conn).await?;
+                    if let Err(e) = new_shadow_schema(
+                        &pool,
+                        &mut conn,
+                        &shadow.namespace,
+                        &shadow.identifier,
+                        &shadow_identifier,
+                        &schema,
+                    )
+                    .await
+                    {
+                        queries::revert_transaction(&mut conn).await?;
+                        error!("Failed to create the schema of the blue/green deployment of Indexer({uid}): {e}");
+                        continue;
+                    }
+                    queries::commit_transaction(&mut conn).await?;
+
+                    let executor = match start_shadow_executor(
+                        &config, &blocks, &mut conn, shadow,
+                    )
+                    .await
+                    {
+                        Ok(started) => started,
+                        Err(e) => {
+                            error!("Failed to start blue/green deployment of Indexer({uid}): {e}");
+                            continue;
+                        }
+                    };
+
+                    info!("Started blue/green deployment of Indexer({uid})");
+                    shadows.insert(uid, executor);
+                }
                 ServiceRequest::IndexStop(request) => {
                     let uid = format!("{}.{}", request.namespace, request.identifier);
 
-                    if let Some(killer) = killers.remove(&uid) {
-                        killer.store(true, Ordering::SeqCst);
+                    if let Some(shadow) = shadows.remove(&uid) {
+                        shadow.executor.kill();
+                    }
+
+                    if let Some(executor) = executors.remove(&uid) {
+                        executor.kill();
                     } else {
                         warn!("Stop Indexer: No indexer with the name Indexer({uid})");
                     }
@@ -283,8 +548,8 @@ async fn create_service_task(
                 ServiceRequest::IndexRevert(request) => {
                     let uid = format!("{}.{}", request.namespace, request.identifier);
 
-                    if let Some(killer) = killers.get(&uid) {
-                        killer.store(true, Ordering::SeqCst);
+                    if let Some(executor) = executors.get(&uid) {
+                        executor.kill();
                     } else {
                         warn!("Revert Indexer: Indexer({uid}) not found.");
                     }
@@ -321,11 +586,11 @@ async fn create_service_task(
                         &config,
                         &manifest,
                         ExecutorSource::Registry(request.penultimate_asset_bytes),
+                        blocks.source_for(&config, &manifest)?,
                     )
                     .await?;
 
-                    futs.push(handle);
-                    killers.insert(manifest.uid(), killer);
+                    executors.insert(manifest.uid(), ExecutorHandle::new(handle, killer));
                 }
             },
             Err(e) => {
@@ -336,21 +601,212 @@ async fn create_service_task(
     }
 }
 
+/// Start an executor for the latest assets registered for an indexer.
+async fn start_registered_executor(
+    config: &IndexerConfig,
+    blocks: &BlockFetcher,
+    conn: &mut IndexerConnection,
+    index_id: &i64,
+) -> IndexerResult<(String, ExecutorHandle)> {
+    let assets = queries::latest_assets_for_index(conn, index_id).await?;
+    let mut manifest = Manifest::try_from(&assets.manifest.bytes)?;
+
+    queries::set_index_completed(conn, &manifest.namespace, &manifest.identifier, false)
+        .await?;
+
+    let start_block = get_start_block(conn, &manifest).await?;
+    manifest.start_block = Some(start_block);
+    let (handle, _module_bytes, killer) = WasmIndexExecutor::create(
+        config,
+        &manifest,
+        ExecutorSource::Registry(assets.wasm.bytes),
+        blocks.source_for(config, &manifest)?,
+    )
+    .await?;
+
+    Ok((manifest.uid(), ExecutorHandle::new(handle, killer)))
+}
+
+/// Replace any previous tables of a blue/green deployment with those of its
+/// schema, dropping the progress indexed into them. Expects to be run in a
+/// transaction.
+async fn new_shadow_schema(
+    pool: &IndexerConnectionPool,
+    conn: &mut IndexerConnection,
+    namespace: &str,
+    identifier: &str,
+    shadow_identifier: &str,
+    schema: &str,
+) -> IndexerResult<()> {
+    queries::remove_indexer_state(conn, namespace, shadow_identifier).await?;
+    SchemaManager::new(pool.clone())
+        .new_shadow_schema(namespace, identifier, schema, conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Start the executor of a blue/green deployment, resuming from the progress
+/// kept under its shadow identifier.
+async fn start_shadow_executor(
+    config: &IndexerConfig,
+    blocks: &BlockFetcher,
+    conn: &mut IndexerConnection,
+    shadow: ShadowDeployment,
+) -> IndexerResult<ShadowExecutor> {
+    let mut manifest = Manifest::try_from(&shadow.manifest)?;
+    let schema = String::from_utf8_lossy(&shadow.schema).to_string();
+
+    let shadow_identifier = format!("{}{SHADOW_IDENTIFIER_SUFFIX}", manifest.identifier);
+    let start_block = start_block_for(conn, &manifest, &shadow_identifier).await?;
+    manifest.start_block = Some(start_block);
+    let (handle, killer) = WasmIndexExecutor::create_shadow(
+        config,
+        &manifest,
+        shadow.wasm,
+        &schema,
+        blocks.source_for(config, &manifest)?,
+    )
+    .await?;
+
+    Ok(ShadowExecutor {
+        namespace: shadow.namespace,
+        identifier: shadow.identifier,
+        executor: ExecutorHandle::new(handle, killer),
+    })
+}
+
+/// Replace an indexer with its blue/green deployment, if the deployment caught
+/// up with the chain before its executor stopped.
+///
+/// The live executor is stopped first, so nothing is written to the indexer's
+/// tables while they're swapped. Whether or not the swap succeeds, the indexer
+/// is then restarted from its latest registered assets.
+async fn promote_shadow_executor(
+    config: &IndexerConfig,
+    blocks: &BlockFetcher,
+    pool: &IndexerConnectionPool,
+    executors: &mut HashMap<String, ExecutorHandle>,
+    shadow: ShadowExecutor,
+) -> IndexerResult<()> {
+    let ShadowExecutor {
+        namespace,
+        identifier,
+        ..
+    } = shadow;
+    let uid = format!("{namespace}.{identifier}");
+
+    let mut conn = pool.acquire().await?;
+    let deployment = match queries::shadow_deployment_for(
+        &mut conn,
+        &namespace,
+        &identifier,
+    )
+    .await?
+    {
+        Some(deployment) if deployment.caught_up => deployment,
+        _ => {
+            warn!("Blue/green deployment of Indexer({uid}) stopped before catching up with the chain.");
+            return Ok(());
+        }
+    };
+
+    if let Some(executor) = executors.remove(&uid) {
+        info!("Stopping Indexer({uid}) to replace it with its blue/green deployment.");
+        executor.stop().await;
+    }
+
+    queries::start_transaction(&mut conn).await?;
+    match promote_shadow_deployment(pool, &mut conn, deployment).await {
+        Ok(()) => {
+            queries::commit_transaction(&mut conn).await?;
+            info!("Promoted blue/green deployment of Indexer({uid}).");
+        }
+        Err(e) => {
+            queries::revert_transaction(&mut conn).await?;
+            error!("Failed to promote blue/green deployment of Indexer({uid}): {e}");
+        }
+    }
+
+    let id = queries::index_id_for(&mut conn, &namespace, &identifier).await?;
+    let (uid, executor) =
+        start_registered_executor(config, blocks, &mut conn, &id).await?;
+    executors.insert(uid, executor);
+
+    Ok(())
+}
+
+/// Swap an indexer's tables, progress, and assets for those of its blue/green
+/// deployment. Expects to be run in a transaction.
+async fn promote_shadow_deployment(
+    pool: &IndexerConnectionPool,
+    conn: &mut IndexerConnection,
+    deployment: ShadowDeployment,
+) -> IndexerResult<()> {
+    let ShadowDeployment {
+        namespace,
+        identifier,
+        wasm,
+        manifest,
+        schema,
+        ..
+    } = deployment;
+    let shadow_identifier = format!("{identifier}{SHADOW_IDENTIFIER_SUFFIX}");
+
+    SchemaManager::new(pool.clone())
+        .promote_shadow_schema(
+            &namespace,
+            &identifier,
+            &String::from_utf8_lossy(&schema),
+            conn,
+        )
+        .await?;
+
+    queries::remove_indexer_state(conn, &namespace, &identifier).await?;
+    queries::rename_indexer_state(conn, &namespace, &shadow_identifier, &identifier)
+        .await?;
+
+    for (asset_type, bytes) in [
+        (IndexAssetType::Wasm, wasm),
+        (IndexAssetType::Manifest, manifest),
+        (IndexAssetType::Schema, schema),
+    ] {
+        queries::register_index_asset(
+            conn,
+            &namespace,
+            &identifier,
+            bytes,
+            asset_type,
+            None,
+        )
+        .await?;
+    }
+
+    queries::remove_shadow_deployment(conn, &namespace, &identifier).await?;
+
+    Ok(())
+}
+
 async fn get_start_block(
     conn: &mut IndexerConnection,
     manifest: &Manifest,
 ) -> Result<u64, IndexerError> {
-    match &manifest.resumable {
-        Some(_) => {
-            let last = queries::last_block_height_for_indexer(
-                conn,
-                &manifest.namespace,
-                &manifest.identifier,
-            )
-            .await?;
-            info!("Resuming index from block {}", last);
-            Ok(last)
+    start_block_for(conn, manifest, &manifest.identifier).await
+}
+
+/// The block to start indexing from, given the progress kept under `identifier`.
+async fn start_block_for(
+    conn: &mut IndexerConnection,
+    manifest: &Manifest,
+    identifier: &str,
+) -> Result<u64, IndexerError> {
+    let start_block = manifest.start_block.unwrap_or(1);
+    match queries::checkpoint_for_indexer(conn, &manifest.namespace, identifier).await? {
+        Some(checkpoint) => {
+            let next = (checkpoint.block_height + 1).max(start_block);
+            info!("Resuming Indexer({}) from block {next}", manifest.uid());
+            Ok(next)
         }
-        None => Ok(manifest.start_block.unwrap_or(1)),
+        None => Ok(start_block),
     }
 }
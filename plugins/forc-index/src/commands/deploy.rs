@@ -65,6 +65,20 @@ pub struct Command {
     /// Do not build before deploying.
     #[clap(long, help = "Do not build before deploying.")]
     pub skip_build: bool,
+
+    /// Migrate the indexer's tables even if schema changes drop indexed data.
+    #[clap(
+        long,
+        help = "Migrate the indexer's tables even if schema changes drop indexed data."
+    )]
+    pub force_migration: bool,
+
+    /// Keep the current version serving queries until the new version catches up with the chain.
+    #[clap(
+        long,
+        help = "Keep the current version serving queries until the new version catches up with the chain."
+    )]
+    pub blue_green: bool,
 }
 
 impl Default for Command {
@@ -81,6 +95,8 @@ impl Default for Command {
             locked: false,
             native: false,
             skip_build: false,
+            force_migration: false,
+            blue_green: false,
             target_dir: Some(std::path::PathBuf::from(".")),
         }
     }
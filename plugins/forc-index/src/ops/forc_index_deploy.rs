@@ -31,6 +31,8 @@ pub fn init(command: DeployCommand) -> anyhow::Result<()> {
         target_dir,
         verbose,
         skip_build,
+        force_migration,
+        blue_green,
     } = command;
 
     if !skip_build {
@@ -65,7 +67,18 @@ pub fn init(command: DeployCommand) -> anyhow::Result<()> {
         .file("schema", graphql_schema)?
         .file("wasm", module.to_string())?;
 
-    let target = format!("{url}/api/index/{namespace}/{identifier}");
+    let params: Vec<&str> = [
+        (force_migration, "force_migration=true"),
+        (blue_green, "blue_green=true"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, param)| enabled.then_some(param))
+    .collect();
+
+    let mut target = format!("{url}/api/index/{namespace}/{identifier}");
+    if !params.is_empty() {
+        target.push_str(&format!("?{}", params.join("&")));
+    }
 
     if verbose {
         info!(
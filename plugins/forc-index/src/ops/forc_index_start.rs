@@ -18,6 +18,7 @@ pub async fn init(command: StartCommand) -> anyhow::Result<()> {
         postgres_database,
         postgres_host,
         postgres_port,
+        sqlite_database,
         local_fuel_node,
         run_migrations,
         metrics,
@@ -105,6 +106,8 @@ pub async fn init(command: StartCommand) -> anyhow::Result<()> {
             }
         }
 
+        cmd.arg("--database").arg(&database);
+
         match database.as_ref() {
             "postgres" => {
                 // Postgres optional values
@@ -122,8 +125,13 @@ pub async fn init(command: StartCommand) -> anyhow::Result<()> {
                     }
                 }
             }
+            "sqlite" => {
+                if let Some(path) = &sqlite_database {
+                    cmd.arg("--sqlite-database").arg(path);
+                }
+            }
             _ => unreachable!(
-                "'postgres' is currently the only supported database option."
+                "'postgres' and 'sqlite' are the only supported database options."
             ),
         }
     }
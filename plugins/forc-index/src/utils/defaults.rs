@@ -89,6 +89,10 @@ abi: ~
 # The particular start block after which you'd like your indexer to start indexing events.
 start_block: ~
 
+# The block height after which you'd like your indexer to stop indexing events. Once this block
+# has been indexed, the indexer is marked as completed and will not be restarted.
+end_block: ~
+
 # The `fuel_client` denotes the address (host, port combination) of the running Fuel client
 # that you would like your indexer to index events from. In order to use this per-indexer
 # `fuel_client` option, the indexer service at which your indexer is deployed will have to run
@@ -111,10 +115,6 @@ module:
 # The report_metrics field contains boolean whether or not to report Prometheus  metrics to the
 # Fuel backend
 report_metrics: true
-
-# The resumable field contains a boolean that specifies whether or not the indexer should, synchronise
-# with the latest block if it has fallen out of sync.
-resumable: ~
 "#
     )
 }
@@ -76,6 +76,9 @@ impl From<IndexerConfig> for PgEmbedConfig {
                 migration_dir: None,
                 postgres_version: PostgresVersion::V14,
             },
+            DatabaseConfig::Sqlite { .. } => {
+                panic!("forc-postgres can only create Postgres databases.")
+            }
         }
     }
 }